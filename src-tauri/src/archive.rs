@@ -0,0 +1,347 @@
+use tauri::command;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::runner::expand_tilde;
+
+// 一组逻辑上属于同一个压缩包的分卷
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveSet {
+    pub name: String,
+    // rar / 7z / zip
+    pub kind: String,
+    pub dir: String,
+    // 解压时传给解压工具的首个分卷
+    pub first_part: String,
+    pub parts: Vec<String>,
+    // 缺失的分卷序号
+    pub missing: Vec<u32>,
+    pub complete: bool,
+    pub total_size: u64,
+}
+
+// 分卷命名方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum VolumeStyle {
+    // name.part1.rar
+    RarPart,
+    // name.rar + name.r00 ...
+    RarOld,
+    // name.7z.001 / name.zip.001 / name.rar.001
+    Numbered,
+    // name.zip + name.z01 ...
+    ZipSplit,
+}
+
+// 解压前的空间估算
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveEstimate {
+    pub uncompressed_size: u64,
+    pub file_count: u64,
+    // 目标卷的可用空间，无法获取时为 None
+    pub free_space: Option<u64>,
+    pub enough_space: bool,
+}
+
+struct VolumeName {
+    base: String,
+    kind: &'static str,
+    style: VolumeStyle,
+    // 从 1 开始的分卷序号；ZipSplit 中 .zip 本身视为最后一卷，用 0 占位
+    index: u32,
+}
+
+// (基础名, 类型, 分卷方式) -> [(序号, 文件名, 大小)]
+type VolumeGroups = BTreeMap<(String, &'static str, VolumeStyle), Vec<(u32, String, u64)>>;
+
+fn kind_of(ext: &str) -> Option<&'static str> {
+    match ext {
+        "rar" => Some("rar"),
+        "7z" => Some("7z"),
+        "zip" => Some("zip"),
+        _ => None,
+    }
+}
+
+fn parse_volume_name(file_name: &str) -> Option<VolumeName> {
+    let lower = file_name.to_lowercase();
+    let (stem, ext) = lower.rsplit_once('.')?;
+
+    // name.part01.rar
+    if ext == "rar" {
+        if let Some((base, part)) = stem.rsplit_once(".part") {
+            if let Ok(index) = part.parse::<u32>() {
+                return Some(VolumeName { base: base.to_string(), kind: "rar", style: VolumeStyle::RarPart, index });
+            }
+        }
+    }
+
+    // name.7z.001
+    if ext.len() == 3 && ext.chars().all(|c| c.is_ascii_digit()) {
+        let (base, inner) = stem.rsplit_once('.')?;
+        let kind = kind_of(inner)?;
+        let index = ext.parse::<u32>().ok()?;
+        return Some(VolumeName { base: base.to_string(), kind, style: VolumeStyle::Numbered, index });
+    }
+
+    // name.r00 / name.z01（先确认是 ASCII，避免按字节切片时截断多字节字符）
+    if ext.len() == 3 && ext.is_ascii() && ext[1..].chars().all(|c| c.is_ascii_digit()) {
+        let n = ext[1..].parse::<u32>().ok()?;
+        return match &ext[..1] {
+            "r" => Some(VolumeName { base: stem.to_string(), kind: "rar", style: VolumeStyle::RarOld, index: n + 2 }),
+            "z" => Some(VolumeName { base: stem.to_string(), kind: "zip", style: VolumeStyle::ZipSplit, index: n }),
+            _ => None,
+        };
+    }
+
+    // 单个 .rar/.zip 可能是旧式分卷的首卷/末卷，.7z 则是普通压缩包
+    let kind = kind_of(ext)?;
+    let (style, index) = match kind {
+        "zip" => (VolumeStyle::ZipSplit, 0),
+        "rar" => (VolumeStyle::RarOld, 1),
+        _ => (VolumeStyle::Numbered, 1),
+    };
+    Some(VolumeName { base: stem.to_string(), kind, style, index })
+}
+
+// 扫描单个目录中的压缩包，并把分卷归并为逻辑压缩包
+pub fn detect_archive_sets(dir: &Path) -> Vec<ArchiveSet> {
+    let mut groups = VolumeGroups::new();
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+                continue;
+            }
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if let Some(v) = parse_volume_name(&file_name) {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                groups.entry((v.base, v.kind, v.style)).or_default().push((v.index, file_name, size));
+            }
+        }
+    }
+
+    let mut sets = Vec::new();
+    for ((base, kind, style), mut parts) in groups {
+        parts.sort_by_key(|(i, _, _)| *i);
+
+        let (first_part, missing) = match style {
+            VolumeStyle::ZipSplit => {
+                let head = parts.iter().find(|(i, _, _)| *i == 0).map(|(_, n, _)| n.clone());
+                let max = parts.iter().map(|(i, _, _)| *i).max().unwrap_or(0);
+                let mut missing: Vec<u32> = (1..=max).filter(|n| !parts.iter().any(|(i, _, _)| i == n)).collect();
+                if head.is_none() {
+                    // 缺少 .zip 本体（最后一卷）
+                    missing.push(max + 1);
+                }
+                (head.unwrap_or_default(), missing)
+            }
+            _ => {
+                let max = parts.iter().map(|(i, _, _)| *i).max().unwrap_or(0);
+                let missing: Vec<u32> = (1..=max).filter(|n| !parts.iter().any(|(i, _, _)| i == n)).collect();
+                let first = parts.iter().find(|(i, _, _)| *i == 1).map(|(_, n, _)| n.clone()).unwrap_or_default();
+                (first, missing)
+            }
+        };
+
+        sets.push(ArchiveSet {
+            name: base,
+            kind: kind.to_string(),
+            dir: dir.to_string_lossy().to_string(),
+            complete: missing.is_empty() && !first_part.is_empty(),
+            first_part,
+            total_size: parts.iter().map(|(_, _, s)| *s).sum(),
+            parts: parts.into_iter().map(|(_, n, _)| n).collect(),
+            missing,
+        });
+    }
+    sets
+}
+
+// 查找可用的解压工具：优先 7-Zip，其次 The Unarchiver 的 unar
+pub(crate) fn find_extractor() -> Option<(PathBuf, bool)> {
+    let candidates = ["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin"];
+    for name in ["7zz", "7z"] {
+        for dir in candidates {
+            let p = Path::new(dir).join(name);
+            if p.exists() {
+                return Some((p, true));
+            }
+        }
+    }
+    for dir in candidates {
+        let p = Path::new(dir).join("unar");
+        if p.exists() {
+            return Some((p, false));
+        }
+    }
+    None
+}
+
+// 解析 7z l -slt 的输出：分隔线之后每个空行分隔的块对应一个条目
+fn parse_7z_listing(text: &str) -> (u64, u64) {
+    let body = text.split_once("\n----------").map(|(_, b)| b).unwrap_or("");
+    let (mut size, mut count) = (0u64, 0u64);
+    for block in body.split("\n\n") {
+        let mut entry_size = None;
+        let mut is_dir = false;
+        for line in block.lines() {
+            if let Some((k, v)) = line.split_once(" = ") {
+                match k {
+                    "Size" => entry_size = v.trim().parse::<u64>().ok(),
+                    "Folder" => is_dir |= v.trim() == "+",
+                    "Attributes" => is_dir |= v.trim().starts_with('D'),
+                    _ => {}
+                }
+            }
+        }
+        if let (Some(n), false) = (entry_size, is_dir) {
+            size += n;
+            count += 1;
+        }
+    }
+    (size, count)
+}
+
+// 解析 lsar -j 的 JSON 输出
+fn parse_lsar_listing(text: &str) -> Result<(u64, u64), String> {
+    let json: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("解析压缩包信息失败: {}", e))?;
+    let entries = json["lsarContents"].as_array().ok_or("解析压缩包信息失败")?;
+    // 不同版本的 lsar 中 XADIsDirectory 可能是布尔值或 0/1
+    let is_dir = |e: &serde_json::Value| {
+        e["XADIsDirectory"].as_bool().or_else(|| e["XADIsDirectory"].as_i64().map(|v| v != 0)).unwrap_or(false)
+    };
+    let files = entries.iter().filter(|e| !is_dir(e));
+    Ok(files.fold((0, 0), |(size, count), e| (size + e["XADFileSize"].as_u64().unwrap_or(0), count + 1)))
+}
+
+// 读取压缩包目录得到解压后的总大小与文件数
+fn read_archive_totals(archive: &Path) -> Result<(u64, u64), String> {
+    let (tool, is_7z) = find_extractor().ok_or("未找到解压工具，请先安装 7-Zip (7zz) 或 unar")?;
+    let output = if is_7z {
+        Command::new(&tool).arg("l").arg("-slt").arg(archive).output()
+    } else {
+        Command::new(tool.with_file_name("lsar")).arg("-j").arg(archive).output()
+    }
+    .map_err(|e| format!("读取压缩包信息失败: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("读取压缩包信息失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    if is_7z { Ok(parse_7z_listing(&text)) } else { parse_lsar_listing(&text) }
+}
+
+// 通过 df 获取路径所在卷的可用空间；目标目录尚未创建时向上找到已存在的父目录
+pub(crate) fn free_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let output = Command::new("df").arg("-k").arg(existing).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let available = text.lines().nth(1)?.split_whitespace().nth(3)?.parse::<u64>().ok()?;
+    Some(available * 1024)
+}
+
+fn estimate(archive: &Path, dest: &Path) -> Result<ArchiveEstimate, String> {
+    let (uncompressed_size, file_count) = read_archive_totals(archive)?;
+    let free_space = free_space(dest);
+    Ok(ArchiveEstimate {
+        uncompressed_size,
+        file_count,
+        enough_space: free_space.map(|f| f >= uncompressed_size).unwrap_or(true),
+        free_space,
+    })
+}
+
+#[command]
+pub fn scan_archives(path: String) -> Result<Vec<ArchiveSet>, String> {
+    let root = expand_tilde(&path);
+    if !root.is_dir() {
+        return Err("Selected path is not a directory".into());
+    }
+
+    let mut sets = detect_archive_sets(&root);
+    if let Ok(entries) = fs::read_dir(&root) {
+        for entry in entries.flatten() {
+            if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                sets.extend(detect_archive_sets(&entry.path()));
+            }
+        }
+    }
+    Ok(sets)
+}
+
+// 在导入流程中提前展示解压后的大小、文件数与目标卷剩余空间
+#[command]
+pub async fn estimate_archive(first_part: String, dest_dir: String) -> Result<ArchiveEstimate, String> {
+    let (first, dest) = (expand_tilde(&first_part), expand_tilde(&dest_dir));
+    tokio::task::spawn_blocking(move || estimate(&first, &dest))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[command]
+pub async fn extract_archive(first_part: String, dest_dir: String, cleanup_parts: bool) -> Result<String, String> {
+    let first = expand_tilde(&first_part);
+    let dir = first.parent().ok_or("无法解析压缩包所在目录")?.to_path_buf();
+    let first_name = first.file_name().map(|n| n.to_string_lossy().to_string()).ok_or("无法解析压缩包文件名")?;
+
+    // 解压前确认所有分卷齐全
+    let set = detect_archive_sets(&dir)
+        .into_iter()
+        .find(|s| s.parts.contains(&first_name))
+        .ok_or("未识别到该压缩包")?;
+    if !set.complete {
+        return Err(format!("压缩包分卷不完整，缺少第 {:?} 卷", set.missing));
+    }
+
+    let (tool, is_7z) = find_extractor().ok_or("未找到解压工具，请先安装 7-Zip (7zz) 或 unar")?;
+    let dest = expand_tilde(&dest_dir);
+
+    // 空间不足时直接拒绝，避免解压到一半失败；读取目录失败时不阻止解压
+    let (archive, dest_for_check) = (dir.join(&set.first_part), dest.clone());
+    let check = tokio::task::spawn_blocking(move || estimate(&archive, &dest_for_check))
+        .await
+        .map_err(|e| e.to_string())?;
+    match check {
+        Ok(est) if !est.enough_space => {
+            return Err(format!(
+                "目标磁盘空间不足：解压需要 {} MB，可用 {} MB",
+                est.uncompressed_size / 1024 / 1024,
+                est.free_space.unwrap_or(0) / 1024 / 1024
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => log_warn!("无法估算解压大小: {}", e),
+    }
+    fs::create_dir_all(&dest).map_err(|e| format!("创建解压目录失败: {}", e))?;
+
+    let mut cmd = Command::new(&tool);
+    if is_7z {
+        cmd.arg("x").arg("-y").arg(format!("-o{}", dest.to_string_lossy())).arg(dir.join(&set.first_part));
+    } else {
+        cmd.arg("-o").arg(&dest).arg("-f").arg(dir.join(&set.first_part));
+    }
+
+    let output = tokio::task::spawn_blocking(move || cmd.output())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("执行解压工具失败: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("解压失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    // 用户选择后，解压成功再删除全部分卷
+    if cleanup_parts {
+        for part in &set.parts {
+            if let Err(e) = fs::remove_file(dir.join(part)) {
+                log_warn!("删除分卷 {} 失败: {}", part, e);
+            }
+        }
+    }
+
+    Ok(dest.to_string_lossy().to_string())
+}
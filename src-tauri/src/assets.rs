@@ -0,0 +1,216 @@
+use tauri::{AppHandle, command};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::db;
+use crate::instance::GameInstance;
+use crate::libraries;
+use crate::runner::expand_tilde;
+use crate::storage;
+use crate::sync;
+
+// 封面等图片保存在本机的 assets/<实例 ID>/ 中，文件名为内容哈希，相同的图片只保存一份。
+// 网络封面下载到本地后离线也能显示
+const ASSETS_DIR: &str = "assets";
+const ASSET_URL_PREFIX: &str = "asset://localhost/";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(20);
+// 刚导入、实例还没保存的图片不会被清理
+const GC_GRACE: Duration = Duration::from_secs(60 * 60);
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "webp", "gif", "avif"];
+
+fn assets_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    storage::local_data_path(app, ASSETS_DIR)
+}
+
+// 与前端 convertFileSrc 的结果一致
+fn asset_url(path: &Path) -> String {
+    format!("{}{}", ASSET_URL_PREFIX, urlencoding::encode(&path.to_string_lossy()))
+}
+
+fn asset_url_path(url: &str) -> Option<PathBuf> {
+    let encoded = url.strip_prefix(ASSET_URL_PREFIX)?;
+    urlencoding::decode(encoded).ok().map(|p| PathBuf::from(p.into_owned()))
+}
+
+fn is_remote(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+// 按文件头识别图片格式，识别不了时用来源中的扩展名
+fn image_extension(data: &[u8], source: &str) -> Option<String> {
+    let sniffed = if data.starts_with(b"\x89PNG") {
+        Some("png")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if data.starts_with(b"GIF8") {
+        Some("gif")
+    } else if data.len() > 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("webp")
+    } else if data.len() > 12 && &data[4..12] == b"ftypavif" {
+        Some("avif")
+    } else {
+        None
+    };
+    sniffed.map(|e| e.to_string()).or_else(|| {
+        source
+            .split(['?', '#'])
+            .next()
+            .and_then(|s| s.rsplit(['/', '\\']).next())
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_lowercase())
+            .filter(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+    })
+}
+
+async fn fetch(source: &str) -> Result<Vec<u8>, String> {
+    if is_remote(source) {
+        let client = reqwest::Client::builder().timeout(DOWNLOAD_TIMEOUT).build().map_err(|e| e.to_string())?;
+        let res = client.get(source).send().await.and_then(|r| r.error_for_status()).map_err(|e| format!("下载图片失败: {}", e))?;
+        return res.bytes().await.map(|b| b.to_vec()).map_err(|e| format!("下载图片失败: {}", e));
+    }
+    let path = asset_url_path(source).unwrap_or_else(|| expand_tilde(source));
+    fs::read(&path).map_err(|e| format!("无法读取图片 {:?}: {}", path, e))
+}
+
+// 在其他实例的目录中查找内容相同的图片，找到时建立硬链接，不重复占用空间
+fn find_existing(root: &Path, file_name: &str) -> Option<PathBuf> {
+    fs::read_dir(root).ok()?.flatten().map(|e| e.path().join(file_name)).find(|p| p.is_file())
+}
+
+fn store(app: &AppHandle, instance_id: &str, data: &[u8], source: &str) -> Result<PathBuf, String> {
+    storage::check_instance_id(instance_id)?;
+    let ext = image_extension(data, source).ok_or("不支持的图片格式".to_string())?;
+    let file_name = format!("{}.{}", sync::content_hash(data), ext);
+    let root = assets_dir(app)?;
+    let dir = root.join(instance_id);
+    let dest = dir.join(&file_name);
+    if dest.is_file() {
+        return Ok(dest);
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("无法创建资源目录: {}", e))?;
+    if let Some(existing) = find_existing(&root, &file_name) {
+        if fs::hard_link(&existing, &dest).is_ok() {
+            return Ok(dest);
+        }
+    }
+    storage::write_atomic(&dest, data)?;
+    Ok(dest)
+}
+
+// 已经在资源库中的图片原样返回
+fn is_stored(app: &AppHandle, source: &str) -> bool {
+    let root = match assets_dir(app) {
+        Ok(root) => root,
+        Err(_) => return false,
+    };
+    asset_url_path(source).unwrap_or_else(|| expand_tilde(source)).starts_with(root)
+}
+
+// 把网络图片、本地文件或 asset:// 地址保存到实例的资源目录，返回 asset:// 地址
+#[command]
+pub async fn import_asset(app: AppHandle, instance_id: String, source: String) -> Result<String, String> {
+    let source = source.trim().to_string();
+    if is_stored(&app, &source) {
+        return Ok(source);
+    }
+    let data = fetch(&source).await?;
+    let path = store(&app, &instance_id, &data, &source)?;
+    Ok(asset_url(&path))
+}
+
+// 把所有实例的网络封面下载到本地并改为 asset:// 地址，返回转换的数量。
+// 下载失败的保持原地址，下次再试
+#[command]
+pub async fn localize_covers(app: AppHandle) -> Result<usize, String> {
+    let remote: Vec<(String, String)> = storage::read_instances(&app)
+        .into_iter()
+        .filter_map(|i| i.background_image.filter(|u| is_remote(u)).map(|u| (i.id, u)))
+        .collect();
+    let mut localized: HashMap<String, (String, String)> = HashMap::new();
+    for (id, url) in remote {
+        let stored = match fetch(&url).await {
+            Ok(data) => store(&app, &id, &data, &url),
+            Err(e) => Err(e),
+        };
+        match stored {
+            Ok(path) => {
+                localized.insert(id, (url, asset_url(&path)));
+            }
+            Err(e) => log_warn!("封面 {} 保存失败: {}", url, e),
+        }
+    }
+    if localized.is_empty() {
+        return Ok(0);
+    }
+
+    let count = db::update_instances(&app, "localize_covers", |instances| {
+        let mut count = 0;
+        for inst in instances.iter_mut() {
+            // 下载期间封面被修改过的不覆盖
+            if let Some((url, local)) = localized.get(&inst.id) {
+                if inst.background_image.as_deref() == Some(url.as_str()) {
+                    inst.background_image = Some(local.clone());
+                    count += 1;
+                }
+            }
+        }
+        Ok(count)
+    })
+    .await?;
+    log_info!("已将 {} 个网络封面保存到本地", count);
+    Ok(count)
+}
+
+fn is_recent(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|age| age < GC_GRACE)
+}
+
+// 清理资源库：删除已彻底删除的实例的目录，以及实例不再引用的图片，返回删除的文件数。
+// 回收站中的实例仍可恢复，其图片保留
+pub(crate) fn collect_garbage(app: &AppHandle) -> Result<usize, String> {
+    let root = assets_dir(app)?;
+    let entries = match fs::read_dir(&root) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(0),
+    };
+    let instances: HashMap<String, GameInstance> = storage::load_all_instances(app)?.into_iter().map(|i| (i.id.clone(), i)).collect();
+    // 其他游戏库的实例不在当前列表中，其图片保持不动
+    let elsewhere = libraries::other_library_instance_ids(app)?;
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let id = entry.file_name().to_string_lossy().to_string();
+        if !dir.is_dir() || elsewhere.contains(&id) {
+            continue;
+        }
+        let referenced = instances.get(&id).and_then(|inst| serde_json::to_string(inst).ok());
+        for file in fs::read_dir(&dir).map(|e| e.flatten().map(|e| e.path()).collect::<Vec<_>>()).unwrap_or_default() {
+            let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let in_use = referenced.as_ref().is_some_and(|json| json.contains(&name));
+            if in_use || is_recent(&file) {
+                continue;
+            }
+            if fs::remove_file(&file).is_ok() {
+                removed += 1;
+            }
+        }
+        // 目录为空时才能删除成功
+        let _ = fs::remove_dir(&dir);
+    }
+    if removed > 0 {
+        log_info!("已清理 {} 个不再使用的图片", removed);
+    }
+    Ok(removed)
+}
+
+#[command]
+pub fn gc_assets(app: AppHandle) -> Result<usize, String> {
+    collect_garbage(&app)
+}
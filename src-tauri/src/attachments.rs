@@ -0,0 +1,199 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runner::expand_tilde;
+use crate::storage;
+
+// 附件索引文件，按实例 ID 记录附件列表
+const ATTACHMENTS_INDEX: &str = "attachments.json";
+const ATTACHMENTS_DIR: &str = "attachments";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    id: String,
+    // 原始文件名，用于展示
+    name: String,
+    // 实际存储在附件目录中的文件名
+    file_name: String,
+    size: u64,
+    // image / pdf / file
+    kind: String,
+    added_at: u64,
+}
+
+type AttachmentIndex = HashMap<String, Vec<Attachment>>;
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn attachment_kind(name: &str) -> &'static str {
+    let ext = name.rsplit('.').next().unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "heic" => "image",
+        "pdf" => "pdf",
+        _ => "file",
+    }
+}
+
+fn instance_attachments_dir(app: &AppHandle, instance_id: &str) -> Result<PathBuf, String> {
+    storage::check_instance_id(instance_id)?;
+    storage::resolve_data_path(app, &format!("{}/{}", ATTACHMENTS_DIR, instance_id))
+}
+
+fn read_index(app: &AppHandle) -> Result<AttachmentIndex, String> {
+    let path = storage::resolve_data_path(app, ATTACHMENTS_INDEX)?;
+    if !path.exists() {
+        return Ok(AttachmentIndex::new());
+    }
+    let text = fs::read_to_string(&path).map_err(|e| format!("无法读取附件索引: {}", e))?;
+    serde_json::from_str(&text).map_err(|e| format!("附件索引解析失败: {}", e))
+}
+
+fn write_index(app: &AppHandle, index: &AttachmentIndex) -> Result<(), String> {
+    let path = storage::resolve_data_path(app, ATTACHMENTS_INDEX)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| format!("无法写入附件索引: {}", e))
+}
+
+fn find_attachment(app: &AppHandle, instance_id: &str, attachment_id: &str) -> Result<(Attachment, PathBuf), String> {
+    let index = read_index(app)?;
+    let attachment = index
+        .get(instance_id)
+        .and_then(|list| list.iter().find(|a| a.id == attachment_id))
+        .cloned()
+        .ok_or("附件不存在")?;
+    let path = instance_attachments_dir(app, instance_id)?.join(&attachment.file_name);
+    Ok((attachment, path))
+}
+
+#[command]
+pub fn list_attachments(app: AppHandle, instance_id: String) -> Result<Vec<Attachment>, String> {
+    let index = read_index(&app)?;
+    Ok(index.get(&instance_id).cloned().unwrap_or_default())
+}
+
+#[command]
+pub fn add_attachment(app: AppHandle, instance_id: String, source_path: String) -> Result<Attachment, String> {
+    let source = expand_tilde(&source_path);
+    if !source.is_file() {
+        return Err(format!("找不到要添加的文件: {:?}", source));
+    }
+
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("无法解析文件名")?;
+
+    let dir = instance_attachments_dir(&app, &instance_id)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建附件目录失败: {}", e))?;
+
+    // 以时间戳作为附件 ID，并作为存储文件名前缀避免重名覆盖
+    let id = now_millis().to_string();
+    let file_name = format!("{}-{}", id, name);
+    let size = fs::copy(&source, dir.join(&file_name)).map_err(|e| format!("复制附件失败: {}", e))?;
+
+    let attachment = Attachment {
+        id,
+        kind: attachment_kind(&name).to_string(),
+        name,
+        file_name,
+        size,
+        added_at: now_millis(),
+    };
+
+    let mut index = read_index(&app)?;
+    index.entry(instance_id).or_default().push(attachment.clone());
+    write_index(&app, &index)?;
+
+    Ok(attachment)
+}
+
+#[command]
+pub fn open_attachment(app: AppHandle, instance_id: String, attachment_id: String) -> Result<(), String> {
+    let (_, path) = find_attachment(&app, &instance_id, &attachment_id)?;
+    if !path.exists() {
+        return Err(format!("附件文件已丢失: {:?}", path));
+    }
+
+    Command::new("open")
+        .arg(&path)
+        .spawn()
+        .map_err(|e| format!("无法打开附件: {}", e))?;
+    Ok(())
+}
+
+#[command]
+pub fn delete_attachment(app: AppHandle, instance_id: String, attachment_id: String) -> Result<(), String> {
+    let (_, path) = find_attachment(&app, &instance_id, &attachment_id)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("删除附件失败: {}", e))?;
+    }
+
+    let mut index = read_index(&app)?;
+    if let Some(list) = index.get_mut(&instance_id) {
+        list.retain(|a| a.id != attachment_id);
+        if list.is_empty() {
+            index.remove(&instance_id);
+        }
+    }
+    write_index(&app, &index)
+}
+
+// 完整迁移导出：把附件目录与索引复制到 dest 下，返回附件数
+pub(crate) fn export_attachments(app: &AppHandle, dest: &Path) -> Result<usize, String> {
+    let index = read_index(app)?;
+    let src = storage::resolve_data_path(app, ATTACHMENTS_DIR)?;
+    if index.is_empty() || !src.is_dir() {
+        return Ok(0);
+    }
+    storage::copy_tree(&src, &dest.join(ATTACHMENTS_DIR))?;
+    let text = serde_json::to_string_pretty(&index).map_err(|e| e.to_string())?;
+    fs::write(dest.join(ATTACHMENTS_INDEX), text).map_err(|e| format!("写入附件索引失败: {}", e))?;
+    Ok(index.values().map(|list| list.len()).sum())
+}
+
+// 从导出包恢复 instance_ids 中实例的附件：复制文件并合并索引，本机已有的同一附件保留不变，返回新增的附件数
+pub(crate) fn import_attachments(app: &AppHandle, root: &Path, instance_ids: &HashSet<String>) -> Result<usize, String> {
+    let index_path = root.join(ATTACHMENTS_INDEX);
+    if !index_path.is_file() {
+        return Ok(0);
+    }
+    let text = fs::read_to_string(&index_path).map_err(|e| format!("无法读取附件索引: {}", e))?;
+    let imported: AttachmentIndex = serde_json::from_str(&text).map_err(|e| format!("附件索引解析失败: {}", e))?;
+
+    let mut index = read_index(app)?;
+    let mut added = 0;
+    for (instance_id, list) in imported.into_iter().filter(|(id, _)| instance_ids.contains(id)) {
+        let src_dir = root.join(ATTACHMENTS_DIR).join(&instance_id);
+        let dest_dir = match instance_attachments_dir(app, &instance_id) {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        let local = index.entry(instance_id).or_default();
+        for attachment in list {
+            let src = src_dir.join(&attachment.file_name);
+            if local.iter().any(|a| a.id == attachment.id) || attachment.file_name.contains('/') || !src.is_file() {
+                continue;
+            }
+            fs::create_dir_all(&dest_dir).map_err(|e| format!("创建附件目录失败: {}", e))?;
+            fs::copy(&src, dest_dir.join(&attachment.file_name)).map_err(|e| format!("复制附件失败: {}", e))?;
+            local.push(attachment);
+            added += 1;
+        }
+    }
+    index.retain(|_, list| !list.is_empty());
+    write_index(app, &index)?;
+    Ok(added)
+}
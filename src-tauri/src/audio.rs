@@ -0,0 +1,98 @@
+use tauri::command;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::registry;
+use crate::runner::expand_tilde;
+
+const DRIVERS_KEY: &str = "Software\\Wine\\Drivers";
+const DIRECTSOUND_KEY: &str = "Software\\Wine\\DirectSound";
+// DirectSound 软件混音缓冲区（HelBuflen，字节），wine 默认 65536。
+// 调大可缓解爆音、卡顿，代价是声音延迟增加
+const MIN_BUFFER_SIZE: u32 = 4096;
+const MAX_BUFFER_SIZE: u32 = 1048576;
+
+#[derive(Serialize, Clone)]
+pub struct AudioSettings {
+    // default / coreaudio / disabled
+    driver: String,
+    buffer_size: Option<u32>,
+}
+
+// 驱动名对应注册表中 Audio 的取值，None 表示删除该值交给 wine 自动选择
+fn driver_value(driver: &str) -> Result<Option<&'static str>, String> {
+    match driver {
+        "default" | "" => Ok(None),
+        "coreaudio" => Ok(Some("coreaudio")),
+        // 留空即不加载任何音频驱动，用于因音频初始化崩溃的游戏
+        "disabled" => Ok(Some("")),
+        other => Err(format!("未知的音频驱动: {}", other)),
+    }
+}
+
+// 写入容器的音频驱动与 DirectSound 缓冲区，已一致时跳过
+pub fn apply_audio_settings(
+    crossover_app_path: &str,
+    bottle_path: &Path,
+    driver: Option<&str>,
+    buffer_size: Option<u32>,
+) -> Result<(), String> {
+    let user_reg = bottle_path.join("user.reg");
+    let mut body = String::new();
+
+    if let Some(driver) = driver {
+        let current = registry::read_value(&user_reg, DRIVERS_KEY, "Audio");
+        match driver_value(driver)? {
+            Some(value) if current.as_deref() != Some(value) => {
+                body.push_str(&format!("[HKEY_CURRENT_USER\\{}]\n\"Audio\"=\"{}\"\n\n", DRIVERS_KEY, value));
+            }
+            None if current.is_some() => {
+                body.push_str(&format!("[HKEY_CURRENT_USER\\{}]\n\"Audio\"=-\n\n", DRIVERS_KEY));
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(size) = buffer_size {
+        if !(MIN_BUFFER_SIZE..=MAX_BUFFER_SIZE).contains(&size) {
+            return Err(format!("音频缓冲区需在 {} 到 {} 字节之间", MIN_BUFFER_SIZE, MAX_BUFFER_SIZE));
+        }
+        if !registry::value_matches(&user_reg, DIRECTSOUND_KEY, "HelBuflen", &size.to_string()) {
+            body.push_str(&format!("[HKEY_CURRENT_USER\\{}]\n\"HelBuflen\"=\"{}\"\n\n", DIRECTSOUND_KEY, size));
+        }
+    }
+
+    if body.is_empty() {
+        return Ok(());
+    }
+    log_info!("正在为容器 {:?} 写入音频设置", bottle_path);
+    registry::wine_reg_import(crossover_app_path, bottle_path, &body)
+}
+
+#[command]
+pub fn get_bottle_audio(bottle_path: String) -> Result<AudioSettings, String> {
+    let user_reg = expand_tilde(&bottle_path).join("user.reg");
+    if !user_reg.exists() {
+        return Err(format!("找不到容器注册表: {:?}", user_reg));
+    }
+    let driver = match registry::read_value(&user_reg, DRIVERS_KEY, "Audio") {
+        None => "default",
+        Some(v) if v.is_empty() => "disabled",
+        Some(_) => "coreaudio",
+    };
+    let buffer_size = registry::read_value(&user_reg, DIRECTSOUND_KEY, "HelBuflen").and_then(|v| v.parse().ok());
+    Ok(AudioSettings { driver: driver.to_string(), buffer_size })
+}
+
+#[command]
+pub async fn set_bottle_audio(
+    bottle_path: String,
+    crossover_app_path: String,
+    driver: String,
+    buffer_size: Option<u32>,
+) -> Result<(), String> {
+    let bottle = expand_tilde(&bottle_path);
+    tokio::task::spawn_blocking(move || apply_audio_settings(&crossover_app_path, &bottle, Some(&driver), buffer_size))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,144 @@
+use tauri::{AppHandle, Emitter};
+use std::process::ExitStatus;
+
+use crate::runner::{self, WineConfig};
+
+// 开启虚拟桌面时若实例未设置分辨率则使用该值
+const FALLBACK_DESKTOP: &str = "1280x720";
+
+// 自动修复依次尝试的一种组合。每一步都在实例原有配置上修改，而不是叠加上一步
+struct FixStep {
+    name: &'static str,
+    virtual_desktop: bool,
+    graphics_backend: Option<&'static str>,
+    japanese_compat: bool,
+}
+
+// 先开启虚拟桌面，再依次切换 D3DMetal → DXVK → WineD3D，最后尝试日文环境。
+// 注意日文兼容会把 932 代码页写入容器注册表，即使这一步也失败，该修改仍会保留
+const AUTO_FIX_LADDER: [FixStep; 5] = [
+    FixStep { name: "virtual_desktop", virtual_desktop: true, graphics_backend: None, japanese_compat: false },
+    FixStep { name: "d3dmetal", virtual_desktop: true, graphics_backend: Some("d3dmetal"), japanese_compat: false },
+    FixStep { name: "dxvk", virtual_desktop: true, graphics_backend: Some("dxvk"), japanese_compat: false },
+    FixStep { name: "wined3d", virtual_desktop: true, graphics_backend: Some("wined3d"), japanese_compat: false },
+    FixStep { name: "ja_jp", virtual_desktop: true, graphics_backend: None, japanese_compat: true },
+];
+
+#[derive(serde::Serialize, Clone)]
+struct AutoFixAttemptPayload {
+    instance_id: String,
+    step: String,
+    attempt: usize,
+    total: usize,
+}
+
+// 最终结果，成功时附带可直接保存到实例的配置组合
+#[derive(serde::Serialize, Clone)]
+struct AutoFixResultPayload {
+    instance_id: String,
+    success: bool,
+    step: Option<String>,
+    attempts: usize,
+    virtual_desktop: Option<String>,
+    graphics_backend: Option<String>,
+    japanese_compat: bool,
+}
+
+impl FixStep {
+    // 返回该步骤对应的配置；与原配置相同时返回 None，跳过这一步
+    fn apply(&self, base: &WineConfig) -> Option<WineConfig> {
+        let mut config = base.clone();
+        config.auto_fix = Some(false);
+        if self.virtual_desktop && config.virtual_desktop.as_deref().map(str::trim).unwrap_or("").is_empty() {
+            config.virtual_desktop = Some(FALLBACK_DESKTOP.to_string());
+        }
+        if let Some(backend) = self.graphics_backend {
+            config.graphics_backend = Some(backend.to_string());
+        }
+        if self.japanese_compat {
+            config.japanese_compat = Some(true);
+        }
+
+        let unchanged = config.virtual_desktop == base.virtual_desktop
+            && config.graphics_backend == base.graphics_backend
+            && config.japanese_compat.unwrap_or(false) == base.japanese_compat.unwrap_or(false);
+        if unchanged { None } else { Some(config) }
+    }
+}
+
+fn failed_quickly(app: &AppHandle, instance_id: &str, status: Option<ExitStatus>, duration: u64, window: u64) -> bool {
+    duration < window && runner::exited_with_error(app, instance_id, status)
+}
+
+// 首次启动很快出错退出时按 AUTO_FIX_LADDER 依次重试，返回最后一次的退出状态与累计时长
+pub fn run_ladder(
+    app: &AppHandle,
+    instance_id: &str,
+    base: &WineConfig,
+    status: Option<ExitStatus>,
+    duration: u64,
+) -> (Option<ExitStatus>, u64) {
+    let window = base.auto_fix_window_sec.unwrap_or(runner::CRASH_MIN_RUNTIME_SEC);
+    let (mut status, mut total) = (status, duration);
+    if !failed_quickly(app, instance_id, status, duration, window) {
+        return (status, total);
+    }
+
+    let mut attempts = 0;
+    for step in &AUTO_FIX_LADDER {
+        // 用户手动停止后不再重试
+        if !runner::running_instance_ids().iter().any(|id| id == instance_id) {
+            return (status, total);
+        }
+        let config = match step.apply(base) {
+            Some(c) => c,
+            None => continue,
+        };
+
+        attempts += 1;
+        log_info!("实例 {} 启动失败，自动修复尝试 {}", instance_id, step.name);
+        let _ = app.emit("auto-fix-attempt", AutoFixAttemptPayload {
+            instance_id: instance_id.to_string(),
+            step: step.name.to_string(),
+            attempt: attempts,
+            total: AUTO_FIX_LADDER.len(),
+        });
+
+        let (mut child, exe, bottle) = match runner::spawn_crossover_session(app, instance_id, &config) {
+            Ok(v) => v,
+            Err(e) => {
+                log_warn!("自动修复步骤 {} 启动失败: {}", step.name, e);
+                continue;
+            }
+        };
+        let (s, d) = runner::wait_for_wine_tree(&mut child, &exe, &bottle);
+        status = s;
+        total += d;
+
+        if !failed_quickly(app, instance_id, s, d, window) {
+            log_info!("实例 {} 使用 {} 后启动成功", instance_id, step.name);
+            let _ = app.emit("auto-fix-result", AutoFixResultPayload {
+                instance_id: instance_id.to_string(),
+                success: true,
+                step: Some(step.name.to_string()),
+                attempts,
+                virtual_desktop: config.virtual_desktop,
+                graphics_backend: config.graphics_backend,
+                japanese_compat: config.japanese_compat.unwrap_or(false),
+            });
+            return (status, total);
+        }
+    }
+
+    log_warn!("实例 {} 的自动修复未找到可用的配置", instance_id);
+    let _ = app.emit("auto-fix-result", AutoFixResultPayload {
+        instance_id: instance_id.to_string(),
+        success: false,
+        step: None,
+        attempts,
+        virtual_desktop: base.virtual_desktop.clone(),
+        graphics_backend: base.graphics_backend.clone(),
+        japanese_compat: base.japanese_compat.unwrap_or(false),
+    });
+    (status, total)
+}
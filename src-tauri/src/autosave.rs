@@ -0,0 +1,202 @@
+use tauri::{AppHandle, Emitter, command};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::db;
+use crate::instance::GameInstance;
+use crate::storage;
+
+// 自动保存：前端的编辑先暂存在内存中，停止编辑一段时间后再一次写入游戏库，
+// 连续修改（拖动评分、逐字输入简介）不会每次都重写整个 instances.json。
+// 其他写入游戏库的操作会先带上暂存的修改，退出应用前也会全部写入
+pub(crate) const SOURCE: &str = "autosave";
+const DEBOUNCE: Duration = Duration::from_millis(1500);
+// 一直有新的修改时，最多间隔这么久也要写入一次
+const MAX_DELAY: Duration = Duration::from_secs(10);
+// 因游戏库已被其他窗口修改而无法写入的暂存修改另存在这里，与游戏库一样按设置加密
+const CONFLICTS_DIR: &str = "autosave_conflicts";
+
+pub(crate) struct Staged {
+    instance: GameInstance,
+    // 暂存时游戏库中还没有该实例，写入时作为新实例加入；
+    // 否则写入时实例已不存在说明已被彻底删除，丢弃这次修改
+    is_new: bool,
+    // 前端暂存时所基于的游戏库代数
+    generation: Option<u64>,
+}
+
+struct Pending {
+    // 按暂存顺序，同一实例只保留最新的版本
+    instances: Vec<Staged>,
+    // 最早一条尚未写入的修改的时间
+    since: Option<Instant>,
+}
+
+static PENDING: Mutex<Pending> = Mutex::new(Pending { instances: Vec::new(), since: None });
+// 每次暂存加一，定时器到期时编号未变才写入
+static STAGE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn pending() -> std::sync::MutexGuard<'static, Pending> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 取出所有暂存的修改，在游戏库写入锁内调用
+pub(crate) fn take_pending() -> Vec<Staged> {
+    let mut pending = pending();
+    pending.since = None;
+    std::mem::take(&mut pending.instances)
+}
+
+// 写入失败时放回暂存区，已有更新版本的实例不覆盖
+pub(crate) fn requeue(staged: Vec<Staged>) {
+    if staged.is_empty() {
+        return;
+    }
+    let mut pending = pending();
+    for s in staged.into_iter().rev() {
+        if !pending.instances.iter().any(|p| p.instance.id == s.instance.id) {
+            pending.instances.insert(0, s);
+        }
+    }
+    pending.since.get_or_insert_with(Instant::now);
+}
+
+// 把暂存的修改合并到实例列表中。已在回收站中或已彻底删除的实例不会因为迟到的编辑被恢复
+pub(crate) fn apply(instances: &mut Vec<GameInstance>, staged: &[Staged]) {
+    for s in staged {
+        let inst = &s.instance;
+        match instances.iter_mut().find(|i| i.id == inst.id) {
+            Some(existing) if existing.deleted_at.is_some() => log_info!("实例 {} 已移入回收站，忽略未保存的修改", existing.name),
+            Some(existing) => *existing = inst.clone(),
+            None if s.is_new => instances.push(inst.clone()),
+            None => log_info!("实例 {} 已被彻底删除，丢弃未保存的修改", inst.name),
+        }
+    }
+}
+
+// 在不写入的情况下把暂存的修改合并到读取结果中，供持有写入锁时也可能调用的读取使用
+pub(crate) fn overlay(instances: &mut Vec<GameInstance>) {
+    let pending = pending();
+    if !pending.instances.is_empty() {
+        apply(instances, &pending.instances);
+    }
+}
+
+// 把无法写入的暂存修改另存一份，返回文件路径
+fn save_conflict_copy(app: &AppHandle, stale: &[Staged]) -> Result<String, String> {
+    let instances: Vec<&GameInstance> = stale.iter().map(|s| &s.instance).collect();
+    let text = serde_json::to_string_pretty(&instances).map_err(|e| e.to_string())?;
+    let dir = storage::resolve_data_path(app, CONFLICTS_DIR)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("无法创建目录: {}", e))?;
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    let path = dir.join(format!("instances-{}.json", millis));
+    storage::write_atomic(&path, &storage::encode_library(app, &text)?)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// 立即写入所有暂存的修改，没有暂存的修改时什么也不做。
+// 暂存后游戏库被其他窗口修改过的修改基于旧数据，不写入：另存一份后从暂存区移除，
+// 并通过 autosave-failed 事件告知前端涉及的实例；其余修改照常写入
+pub(crate) fn flush(app: &AppHandle) -> Result<(), String> {
+    let (stale, expected, remaining) = {
+        let mut pending = pending();
+        if pending.instances.is_empty() {
+            return Ok(());
+        }
+        let current = storage::read_generation(app);
+        let (stale, fresh): (Vec<Staged>, Vec<Staged>) = std::mem::take(&mut pending.instances)
+            .into_iter()
+            .partition(|s| s.generation.is_some_and(|g| storage::is_stale(g, current)));
+        pending.instances = fresh;
+        if pending.instances.is_empty() {
+            pending.since = None;
+        }
+        (stale, pending.instances.iter().filter_map(|s| s.generation).min(), pending.instances.len())
+    };
+    if !stale.is_empty() {
+        let names = stale.iter().map(|s| s.instance.name.as_str()).collect::<Vec<_>>().join("、");
+        let message = match save_conflict_copy(app, &stale) {
+            Ok(path) => format!("游戏库已在其他窗口中被修改，{} 的修改未保存，已另存到 {}", names, path),
+            Err(e) => {
+                log_error!("另存未保存的修改失败: {}", e);
+                format!("游戏库已在其他窗口中被修改，{} 的修改未保存", names)
+            }
+        };
+        log_warn!("{}", message);
+        let _ = app.emit("autosave-failed", message);
+    }
+    if remaining == 0 {
+        return Ok(());
+    }
+    // 暂存的修改由 update_all_instances 取出并写入
+    storage::update_all_instances(app, SOURCE, expected, |_| Ok(()))?;
+    db::sync_in_background(app, storage::stored_values(app)?);
+    Ok(())
+}
+
+fn schedule(app: AppHandle, stage: u64) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(DEBOUNCE).await;
+        let overdue = pending().since.is_some_and(|since| since.elapsed() >= MAX_DELAY);
+        if STAGE_COUNTER.load(Ordering::SeqCst) != stage && !overdue {
+            return;
+        }
+        let result = tokio::task::spawn_blocking({
+            let app = app.clone();
+            move || flush(&app)
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+        if let Err(e) = result {
+            log_error!("自动保存失败: {}", e);
+            let _ = app.emit("autosave-failed", e);
+        }
+    });
+}
+
+// 暂存修改后的实例（新增或整体替换），稍后自动写入。校验失败的修改直接返回错误，不进入暂存区。
+// expected_generation 为前端读取游戏库时的代数，游戏库已被其他窗口修改时拒绝暂存；返回当前代数
+#[command]
+pub fn stage_instances(app: AppHandle, instances: Vec<GameInstance>, expected_generation: Option<u64>) -> Result<u64, String> {
+    for inst in &instances {
+        inst.validate()?;
+    }
+    let generation = storage::check_generation(&app, expected_generation)?;
+    if instances.is_empty() {
+        return Ok(generation);
+    }
+    // 只有第一次暂存的实例需要查看游戏库中是否已有
+    let unseen = {
+        let pending = pending();
+        instances.iter().any(|inst| !pending.instances.iter().any(|p| p.instance.id == inst.id))
+    };
+    let known: Vec<String> = if unseen { storage::load_all_instances(&app)?.into_iter().map(|i| i.id).collect() } else { Vec::new() };
+    {
+        let mut pending = pending();
+        for inst in instances {
+            match pending.instances.iter_mut().find(|p| p.instance.id == inst.id) {
+                Some(existing) => {
+                    existing.instance = inst;
+                    existing.generation = expected_generation;
+                }
+                None => {
+                    let is_new = !known.contains(&inst.id);
+                    pending.instances.push(Staged { instance: inst, is_new, generation: expected_generation });
+                }
+            }
+        }
+        pending.since.get_or_insert_with(Instant::now);
+    }
+    let stage = STAGE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1;
+    schedule(app, stage);
+    Ok(generation)
+}
+
+// 立即写入暂存的修改，如切换页面或导出前调用
+#[command]
+pub fn flush_autosave(app: AppHandle) -> Result<(), String> {
+    flush(&app)
+}
@@ -0,0 +1,281 @@
+use tauri::{AppHandle, command};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::db;
+use crate::instance::GameInstance;
+use crate::keychain;
+use crate::{pick_title, SearchResult};
+
+// Bangumi（bgm.tv）的 v0 接口。不登录也能搜索，但部分成人向条目只对带令牌的请求可见；
+// 个人令牌（https://next.bgm.tv/demo/access-token）保存在钥匙串中
+const API_BASE: &str = "https://api.bgm.tv";
+const KEYCHAIN_SERVICE: &str = "bangumi";
+const KEYCHAIN_ACCOUNT: &str = "access-token";
+// Bangumi 要求请求带上能识别应用的 User-Agent
+const USER_AGENT: &str = "jayi0908/AsumiGal (https://github.com/jayi0908/MacGal)";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+// 条目类型：4 为游戏
+const SUBJECT_TYPE_GAME: u32 = 4;
+const SORT_FIELDS: [&str; 4] = ["match", "heat", "rank", "score"];
+
+#[derive(Serialize)]
+pub struct BangumiTokenStatus {
+    configured: bool,
+    username: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BangumiTag {
+    name: String,
+    count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BangumiCharacter {
+    id: u64,
+    name: String,
+    // 主角 / 配角 / 客串
+    relation: String,
+    image: Option<String>,
+    // 声优
+    actors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BangumiDetails {
+    id: String,
+    title: String,
+    titles: BTreeMap<String, String>,
+    date: Option<String>,
+    cover: Option<String>,
+    summary: String,
+    // 条目信息中的开发商
+    developers: Vec<String>,
+    // 0-10，评分人数过少时为空
+    score: Option<f64>,
+    rank: Option<u64>,
+    votes: u64,
+    // 按标注人数排序
+    tags: Vec<BangumiTag>,
+    characters: Vec<BangumiCharacter>,
+    url: String,
+}
+
+fn token() -> Option<String> {
+    keychain::get_secret(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).filter(|t| !t.is_empty())
+}
+
+fn request(req: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    let req = req.header("User-Agent", USER_AGENT).header("Accept", "application/json").timeout(REQUEST_TIMEOUT);
+    match token {
+        Some(token) => req.header("Authorization", format!("Bearer {}", token)),
+        None => req,
+    }
+}
+
+async fn check_response(res: reqwest::Response) -> Result<Value, String> {
+    let status = res.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("Bangumi 令牌无效或已过期".to_string());
+    }
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err("Bangumi 中未找到该条目".to_string());
+    }
+    let raw_text = res.text().await.map_err(|e| format!("Read Text Failed: {}", e))?;
+    log_debug!("[Bangumi] 原始响应: {}", raw_text);
+    if !status.is_success() {
+        return Err(format!("Bangumi 请求失败 ({}): {}", status, raw_text.trim()));
+    }
+    serde_json::from_str(&raw_text).map_err(|e| format!("JSON Parse Failed: {}", e))
+}
+
+async fn get(client: &reqwest::Client, path: &str) -> Result<Value, String> {
+    let res = request(client.get(format!("{}{}", API_BASE, path)), token().as_deref())
+        .send()
+        .await
+        .map_err(|e| format!("Request Failed: {}", e))?;
+    check_response(res).await
+}
+
+// 中文名对应 zh-cn，原名（通常为日文）对应 ja-jp
+fn titles_of(subject: &Value) -> BTreeMap<String, String> {
+    [("zh-cn", "name_cn"), ("ja-jp", "name")]
+        .iter()
+        .filter_map(|(lang, key)| subject[*key].as_str().filter(|s| !s.is_empty()).map(|s| (lang.to_string(), s.to_string())))
+        .collect()
+}
+
+fn cover_of(subject: &Value) -> Option<String> {
+    subject["images"]["large"].as_str().or_else(|| subject["image"].as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string())
+}
+
+// 按关键词搜索游戏条目，返回本页结果与结果总数
+pub(crate) async fn search(
+    client: &reqwest::Client,
+    keyword: &str,
+    page: u32,
+    limit: u32,
+    sort_field: Option<&str>,
+    title_languages: &[String],
+) -> Result<(Vec<SearchResult>, Option<u64>), String> {
+    let sort = sort_field.filter(|f| SORT_FIELDS.contains(f)).unwrap_or("match");
+    let url = format!("{}/v0/search/subjects?limit={}&offset={}", API_BASE, limit, (page - 1) * limit);
+    let body = json!({
+        "keyword": keyword,
+        "sort": sort,
+        "filter": { "type": [SUBJECT_TYPE_GAME] },
+    });
+    let res = request(client.post(&url), token().as_deref())
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Request Failed: {}", e))?;
+    let json_val = check_response(res).await?;
+
+    let mut results = Vec::new();
+    for subject in json_val["data"].as_array().into_iter().flatten() {
+        let id = subject["id"].as_u64().map(|n| n.to_string()).unwrap_or_default();
+        let titles = titles_of(subject);
+        let title = match pick_title(&titles, title_languages) {
+            t if t.is_empty() => "未知标题".to_string(),
+            t => t,
+        };
+        results.push(SearchResult {
+            id: id.clone(),
+            title,
+            cover: cover_of(subject).unwrap_or_default(),
+            source: "Bangumi".to_string(),
+            url: format!("https://bgm.tv/subject/{}", id),
+            date: subject["date"].as_str().filter(|d| !d.is_empty()).map(|d| d.to_string()),
+            titles,
+        });
+    }
+    Ok((results, json_val["total"].as_u64()))
+}
+
+// 条目信息（infobox）中某一项的取值，值可能是字符串或 [{ v }] 列表
+fn infobox_values(subject: &Value, key: &str) -> Vec<String> {
+    let item = subject["infobox"].as_array().into_iter().flatten().find(|i| i["key"].as_str() == Some(key));
+    match item.map(|i| &i["value"]) {
+        Some(Value::String(s)) => s.split(['、', ',', '，']).map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+        Some(Value::Array(list)) => list.iter().filter_map(|v| v["v"].as_str().map(|s| s.trim().to_string())).filter(|s| !s.is_empty()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn parse_character(c: &Value) -> Option<BangumiCharacter> {
+    Some(BangumiCharacter {
+        id: c["id"].as_u64()?,
+        name: c["name"].as_str()?.to_string(),
+        relation: c["relation"].as_str().unwrap_or("").to_string(),
+        image: c["images"]["medium"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string()),
+        actors: c["actors"].as_array().into_iter().flatten().filter_map(|a| a["name"].as_str().map(|s| s.to_string())).collect(),
+    })
+}
+
+fn check_id(id: &str) -> Result<String, String> {
+    let id = id.trim();
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("无效的 Bangumi 条目 ID: {}", id));
+    }
+    Ok(id.to_string())
+}
+
+async fn fetch_details(app: &AppHandle, id: &str) -> Result<BangumiDetails, String> {
+    let id = check_id(id)?;
+    let client = reqwest::Client::new();
+    let (subject_path, characters_path) = (format!("/v0/subjects/{}", id), format!("/v0/subjects/{}/characters", id));
+    let (subject, characters) = tokio::join!(get(&client, &subject_path), get(&client, &characters_path));
+    let subject = subject?;
+    // 角色列表获取失败时不影响其他信息
+    let characters = characters.unwrap_or_else(|e| {
+        log_warn!("[Bangumi] 获取角色列表失败: {}", e);
+        Value::Null
+    });
+
+    let title_languages = crate::settings::load_settings(app).title_languages_for("bangumi");
+    let titles = titles_of(&subject);
+    let mut tags: Vec<BangumiTag> = subject["tags"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| Some(BangumiTag { name: t["name"].as_str()?.to_string(), count: t["count"].as_u64().unwrap_or(0) }))
+        .collect();
+    tags.sort_by_key(|t| std::cmp::Reverse(t.count));
+    let rating = &subject["rating"];
+    Ok(BangumiDetails {
+        url: format!("https://bgm.tv/subject/{}", id),
+        id,
+        title: pick_title(&titles, &title_languages),
+        titles,
+        date: subject["date"].as_str().filter(|d| !d.is_empty()).map(|d| d.to_string()),
+        cover: cover_of(&subject),
+        summary: subject["summary"].as_str().unwrap_or("").trim().to_string(),
+        developers: infobox_values(&subject, "开发"),
+        score: rating["score"].as_f64().filter(|s| *s > 0.0),
+        rank: rating["rank"].as_u64().filter(|r| *r > 0),
+        votes: rating["total"].as_u64().unwrap_or(0),
+        tags,
+        characters: characters.as_array().into_iter().flatten().filter_map(parse_character).collect(),
+    })
+}
+
+// 获取条目详情（评分、标签、角色），供详情页显示
+#[command]
+pub async fn bangumi_get_details(app: AppHandle, id: String) -> Result<BangumiDetails, String> {
+    fetch_details(&app, &id).await
+}
+
+// 把实例关联到 Bangumi 条目：记录 bangumiId，并补全实例中为空的简介、品牌与封面，已有的内容不覆盖
+#[command]
+pub async fn link_bangumi(app: AppHandle, instance_id: String, bangumi_id: String) -> Result<GameInstance, String> {
+    let details = fetch_details(&app, &bangumi_id).await?;
+    db::update_instances(&app, "link_bangumi", |instances| {
+        let inst = instances.iter_mut().find(|i| i.id == instance_id).ok_or(format!("未找到实例: {}", instance_id))?;
+        inst.extra.insert("bangumiId".to_string(), Value::String(details.id.clone()));
+        if inst.info.trim().is_empty() {
+            inst.info = details.summary.clone();
+        }
+        let has_developer = inst.extra.get("developer").and_then(|v| v.as_str()).is_some_and(|s| !s.is_empty());
+        if !has_developer && !details.developers.is_empty() {
+            inst.extra.insert("developer".to_string(), Value::String(details.developers.join(", ")));
+        }
+        if inst.background_image.as_deref().is_none_or(|s| s.is_empty()) {
+            inst.background_image = details.cover.clone();
+        }
+        Ok(inst.clone())
+    })
+    .await
+}
+
+// 保存个人令牌，保存前请求一次 /v0/me 确认有效；token 为空时删除
+#[command]
+pub async fn bangumi_set_token(token: Option<String>) -> Result<BangumiTokenStatus, String> {
+    let token = match token.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()) {
+        Some(token) => token,
+        None => {
+            if keychain::get_secret(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).is_some() {
+                keychain::delete_secret(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)?;
+            }
+            return Ok(BangumiTokenStatus { configured: false, username: None });
+        }
+    };
+    let res = request(reqwest::Client::new().get(format!("{}/v0/me", API_BASE)), Some(&token))
+        .send()
+        .await
+        .map_err(|e| format!("Request Failed: {}", e))?;
+    let me = check_response(res).await?;
+    keychain::set_secret(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT, &token)?;
+    Ok(BangumiTokenStatus { configured: true, username: me["username"].as_str().map(|s| s.to_string()) })
+}
+
+#[command]
+pub fn bangumi_token_status() -> BangumiTokenStatus {
+    BangumiTokenStatus { configured: token().is_some(), username: None }
+}
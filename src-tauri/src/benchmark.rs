@@ -0,0 +1,220 @@
+use tauri::{AppHandle, Emitter, command};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::bottle;
+use crate::runner;
+use crate::storage;
+
+const BENCHMARK_FILENAME: &str = "boot_benchmarks.json";
+// 每个实例最多保留的记录数
+const MAX_RECORDS_PER_INSTANCE: usize = 100;
+const PROBE_INTERVAL: Duration = Duration::from_millis(500);
+// 超过这个时间仍未出现窗口就放弃本次记录
+const PROBE_TIMEOUT: Duration = Duration::from_secs(180);
+// 记录时一并保存的容器环境变量，便于对比调整前后的效果
+const TRACKED_ENV_VARS: [&str; 3] = ["CX_GRAPHICS_BACKEND", "WINEESYNC", "WINEMSYNC"];
+
+// 通过 CoreGraphics 列出屏幕上普通窗口的所属 PID 与窗口 ID，不需要辅助功能权限
+const WINDOW_LIST_JXA: &str = r#"ObjC.import('CoreGraphics');
+var list = ObjC.castRefToObject($.CGWindowListCopyWindowInfo($.kCGWindowListOptionOnScreenOnly | $.kCGWindowListExcludeDesktopElements, $.kCGNullWindowID));
+var out = [];
+for (var i = 0; i < list.count; i++) {
+  var w = list.objectAtIndex(i);
+  if (w.objectForKey('kCGWindowLayer').js === 0) out.push(w.objectForKey('kCGWindowOwnerPID').js + ' ' + w.objectForKey('kCGWindowNumber').js);
+}
+out.join('\n');"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootRecord {
+    instance_id: String,
+    boot_ms: u64,
+    timestamp: u64,
+    crossover_version: Option<String>,
+    virtual_desktop: Option<String>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct BootSummary {
+    // CrossOver 版本与容器设置组合成的分组标签
+    label: String,
+    count: usize,
+    avg_ms: u64,
+    min_ms: u64,
+    max_ms: u64,
+    last_timestamp: u64,
+}
+
+// 启动时采集的环境信息，窗口出现后与耗时一起写入记录
+pub struct BootContext {
+    pub crossover_version: Option<String>,
+    pub virtual_desktop: Option<String>,
+    pub env: BTreeMap<String, String>,
+}
+
+impl BootContext {
+    pub fn collect(crossover_app_path: &str, bottle_path: &Path, virtual_desktop: Option<String>) -> Self {
+        let env = TRACKED_ENV_VARS
+            .iter()
+            .filter_map(|v| bottle::get_cxbottle_env(bottle_path, v).map(|val| (v.to_string(), val)))
+            .collect();
+        Self {
+            crossover_version: runner::read_crossover_version(crossover_app_path),
+            virtual_desktop: virtual_desktop.filter(|v| !v.trim().is_empty()),
+            env,
+        }
+    }
+
+    fn label(&self) -> String {
+        let mut parts = vec![self.crossover_version.clone().unwrap_or_else(|| "unknown".to_string())];
+        parts.extend(self.env.iter().map(|(k, v)| format!("{}={}", k, v)));
+        if let Some(d) = &self.virtual_desktop {
+            parts.push(format!("desktop={}", d));
+        }
+        parts.join(" · ")
+    }
+}
+
+// 返回屏幕上普通窗口的 (所属 PID, 窗口 ID)
+pub(crate) fn list_windows() -> Vec<(u32, u32)> {
+    Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", WINDOW_LIST_JXA])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .filter_map(|l| {
+                    let (pid, wid) = l.trim().split_once(' ')?;
+                    Some((pid.parse().ok()?, wid.parse().ok()?))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn load_records(app: &AppHandle) -> Vec<BootRecord> {
+    storage::resolve_data_path(app, BENCHMARK_FILENAME)
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|t| serde_json::from_str(&t).ok())
+        .unwrap_or_default()
+}
+
+fn append_record(app: &AppHandle, record: BootRecord) -> Result<(), String> {
+    let mut records = load_records(app);
+    records.push(record.clone());
+
+    let count = records.iter().filter(|r| r.instance_id == record.instance_id).count();
+    if count > MAX_RECORDS_PER_INSTANCE {
+        let mut to_drop = count - MAX_RECORDS_PER_INSTANCE;
+        records.retain(|r| {
+            if to_drop > 0 && r.instance_id == record.instance_id {
+                to_drop -= 1;
+                return false;
+            }
+            true
+        });
+    }
+
+    let path = storage::resolve_data_path(app, BENCHMARK_FILENAME)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string(&records).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| format!("无法写入启动耗时记录: {}", e))
+}
+
+// 在后台轮询窗口列表，启动器或其子进程出现第一个窗口时记录耗时
+pub fn spawn_boot_probe(app: AppHandle, instance_id: String, launcher_pid: u32, context: BootContext) {
+    thread::spawn(move || {
+        let start_time = Instant::now();
+        // 累积记录见过的进程，启动器退出后其子进程被 launchd 接管也能继续匹配
+        let mut tree: HashSet<u32> = HashSet::from([launcher_pid]);
+        while start_time.elapsed() < PROBE_TIMEOUT {
+            thread::sleep(PROBE_INTERVAL);
+
+            let processes = match runner::list_processes() {
+                Ok(p) => p,
+                Err(_) => continue,
+            };
+            let children_map = runner::build_children_map(&processes);
+            for pid in tree.clone() {
+                tree.extend(runner::collect_descendants(pid, &children_map));
+            }
+            tree.retain(|pid| processes.iter().any(|p| p.pid == *pid));
+            if tree.is_empty() {
+                log_info!("实例 {} 在出现窗口前已退出，跳过启动耗时记录", instance_id);
+                return;
+            }
+
+            if list_windows().iter().any(|(pid, _)| tree.contains(pid)) {
+                let boot_ms = start_time.elapsed().as_millis() as u64;
+                log_info!("实例 {} 首个窗口出现，启动耗时 {}ms", instance_id, boot_ms);
+
+                let record = BootRecord {
+                    instance_id: instance_id.clone(),
+                    boot_ms,
+                    timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                    crossover_version: context.crossover_version,
+                    virtual_desktop: context.virtual_desktop,
+                    env: context.env,
+                };
+                let _ = app.emit("game-boot-time", record.clone());
+                if let Err(e) = append_record(&app, record) {
+                    log_info!("{}", e);
+                }
+                return;
+            }
+        }
+        log_warn!("实例 {} 在 {} 秒内未出现窗口，放弃启动耗时记录", instance_id, PROBE_TIMEOUT.as_secs());
+    });
+}
+
+#[command]
+pub fn get_boot_benchmarks(app: AppHandle, instance_id: String) -> Vec<BootRecord> {
+    load_records(&app)
+        .into_iter()
+        .filter(|r| r.instance_id == instance_id)
+        .collect()
+}
+
+// 按 CrossOver 版本与设置分组汇总，供前端绘制对比图
+#[command]
+pub fn get_boot_benchmark_summary(app: AppHandle, instance_id: String) -> Vec<BootSummary> {
+    let mut groups: BTreeMap<String, Vec<BootRecord>> = BTreeMap::new();
+    for r in load_records(&app).into_iter().filter(|r| r.instance_id == instance_id) {
+        let label = BootContext {
+            crossover_version: r.crossover_version.clone(),
+            virtual_desktop: r.virtual_desktop.clone(),
+            env: r.env.clone(),
+        }
+        .label();
+        groups.entry(label).or_default().push(r);
+    }
+
+    let mut summaries: Vec<BootSummary> = groups
+        .into_iter()
+        .map(|(label, records)| {
+            let times: Vec<u64> = records.iter().map(|r| r.boot_ms).collect();
+            BootSummary {
+                label,
+                count: times.len(),
+                avg_ms: times.iter().sum::<u64>() / times.len() as u64,
+                min_ms: times.iter().copied().min().unwrap_or(0),
+                max_ms: times.iter().copied().max().unwrap_or(0),
+                last_timestamp: records.iter().map(|r| r.timestamp).max().unwrap_or(0),
+            }
+        })
+        .collect();
+    summaries.sort_by_key(|s| s.last_timestamp);
+    summaries
+}
@@ -0,0 +1,840 @@
+use tauri::{AppHandle, Emitter, command};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::engine;
+use crate::exe_info;
+use crate::registry;
+use crate::runner::{self, expand_tilde};
+use crate::storage;
+
+// 累计游玩超过 10 分钟视为该容器运行成功
+const SUCCESS_PLAYTIME_SEC: u64 = 600;
+
+#[derive(Serialize)]
+pub struct BottleCandidate {
+    bottle: String,
+    score: u32,
+    reasons: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct BottleSuggestion {
+    engine: String,
+    bitness: Option<u32>,
+    candidates: Vec<BottleCandidate>,
+    // 没有合适容器时建议用于新建容器的模板
+    template: String,
+}
+
+// 64 位前缀会包含 syswow64 目录
+pub(crate) fn bottle_is_64bit(bottle_path: &Path) -> bool {
+    bottle_path.join("drive_c/windows/syswow64").is_dir()
+}
+
+fn suggest_template(engine: &str, bitness: Option<u32>) -> &'static str {
+    match (engine, bitness) {
+        (_, Some(64)) | ("unity", _) => "win10_64",
+        ("kirikiri", _) | ("siglus", _) | ("reallive", _) | ("bgi", _) | ("catsystem2", _) => "win7",
+        _ => "win10_64",
+    }
+}
+
+#[command]
+pub fn suggest_bottle(app: AppHandle, exe_path: String, bottles_path: String) -> Result<BottleSuggestion, String> {
+    let exe = expand_tilde(&exe_path);
+    let game_dir = exe.parent().ok_or("无法解析可执行文件所在目录")?;
+    let engine = engine::detect_engine(game_dir);
+    let bitness = exe_info::read_pe_bitness(&exe);
+
+    let bottles = runner::get_crossover_bottles(bottles_path.clone())?;
+    let bottles_root = expand_tilde(&bottles_path);
+
+    // 统计同引擎实例在各容器中的使用情况
+    let mut engine_hits: HashMap<String, (u32, u32)> = HashMap::new();
+    for inst in storage::read_instance_values(&app) {
+        if inst["runMode"].as_str().unwrap_or("crossover") != "crossover" {
+            continue;
+        }
+        let bottle = inst["bottleName"].as_str().unwrap_or("");
+        let inst_exe = inst["executablePath"].as_str().unwrap_or("");
+        if bottle.is_empty() || inst_exe.is_empty() {
+            continue;
+        }
+
+        let inst_dir = match expand_tilde(inst_exe).parent() {
+            Some(d) => d.to_path_buf(),
+            None => continue,
+        };
+        if engine == "unknown" || engine::detect_engine(&inst_dir) != engine {
+            continue;
+        }
+
+        let played = inst["totalPlayTime"].as_u64().unwrap_or(0) >= SUCCESS_PLAYTIME_SEC;
+        let entry = engine_hits.entry(bottle.to_string()).or_default();
+        if played { entry.0 += 1; } else { entry.1 += 1; }
+    }
+
+    let mut candidates = Vec::new();
+    for bottle in bottles {
+        let bottle_path: PathBuf = bottles_root.join(&bottle);
+        let mut score = 0;
+        let mut reasons = Vec::new();
+
+        if bitness == Some(64) && !bottle_is_64bit(&bottle_path) {
+            // 32 位容器无法运行 64 位程序，直接跳过
+            continue;
+        }
+
+        if let Some(&(played, others)) = engine_hits.get(&bottle) {
+            if played > 0 {
+                score += played * 3;
+                reasons.push(format!("已有 {} 个 {} 引擎游戏在此容器中正常游玩", played, engine));
+            }
+            if others > 0 {
+                score += others;
+                reasons.push(format!("有 {} 个 {} 引擎游戏使用此容器", others, engine));
+            }
+        }
+
+        if bitness == Some(32) && bottle_is_64bit(&bottle_path) {
+            reasons.push("64 位容器，可通过 WoW64 运行 32 位程序".to_string());
+        }
+
+        if score > 0 {
+            candidates.push(BottleCandidate { bottle, score, reasons });
+        }
+    }
+
+    candidates.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.bottle.cmp(&b.bottle)));
+
+    Ok(BottleSuggestion {
+        engine: engine.to_string(),
+        bitness,
+        candidates,
+        template: suggest_template(engine, bitness).to_string(),
+    })
+}
+
+#[derive(serde::Serialize, Clone)]
+struct BottleProgressPayload {
+    bottle: String,
+    line: String,
+}
+
+const BOTTLE_TEMPLATES: [&str; 4] = ["win10_64", "win7_64", "win7", "winxp"];
+
+#[command]
+pub async fn create_bottle(
+    app: AppHandle,
+    name: String,
+    template: String,
+    crossover_app_path: String,
+    bottles_path: String,
+) -> Result<String, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.contains('/') || name.starts_with('.') {
+        return Err("容器名称无效".to_string());
+    }
+    if !BOTTLE_TEMPLATES.contains(&template.as_str()) {
+        return Err(format!("不支持的容器模板: {}", template));
+    }
+
+    let bottle_path = expand_tilde(&bottles_path).join(&name);
+    if bottle_path.exists() {
+        return Err(format!("容器已存在: {:?}", bottle_path));
+    }
+
+    let cxbottle = runner::crossover_tool(&crossover_app_path, "cxbottle");
+    if !cxbottle.exists() {
+        return Err(format!("未找到 cxbottle，请检查 CrossOver 路径: {:?}", cxbottle));
+    }
+
+    log_info!("正在创建容器 {} (模板: {})", name, template);
+    let mut child = Command::new(&cxbottle)
+        .env("CX_BOTTLE_PATH", expand_tilde(&bottles_path))
+        .arg("--bottle")
+        .arg(&name)
+        .arg("--create")
+        .arg("--template")
+        .arg(&template)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("无法执行 cxbottle: {}", e))?;
+
+    let (tx, rx) = mpsc::channel::<String>();
+    if let Some(stdout) = child.stdout.take() {
+        runner::spawn_line_reader(stdout, tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        runner::spawn_line_reader(stderr, tx.clone());
+    }
+    drop(tx);
+
+    let bottle_name = name.clone();
+    let status = tokio::task::spawn_blocking(move || {
+        for line in rx {
+            let _ = app.emit("bottle-progress", BottleProgressPayload {
+                bottle: bottle_name.clone(),
+                line,
+            });
+        }
+        child.wait()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("等待 cxbottle 失败: {}", e))?;
+
+    if !status.success() || !bottle_path.exists() {
+        return Err(format!("创建容器失败，退出码: {:?}", status.code()));
+    }
+
+    log_info!("容器 {} 创建完成: {:?}", name, bottle_path);
+    Ok(bottle_path.to_string_lossy().to_string())
+}
+
+#[derive(Serialize)]
+pub struct DeleteBottleResult {
+    removed: bool,
+    // 仍在使用该容器的实例名称
+    referenced_by: Vec<String>,
+}
+
+// 通过 Finder 将文件移到废纸篓，保留恢复的可能
+pub(crate) fn move_to_trash(path: &Path) -> Result<(), String> {
+    let escaped = path.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!("tell application \"Finder\" to delete POSIX file \"{}\"", escaped);
+    let status = Command::new("osascript")
+        .arg("-e")
+        .arg(&script)
+        .status()
+        .map_err(|e| format!("无法调用 Finder: {}", e))?;
+
+    if !status.success() {
+        return Err("移到废纸篓失败".to_string());
+    }
+    Ok(())
+}
+
+#[command]
+pub fn delete_bottle(
+    app: AppHandle,
+    bottle_path: String,
+    bottles_path: String,
+    use_trash: bool,
+    force: bool,
+) -> Result<DeleteBottleResult, String> {
+    let bottles_root = expand_tilde(&bottles_path)
+        .canonicalize()
+        .map_err(|_| format!("容器目录无法访问: {}", bottles_path))?;
+    let target = expand_tilde(&bottle_path)
+        .canonicalize()
+        .map_err(|_| format!("容器不存在: {}", bottle_path))?;
+
+    // 只允许删除容器目录下的一级子目录
+    if target.parent() != Some(bottles_root.as_path()) || !target.is_dir() {
+        return Err(format!("拒绝删除容器目录之外的路径: {:?}", target));
+    }
+
+    let bottle_name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("无法解析容器名称")?;
+
+    let referenced_by: Vec<String> = storage::read_instance_values(&app)
+        .iter()
+        .filter(|inst| inst["runMode"].as_str().unwrap_or("crossover") == "crossover")
+        .filter(|inst| uses_bottle(inst, &bottle_name, &target))
+        .map(|inst| inst["name"].as_str().unwrap_or("未命名实例").to_string())
+        .collect();
+
+    if !referenced_by.is_empty() && !force {
+        return Ok(DeleteBottleResult { removed: false, referenced_by });
+    }
+
+    if use_trash {
+        move_to_trash(&target)?;
+    } else {
+        fs::remove_dir_all(&target).map_err(|e| format!("删除容器失败: {}", e))?;
+    }
+
+    log_info!("容器 {} 已删除 (废纸篓: {})", bottle_name, use_trash);
+    Ok(DeleteBottleResult { removed: true, referenced_by })
+}
+
+// 实例的 bottleName 可能是容器名，也可能是容器路径（含 ~ 或未规范化的路径）
+fn uses_bottle(inst: &Value, bottle_name: &str, bottle: &Path) -> bool {
+    inst["bottleName"].as_str().is_some_and(|b| {
+        let path = expand_tilde(b);
+        b == bottle_name || path == bottle || path.canonicalize().is_ok_and(|p| p == bottle)
+    })
+}
+
+// 递归复制目录，返回复制的文件数。不使用硬链接：程序原地修改文件时会同时改动两个容器
+fn copy_dir(src: &Path, dst: &Path) -> Result<u64, String> {
+    fs::create_dir_all(dst).map_err(|e| format!("创建目录失败 {:?}: {}", dst, e))?;
+    let mut copied = 0;
+
+    let entries = fs::read_dir(src).map_err(|e| format!("读取目录失败 {:?}: {}", src, e))?;
+    for entry in entries.flatten() {
+        let from = entry.path();
+        let to = dst.join(entry.file_name());
+        let ft = entry.file_type().map_err(|e| e.to_string())?;
+
+        if ft.is_symlink() {
+            // dosdevices 等符号链接原样保留
+            let target = fs::read_link(&from).map_err(|e| e.to_string())?;
+            std::os::unix::fs::symlink(&target, &to).map_err(|e| format!("创建符号链接失败 {:?}: {}", to, e))?;
+        } else if ft.is_dir() {
+            copied += copy_dir(&from, &to)?;
+        } else {
+            fs::copy(&from, &to).map_err(|e| format!("复制文件失败 {:?}: {}", from, e))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+#[command]
+pub async fn clone_bottle(src: String, new_name: String, bottles_path: String) -> Result<String, String> {
+    let new_name = new_name.trim().to_string();
+    if new_name.is_empty() || new_name.contains('/') || new_name.starts_with('.') {
+        return Err("容器名称无效".to_string());
+    }
+
+    let bottles_root = expand_tilde(&bottles_path);
+    let src_path = {
+        let p = expand_tilde(&src);
+        if p.is_absolute() { p } else { bottles_root.join(&src) }
+    };
+    if !src_path.is_dir() {
+        return Err(format!("源容器不存在: {:?}", src_path));
+    }
+
+    let dst_path = bottles_root.join(&new_name);
+    if dst_path.exists() {
+        return Err(format!("容器已存在: {:?}", dst_path));
+    }
+
+    log_info!("正在克隆容器 {:?} -> {:?}", src_path, dst_path);
+    let (src_c, dst_c) = (src_path.clone(), dst_path.clone());
+    let result = tokio::task::spawn_blocking(move || -> Result<u64, String> {
+        // 优先使用 APFS 克隆（写时复制，速度快且两个容器互不影响）
+        let cloned = Command::new("cp")
+            .arg("-cR")
+            .arg(&src_c)
+            .arg(&dst_c)
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false);
+        if cloned {
+            return Ok(0);
+        }
+
+        // 非 APFS 卷上退回逐个复制
+        let _ = fs::remove_dir_all(&dst_c);
+        copy_dir(&src_c, &dst_c)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let copied = match result {
+        Ok(n) => n,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&dst_path);
+            return Err(e);
+        }
+    };
+
+    // CrossOver 通过 cxbottle.conf 中的名称识别容器，克隆后需改为新名称
+    let conf_path = dst_path.join("cxbottle.conf");
+    if let (Ok(conf), Some(old_name)) = (fs::read_to_string(&conf_path), src_path.file_name()) {
+        let old_name = old_name.to_string_lossy();
+        let updated = conf.replace(&format!("\"{}\"", old_name), &format!("\"{}\"", new_name));
+        let _ = fs::write(&conf_path, updated);
+    }
+
+    log_info!("容器克隆完成，逐个复制的文件数: {}", copied);
+    Ok(dst_path.to_string_lossy().to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledProgram {
+    pub name: String,
+    pub version: Option<String>,
+    pub publisher: Option<String>,
+    pub install_location: Option<String>,
+    // 程序图标，通常就是主程序本身（如 C:\Game\game.exe,0）
+    pub display_icon: Option<String>,
+    // 注册表 Uninstall 下的子键名
+    pub key: String,
+}
+
+#[derive(Serialize)]
+pub struct BottleInfo {
+    name: String,
+    path: String,
+    windows_version: Option<String>,
+    is_64bit: bool,
+    size_bytes: u64,
+    wine_version: Option<String>,
+    programs: Vec<InstalledProgram>,
+}
+
+const UNINSTALL_KEYS: [&str; 2] = [
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\",
+    "Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\",
+];
+
+// 使用 du 统计目录占用（比逐个 stat 快得多）
+pub(crate) fn dir_size_bytes(path: &Path) -> u64 {
+    Command::new("du")
+        .arg("-sk")
+        .arg(path)
+        .output()
+        .ok()
+        .and_then(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .split_whitespace()
+                .next()
+                .and_then(|kb| kb.parse::<u64>().ok())
+        })
+        .map(|kb| kb * 1024)
+        .unwrap_or(0)
+}
+
+pub(crate) fn read_windows_version(keys: &[registry::RegKey]) -> Option<String> {
+    let key = registry::find_key(keys, "Software\\Microsoft\\Windows NT\\CurrentVersion")?;
+    let product = key.get("ProductName").unwrap_or("Windows");
+    match (key.get("CurrentVersion"), key.get("CurrentBuildNumber")) {
+        (Some(v), Some(b)) => Some(format!("{} ({} build {})", product, v, b)),
+        (Some(v), None) => Some(format!("{} ({})", product, v)),
+        _ => key.get("ProductName").map(|p| p.to_string()),
+    }
+}
+
+// 从注册表 Uninstall 键中列出已安装程序
+pub(crate) fn read_installed_programs(keys: &[registry::RegKey]) -> Vec<InstalledProgram> {
+    let mut programs: Vec<InstalledProgram> = Vec::new();
+    for key in keys {
+        let sub = UNINSTALL_KEYS.iter().find_map(|prefix| {
+            key.path.get(..prefix.len())
+                .filter(|head| head.eq_ignore_ascii_case(prefix))
+                .map(|_| &key.path[prefix.len()..])
+        });
+        let sub = match sub {
+            Some(s) if !s.contains('\\') => s,
+            _ => continue,
+        };
+
+        let name = match key.get("DisplayName") {
+            Some(n) if !n.is_empty() => n.to_string(),
+            _ => continue,
+        };
+        if programs.iter().any(|p| p.name == name) {
+            continue;
+        }
+
+        programs.push(InstalledProgram {
+            name,
+            version: key.get("DisplayVersion").map(|s| s.to_string()),
+            publisher: key.get("Publisher").map(|s| s.to_string()),
+            install_location: key.get("InstallLocation").map(|s| s.to_string()).filter(|s| !s.is_empty()),
+            display_icon: key.get("DisplayIcon").map(|s| s.to_string()).filter(|s| !s.is_empty()),
+            key: sub.to_string(),
+        });
+    }
+    programs.sort_by_key(|p| p.name.to_lowercase());
+    programs
+}
+
+pub(crate) fn read_wine_version(crossover_app_path: &str) -> Option<String> {
+    let output = Command::new(runner::crossover_tool(crossover_app_path, "wine"))
+        .arg("--version")
+        .output()
+        .ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !version.is_empty() { Some(version) } else { None }
+}
+
+#[command]
+pub async fn get_bottle_info(bottle_path: String, crossover_app_path: Option<String>) -> Result<BottleInfo, String> {
+    let path = expand_tilde(&bottle_path);
+    if !path.is_dir() {
+        return Err(format!("容器不存在: {:?}", path));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let keys = registry::parse_reg_file(&path.join("system.reg")).unwrap_or_default();
+
+        Ok(BottleInfo {
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            windows_version: read_windows_version(&keys),
+            is_64bit: bottle_is_64bit(&path),
+            size_bytes: dir_size_bytes(&path),
+            wine_version: crossover_app_path.as_deref().and_then(read_wine_version),
+            programs: read_installed_programs(&keys),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+const ENV_SECTION: &str = "[EnvironmentVariables]";
+
+// 修改 cxbottle.conf 中 [EnvironmentVariables] 段的某个变量，value 为 None 时删除
+pub(crate) fn set_cxbottle_env(bottle_path: &Path, var: &str, value: Option<&str>) -> Result<(), String> {
+    let conf_path = bottle_path.join("cxbottle.conf");
+    let text = fs::read_to_string(&conf_path).map_err(|e| format!("无法读取 cxbottle.conf: {}", e))?;
+
+    let key_prefix = format!("\"{}\"", var);
+    let new_line = value.map(|v| format!("\"{}\" = \"{}\"", var, v));
+    let mut lines: Vec<String> = text.lines().map(|l| l.to_string()).collect();
+
+    let section_start = lines.iter().position(|l| l.trim().eq_ignore_ascii_case(ENV_SECTION));
+    match section_start {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|l| l.trim_start().starts_with('['))
+                .map(|i| start + 1 + i)
+                .unwrap_or(lines.len());
+            let existing = lines[start + 1..end]
+                .iter()
+                .position(|l| l.trim_start().starts_with(&key_prefix))
+                .map(|i| start + 1 + i);
+
+            match (existing, new_line) {
+                (Some(i), Some(line)) => lines[i] = line,
+                (Some(i), None) => {
+                    lines.remove(i);
+                }
+                (None, Some(line)) => lines.insert(end, line),
+                (None, None) => {}
+            }
+        }
+        None => {
+            if let Some(line) = new_line {
+                lines.push(String::new());
+                lines.push(ENV_SECTION.to_string());
+                lines.push(line);
+            }
+        }
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    fs::write(&conf_path, out).map_err(|e| format!("无法写入 cxbottle.conf: {}", e))
+}
+
+pub(crate) fn get_cxbottle_env(bottle_path: &Path, var: &str) -> Option<String> {
+    let text = fs::read_to_string(bottle_path.join("cxbottle.conf")).ok()?;
+    let key_prefix = format!("\"{}\"", var);
+    let mut in_section = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_section = trimmed.eq_ignore_ascii_case(ENV_SECTION);
+        } else if in_section && trimmed.starts_with(&key_prefix) {
+            let value = trimmed.split_once('=')?.1.trim().trim_matches('"');
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+const GRAPHICS_BACKEND_VAR: &str = "CX_GRAPHICS_BACKEND";
+const GRAPHICS_BACKENDS: [&str; 3] = ["d3dmetal", "dxvk", "wined3d"];
+
+#[command]
+pub fn get_graphics_backend(bottle_path: String) -> Result<String, String> {
+    let bottle = expand_tilde(&bottle_path);
+    if !bottle.is_dir() {
+        return Err(format!("容器不存在: {:?}", bottle));
+    }
+    Ok(get_cxbottle_env(&bottle, GRAPHICS_BACKEND_VAR).unwrap_or_else(|| "default".to_string()))
+}
+
+// backend 为 default 时恢复 CrossOver 的自动选择
+#[command]
+pub fn set_graphics_backend(bottle_path: String, backend: String) -> Result<(), String> {
+    let bottle = expand_tilde(&bottle_path);
+    if !bottle.is_dir() {
+        return Err(format!("容器不存在: {:?}", bottle));
+    }
+
+    let backend = backend.to_lowercase();
+    let value = match backend.as_str() {
+        "default" => None,
+        b if GRAPHICS_BACKENDS.contains(&b) => Some(b),
+        other => return Err(format!("不支持的图形后端: {}", other)),
+    };
+
+    set_cxbottle_env(&bottle, GRAPHICS_BACKEND_VAR, value)?;
+    log_info!("容器 {:?} 图形后端已设置为 {}", bottle, backend);
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct KillBottleResult {
+    terminated: usize,
+    pids: Vec<u32>,
+}
+
+// 以容器的 WINEPREFIX 执行 wineserver -k，结束该容器内的全部 wine 进程
+pub(crate) fn kill_wineserver(crossover_app_path: &str, bottle_path: &Path) -> Result<(), String> {
+    let status = Command::new(runner::crossover_tool(crossover_app_path, "wineserver"))
+        .env("WINEPREFIX", bottle_path)
+        .arg("-k")
+        .status()
+        .map_err(|e| format!("执行 wineserver 失败: {}", e))?;
+    if !status.success() {
+        return Err("wineserver -k 执行失败".to_string());
+    }
+    Ok(())
+}
+
+// 环境变量中含有 key=value（值完整匹配）
+fn has_env(env: &str, key: &str, value: &str) -> bool {
+    let needle = format!("{}={}", key, value);
+    env.match_indices(&needle).any(|(i, _)| {
+        let starts = i == 0 || env[..i].ends_with(' ');
+        let rest = &env[i + needle.len()..];
+        starts && (rest.is_empty() || rest.starts_with(' ') || rest.starts_with("/ "))
+    })
+}
+
+// 属于该容器的 wine 进程：按进程环境中的 WINEPREFIX 或 CX_BOTTLE 区分，不影响其他容器
+fn wine_pids(bottle: &Path) -> Result<Vec<u32>, String> {
+    // ps -E 在命令之后附带进程的环境变量（只能读到当前用户的进程，wine 进程都属于当前用户）
+    let output = Command::new("ps")
+        .args(["-axwwE", "-o", "pid=,command="])
+        .output()
+        .map_err(|e| format!("读取进程列表失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("读取进程列表失败，退出码: {}", output.status));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let prefix = bottle.to_string_lossy().trim_end_matches('/').to_string();
+    let name = bottle.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let in_bottle: HashSet<u32> = stdout
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let (pid, env) = line.split_once(char::is_whitespace)?;
+            let matches = has_env(env, "WINEPREFIX", &prefix) || (!name.is_empty() && has_env(env, "CX_BOTTLE", &name));
+            matches.then(|| pid.parse().ok()).flatten()
+        })
+        .collect();
+
+    Ok(runner::list_processes()?
+        .into_iter()
+        .filter(|p| in_bottle.contains(&p.pid) && runner::is_wine_process_command(&p.command))
+        .map(|p| p.pid)
+        .collect())
+}
+
+// 游戏卡死后残留的 wine 进程会占用容器，这里强制结束并统计被结束的进程数
+#[command]
+pub async fn kill_bottle(bottle_path: String, crossover_app_path: String) -> Result<KillBottleResult, String> {
+    let bottle = expand_tilde(&bottle_path);
+    if !bottle.is_dir() {
+        return Err(format!("容器不存在: {:?}", bottle));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let before = wine_pids(&bottle)?;
+        kill_wineserver(&crossover_app_path, &bottle)?;
+
+        // 进程收到信号后需要一点时间退出
+        let started = Instant::now();
+        let mut remaining = before.clone();
+        while started.elapsed() < Duration::from_secs(5) {
+            let alive = wine_pids(&bottle)?;
+            remaining.retain(|pid| alive.contains(pid));
+            if remaining.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(300));
+        }
+
+        let pids: Vec<u32> = before.into_iter().filter(|pid| !remaining.contains(pid)).collect();
+        log_info!("容器 {:?} 已结束 {} 个 wine 进程", bottle, pids.len());
+        Ok(KillBottleResult { terminated: pids.len(), pids })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// 可以安全清理的缓存，路径相对 drive_c；<user> 会替换为容器中的每个用户目录
+const BOTTLE_CACHE_DIRS: [&str; 7] = [
+    "windows/temp",
+    "users/<user>/Temp",
+    "users/<user>/AppData/Local/Temp",
+    "users/<user>/AppData/Local/D3DSCache",
+    "users/<user>/AppData/Local/Downloaded Installations",
+    "ProgramData/Package Cache",
+    "windows/Installer/$PatchCache$",
+];
+// DXVK 的着色器缓存默认写在游戏目录中，删除后首次运行会重新编译
+const DXVK_CACHE_EXT: &str = "dxvk-cache";
+
+#[derive(Serialize, Clone)]
+pub struct BottleEntrySize {
+    path: String,
+    bytes: u64,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct BottleCleanReport {
+    // drive_c 下各子目录的占用，从大到小
+    subtrees: Vec<BottleEntrySize>,
+    // 可清理的缓存
+    caches: Vec<BottleEntrySize>,
+    reclaimable_bytes: u64,
+    reclaimed_bytes: u64,
+    errors: Vec<String>,
+}
+
+fn bottle_cache_paths(drive_c: &Path) -> Vec<PathBuf> {
+    let users: Vec<String> = fs::read_dir(drive_c.join("users"))
+        .map(|e| e.flatten().filter(|e| e.path().is_dir()).map(|e| e.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_default();
+
+    let mut paths = Vec::new();
+    for rel in BOTTLE_CACHE_DIRS {
+        if rel.contains("<user>") {
+            paths.extend(users.iter().map(|u| drive_c.join(rel.replace("<user>", u))));
+        } else {
+            paths.push(drive_c.join(rel));
+        }
+    }
+    paths.retain(|p| p.symlink_metadata().map(|m| m.is_dir()).unwrap_or(false));
+
+    // 不跟随符号链接，避免统计到容器之外
+    let mut stack = vec![drive_c.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let ft = match entry.file_type() {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            let path = entry.path();
+            if ft.is_dir() {
+                stack.push(path);
+            } else if ft.is_file() && path.extension().and_then(|e| e.to_str()) == Some(DXVK_CACHE_EXT) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+fn entry_size(path: &Path) -> u64 {
+    if path.is_dir() {
+        dir_size_bytes(path)
+    } else {
+        fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+    }
+}
+
+// 清空目录中的内容，保留目录本身（wine 依赖 windows/temp 等目录存在）
+fn purge_cache_entry(path: &Path) -> Result<(), String> {
+    if !path.is_dir() {
+        return fs::remove_file(path).map_err(|e| format!("删除 {:?} 失败: {}", path, e));
+    }
+    let entries = fs::read_dir(path).map_err(|e| format!("读取 {:?} 失败: {}", path, e))?;
+    for entry in entries.flatten() {
+        let child = entry.path();
+        let result = if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            fs::remove_dir_all(&child)
+        } else {
+            fs::remove_file(&child)
+        };
+        result.map_err(|e| format!("删除 {:?} 失败: {}", child, e))?;
+    }
+    Ok(())
+}
+
+// 统计 drive_c 各子目录与可清理缓存的占用；purge 为 true 时清理缓存并报告释放的空间
+#[command]
+pub async fn clean_bottle(app: AppHandle, bottle_path: String, purge: bool) -> Result<BottleCleanReport, String> {
+    let bottle = expand_tilde(&bottle_path);
+    let drive_c = bottle.join("drive_c");
+    if !drive_c.is_dir() {
+        return Err(format!("不是有效的容器: {:?}", bottle));
+    }
+
+    if purge {
+        // 运行中的游戏可能正在使用临时文件
+        let bottle_name = bottle.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let running = runner::running_instance_ids();
+        let in_use = storage::read_instance_values(&app).iter().any(|inst| {
+            uses_bottle(inst, &bottle_name, &bottle)
+                && inst["id"].as_str().is_some_and(|id| running.iter().any(|r| r == id))
+        });
+        if in_use {
+            return Err("该容器中有正在运行的游戏，请先退出游戏再清理".to_string());
+        }
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let mut report = BottleCleanReport::default();
+
+        let mut subtrees: Vec<BottleEntrySize> = fs::read_dir(&drive_c)
+            .map(|e| e.flatten().map(|e| e.path()).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.symlink_metadata().map(|m| m.is_dir()).unwrap_or(false))
+            .map(|p| BottleEntrySize {
+                bytes: dir_size_bytes(&p),
+                path: p.strip_prefix(&drive_c).unwrap_or(&p).to_string_lossy().to_string(),
+            })
+            .collect();
+        subtrees.sort_by_key(|s| std::cmp::Reverse(s.bytes));
+        report.subtrees = subtrees;
+
+        for path in bottle_cache_paths(&drive_c) {
+            let bytes = entry_size(&path);
+            if bytes == 0 {
+                continue;
+            }
+            report.reclaimable_bytes += bytes;
+            if purge {
+                match purge_cache_entry(&path) {
+                    Ok(()) => report.reclaimed_bytes += bytes,
+                    Err(e) => {
+                        // 部分删除时按剩余体积计算实际释放的空间
+                        report.reclaimed_bytes += bytes.saturating_sub(entry_size(&path));
+                        report.errors.push(e);
+                    }
+                }
+            }
+            report.caches.push(BottleEntrySize {
+                path: path.strip_prefix(&drive_c).unwrap_or(&path).to_string_lossy().to_string(),
+                bytes,
+            });
+        }
+
+        if purge {
+            log_info!("容器 {:?} 清理完成，释放 {} 字节", bottle, report.reclaimed_bytes);
+        }
+        report
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,159 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::runner;
+use crate::settings;
+
+// 兼容性报告（匿名，不包含任何用户或设备标识）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatReport {
+    // 游戏 ID，使用搜索源的 ID（如 TouchGal unique_id / KunGal id），便于跨用户聚合
+    pub game_id: String,
+    #[serde(default)]
+    pub crossover_version: String,
+    #[serde(default)]
+    pub macos_version: String,
+    // works / issues / crashes
+    pub status: String,
+    #[serde(default)]
+    pub fixes: Vec<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SubmitCompatPayload {
+    pub game_id: String,
+    pub status: String,
+    pub fixes: Vec<String>,
+    pub comment: Option<String>,
+    pub crossover_app_path: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct CompatSummary {
+    game_id: String,
+    total: usize,
+    works: usize,
+    issues: usize,
+    crashes: usize,
+    // 按出现次数降序排列的常用修复手段
+    common_fixes: Vec<(String, usize)>,
+    // 出现过的 CrossOver 版本
+    crossover_versions: Vec<String>,
+    reports: Vec<CompatReport>,
+}
+
+const VALID_STATUS: [&str; 3] = ["works", "issues", "crashes"];
+
+fn read_macos_version() -> String {
+    Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+// 读取配置并确认用户已开启兼容性报告，返回服务地址
+fn compat_endpoint(app: &AppHandle) -> Result<String, String> {
+    let settings = settings::load_settings(app);
+    if !settings.compat_reports_enabled {
+        return Err("未开启社区兼容性报告".to_string());
+    }
+
+    let endpoint = settings.compat_endpoint.trim().trim_end_matches('/').to_string();
+    if endpoint.is_empty() {
+        return Err("未配置兼容性报告服务地址".to_string());
+    }
+    Ok(endpoint)
+}
+
+fn summarize_reports(game_id: &str, reports: Vec<CompatReport>) -> CompatSummary {
+    let mut summary = CompatSummary {
+        game_id: game_id.to_string(),
+        total: reports.len(),
+        ..Default::default()
+    };
+
+    let mut fix_counts: HashMap<String, usize> = HashMap::new();
+    for r in &reports {
+        match r.status.as_str() {
+            "works" => summary.works += 1,
+            "issues" => summary.issues += 1,
+            "crashes" => summary.crashes += 1,
+            _ => {}
+        }
+        for fix in &r.fixes {
+            *fix_counts.entry(fix.trim().to_string()).or_default() += 1;
+        }
+        if !r.crossover_version.is_empty() && !summary.crossover_versions.contains(&r.crossover_version) {
+            summary.crossover_versions.push(r.crossover_version.clone());
+        }
+    }
+
+    let mut fixes: Vec<(String, usize)> = fix_counts.into_iter().filter(|(k, _)| !k.is_empty()).collect();
+    fixes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    summary.common_fixes = fixes;
+    summary.crossover_versions.sort();
+    summary.reports = reports;
+    summary
+}
+
+#[command]
+pub async fn submit_compat_report(app: AppHandle, payload: SubmitCompatPayload) -> Result<(), String> {
+    let endpoint = compat_endpoint(&app)?;
+
+    if payload.game_id.trim().is_empty() {
+        return Err("游戏 ID 为空，无法提交报告".to_string());
+    }
+    if !VALID_STATUS.contains(&payload.status.as_str()) {
+        return Err(format!("无效的运行状态: {}", payload.status));
+    }
+
+    let report = CompatReport {
+        game_id: payload.game_id.trim().to_string(),
+        crossover_version: runner::read_crossover_version(&payload.crossover_app_path).unwrap_or_default(),
+        macos_version: read_macos_version(),
+        status: payload.status,
+        fixes: payload.fixes,
+        comment: payload.comment.filter(|c| !c.trim().is_empty()),
+    };
+
+    let client = reqwest::Client::new();
+    let res = client.post(format!("{}/reports", endpoint))
+        .json(&report)
+        .send()
+        .await
+        .map_err(|e| format!("Request Failed: {}", e))?;
+
+    if !res.status().is_success() {
+        return Err(format!("Server returned status: {}", res.status()));
+    }
+
+    log_info!("[Compat] 已提交 {} 的兼容性报告", report.game_id);
+    Ok(())
+}
+
+#[command]
+pub async fn fetch_compat_reports(app: AppHandle, game_id: String) -> Result<CompatSummary, String> {
+    let endpoint = compat_endpoint(&app)?;
+    let url = format!("{}/reports?game_id={}", endpoint, urlencoding::encode(&game_id));
+
+    let client = reqwest::Client::new();
+    let res = client.get(&url)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("Request Failed: {}", e))?;
+
+    if !res.status().is_success() {
+        return Err(format!("Server returned status: {}", res.status()));
+    }
+
+    let reports: Vec<CompatReport> = res.json().await.map_err(|e| format!("Parse Error: {}", e))?;
+    Ok(summarize_reports(&game_id, reports))
+}
@@ -0,0 +1,210 @@
+use tauri::{AppHandle, Manager, command};
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runner::expand_tilde;
+use crate::storage;
+
+// 记录最近调用的命令数量
+const RECENT_COMMANDS_LIMIT: usize = 50;
+const CRASH_DIR: &str = "crashes";
+// 用户处理过（打包或忽略）的崩溃报告移动到这里
+const REPORTED_DIR: &str = "crashes/reported";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashDump {
+    timestamp: u64,
+    app_version: String,
+    os_version: Option<String>,
+    thread: String,
+    message: String,
+    location: Option<String>,
+    backtrace: String,
+    // 崩溃前最近调用的命令（时间戳, 命令名），按时间顺序
+    recent_commands: Vec<(u64, String)>,
+    #[serde(skip_deserializing)]
+    file: String,
+}
+
+static CRASH_DIR_PATH: OnceLock<PathBuf> = OnceLock::new();
+static APP_VERSION: OnceLock<String> = OnceLock::new();
+static RECENT_COMMANDS: OnceLock<Mutex<VecDeque<(u64, String)>>> = OnceLock::new();
+
+fn recent_commands() -> &'static Mutex<VecDeque<(u64, String)>> {
+    RECENT_COMMANDS.get_or_init(|| Mutex::new(VecDeque::with_capacity(RECENT_COMMANDS_LIMIT)))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// 由 invoke_handler 在每次命令调用时记录
+pub fn record_command(name: &str) {
+    if let Ok(mut list) = recent_commands().lock() {
+        if list.len() >= RECENT_COMMANDS_LIMIT {
+            list.pop_front();
+        }
+        list.push_back((now_secs(), name.to_string()));
+    }
+}
+
+fn os_version() -> Option<String> {
+    let output = Command::new("sw_vers").arg("-productVersion").output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string()).filter(|v| !v.is_empty())
+}
+
+// 安装 panic hook：把崩溃信息写入 crashes/ 后再交给默认 hook 输出
+pub fn install_panic_hook(app: &AppHandle) {
+    if let Ok(dir) = storage::resolve_data_path(app, CRASH_DIR) {
+        let _ = CRASH_DIR_PATH.set(dir);
+    }
+    let _ = APP_VERSION.set(app.package_info().version.to_string());
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = info.payload().downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+
+        // panic 可能发生在持有锁的时候，这里只尝试获取，避免死锁
+        let recent = recent_commands()
+            .try_lock()
+            .map(|list| list.iter().cloned().collect())
+            .unwrap_or_default();
+
+        let timestamp = now_secs();
+        let dump = CrashDump {
+            timestamp,
+            app_version: APP_VERSION.get().cloned().unwrap_or_default(),
+            os_version: os_version(),
+            thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+            message,
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: Backtrace::force_capture().to_string(),
+            recent_commands: recent,
+            file: String::new(),
+        };
+
+        if let Some(dir) = CRASH_DIR_PATH.get() {
+            let path = dir.join(format!("crash-{}.json", timestamp));
+            let written = fs::create_dir_all(dir)
+                .ok()
+                .and_then(|_| serde_json::to_string_pretty(&dump).ok())
+                .and_then(|text| fs::write(&path, text).ok());
+            if written.is_some() {
+                eprintln!("崩溃信息已写入: {:?}", path);
+            }
+        }
+
+        default_hook(info);
+    }));
+}
+
+// 未处理的崩溃报告，启动时提示用户是否附带到诊断包
+#[command]
+pub fn get_pending_crash_reports(app: AppHandle) -> Result<Vec<CrashDump>, String> {
+    let dir = storage::resolve_data_path(&app, CRASH_DIR)?;
+    let mut dumps = Vec::new();
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(mut dump) = fs::read_to_string(&path).ok().and_then(|t| serde_json::from_str::<CrashDump>(&t).ok()) {
+                dump.file = entry.file_name().to_string_lossy().to_string();
+                dumps.push(dump);
+            }
+        }
+    }
+    dumps.sort_by_key(|d| d.timestamp);
+    Ok(dumps)
+}
+
+fn mark_reported(app: &AppHandle) -> Result<(), String> {
+    let dir = storage::resolve_data_path(app, CRASH_DIR)?;
+    let reported = storage::resolve_data_path(app, REPORTED_DIR)?;
+    fs::create_dir_all(&reported).map_err(|e| e.to_string())?;
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            if entry.path().is_file() {
+                let _ = fs::rename(entry.path(), reported.join(entry.file_name()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[command]
+pub fn dismiss_crash_reports(app: AppHandle) -> Result<(), String> {
+    mark_reported(&app)
+}
+
+fn copy_into(src: &Path, dest_dir: &Path) {
+    if !src.exists() {
+        return;
+    }
+    let _ = Command::new("cp").arg("-R").arg(src).arg(dest_dir).status();
+}
+
+// 打包诊断信息（日志、配置、系统版本，可选附带崩溃报告）为 zip，返回生成的文件路径
+#[command]
+pub async fn create_diagnostics_bundle(app: AppHandle, dest_dir: String, include_crash_reports: bool) -> Result<String, String> {
+    let dest = expand_tilde(&dest_dir);
+    if !dest.is_dir() {
+        return Err(format!("目标目录不存在: {:?}", dest));
+    }
+
+    let staging = std::env::temp_dir().join(format!("asumigal-diagnostics-{}", now_secs()));
+    fs::create_dir_all(&staging).map_err(|e| e.to_string())?;
+
+    let info = serde_json::json!({
+        "appVersion": app.package_info().version.to_string(),
+        "osVersion": os_version(),
+        "createdAt": now_secs(),
+    });
+    fs::write(staging.join("system.json"), serde_json::to_string_pretty(&info).unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+
+    copy_into(&storage::resolve_data_path(&app, "logs")?, &staging);
+    copy_into(&storage::resolve_data_path(&app, "settings.json")?, &staging);
+    copy_into(&storage::resolve_data_path(&app, "runner_versions.json")?, &staging);
+
+    let pending = get_pending_crash_reports(app.clone())?;
+    if include_crash_reports && !pending.is_empty() {
+        let crash_dir = staging.join("crashes");
+        fs::create_dir_all(&crash_dir).map_err(|e| e.to_string())?;
+        let src = storage::resolve_data_path(&app, CRASH_DIR)?;
+        for dump in &pending {
+            let _ = fs::copy(src.join(&dump.file), crash_dir.join(&dump.file));
+        }
+    }
+
+    let zip_path = dest.join(format!("AsumiGal-diagnostics-{}.zip", now_secs()));
+    let status = Command::new("ditto")
+        .args(["-c", "-k", "--sequesterRsrc", "--keepParent"])
+        .arg(&staging)
+        .arg(&zip_path)
+        .status()
+        .map_err(|e| format!("打包诊断信息失败: {}", e))?;
+    let _ = fs::remove_dir_all(&staging);
+    if !status.success() {
+        return Err("打包诊断信息失败".to_string());
+    }
+
+    // 无论是否附带，处理过后都不再重复提示
+    if !pending.is_empty() {
+        mark_reported(&app)?;
+    }
+    Ok(zip_path.to_string_lossy().to_string())
+}
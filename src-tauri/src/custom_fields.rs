@@ -0,0 +1,179 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+use crate::db;
+use crate::instance::{self, GameInstance};
+use crate::storage;
+
+// 自定义字段：字段定义保存在这里，各实例的取值保存在实例的 customFields 中（字段 ID -> 值）
+pub(crate) const FIELDS_FILE: &str = "custom_fields.json";
+const VALUES_KEY: &str = "customFields";
+const KINDS: [&str; 4] = ["text", "number", "date", "enum"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FieldDef {
+    // 新建时留空，由后端生成
+    pub id: String,
+    pub name: String,
+    // text / number / date（YYYY-MM-DD）/ enum
+    pub kind: String,
+    // enum 的可选值
+    pub options: Vec<String>,
+}
+
+fn read_fields(app: &AppHandle) -> Vec<FieldDef> {
+    storage::resolve_library_path(app, FIELDS_FILE)
+        .ok()
+        .and_then(|p| storage::read_data_file(&p).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write_fields(app: &AppHandle, fields: &[FieldDef]) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(fields).map_err(|e| e.to_string())?;
+    storage::write_atomic(&storage::resolve_library_path(app, FIELDS_FILE)?, text.as_bytes()).map_err(|e| format!("保存自定义字段失败: {}", e))
+}
+
+// 按字段类型检查并规范化取值，空值返回 None（清除该字段）
+fn normalize_value(field: &FieldDef, value: Value) -> Result<Option<Value>, String> {
+    let value = match value {
+        Value::Null => return Ok(None),
+        Value::String(s) if s.trim().is_empty() => return Ok(None),
+        Value::String(s) => Value::String(s.trim().to_string()),
+        other => other,
+    };
+    let valid = match field.kind.as_str() {
+        "text" => value.is_string(),
+        "number" => value.is_number(),
+        "date" => value.as_str().is_some_and(instance::is_day_key),
+        "enum" => value.as_str().is_some_and(|s| field.options.iter().any(|o| o == s)),
+        _ => false,
+    };
+    if !valid {
+        return Err(format!("字段「{}」的值无效: {}", field.name, value));
+    }
+    Ok(Some(value))
+}
+
+fn values_mut(inst: &mut GameInstance) -> Option<&mut Map<String, Value>> {
+    inst.extra.get_mut(VALUES_KEY).and_then(|v| v.as_object_mut())
+}
+
+// 删除所有实例上不满足 keep 的字段值，只在有实例被修改时才写入
+async fn retain_values(app: &AppHandle, keep: impl Fn(&str, &Value) -> bool) -> Result<(), String> {
+    let changed = storage::read_instances(app)
+        .iter()
+        .any(|inst| inst.extra.get(VALUES_KEY).and_then(|v| v.as_object()).is_some_and(|values| values.iter().any(|(id, v)| !keep(id, v))));
+    if !changed {
+        return Ok(());
+    }
+    db::update_instances(app, "custom_fields", |instances| {
+        for inst in instances.iter_mut() {
+            if let Some(values) = values_mut(inst) {
+                values.retain(|id, v| keep(id, v));
+                if values.is_empty() {
+                    inst.extra.remove(VALUES_KEY);
+                }
+            }
+        }
+        Ok(())
+    })
+    .await
+}
+
+#[command]
+pub fn list_custom_fields(app: AppHandle) -> Vec<FieldDef> {
+    read_fields(&app)
+}
+
+// 新建（id 为空）或修改自定义字段。字段类型创建后不能修改；
+// 枚举删去的选项会从实例上清除
+#[command]
+pub async fn define_custom_field(app: AppHandle, field: FieldDef) -> Result<FieldDef, String> {
+    let mut field = field;
+    field.name = field.name.trim().to_string();
+    if field.name.is_empty() {
+        return Err("字段名称不能为空".to_string());
+    }
+    if !KINDS.contains(&field.kind.as_str()) {
+        return Err(format!("不支持的字段类型: {}", field.kind));
+    }
+    let mut seen = HashSet::new();
+    field.options = field.options.iter().map(|o| o.trim().to_string()).filter(|o| !o.is_empty() && seen.insert(o.clone())).collect();
+    if field.kind == "enum" && field.options.is_empty() {
+        return Err("枚举字段至少需要一个选项".to_string());
+    }
+
+    let mut fields = read_fields(&app);
+    if fields.iter().any(|f| f.name == field.name && f.id != field.id) {
+        return Err(format!("字段已存在: {}", field.name));
+    }
+    if field.id.is_empty() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        field.id = format!("field-{}", now);
+        fields.push(field.clone());
+        write_fields(&app, &fields)?;
+        return Ok(field);
+    }
+
+    let existing = fields.iter_mut().find(|f| f.id == field.id).ok_or(format!("未找到字段: {}", field.id))?;
+    if existing.kind != field.kind {
+        return Err("字段类型创建后不能修改".to_string());
+    }
+    *existing = field.clone();
+    write_fields(&app, &fields)?;
+    if field.kind == "enum" {
+        retain_values(&app, |id, v| id != field.id || v.as_str().is_some_and(|s| field.options.iter().any(|o| o == s))).await?;
+    }
+    Ok(field)
+}
+
+// 删除自定义字段，并从所有实例上移除它的值
+#[command]
+pub async fn delete_custom_field(app: AppHandle, field_id: String) -> Result<(), String> {
+    retain_values(&app, |id, _| id != field_id).await?;
+    let mut fields = read_fields(&app);
+    fields.retain(|f| f.id != field_id);
+    write_fields(&app, &fields)
+}
+
+// 设置实例的自定义字段值，value 为空时清除
+#[command]
+pub async fn set_custom_field_value(app: AppHandle, instance_id: String, field_id: String, value: Option<Value>) -> Result<GameInstance, String> {
+    let field = read_fields(&app)
+        .into_iter()
+        .find(|f| f.id == field_id)
+        .ok_or(format!("未找到字段: {}", field_id))?;
+    let value = normalize_value(&field, value.unwrap_or(Value::Null))?;
+
+    db::update_instances(&app, "set_custom_field_value", |instances| {
+        let inst = instances.iter_mut().find(|i| i.id == instance_id).ok_or(format!("未找到实例: {}", instance_id))?;
+        match value {
+            Some(value) => {
+                let values = inst.extra.entry(VALUES_KEY).or_insert_with(|| Value::Object(Map::new()));
+                if !values.is_object() {
+                    *values = Value::Object(Map::new());
+                }
+                if let Some(values) = values.as_object_mut() {
+                    values.insert(field_id.clone(), value);
+                }
+            }
+            None => {
+                if let Some(values) = values_mut(inst) {
+                    values.remove(&field_id);
+                    if values.is_empty() {
+                        inst.extra.remove(VALUES_KEY);
+                    }
+                }
+            }
+        }
+        Ok(inst.clone())
+    })
+    .await
+}
@@ -0,0 +1,622 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePool, SqlitePoolOptions};
+use sqlx::{FromRow, Row};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::instance::GameInstance;
+use crate::libraries;
+use crate::paths;
+use crate::settings;
+use crate::storage;
+
+// 游戏库数据库：实例、游玩会话与标签。
+// 前端目前仍整体读写 instances.json，每次保存后同步到 instances 表与 tags 表；会话只保存在数据库中。
+// instances.json 中已不存在的实例只标记 removed_at，不删除行，其会话与标签随之保留，恢复备份或重新导入后仍在；
+// 只有从回收站彻底删除时才删除。
+// 游戏库加密时数据库中只保存 REDACTED_FIELDS 中的字段（不含名称、路径与标签），完整数据仍从加密的 instances.json 读取
+const DB_FILENAME: &str = "library.db";
+const MIGRATED_KEY: &str = "migrated_from_json";
+// 游戏库加密时写入数据库的字段，不含名称、简介、路径等内容
+const REDACTED_FIELDS: [&str; 5] = ["id", "deletedAt", "runMode", "totalPlayTime", "lastPlayed"];
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS instances (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    bottle_name TEXT NOT NULL DEFAULT '',
+    executable_path TEXT NOT NULL DEFAULT '',
+    run_mode TEXT NOT NULL DEFAULT 'crossover',
+    total_play_time INTEGER NOT NULL DEFAULT 0,
+    last_played INTEGER,
+    data TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    removed_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    instance_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER NOT NULL,
+    duration_sec INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_sessions_instance ON sessions(instance_id, started_at);
+CREATE TABLE IF NOT EXISTS tags (
+    instance_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (instance_id, tag)
+);
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+"#;
+
+static POOL: OnceLock<SqlitePool> = OnceLock::new();
+// 实例快照的序号与已写入数据库的最新序号
+static SYNC_SEQ: AtomicU64 = AtomicU64::new(0);
+static SYNC_APPLIED: tokio::sync::Mutex<u64> = tokio::sync::Mutex::const_new(0);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceRow {
+    pub id: String,
+    pub name: String,
+    pub bottle_name: String,
+    pub executable_path: String,
+    pub run_mode: String,
+    pub total_play_time: i64,
+    pub last_played: Option<i64>,
+    // 完整的实例对象，包含前端使用的其他字段
+    pub data: Value,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRow {
+    pub id: i64,
+    pub instance_id: String,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub duration_sec: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRow {
+    pub instance_id: String,
+    pub tag: String,
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+pub fn pool() -> Result<&'static SqlitePool, String> {
+    POOL.get().ok_or_else(|| "数据库尚未初始化".to_string())
+}
+
+fn db_err(e: sqlx::Error) -> String {
+    format!("数据库错误: {}", e)
+}
+
+// "YYYY-MM-DD" 转为当天 0 点（UTC）的时间戳
+fn day_to_timestamp(day: &str) -> Option<i64> {
+    let mut parts = day.split('-').map(|p| p.parse::<i64>().ok());
+    let (y, m, d) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    // 公历日期到 1970-01-01 的天数
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some((era * 146097 + doe - 719468) * 86400)
+}
+
+fn row_to_instance(row: &sqlx::sqlite::SqliteRow) -> Result<InstanceRow, String> {
+    let text: String = row.try_get("data").map_err(db_err)?;
+    // 数据库中保存的是规范形式的路径，交给前端前解析为本机路径
+    let mut wrapped = Value::Array(vec![serde_json::from_str(&text).unwrap_or(Value::Null)]);
+    paths::map_instance_paths(&mut wrapped, |p| paths::resolve_stored_path(p).to_string_lossy().to_string());
+    let data = wrapped.as_array_mut().and_then(|a| a.pop()).unwrap_or(Value::Null);
+    Ok(InstanceRow {
+        id: row.try_get("id").map_err(db_err)?,
+        name: row.try_get("name").map_err(db_err)?,
+        bottle_name: row.try_get("bottle_name").map_err(db_err)?,
+        executable_path: data["executablePath"].as_str().unwrap_or("").to_string(),
+        run_mode: row.try_get("run_mode").map_err(db_err)?,
+        total_play_time: row.try_get("total_play_time").map_err(db_err)?,
+        last_played: row.try_get("last_played").map_err(db_err)?,
+        updated_at: row.try_get("updated_at").map_err(db_err)?,
+        data,
+    })
+}
+
+// 写入实例行，并用实例自身的 tags 字段替换其在 tags 表中的标签
+async fn upsert_instance_value(conn: &mut SqliteConnection, inst: &Value) -> Result<(), String> {
+    let id = inst["id"].as_str().ok_or("实例缺少 id")?;
+    sqlx::query(
+        "INSERT INTO instances (id, name, bottle_name, executable_path, run_mode, total_play_time, last_played, data, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name, bottle_name = excluded.bottle_name,
+            executable_path = excluded.executable_path, run_mode = excluded.run_mode,
+            total_play_time = excluded.total_play_time, last_played = excluded.last_played,
+            data = excluded.data, updated_at = excluded.updated_at, removed_at = NULL",
+    )
+    .bind(id)
+    .bind(inst["name"].as_str().unwrap_or(""))
+    .bind(inst["bottleName"].as_str().unwrap_or(""))
+    .bind(inst["executablePath"].as_str().unwrap_or(""))
+    .bind(inst["runMode"].as_str().unwrap_or("crossover"))
+    .bind(inst["totalPlayTime"].as_i64().unwrap_or(0))
+    .bind(inst["lastPlayed"].as_i64())
+    .bind(inst.to_string())
+    .bind(unix_now())
+    .execute(&mut *conn)
+    .await
+    .map_err(db_err)?;
+
+    sqlx::query("DELETE FROM tags WHERE instance_id = ?").bind(id).execute(&mut *conn).await.map_err(db_err)?;
+    for tag in inst["tags"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+        sqlx::query("INSERT OR IGNORE INTO tags (instance_id, tag) VALUES (?, ?)")
+            .bind(id)
+            .bind(tag)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_err)?;
+    }
+    Ok(())
+}
+
+fn is_encrypted(app: &AppHandle) -> bool {
+    settings::load_settings(app).encrypt_library
+}
+
+// 只保留 REDACTED_FIELDS 中的字段
+fn redact(inst: &Value) -> Value {
+    let kept = REDACTED_FIELDS.iter().filter_map(|key| inst.get(*key).map(|v| (key.to_string(), v.clone())));
+    Value::Object(kept.collect())
+}
+
+// 让 instances 表与 instances.json 保持一致：新增或更新 JSON 中的实例，JSON 中已不存在的实例标记为已移除。
+// redacted 为 true 时只写入 REDACTED_FIELDS，并清除此前以明文写入的内容
+async fn sync_instances(values: &[Value], redacted: bool) -> Result<(), String> {
+    let pool = pool()?;
+    let mut tx = pool.begin().await.map_err(db_err)?;
+    let redacted_values: Vec<Value>;
+    let values = if redacted {
+        redacted_values = values.iter().map(redact).collect();
+        &redacted_values
+    } else {
+        values
+    };
+
+    let keep: HashSet<&str> = values.iter().filter_map(|v| v["id"].as_str()).collect();
+    for inst in values.iter().filter(|v| v["id"].is_string()) {
+        upsert_instance_value(&mut tx, inst).await?;
+    }
+
+    let existing: Vec<String> = sqlx::query_scalar("SELECT id FROM instances WHERE removed_at IS NULL")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    let now = unix_now();
+    for id in existing.iter().filter(|id| !keep.contains(id.as_str())) {
+        sqlx::query("UPDATE instances SET removed_at = ? WHERE id = ?").bind(now).bind(id).execute(&mut *tx).await.map_err(db_err)?;
+    }
+    if redacted {
+        sqlx::query("UPDATE instances SET name = '', bottle_name = '', executable_path = '', data = json_object('id', id) WHERE removed_at IS NOT NULL AND name != ''")
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        sqlx::query("DELETE FROM tags").execute(&mut *tx).await.map_err(db_err)?;
+    }
+    tx.commit().await.map_err(db_err)
+}
+
+// 按快照的先后写入数据库：序号在读取快照前分配，已写入更新的快照时跳过旧快照，
+// 后台任务完成的顺序不同也不会用旧数据覆盖新数据
+async fn sync_snapshot(seq: u64, values: &[Value], redacted: bool) -> Result<(), String> {
+    let mut applied = SYNC_APPLIED.lock().await;
+    if *applied > seq {
+        return Ok(());
+    }
+    sync_instances(values, redacted).await?;
+    *applied = seq;
+    Ok(())
+}
+
+fn next_sync_seq() -> u64 {
+    SYNC_SEQ.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+// 保存 instances.json 后在后台同步，不阻塞保存
+pub fn sync_in_background(app: &AppHandle, values: Vec<Value>) {
+    if POOL.get().is_none() {
+        return;
+    }
+    let seq = next_sync_seq();
+    let redacted = is_encrypted(app);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = sync_snapshot(seq, &values, redacted).await {
+            log_warn!("同步实例到数据库失败: {}", e);
+        }
+    });
+}
+
+// 从回收站彻底删除的实例连同会话与标签一起删除，在后台进行
+pub fn forget_in_background(ids: Vec<String>) {
+    if POOL.get().is_none() || ids.is_empty() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        // 与实例快照的同步互斥，避免正在写入的快照与删除交错
+        let _applied = SYNC_APPLIED.lock().await;
+        let result: Result<(), String> = async {
+            let mut tx = pool()?.begin().await.map_err(db_err)?;
+            for id in &ids {
+                sqlx::query("DELETE FROM instances WHERE id = ?").bind(id).execute(&mut *tx).await.map_err(db_err)?;
+            }
+            tx.commit().await.map_err(db_err)
+        }
+        .await;
+        if let Err(e) = result {
+            log_warn!("从数据库删除实例失败: {}", e);
+        }
+    });
+}
+
+// 首次运行时导入 instances.json，并把 playHistory 中按天汇总的时长转为会话记录
+async fn migrate_from_json(app: &AppHandle, pool: &SqlitePool) -> Result<(), String> {
+    let migrated: Option<String> = sqlx::query_scalar("SELECT value FROM meta WHERE key = ?")
+        .bind(MIGRATED_KEY)
+        .fetch_optional(pool)
+        .await
+        .map_err(db_err)?;
+    if migrated.is_some() {
+        return Ok(());
+    }
+
+    // 迁移前先备份，导入出错时可以恢复
+    storage::create_backup(app, "migration")?;
+
+    // 规范形式的路径，playHistory 已合并时长记录
+    let values: Vec<Value> = storage::stored_values(app)?;
+
+    let mut tx = pool.begin().await.map_err(db_err)?;
+    let mut sessions = 0;
+    let redacted = is_encrypted(app);
+    for inst in values.iter().filter(|v| v["id"].is_string()) {
+        upsert_instance_value(&mut tx, &if redacted { redact(inst) } else { inst.clone() }).await?;
+        let id = inst["id"].as_str().unwrap_or_default();
+        if let Some(history) = inst["playHistory"].as_object() {
+            for (day, secs) in history {
+                let (started_at, duration) = match (day_to_timestamp(day), secs.as_i64()) {
+                    (Some(t), Some(d)) if d > 0 => (t, d),
+                    _ => continue,
+                };
+                sqlx::query("INSERT INTO sessions (instance_id, started_at, ended_at, duration_sec) VALUES (?, ?, ?, ?)")
+                    .bind(id)
+                    .bind(started_at)
+                    .bind(started_at + duration)
+                    .bind(duration)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_err)?;
+                sessions += 1;
+            }
+        }
+    }
+    sqlx::query("INSERT INTO meta (key, value) VALUES (?, ?)")
+        .bind(MIGRATED_KEY)
+        .bind(unix_now().to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    tx.commit().await.map_err(db_err)?;
+
+    log_info!("已从 instances.json 导入 {} 个实例、{} 条会话记录", values.len(), sessions);
+    Ok(())
+}
+
+// 启动时打开数据库、建表，并在首次运行时迁移旧数据
+pub async fn init(app: &AppHandle) -> Result<(), String> {
+    if POOL.get().is_some() {
+        return Ok(());
+    }
+    // 数据库只保存在本机，数据目录位于 iCloud 等同步目录时也不会随之同步
+    let path = storage::local_data_path(app, &libraries::db_file_name(app, DB_FILENAME))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let options = SqliteConnectOptions::new()
+        .filename(&path)
+        .create_if_missing(true)
+        .foreign_keys(true)
+        // 删除或覆盖的内容清零，游戏库加密后不会在空闲页中留下明文
+        .pragma("secure_delete", "ON");
+    let pool = SqlitePoolOptions::new()
+        .max_connections(4)
+        .connect_with(options)
+        .await
+        .map_err(db_err)?;
+
+    sqlx::raw_sql(SCHEMA).execute(&pool).await.map_err(db_err)?;
+    // 早期版本的 instances 表没有 removed_at 列
+    let has_removed_at: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info('instances') WHERE name = 'removed_at'")
+        .fetch_one(&pool)
+        .await
+        .map_err(db_err)?;
+    if has_removed_at == 0 {
+        sqlx::query("ALTER TABLE instances ADD COLUMN removed_at INTEGER").execute(&pool).await.map_err(db_err)?;
+    }
+    migrate_from_json(app, &pool).await?;
+    let _ = POOL.set(pool);
+    Ok(())
+}
+
+pub(crate) fn check_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim();
+    if tag.is_empty() || tag.chars().count() > 64 {
+        return Err(format!("无效的标签: {}", tag));
+    }
+    Ok(tag.to_string())
+}
+
+pub async fn all_sessions() -> Result<Vec<SessionRow>, String> {
+    sqlx::query_as::<_, SessionRow>("SELECT * FROM sessions ORDER BY instance_id, started_at")
+        .fetch_all(pool()?)
+        .await
+        .map_err(db_err)
+}
+
+// 导入其他设备导出的会话与标签，只处理本机已存在的实例。
+// replace 为 true 时先清空这些实例原有的记录；否则按开始时间去重后合并。游戏库加密时标签只随实例保存，不写入 tags 表
+pub async fn import_history(app: &AppHandle, sessions: &[SessionRow], tags: &[TagRow], replace: bool) -> Result<usize, String> {
+    let tags = if is_encrypted(app) { &[] } else { tags };
+    let mut tx = pool()?.begin().await.map_err(db_err)?;
+    let known: HashSet<String> = sqlx::query_scalar::<_, String>("SELECT id FROM instances WHERE removed_at IS NULL")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_err)?
+        .into_iter()
+        .collect();
+
+    if replace {
+        let touched: HashSet<&str> = sessions.iter().map(|s| s.instance_id.as_str())
+            .chain(tags.iter().map(|t| t.instance_id.as_str()))
+            .collect();
+        for id in touched {
+            sqlx::query("DELETE FROM sessions WHERE instance_id = ?").bind(id).execute(&mut *tx).await.map_err(db_err)?;
+            sqlx::query("DELETE FROM tags WHERE instance_id = ?").bind(id).execute(&mut *tx).await.map_err(db_err)?;
+        }
+    }
+
+    let mut imported = 0;
+    for session in sessions.iter().filter(|s| known.contains(&s.instance_id)) {
+        let result = sqlx::query(
+            "INSERT INTO sessions (instance_id, started_at, ended_at, duration_sec)
+             SELECT ?, ?, ?, ? WHERE NOT EXISTS (SELECT 1 FROM sessions WHERE instance_id = ? AND started_at = ?)",
+        )
+        .bind(&session.instance_id)
+        .bind(session.started_at)
+        .bind(session.ended_at)
+        .bind(session.duration_sec)
+        .bind(&session.instance_id)
+        .bind(session.started_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+        imported += result.rows_affected() as usize;
+    }
+    for tag in tags.iter().filter(|t| known.contains(&t.instance_id)) {
+        sqlx::query("INSERT OR IGNORE INTO tags (instance_id, tag) VALUES (?, ?)")
+            .bind(&tag.instance_id)
+            .bind(&tag.tag)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+    }
+    tx.commit().await.map_err(db_err)?;
+    Ok(imported)
+}
+
+#[command]
+pub async fn db_list_instances(app: AppHandle) -> Result<Vec<InstanceRow>, String> {
+    // 不含回收站中的实例
+    let rows = sqlx::query("SELECT * FROM instances WHERE removed_at IS NULL AND json_extract(data, '$.deletedAt') IS NULL ORDER BY last_played DESC, name")
+        .fetch_all(pool()?)
+        .await
+        .map_err(db_err)?;
+    let mut rows = rows.iter().map(row_to_instance).collect::<Result<Vec<_>, _>>()?;
+    fill_from_library(&app, &mut rows);
+    Ok(rows)
+}
+
+#[command]
+pub async fn db_get_instance(app: AppHandle, id: String) -> Result<Option<InstanceRow>, String> {
+    let row = sqlx::query("SELECT * FROM instances WHERE id = ? AND removed_at IS NULL")
+        .bind(&id)
+        .fetch_optional(pool()?)
+        .await
+        .map_err(db_err)?;
+    let mut rows: Vec<InstanceRow> = row.as_ref().map(row_to_instance).transpose()?.into_iter().collect();
+    fill_from_library(&app, &mut rows);
+    Ok(rows.pop())
+}
+
+// 等待数据库与 instances.json 同步完成（包括回收站中的实例，其会话需要保留）
+pub(crate) async fn sync_library(app: &AppHandle) -> Result<(), String> {
+    let seq = next_sync_seq();
+    sync_snapshot(seq, &storage::stored_values(app)?, is_encrypted(app)).await
+}
+
+// 切换游戏库加密后按新设置重写数据库中的实例；开启加密时再整理数据库文件，不留下此前明文内容的残余页
+pub(crate) fn rewrite_in_background(app: &AppHandle) {
+    if POOL.get().is_none() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = async {
+            sync_library(&app).await?;
+            if is_encrypted(&app) {
+                sqlx::query("VACUUM").execute(pool()?).await.map_err(db_err)?;
+            }
+            Ok::<(), String>(())
+        }
+        .await;
+        if let Err(e) = result {
+            log_warn!("按加密设置重写数据库失败: {}", e);
+        }
+    });
+}
+
+// 游戏库加密时数据库中没有名称与完整数据，从 instances.json 补全
+fn fill_from_library(app: &AppHandle, rows: &mut [InstanceRow]) {
+    if !is_encrypted(app) {
+        return;
+    }
+    let instances = storage::read_instances(app);
+    for row in rows.iter_mut() {
+        if let Some(inst) = instances.iter().find(|i| i.id == row.id) {
+            row.name = inst.name.clone();
+            row.bottle_name = inst.bottle_name.clone();
+            row.executable_path = inst.executable_path.clone();
+            row.data = serde_json::to_value(inst).unwrap_or(Value::Null);
+        }
+    }
+}
+
+// 修改实例时仍写入 instances.json，再等待数据库同步完成，保证两者一致
+pub(crate) async fn update_instances<T>(app: &AppHandle, source: &str, f: impl FnOnce(&mut Vec<GameInstance>) -> Result<T, String>) -> Result<T, String> {
+    let result = storage::update_instances(app, source, None, f)?;
+    sync_library(app).await?;
+    Ok(result)
+}
+
+// 同 update_instances，但可以修改回收站中的实例
+pub(crate) async fn update_all_instances<T>(app: &AppHandle, source: &str, f: impl FnOnce(&mut Vec<GameInstance>) -> Result<T, String>) -> Result<T, String> {
+    let result = storage::update_all_instances(app, source, None, f)?;
+    sync_library(app).await?;
+    Ok(result)
+}
+
+// 以 instances 整体替换游戏库（包括回收站）。本机有而 instances 中没有的实例直接移除，不移入回收站；
+// 供覆盖导入与同步拉取使用，调用前应先备份
+pub(crate) async fn replace_instances(app: &AppHandle, source: &str, instances: Vec<GameInstance>) -> Result<(), String> {
+    update_all_instances(app, source, |all| {
+        *all = instances;
+        Ok(())
+    })
+    .await
+}
+
+#[command]
+pub async fn db_upsert_instance(app: AppHandle, instance: GameInstance) -> Result<InstanceRow, String> {
+    instance.validate()?;
+    let id = instance.id.clone();
+    update_instances(&app, "db_upsert_instance", |instances| {
+        match instances.iter_mut().find(|i| i.id == id) {
+            Some(existing) => *existing = instance,
+            None => instances.push(instance),
+        }
+        Ok(())
+    })
+    .await?;
+    db_get_instance(app, id.clone()).await?.ok_or(format!("未找到实例: {}", id))
+}
+
+// 删除实例：移入回收站，数据库中的行、会话与标签保留，从回收站彻底删除时才一并删除
+#[command]
+pub async fn db_delete_instance(app: AppHandle, id: String) -> Result<bool, String> {
+    update_instances(&app, "db_delete_instance", |instances| {
+        let before = instances.len();
+        instances.retain(|i| i.id != id);
+        Ok(instances.len() != before)
+    })
+    .await
+}
+
+#[command]
+pub async fn db_list_sessions(instance_id: String) -> Result<Vec<SessionRow>, String> {
+    sqlx::query_as::<_, SessionRow>("SELECT * FROM sessions WHERE instance_id = ? ORDER BY started_at DESC")
+        .bind(&instance_id)
+        .fetch_all(pool()?)
+        .await
+        .map_err(db_err)
+}
+
+#[command]
+pub async fn db_add_session(instance_id: String, started_at: i64, duration_sec: i64) -> Result<SessionRow, String> {
+    if duration_sec < 0 {
+        return Err("会话时长不能为负数".to_string());
+    }
+    sqlx::query_as::<_, SessionRow>(
+        "INSERT INTO sessions (instance_id, started_at, ended_at, duration_sec) VALUES (?, ?, ?, ?) RETURNING *",
+    )
+    .bind(&instance_id)
+    .bind(started_at)
+    .bind(started_at + duration_sec)
+    .bind(duration_sec)
+    .fetch_one(pool()?)
+    .await
+    .map_err(db_err)
+}
+
+#[command]
+pub async fn db_delete_session(id: i64) -> Result<bool, String> {
+    let result = sqlx::query("DELETE FROM sessions WHERE id = ?")
+        .bind(id)
+        .execute(pool()?)
+        .await
+        .map_err(db_err)?;
+    Ok(result.rows_affected() > 0)
+}
+
+// 不传 instance_id 时列出游戏库中所有实例的标签；游戏库加密时 tags 表为空，从 instances.json 读取
+#[command]
+pub async fn db_list_tags(app: AppHandle, instance_id: Option<String>) -> Result<Vec<TagRow>, String> {
+    if is_encrypted(&app) {
+        let mut rows: Vec<TagRow> = storage::read_instances(&app)
+            .into_iter()
+            .filter(|inst| instance_id.as_ref().is_none_or(|id| *id == inst.id))
+            .flat_map(|inst| inst.tags.into_iter().map(move |tag| TagRow { instance_id: inst.id.clone(), tag }))
+            .collect();
+        rows.sort_by(|a, b| (&a.instance_id, &a.tag).cmp(&(&b.instance_id, &b.tag)));
+        rows.dedup_by(|a, b| a.instance_id == b.instance_id && a.tag == b.tag);
+        return Ok(rows);
+    }
+    let query = match instance_id {
+        Some(id) => sqlx::query_as::<_, TagRow>("SELECT * FROM tags WHERE instance_id = ? ORDER BY tag").bind(id),
+        None => sqlx::query_as::<_, TagRow>("SELECT * FROM tags WHERE instance_id IN (SELECT id FROM instances WHERE removed_at IS NULL) ORDER BY instance_id, tag"),
+    };
+    query.fetch_all(pool()?).await.map_err(db_err)
+}
+
+// 整体替换某个实例的标签；标签随实例写入 instances.json，再同步到 tags 表
+#[command]
+pub async fn db_set_tags(app: AppHandle, instance_id: String, tags: Vec<String>) -> Result<Vec<TagRow>, String> {
+    let mut tags = tags.iter().map(|t| check_tag(t)).collect::<Result<Vec<_>, _>>()?;
+    tags.sort();
+    tags.dedup();
+    update_instances(&app, "db_set_tags", |instances| {
+        let inst = instances
+            .iter_mut()
+            .find(|i| i.id == instance_id)
+            .ok_or(format!("未找到实例: {}", instance_id))?;
+        inst.tags = tags;
+        Ok(())
+    })
+    .await?;
+    db_list_tags(app, Some(instance_id)).await
+}
@@ -0,0 +1,227 @@
+use tauri::{AppHandle, command};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::exe_info;
+use crate::installer;
+use crate::runner::expand_tilde;
+use crate::storage;
+
+// 扫描各容器中已经安装的程序，作为新实例的候选：
+// 注册表 Uninstall 键给出名称与安装目录，开始菜单快捷方式给出主程序路径
+const START_MENU_DIRS: [&str; 2] = ["ProgramData/Microsoft/Windows/Start Menu/Programs", "AppData/Roaming/Microsoft/Windows/Start Menu/Programs"];
+// 旧版 wine 的用户目录结构
+const LEGACY_START_MENU: &str = "Start Menu/Programs";
+const MAX_SHORTCUT_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GameProposal {
+    name: String,
+    bottle_name: String,
+    // 推测的游戏本体，找不到时为空，由用户从 candidates 中选择
+    executable_path: Option<String>,
+    candidates: Vec<String>,
+    publisher: Option<String>,
+    // registry / shortcut
+    source: String,
+}
+
+fn is_non_game(path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    installer::NON_GAME_HINTS.iter().any(|h| name.contains(h))
+}
+
+// 读取以 NUL 结尾的字符串，wide 为 true 时按 UTF-16 读取
+fn read_c_string(data: &[u8], offset: usize, wide: bool) -> Option<String> {
+    let tail = data.get(offset..)?;
+    if wide {
+        let units: Vec<u16> = tail.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).take_while(|u| *u != 0).collect();
+        String::from_utf16(&units).ok()
+    } else {
+        let end = tail.iter().position(|b| *b == 0)?;
+        Some(String::from_utf8_lossy(&tail[..end]).to_string())
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<usize> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+}
+
+// 从 .lnk 快捷方式（Shell Link 格式）中取出目标的本地路径
+fn read_lnk_target(path: &Path) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    if read_u32(&data, 0)? != 0x4C {
+        return None;
+    }
+    let flags = read_u32(&data, 0x14)?;
+    let mut offset = 0x4C;
+    // HasLinkTargetIDList
+    if flags & 0x1 != 0 {
+        let size = data.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)?;
+        offset += 2 + size;
+    }
+    // HasLinkInfo
+    if flags & 0x2 == 0 {
+        return None;
+    }
+    let header_size = read_u32(&data, offset + 4)?;
+    let info_flags = read_u32(&data, offset + 8)?;
+    // VolumeIDAndLocalBasePath
+    if info_flags & 0x1 == 0 {
+        return None;
+    }
+    // 头部较长时带有 Unicode 版本的路径
+    let (base, suffix) = if header_size >= 0x24 {
+        (read_u32(&data, offset + 0x1C)?, read_u32(&data, offset + 0x20)?)
+    } else {
+        (read_u32(&data, offset + 0x10)?, read_u32(&data, offset + 0x18)?)
+    };
+    let wide = header_size >= 0x24;
+    let base = read_c_string(&data, offset + base, wide)?;
+    let suffix = read_c_string(&data, offset + suffix, wide).unwrap_or_default();
+    Some(format!("{}{}", base, suffix))
+}
+
+fn collect_shortcuts(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    if depth > MAX_SHORTCUT_DEPTH {
+        return;
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_shortcuts(&path, depth + 1, out);
+        } else if path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("lnk")) {
+            out.push(path);
+        }
+    }
+}
+
+fn start_menu_dirs(drive_c: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![drive_c.join(START_MENU_DIRS[0])];
+    if let Ok(users) = fs::read_dir(drive_c.join("users")) {
+        for user in users.flatten() {
+            dirs.push(user.path().join(START_MENU_DIRS[1]));
+            dirs.push(user.path().join(LEGACY_START_MENU));
+        }
+    }
+    dirs
+}
+
+// 注册表中记录的已安装程序：DisplayIcon 通常指向主程序，否则在安装目录中挑选
+fn registry_proposals(bottle: &Path, bottle_name: &str) -> Vec<GameProposal> {
+    let mut proposals = Vec::new();
+    for program in installer::read_programs(bottle) {
+        let install_dir = program.install_location.as_deref().and_then(|loc| installer::windows_to_unix(bottle, loc));
+        let icon_exe = program
+            .display_icon
+            .as_deref()
+            .map(|icon| icon.split(',').next().unwrap_or(icon).trim_matches('"'))
+            .and_then(|icon| installer::windows_to_unix(bottle, icon))
+            .filter(|p| p.is_file() && exe_info::is_windows_launchable(p) && !is_non_game(p));
+
+        let exes: Vec<PathBuf> = match &install_dir {
+            Some(dir) if dir.is_dir() && !dir.starts_with(bottle.join("drive_c/windows")) => {
+                installer::snapshot_exes(dir).into_iter().map(|(path, _)| path).collect()
+            }
+            _ => Vec::new(),
+        };
+        if icon_exe.is_none() && exes.is_empty() {
+            continue;
+        }
+        let candidates: Vec<String> = installer::rank_candidates(exes, install_dir.as_slice())
+            .into_iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let executable_path = icon_exe
+            .map(|p| p.to_string_lossy().to_string())
+            .or_else(|| candidates.first().filter(|p| !is_non_game(Path::new(p))).cloned());
+
+        proposals.push(GameProposal {
+            name: program.name,
+            bottle_name: bottle_name.to_string(),
+            executable_path,
+            candidates,
+            publisher: program.publisher,
+            source: "registry".to_string(),
+        });
+    }
+    proposals
+}
+
+// 开始菜单中指向容器内 .exe 的快捷方式，名称取快捷方式的文件名
+fn shortcut_proposals(bottle: &Path, bottle_name: &str) -> Vec<GameProposal> {
+    let drive_c = bottle.join("drive_c");
+    let mut shortcuts = Vec::new();
+    for dir in start_menu_dirs(&drive_c) {
+        collect_shortcuts(&dir, 0, &mut shortcuts);
+    }
+    shortcuts
+        .into_iter()
+        .filter_map(|lnk| {
+            let target = installer::windows_to_unix(bottle, &read_lnk_target(&lnk)?)?;
+            if !target.is_file() || !exe_info::is_windows_launchable(&target) || is_non_game(&target) || target.starts_with(drive_c.join("windows")) {
+                return None;
+            }
+            let target = target.to_string_lossy().to_string();
+            Some(GameProposal {
+                name: lnk.file_stem()?.to_string_lossy().to_string(),
+                bottle_name: bottle_name.to_string(),
+                executable_path: Some(target.clone()),
+                candidates: vec![target],
+                publisher: None,
+                source: "shortcut".to_string(),
+            })
+        })
+        .collect()
+}
+
+// 扫描所有容器中已安装、但还没有对应实例的程序，作为新实例的候选。
+// 同一主程序同时出现在注册表与快捷方式中时只保留注册表的结果
+#[command]
+pub async fn discover_bottle_games(app: AppHandle, bottles_path: String) -> Result<Vec<GameProposal>, String> {
+    let root = expand_tilde(&bottles_path);
+    if !root.is_dir() {
+        return Err(format!("未找到容器目录: {:?}", root));
+    }
+    let known: HashSet<String> = storage::read_instances(&app).into_iter().map(|i| i.executable_path).collect();
+
+    tokio::task::spawn_blocking(move || {
+        let mut bottles: Vec<PathBuf> = fs::read_dir(&root)
+            .map_err(|e| format!("无法读取容器目录: {}", e))?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.join("drive_c").is_dir())
+            .collect();
+        bottles.sort();
+
+        let mut seen = known;
+        let mut proposals = Vec::new();
+        for bottle in bottles {
+            let bottle_name = bottle.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let found: Vec<GameProposal> = registry_proposals(&bottle, &bottle_name)
+                .into_iter()
+                .chain(shortcut_proposals(&bottle, &bottle_name))
+                .collect();
+            for proposal in found {
+                // 已有实例使用候选中的任意一个可执行文件时视为已添加
+                if proposal.candidates.iter().chain(proposal.executable_path.iter()).any(|p| seen.contains(p)) {
+                    continue;
+                }
+                if let Some(exe) = &proposal.executable_path {
+                    seen.insert(exe.clone());
+                }
+                proposals.push(proposal);
+            }
+        }
+        log_info!("在容器中发现 {} 个尚未添加的程序", proposals.len());
+        Ok(proposals)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,149 @@
+use tauri::command;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+use crate::runner::expand_tilde;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MountedImage {
+    image_path: String,
+    mount_point: String,
+    device: String,
+    // 映射到容器中的盘符（如 "d:"），未映射时为空
+    bottle_path: Option<String>,
+    drive: Option<String>,
+}
+
+static MOUNTED_IMAGES: OnceLock<Mutex<Vec<MountedImage>>> = OnceLock::new();
+
+fn mounted_images() -> &'static Mutex<Vec<MountedImage>> {
+    MOUNTED_IMAGES.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+// .mds 只是描述文件，真正的数据在同名 .mdf 中
+fn resolve_image_file(path: &Path) -> Result<PathBuf, String> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "iso" | "cdr" | "dmg" | "mdf" => Ok(path.to_path_buf()),
+        "mds" => {
+            let mdf = path.with_extension("mdf");
+            let mdf_upper = path.with_extension("MDF");
+            if mdf.exists() {
+                Ok(mdf)
+            } else if mdf_upper.exists() {
+                Ok(mdf_upper)
+            } else {
+                Err(format!("找不到与 {:?} 对应的 .mdf 数据文件", path))
+            }
+        }
+        _ => Err(format!("不支持的镜像格式: {}", ext)),
+    }
+}
+
+// 解析 hdiutil attach 的输出，取第一行带挂载点的记录
+fn parse_attach_output(stdout: &str) -> Option<(String, String)> {
+    for line in stdout.lines() {
+        let fields: Vec<&str> = line.split('\t').map(|f| f.trim()).collect();
+        if let (Some(device), Some(mount)) = (fields.first(), fields.last()) {
+            if mount.starts_with('/') && device.starts_with("/dev/") {
+                return Some((device.to_string(), mount.to_string()));
+            }
+        }
+    }
+    None
+}
+
+// 在容器 dosdevices 中找到第一个空闲盘符
+fn find_free_drive(dosdevices: &Path) -> Option<String> {
+    ('d'..='y')
+        .map(|c| format!("{}:", c))
+        .find(|d| fs::symlink_metadata(dosdevices.join(d)).is_err())
+}
+
+#[command]
+pub fn mount_disk_image(image_path: String, bottle_path: Option<String>) -> Result<MountedImage, String> {
+    let image = expand_tilde(&image_path);
+    if !image.is_file() {
+        return Err(format!("找不到镜像文件: {:?}", image));
+    }
+    let data_file = resolve_image_file(&image)?;
+
+    let mut cmd = Command::new("hdiutil");
+    cmd.arg("attach").arg("-nobrowse").arg("-readonly");
+    if data_file.extension().and_then(|e| e.to_str()).map(|e| e.eq_ignore_ascii_case("mdf")).unwrap_or(false) {
+        // MDF 按原始光盘镜像挂载
+        cmd.arg("-imagekey").arg("diskimage-class=CRawDiskImage");
+    }
+    let output = cmd.arg(&data_file).output().map_err(|e| format!("执行 hdiutil 失败: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("挂载镜像失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (device, mount_point) = parse_attach_output(&stdout).ok_or("镜像已挂载但未找到挂载点")?;
+
+    let mut mounted = MountedImage {
+        image_path: image.to_string_lossy().to_string(),
+        mount_point,
+        device,
+        bottle_path: None,
+        drive: None,
+    };
+
+    // 映射为容器内的光驱盘符，供安装程序访问
+    if let Some(bottle) = bottle_path.filter(|b| !b.is_empty()) {
+        let dosdevices = expand_tilde(&bottle).join("dosdevices");
+        let drive = find_free_drive(&dosdevices).ok_or("容器中没有可用的盘符")?;
+        std::os::unix::fs::symlink(&mounted.mount_point, dosdevices.join(&drive))
+            .map_err(|e| format!("映射盘符失败: {}", e))?;
+        mounted.bottle_path = Some(bottle);
+        mounted.drive = Some(drive);
+    }
+
+    log_info!("镜像已挂载: {} -> {}", mounted.image_path, mounted.mount_point);
+    if let Ok(mut list) = mounted_images().lock() {
+        list.push(mounted.clone());
+    }
+    Ok(mounted)
+}
+
+pub(crate) fn unmount_image(mount_point: &str) -> Result<(), String> {
+    let record = mounted_images()
+        .lock()
+        .ok()
+        .and_then(|list| list.iter().find(|m| m.mount_point == mount_point).cloned());
+
+    // 先移除容器中的盘符映射
+    if let Some(MountedImage { bottle_path: Some(bottle), drive: Some(drive), .. }) = record.as_ref() {
+        let _ = fs::remove_file(expand_tilde(bottle).join("dosdevices").join(drive));
+    }
+
+    let output = Command::new("hdiutil")
+        .arg("detach")
+        .arg(mount_point)
+        .output()
+        .map_err(|e| format!("执行 hdiutil 失败: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("卸载镜像失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    if let Ok(mut list) = mounted_images().lock() {
+        list.retain(|m| m.mount_point != mount_point);
+    }
+    Ok(())
+}
+
+#[command]
+pub fn unmount_disk_image(mount_point: String) -> Result<(), String> {
+    unmount_image(&mount_point)
+}
+
+#[command]
+pub fn list_mounted_images() -> Vec<MountedImage> {
+    mounted_images().lock().map(|list| list.clone()).unwrap_or_default()
+}
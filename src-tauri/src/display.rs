@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use crate::registry;
+
+const DESKTOP_KEY: &str = "Control Panel\\Desktop";
+const FONTS_KEY: &str = "System\\CurrentControlSet\\Hardware Profiles\\Current\\Software\\Fonts";
+const MAC_DRIVER_KEY: &str = "Software\\Wine\\Mac Driver";
+// Windows 允许的缩放范围：100% ~ 500%
+const MIN_DPI: u32 = 96;
+const MAX_DPI: u32 = 480;
+
+fn dword(value: u32) -> String {
+    format!("dword:{:08x}", value)
+}
+
+// 启动前写入实例的 DPI（LogPixels）与 Retina 模式，已一致时跳过以免拖慢启动
+pub fn apply_display_settings(
+    crossover_app_path: &str,
+    bottle_path: &Path,
+    dpi: Option<u32>,
+    retina_mode: Option<bool>,
+) -> Result<(), String> {
+    let user_reg = bottle_path.join("user.reg");
+    let system_reg = bottle_path.join("system.reg");
+    let mut body = String::new();
+
+    if let Some(dpi) = dpi {
+        if !(MIN_DPI..=MAX_DPI).contains(&dpi) {
+            return Err(format!("DPI 需在 {} 到 {} 之间", MIN_DPI, MAX_DPI));
+        }
+        // 新版 wine 读取 HKCU 下的值，旧版读取 HKLM 下的硬件配置
+        let value = dword(dpi);
+        if !registry::value_matches(&user_reg, DESKTOP_KEY, "LogPixels", &value) || !registry::value_matches(&system_reg, FONTS_KEY, "LogPixels", &value) {
+            body.push_str(&format!("[HKEY_CURRENT_USER\\{}]\n\"LogPixels\"={}\n\n", DESKTOP_KEY, value));
+            body.push_str(&format!("[HKEY_LOCAL_MACHINE\\{}]\n\"LogPixels\"={}\n\n", FONTS_KEY, value));
+        }
+    }
+
+    // CrossOver 的高分辨率模式即 Mac 驱动的 RetinaMode，开启后按物理像素渲染
+    if let Some(retina) = retina_mode {
+        let value = if retina { "y" } else { "n" };
+        if !registry::value_matches(&user_reg, MAC_DRIVER_KEY, "RetinaMode", value) {
+            body.push_str(&format!("[HKEY_CURRENT_USER\\{}]\n\"RetinaMode\"=\"{}\"\n\n", MAC_DRIVER_KEY, value));
+        }
+    }
+
+    if body.is_empty() {
+        return Ok(());
+    }
+    log_info!("正在为容器 {:?} 写入显示设置", bottle_path);
+    registry::wine_reg_import(crossover_app_path, bottle_path, &body)
+}
@@ -0,0 +1,52 @@
+use std::fs;
+use std::path::Path;
+
+// 根据游戏目录中的特征文件推断引擎类型
+pub fn detect_engine(game_dir: &Path) -> &'static str {
+    let mut file_names = Vec::new();
+    let mut dir_names = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(game_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            match entry.file_type() {
+                Ok(ft) if ft.is_dir() => dir_names.push(name),
+                Ok(_) => file_names.push(name),
+                Err(_) => continue,
+            }
+        }
+    }
+
+    let has_file = |name: &str| file_names.iter().any(|f| f == name);
+    let has_ext = |ext: &str| file_names.iter().any(|f| f.ends_with(ext));
+    let has_dir = |name: &str| dir_names.iter().any(|d| d == name);
+
+    if has_dir("renpy") || game_dir.join("game").join("script.rpyc").exists() || has_ext(".rpa") {
+        "renpy"
+    } else if has_ext(".xp3") {
+        "kirikiri"
+    } else if has_file("siglusengine.exe") || has_file("scene.pck") {
+        "siglus"
+    } else if has_file("reallive.exe") || has_file("seen.txt") {
+        "reallive"
+    } else if has_file("nscript.dat") || has_file("0.txt") || has_file("00.txt") || has_ext(".nsa") {
+        "onscripter"
+    } else if has_file("bgi.exe") || has_file("bgi.gdb") || has_file("sysprg.arc") {
+        "bgi"
+    } else if has_file("cs2.exe") || has_file("config.int") {
+        "catsystem2"
+    } else if has_ext(".pfs") {
+        "artemis"
+    } else if has_ext(".ypf") {
+        "yuris"
+    } else if has_file("unityplayer.dll") || dir_names.iter().any(|d| d.ends_with("_data")) {
+        "unity"
+    } else if has_ext(".rgss3a") || has_ext(".rgss2a") || has_ext(".rgssad")
+        || game_dir.join("www/js/rpg_core.js").exists()
+        || game_dir.join("js/rmmz_core.js").exists()
+    {
+        "rpgmaker"
+    } else {
+        "unknown"
+    }
+}
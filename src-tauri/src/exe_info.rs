@@ -0,0 +1,163 @@
+use tauri::command;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::bottle;
+use crate::runner::{self, expand_tilde};
+
+const IMAGE_FILE_MACHINE_I386: u16 = 0x014c;
+const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+const IMAGE_FILE_MACHINE_ARM64: u16 = 0xaa64;
+const PE32_MAGIC: u16 = 0x10b;
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+const IMAGE_SUBSYSTEM_WINDOWS_GUI: u16 = 2;
+// 数据目录中 CLR 运行时头的序号，非空即为 .NET 程序
+const CLR_RUNTIME_HEADER_INDEX: usize = 14;
+// wine32on64 从 CrossOver 19 开始提供，更早的版本无法在新版 macOS 上运行 32 位程序
+const MIN_CROSSOVER_FOR_32BIT: u32 = 19;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PeInfo {
+    // i386 / amd64 / arm64 / unknown
+    pub machine: String,
+    pub bitness: Option<u32>,
+    pub is_dotnet: bool,
+    // GUI 程序；控制台程序通常是工具或配置器
+    pub gui: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExeReport {
+    pub info: Option<PeInfo>,
+    pub warnings: Vec<String>,
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes([*buf.get(offset)?, *buf.get(offset + 1)?]))
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes([*buf.get(offset)?, *buf.get(offset + 1)?, *buf.get(offset + 2)?, *buf.get(offset + 3)?]))
+}
+
+// 解析 PE 头：COFF 头中的 Machine，以及可选头中的子系统与 CLR 数据目录
+pub fn read_pe_info(path: &Path) -> Option<PeInfo> {
+    let mut file = File::open(path).ok()?;
+
+    let mut dos_header = [0u8; 64];
+    file.read_exact(&mut dos_header).ok()?;
+    if &dos_header[..2] != b"MZ" {
+        return None;
+    }
+
+    // e_lfanew 位于 0x3C，指向 PE 签名
+    let pe_offset = u32_at(&dos_header, 60)?;
+    file.seek(SeekFrom::Start(pe_offset as u64)).ok()?;
+
+    // PE 签名 (4) + COFF 头 (20) + 可选头（PE32+ 含数据目录最多 240 字节）
+    let mut header = vec![0u8; 4 + 20 + 240];
+    let read = file.read(&mut header).ok()?;
+    header.truncate(read);
+    if header.get(..4)? != b"PE\0\0" {
+        return None;
+    }
+
+    let machine = u16_at(&header, 4)?;
+    let (machine_name, bitness) = match machine {
+        IMAGE_FILE_MACHINE_I386 => ("i386", Some(32)),
+        IMAGE_FILE_MACHINE_AMD64 => ("amd64", Some(64)),
+        IMAGE_FILE_MACHINE_ARM64 => ("arm64", Some(64)),
+        _ => ("unknown", None),
+    };
+
+    let opt = 24;
+    let (dirs_count_offset, dirs_offset) = match u16_at(&header, opt) {
+        Some(PE32_MAGIC) => (opt + 92, opt + 96),
+        Some(PE32_PLUS_MAGIC) => (opt + 108, opt + 112),
+        _ => (0, 0),
+    };
+    let gui = u16_at(&header, opt + 68) == Some(IMAGE_SUBSYSTEM_WINDOWS_GUI);
+    let is_dotnet = dirs_offset > 0
+        && u32_at(&header, dirs_count_offset).is_some_and(|n| n as usize > CLR_RUNTIME_HEADER_INDEX)
+        && u32_at(&header, dirs_offset + CLR_RUNTIME_HEADER_INDEX * 8).is_some_and(|rva| rva != 0);
+
+    Some(PeInfo { machine: machine_name.to_string(), bitness, is_dotnet, gui })
+}
+
+// 返回可执行文件的位数（32 / 64），无法解析时返回 None
+pub fn read_pe_bitness(path: &Path) -> Option<u32> {
+    read_pe_info(path)?.bitness
+}
+
+// 可由 wine 直接运行的文件：.exe，以及通过 msiexec 安装的 .msi
+pub fn is_windows_launchable(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref(),
+        Some("exe") | Some("msi")
+    )
+}
+
+pub fn is_msi(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("msi"))
+}
+
+fn crossover_major_version(crossover_app_path: &str) -> Option<u32> {
+    runner::read_crossover_version(crossover_app_path)?.split('.').next()?.trim().parse().ok()
+}
+
+// 容器中是否装过 .NET Framework（winetricks 记录或系统目录中的 clr.dll）
+fn bottle_has_dotnet(bottle_path: &Path) -> bool {
+    let logged = fs::read_to_string(bottle_path.join("winetricks.log"))
+        .map(|t| t.lines().any(|l| l.trim().starts_with("dotnet")))
+        .unwrap_or(false);
+    logged || bottle_path.join("drive_c/windows/Microsoft.NET/Framework/v4.0.30319/clr.dll").exists()
+}
+
+// 根据 PE 信息、容器位数与 CrossOver 版本给出启动前的提示
+pub fn launch_warnings(info: &PeInfo, bottle_path: &Path, crossover_app_path: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let bottle_64 = bottle::bottle_is_64bit(bottle_path);
+
+    if info.machine == "arm64" {
+        warnings.push("这是 ARM64 版本的 Windows 程序，CrossOver 无法运行，请改用 x86/x64 版本".to_string());
+    }
+    match info.bitness {
+        Some(64) if !bottle_64 => {
+            warnings.push("这是 64 位程序，但所选容器是 32 位的，无法运行".to_string());
+        }
+        Some(32) => {
+            if bottle_64 && !bottle_path.join("drive_c/windows/syswow64/kernel32.dll").exists() {
+                warnings.push("这是 32 位程序，但所选容器缺少 32 位系统组件（syswow64），可能无法运行".to_string());
+            }
+            if crossover_major_version(crossover_app_path).is_some_and(|v| v < MIN_CROSSOVER_FOR_32BIT) {
+                warnings.push(format!("CrossOver {} 之前的版本无法在新版 macOS 上运行 32 位程序", MIN_CROSSOVER_FOR_32BIT));
+            }
+        }
+        _ => {}
+    }
+    if info.is_dotnet && !bottle_has_dotnet(bottle_path) {
+        warnings.push(".NET 程序：容器中未检测到 .NET Framework，若启动失败请通过 winetricks 安装 dotnet48".to_string());
+    }
+    if !info.gui {
+        warnings.push("这是控制台程序，可能是配置工具或启动器而非游戏本体".to_string());
+    }
+    warnings
+}
+
+// 选择可执行文件或启动前检查：解析 PE 头并给出与容器、CrossOver 版本不匹配的警告
+#[command]
+pub fn inspect_exe(exe_path: String, bottle_path: String, crossover_app_path: String) -> Result<ExeReport, String> {
+    let exe = expand_tilde(&exe_path);
+    if !exe.is_file() {
+        return Err(format!("找不到可执行文件: {:?}", exe));
+    }
+    if is_msi(&exe) {
+        return Ok(ExeReport { info: None, warnings: Vec::new() });
+    }
+
+    let info = read_pe_info(&exe).ok_or("无法解析 PE 文件头，可能不是 Windows 可执行文件")?;
+    let warnings = launch_warnings(&info, &expand_tilde(&bottle_path), &crossover_app_path);
+    Ok(ExeReport { info: Some(info), warnings })
+}
@@ -0,0 +1,212 @@
+use tauri::{AppHandle, Emitter, command};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+
+use crate::db;
+use crate::instance::GameInstance;
+use crate::runner::expand_tilde;
+use crate::storage;
+
+// 从其他游戏库管理软件导入目录与游玩时长，方便从 Windows 迁移过来的用户。
+// Windows 上的安装路径在 Mac 上无法直接使用，原样保存在 extra 中，可执行文件需要用户重新指定
+const FORMATS: [&str; 1] = ["playnite"];
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ExternalImportReport {
+    added: usize,
+    // 游戏库中已有同名或同一来源的实例
+    skipped: usize,
+    // 导入的总游玩时长（秒）
+    play_time: u64,
+}
+
+// 按字段名取值，不区分大小写（不同的导出插件有 PascalCase 与 camelCase 两种写法）
+fn field<'a>(game: &'a Map<String, Value>, name: &str) -> Option<&'a Value> {
+    game.get(name).or_else(|| game.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v))
+}
+
+fn field_str(game: &Map<String, Value>, name: &str) -> Option<String> {
+    field(game, name).and_then(|v| v.as_str()).map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+// 字符串列表，元素可以是字符串或带 Name 字段的对象（Playnite 的标签、开发商等）
+fn field_names(game: &Map<String, Value>, name: &str) -> Vec<String> {
+    let list = match field(game, name) {
+        Some(Value::Array(list)) => list,
+        _ => return Vec::new(),
+    };
+    list.iter()
+        .filter_map(|v| match v {
+            Value::String(s) => Some(s.trim().to_string()),
+            Value::Object(obj) => field_str(obj, "Name"),
+            _ => None,
+        })
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+// 解析 ISO 8601 时间（如 2023-05-01T12:34:56.789+08:00），返回 Unix 毫秒
+fn parse_iso_millis(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (date, rest) = text.split_at(text.find('T').unwrap_or(text.len()));
+    let mut parts = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (y, m, d) = (parts.next()??, parts.next()??, parts.next()??);
+
+    let rest = rest.trim_start_matches('T');
+    let (time, offset_secs) = match rest.rfind(['+', '-', 'Z']) {
+        Some(pos) if rest[pos..].starts_with('Z') => (&rest[..pos], 0),
+        Some(pos) => {
+            let sign = if rest[pos..].starts_with('-') { -1 } else { 1 };
+            let mut hm = rest[pos + 1..].split(':').map(|p| p.parse::<i64>().ok());
+            let (h, min) = (hm.next()??, hm.next().flatten().unwrap_or(0));
+            (&rest[..pos], sign * (h * 3600 + min * 60))
+        }
+        None => (rest, 0),
+    };
+    let mut hms = time.split(':');
+    let hour: i64 = hms.next().filter(|s| !s.is_empty()).map(|s| s.parse().ok()).unwrap_or(Some(0))?;
+    let minute: i64 = hms.next().map(|s| s.parse().ok()).unwrap_or(Some(0))?;
+    let second: f64 = hms.next().map(|s| s.parse().ok()).unwrap_or(Some(0.0))?;
+
+    // 公历日期转为距 1970-01-01 的天数
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let secs = days * 86400 + hour * 3600 + minute * 60 - offset_secs;
+    let millis = secs as f64 * 1000.0 + second * 1000.0;
+    (millis > 0.0).then_some(millis as u64)
+}
+
+// 去掉简介中的 HTML 标签（Playnite 的简介通常是 HTML）
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&nbsp;", " ");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Playnite 的启动动作中的路径可能引用 {InstallDir} 变量
+fn playnite_executable(game: &Map<String, Value>, install_dir: Option<&str>) -> Option<String> {
+    let actions = match field(game, "GameActions") {
+        Some(Value::Array(list)) => list,
+        _ => return None,
+    };
+    let action = actions
+        .iter()
+        .filter_map(|a| a.as_object())
+        .find(|a| field(a, "IsPlayAction").and_then(|v| v.as_bool()).unwrap_or(true))?;
+    let path = field_str(action, "Path")?;
+    Some(path.replace("{InstallDir}", install_dir.unwrap_or("")))
+}
+
+fn from_playnite(game: &Map<String, Value>) -> Option<GameInstance> {
+    let name = field_str(game, "Name")?;
+    let source_id = field_str(game, "Id").or_else(|| field_str(game, "GameId"))?;
+
+    let mut extra = Map::new();
+    extra.insert("importedFrom".to_string(), Value::String("playnite".to_string()));
+    extra.insert("externalId".to_string(), Value::String(source_id.clone()));
+    let developers = field_names(game, "Developers");
+    if !developers.is_empty() {
+        extra.insert("developer".to_string(), Value::String(developers.join(", ")));
+    }
+    let install_dir = field_str(game, "InstallDirectory");
+    if let Some(dir) = &install_dir {
+        extra.insert("originalInstallDir".to_string(), Value::String(dir.clone()));
+    }
+    if let Some(exe) = playnite_executable(game, install_dir.as_deref()) {
+        extra.insert("originalExecutable".to_string(), Value::String(exe));
+    }
+
+    let mut tags = field_names(game, "Tags");
+    tags.dedup();
+    // Playnite 的评分为 0-100，换算为 1-10
+    let rating = field(game, "UserScore")
+        .and_then(|v| v.as_f64())
+        .filter(|s| *s > 0.0)
+        .map(|s| (s / 10.0).round().clamp(1.0, 10.0) as u8);
+
+    Some(GameInstance {
+        id: format!("playnite-{}", source_id.replace(['/', '\\', '.'], "")),
+        name,
+        info: field_str(game, "Description").map(|d| strip_html(&d)).unwrap_or_default(),
+        tags,
+        last_played: field_str(game, "LastActivity").and_then(|t| parse_iso_millis(&t)),
+        total_play_time: field(game, "Playtime").and_then(|v| v.as_u64()).filter(|t| *t > 0),
+        favorite: field(game, "Favorite").and_then(|v| v.as_bool()).unwrap_or(false),
+        rating,
+        extra,
+        ..Default::default()
+    })
+}
+
+// 导出文件可以是游戏数组，也可以是带 Games 字段的对象
+fn read_games(path: &str) -> Result<Vec<Map<String, Value>>, String> {
+    let text = fs::read_to_string(expand_tilde(path)).map_err(|e| format!("无法读取导入文件: {}", e))?;
+    let value: Value = serde_json::from_str(&text).map_err(|e| format!("导入文件不是有效的 JSON: {}", e))?;
+    let list = match value {
+        Value::Array(list) => list,
+        Value::Object(obj) => match field(&obj, "Games") {
+            Some(Value::Array(list)) => list.clone(),
+            _ => return Err("导入文件中没有游戏列表".to_string()),
+        },
+        _ => return Err("导入文件中没有游戏列表".to_string()),
+    };
+    Ok(list.into_iter().filter_map(|v| match v {
+        Value::Object(obj) => Some(obj),
+        _ => None,
+    }).collect())
+}
+
+// 从其他软件的导出文件导入游戏库，目前支持 Playnite 的 JSON 导出。
+// 已有同名或同一来源的实例会被跳过，不覆盖本机数据
+#[command]
+pub async fn import_external_library(app: AppHandle, path: String, format: String) -> Result<ExternalImportReport, String> {
+    if !FORMATS.contains(&format.as_str()) {
+        return Err(format!("不支持的导入格式: {}", format));
+    }
+    let games = read_games(&path)?;
+    let mut report = ExternalImportReport::default();
+    let imported: Vec<GameInstance> = games.iter().filter_map(from_playnite).collect();
+    report.skipped = games.len() - imported.len();
+    if imported.is_empty() {
+        return Err("导入文件中没有可识别的游戏".to_string());
+    }
+
+    storage::create_backup(&app, "import")?;
+    report = db::update_instances(&app, "import_external_library", |instances| {
+        for inst in imported {
+            let exists = instances.iter().any(|i| i.id == inst.id || i.name.to_lowercase() == inst.name.to_lowercase());
+            if exists {
+                report.skipped += 1;
+                continue;
+            }
+            report.added += 1;
+            report.play_time += inst.total_play_time.unwrap_or(0);
+            instances.push(inst);
+        }
+        Ok(report)
+    })
+    .await?;
+
+    log_info!("已从 {} 导入 {} 个游戏，跳过 {} 个", format, report.added, report.skipped);
+    let _ = app.emit("instances-restored", "import");
+    Ok(report)
+}
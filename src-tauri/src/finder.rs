@@ -0,0 +1,72 @@
+use tauri::{AppHandle, command};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::instance::GameInstance;
+use crate::runner::expand_tilde;
+use crate::storage;
+
+// 前端未传入容器目录时使用 CrossOver 的默认位置
+const DEFAULT_BOTTLES_PATH: &str = "~/Library/Application Support/CrossOver/Bottles";
+
+fn find_instance(app: &AppHandle, instance_id: &str) -> Result<GameInstance, String> {
+    storage::read_instances(app)
+        .into_iter()
+        .find(|inst| inst.id == instance_id)
+        .ok_or(format!("未找到实例: {}", instance_id))
+}
+
+// 游戏路径可能是可执行文件，也可能是游戏目录（原生运行的引擎）
+fn game_dir(inst: &GameInstance) -> Result<PathBuf, String> {
+    let exe = Some(inst.executable_path.as_str()).filter(|p| !p.trim().is_empty()).ok_or("实例未设置游戏路径")?;
+    let path = expand_tilde(exe);
+    if path.is_dir() {
+        return Ok(path);
+    }
+    path.parent().map(Path::to_path_buf).ok_or(format!("无法解析游戏目录: {}", exe))
+}
+
+fn bottle_drive_c(inst: &GameInstance, bottles_path: Option<&str>) -> Result<PathBuf, String> {
+    let bottle = Some(inst.bottle_name.as_str()).filter(|b| !b.trim().is_empty()).ok_or("实例未设置容器")?;
+    let bottle_path = match inst.run_mode() {
+        "crossover" => expand_tilde(bottles_path.unwrap_or(DEFAULT_BOTTLES_PATH)).join(bottle),
+        // direct 模式下 bottleName 保存的是完整的容器路径
+        "direct" => expand_tilde(bottle),
+        other => return Err(format!("{} 模式的实例没有 Wine 容器", other)),
+    };
+    Ok(bottle_path.join("drive_c"))
+}
+
+// 在 Finder 中打开游戏目录（game）、容器的 drive_c（bottle）或应用数据目录（data），
+// 方便找到存档与放置补丁
+#[command]
+pub fn reveal_in_finder(
+    app: AppHandle,
+    kind: String,
+    instance_id: Option<String>,
+    bottles_path: Option<String>,
+) -> Result<(), String> {
+    let target = match kind.as_str() {
+        "data" => storage::data_dir(&app)?,
+        "game" | "bottle" => {
+            let instance_id = instance_id.ok_or("缺少实例 ID")?;
+            storage::check_instance_id(&instance_id)?;
+            let inst = find_instance(&app, &instance_id)?;
+            if kind == "game" {
+                game_dir(&inst)?
+            } else {
+                bottle_drive_c(&inst, bottles_path.as_deref())?
+            }
+        }
+        other => return Err(format!("未知的目录类型: {}", other)),
+    };
+
+    if !target.is_dir() {
+        return Err(format!("目录不存在，可能位于外接硬盘但未连接: {:?}", target));
+    }
+    Command::new("open")
+        .arg(&target)
+        .spawn()
+        .map_err(|e| format!("无法打开 Finder: {}", e))?;
+    Ok(())
+}
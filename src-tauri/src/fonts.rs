@@ -0,0 +1,228 @@
+use tauri::{AppHandle, Manager, command};
+use font_kit::font::Font;
+use font_kit::handle::Handle;
+use font_kit::source::SystemSource;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::registry;
+use crate::settings;
+use crate::runner::expand_tilde;
+
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+// 与前端 ThemeContext 中的默认字体栈保持一致
+const DEFAULT_UI_FONT_STACK: &str = r#"-apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif"#;
+
+// 系统字体列表缓存，安装新字体后清空
+static FONT_CACHE: OnceLock<Mutex<Option<Vec<String>>>> = OnceLock::new();
+
+fn font_cache() -> &'static Mutex<Option<Vec<String>>> {
+    FONT_CACHE.get_or_init(|| Mutex::new(None))
+}
+
+fn enumerate_system_fonts() -> Vec<String> {
+    let source = SystemSource::new();
+    match source.all_families() {
+        Ok(families) => {
+            let mut fonts = families;
+            fonts.sort(); // 排序
+            fonts.dedup(); // 去重
+            fonts
+        },
+        Err(_) => vec!["System Default".to_string()]
+    }
+}
+
+pub(crate) fn invalidate_font_cache() {
+    if let Ok(mut cache) = font_cache().lock() {
+        *cache = None;
+    }
+}
+
+// 字体目录的修改时间轮询间隔
+const FONT_WATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs_list = vec![PathBuf::from("/Library/Fonts"), PathBuf::from("/System/Library/Fonts")];
+    if let Some(home) = dirs::home_dir() {
+        dirs_list.push(home.join("Library/Fonts"));
+    }
+    dirs_list
+}
+
+fn font_dirs_mtime() -> Vec<Option<SystemTime>> {
+    font_dirs()
+        .iter()
+        .map(|d| fs::metadata(d).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+// 后台预热字体缓存，并在字体目录发生变化时自动失效重建
+pub fn start_font_watcher() {
+    thread::spawn(|| {
+        get_system_fonts();
+        let mut last = font_dirs_mtime();
+        loop {
+            thread::sleep(FONT_WATCH_INTERVAL);
+            let current = font_dirs_mtime();
+            if current != last {
+                log_info!("检测到字体目录变化，刷新字体缓存");
+                invalidate_font_cache();
+                get_system_fonts();
+                last = current;
+            }
+        }
+    });
+}
+
+#[command]
+pub fn refresh_fonts() -> Vec<String> {
+    invalidate_font_cache();
+    get_system_fonts()
+}
+
+#[command]
+pub fn get_system_fonts() -> Vec<String> {
+    if let Ok(cache) = font_cache().lock() {
+        if let Some(fonts) = cache.as_ref() {
+            return fonts.clone();
+        }
+    }
+
+    let fonts = enumerate_system_fonts();
+    if let Ok(mut cache) = font_cache().lock() {
+        *cache = Some(fonts.clone());
+    }
+    fonts
+}
+
+// 校验字体文件并复制到 ~/Library/Fonts，返回字体族名
+#[command]
+pub fn install_font(path: String) -> Result<String, String> {
+    let src = expand_tilde(&path);
+    let ext = src.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    if !FONT_EXTENSIONS.contains(&ext.as_str()) {
+        return Err(format!("不支持的字体格式: {}", ext));
+    }
+
+    let font = Font::from_path(&src, 0).map_err(|e| format!("字体文件无效: {:?}", e))?;
+    let family = font.family_name();
+
+    let fonts_dir = dirs::home_dir().ok_or("无法获取用户目录")?.join("Library/Fonts");
+    fs::create_dir_all(&fonts_dir).map_err(|e| format!("创建字体目录失败: {}", e))?;
+
+    let file_name = src.file_name().ok_or("无法解析字体文件名")?;
+    let dst = fonts_dir.join(file_name);
+    if dst.exists() {
+        return Err(format!("字体文件已存在: {:?}", dst));
+    }
+
+    fs::copy(&src, &dst).map_err(|e| format!("安装字体失败: {}", e))?;
+    invalidate_font_cache();
+
+    log_info!("已安装字体 {} -> {:?}", family, dst);
+    Ok(family)
+}
+
+const WINDOWS_FONTS_KEY: &str = "HKLM\\Software\\Microsoft\\Windows NT\\CurrentVersion\\Fonts";
+
+// 将 macOS 中已安装的字体族复制进容器的 Windows 字体目录，并写入注册表
+#[command]
+pub async fn install_font_to_bottle(
+    bottle_path: String,
+    family: String,
+    crossover_app_path: String,
+) -> Result<Vec<String>, String> {
+    let bottle = expand_tilde(&bottle_path);
+    let fonts_dir = bottle.join("drive_c/windows/Fonts");
+    if !fonts_dir.is_dir() {
+        return Err(format!("容器字体目录不存在: {:?}", fonts_dir));
+    }
+
+    let handle = SystemSource::new()
+        .select_family_by_name(&family)
+        .map_err(|_| format!("未找到字体: {}", family))?;
+
+    // 同一 .ttc 文件可能包含多个字重，按文件去重
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for font in handle.fonts() {
+        if let Handle::Path { path, .. } = font {
+            if files.iter().any(|(p, _)| p == path) {
+                continue;
+            }
+            let full_name = font.load().map(|f| f.full_name()).unwrap_or_else(|_| family.clone());
+            files.push((path.clone(), full_name));
+        }
+    }
+    if files.is_empty() {
+        return Err(format!("字体 {} 没有可复制的字体文件", family));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let mut installed = Vec::new();
+        for (path, full_name) in files {
+            let file_name = match path.file_name() {
+                Some(n) => n.to_string_lossy().to_string(),
+                None => continue,
+            };
+
+            fs::copy(&path, fonts_dir.join(&file_name)).map_err(|e| format!("复制字体 {} 失败: {}", file_name, e))?;
+            registry::wine_reg_add(
+                &crossover_app_path,
+                &bottle,
+                WINDOWS_FONTS_KEY,
+                &format!("{} (TrueType)", full_name),
+                "REG_SZ",
+                &file_name,
+            )?;
+            installed.push(file_name);
+        }
+        Ok(installed)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn ui_font_stack(family: &str) -> String {
+    let family = family.trim().replace('"', "");
+    if family.is_empty() || family == "system-ui" {
+        DEFAULT_UI_FONT_STACK.to_string()
+    } else {
+        format!("\"{}\", {}", family, DEFAULT_UI_FONT_STACK)
+    }
+}
+
+// 生成在页面脚本之前执行的字体脚本，保证加载界面也使用所选字体
+pub fn ui_font_script(family: &str) -> String {
+    let stack = serde_json::to_string(&ui_font_stack(family)).unwrap_or_else(|_| "\"sans-serif\"".to_string());
+    format!(
+        r#"(function () {{
+  var stack = {stack};
+  var root = document.documentElement;
+  root.style.setProperty('--app-font-family', stack);
+  var style = document.getElementById('asumigal-ui-font');
+  if (!style) {{
+    style = document.createElement('style');
+    style.id = 'asumigal-ui-font';
+    (document.head || root).appendChild(style);
+  }}
+  style.textContent = 'html, body {{ font-family: ' + stack + '; }}';
+}})();"#
+    )
+}
+
+// 保存界面字体，并立即应用到已打开的主窗口
+#[command]
+pub fn set_ui_font(app: AppHandle, family: String) -> Result<(), String> {
+    let mut s = settings::load_settings(&app);
+    s.ui_font = family;
+    settings::write_settings(&app, &s)?;
+
+    if let Some(window) = app.get_webview_window("main") {
+        window.eval(&ui_font_script(&s.ui_font)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
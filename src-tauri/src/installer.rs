@@ -0,0 +1,173 @@
+use tauri::command;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::UNIX_EPOCH;
+
+use crate::bottle::{self, InstalledProgram};
+use crate::exe_info;
+use crate::registry;
+use crate::runner::{self, expand_tilde, WineConfig};
+
+// 安装程序很少把游戏装进这些目录，扫描时跳过以加快速度
+const SKIP_DIRS: [&str; 2] = ["windows", "users"];
+// 卸载程序、安装程序本身与运行库不会是游戏本体
+pub(crate) const NON_GAME_HINTS: [&str; 6] = ["unins", "uninst", "setup", "install", "vcredist", "dxsetup"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallResult {
+    // 推测的游戏本体，找不到时为空，由用户从 candidates 中选择
+    exe_path: Option<String>,
+    candidates: Vec<String>,
+    // 安装后新出现在注册表 Uninstall 中的程序
+    programs: Vec<InstalledProgram>,
+    exit_code: Option<i32>,
+}
+
+pub(crate) fn read_programs(bottle_path: &Path) -> Vec<InstalledProgram> {
+    let mut programs = Vec::new();
+    for file in ["system.reg", "user.reg"] {
+        let keys = registry::parse_reg_file(&bottle_path.join(file)).unwrap_or_default();
+        programs.extend(bottle::read_installed_programs(&keys));
+    }
+    programs
+}
+
+// 列出 drive_c 中的 .exe 及其修改时间，用于安装前后对比
+pub(crate) fn snapshot_exes(drive_c: &Path) -> HashSet<(PathBuf, u64)> {
+    let mut exes = HashSet::new();
+    let mut stack = vec![drive_c.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let ft = match entry.file_type() {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            let path = entry.path();
+            if ft.is_dir() {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                if !(dir == drive_c && SKIP_DIRS.contains(&name.as_str())) {
+                    stack.push(path);
+                }
+            } else if ft.is_file() && path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("exe")) {
+                let modified = entry
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                exes.insert((path, modified));
+            }
+        }
+    }
+    exes
+}
+
+// 把注册表中的 Windows 路径（如 C:\Program Files\Game）转换为容器中的 macOS 路径
+pub(crate) fn windows_to_unix(bottle_path: &Path, win_path: &str) -> Option<PathBuf> {
+    let mut chars = win_path.chars();
+    let drive = chars.next()?.to_ascii_lowercase();
+    if chars.next()? != ':' {
+        return None;
+    }
+    let rest = win_path[2..].trim_matches('\\').replace('\\', "/");
+    let root = if drive == 'c' { bottle_path.join("drive_c") } else { bottle_path.join("dosdevices").join(format!("{}:", drive)) };
+    Some(if rest.is_empty() { root } else { root.join(rest) })
+}
+
+// 候选排序：位于新程序安装目录中的优先，其次是 GUI 程序、文件名不像安装/卸载程序的，最后按体积
+pub(crate) fn rank_candidates(mut exes: Vec<PathBuf>, install_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    exes.sort_by_cached_key(|path| {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let in_install_dir = install_dirs.iter().any(|d| path.starts_with(d));
+        let gui = exe_info::read_pe_info(path).is_some_and(|i| i.gui);
+        let non_game = NON_GAME_HINTS.iter().any(|h| name.contains(h));
+        let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        (!in_install_dir, !gui, non_game, std::cmp::Reverse(size))
+    });
+    exes
+}
+
+fn run_installer(bottle_path: &Path, installer: &Path, crossover_app_path: &str) -> Result<Option<i32>, String> {
+    let config = WineConfig {
+        bottle_path: bottle_path.to_string_lossy().to_string(),
+        game_exe: installer.to_string_lossy().to_string(),
+        crossover_app_path: crossover_app_path.to_string(),
+        ..Default::default()
+    };
+    let (mut cmd, exe_path, bottle) = runner::build_crossover_command(&config)?;
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::null());
+    if let Some(dir) = installer.parent() {
+        cmd.current_dir(dir);
+    }
+
+    let mut child = cmd.spawn().map_err(|e| format!("无法启动安装程序: {}", e))?;
+    // 安装程序常常再启动子进程完成实际安装，需要等待整个进程树结束
+    let (status, duration) = runner::wait_for_wine_tree(&mut child, &exe_path, &bottle);
+    log_info!("安装程序 {:?} 已退出: {:?}，耗时 {} 秒", installer, status, duration);
+    Ok(status.and_then(|s| s.code()))
+}
+
+// 在容器中运行安装程序，安装结束后对比 drive_c 中的 .exe 与注册表 Uninstall 键，
+// 找出新安装的游戏本体，让实例指向安装后的副本而不是安装程序
+#[command]
+pub async fn install_game(bottle_path: String, installer_path: String, crossover_app_path: String) -> Result<InstallResult, String> {
+    let bottle = expand_tilde(&bottle_path);
+    let drive_c = bottle.join("drive_c");
+    if !drive_c.is_dir() {
+        return Err(format!("不是有效的容器: {:?}", bottle));
+    }
+    let installer = expand_tilde(&installer_path);
+    if !installer.is_file() || !exe_info::is_windows_launchable(&installer) {
+        return Err(format!("不是有效的安装程序: {:?}", installer));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let programs_before: HashSet<String> = read_programs(&bottle).into_iter().map(|p| p.key).collect();
+        let exes_before = snapshot_exes(&drive_c);
+
+        let exit_code = run_installer(&bottle, &installer, &crossover_app_path)?;
+
+        let programs: Vec<InstalledProgram> = read_programs(&bottle)
+            .into_iter()
+            .filter(|p| !programs_before.contains(&p.key))
+            .collect();
+        let new_exes: Vec<PathBuf> = snapshot_exes(&drive_c)
+            .into_iter()
+            .filter(|e| !exes_before.contains(e))
+            .map(|(path, _)| path)
+            .collect();
+        let install_dirs: Vec<PathBuf> = programs
+            .iter()
+            .filter_map(|p| p.install_location.as_deref())
+            .filter_map(|loc| windows_to_unix(&bottle, loc))
+            .collect();
+
+        let candidates: Vec<String> = rank_candidates(new_exes, &install_dirs)
+            .into_iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let exe_path = candidates
+            .first()
+            .filter(|p| {
+                let name = Path::new(p).file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+                !NON_GAME_HINTS.iter().any(|h| name.contains(h))
+            })
+            .cloned();
+        if exe_path.is_none() {
+            log_warn!("安装完成，但未能确定游戏本体，新出现的可执行文件 {} 个", candidates.len());
+        }
+
+        Ok(InstallResult { exe_path, candidates, programs, exit_code })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
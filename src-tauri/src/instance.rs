@@ -0,0 +1,270 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::autosave;
+use crate::db;
+use crate::storage;
+use crate::tags;
+use crate::trash;
+
+// 运行方式，runner 按这些值选择启动与结束进程的方法
+pub(crate) const RUN_MODE_CROSSOVER: &str = "crossover";
+pub(crate) const RUN_MODE_PARALLELS: &str = "parallels";
+pub(crate) const RUN_MODE_DIRECT: &str = "direct";
+pub(crate) const RUN_MODE_NATIVE: &str = "native";
+pub(crate) const RUN_MODES: [&str; 4] = [RUN_MODE_CROSSOVER, RUN_MODE_PARALLELS, RUN_MODE_DIRECT, RUN_MODE_NATIVE];
+pub(crate) const FILE_STATUSES: [&str; 2] = ["disk", "local"];
+pub(crate) const MAX_RATING: u8 = 10;
+
+// 游戏实例，与前端的 GameInstance 接口一一对应。
+// 后端不认识的字段原样保存在 extra 中，前端新增字段不需要同步修改这里
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameInstance {
+    pub id: String,
+    // 游戏标题
+    pub name: String,
+    #[serde(default)]
+    pub info: String,
+    #[serde(default)]
+    pub bottle_name: String,
+    #[serde(default)]
+    pub executable_path: String,
+    // 封面图
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_image: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_played: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_play_time: Option<u64>,
+    // "YYYY-MM-DD" -> 当天游玩秒数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub play_history: Option<BTreeMap<String, u64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_mode: Option<String>,
+    // 游戏文件位于外接硬盘（disk）还是已复制到本地（local）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_file_status: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub favorite: bool,
+    // 已通关
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub finished: bool,
+    // 个人评分 1-10，未评分时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+    // 移入回收站的时间（Unix 秒），为空表示未删除
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+pub(crate) fn is_day_key(day: &str) -> bool {
+    let parts: Vec<&str> = day.split('-').collect();
+    parts.len() == 3
+        && [4, 2, 2].iter().zip(&parts).all(|(len, p)| p.len() == *len && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl GameInstance {
+    pub fn run_mode(&self) -> &str {
+        self.run_mode.as_deref().unwrap_or("crossover")
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        storage::check_instance_id(&self.id)?;
+        if self.name.trim().is_empty() {
+            return Err(format!("实例 {} 的名称不能为空", self.id));
+        }
+        if !RUN_MODES.contains(&self.run_mode()) {
+            return Err(format!("实例 {} 的运行模式无效: {}", self.name, self.run_mode()));
+        }
+        if let Some(status) = self.game_file_status.as_deref() {
+            if !FILE_STATUSES.contains(&status) {
+                return Err(format!("实例 {} 的游戏文件状态无效: {}", self.name, status));
+            }
+        }
+        if let Some(day) = self.play_history.iter().flatten().map(|(day, _)| day).find(|day| !is_day_key(day)) {
+            return Err(format!("实例 {} 的游玩记录日期无效: {}", self.name, day));
+        }
+        if self.rating.is_some_and(|r| !(1..=MAX_RATING).contains(&r)) {
+            return Err(format!("实例 {} 的评分必须在 1 到 {} 之间", self.name, MAX_RATING));
+        }
+        if self.tags.iter().any(|t| t.trim().is_empty()) {
+            return Err(format!("实例 {} 含有空标签", self.name));
+        }
+        Ok(())
+    }
+}
+
+// 校验整个游戏库：逐个校验实例，并拒绝重复的 ID
+// 只校验相对 before 新增或有变化的实例，游戏库中已有的无效实例不会阻止其他实例的保存；
+// ID 重复仍对整个列表检查
+pub fn validate_changed(before: &[GameInstance], after: &[GameInstance]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for inst in after {
+        if !before.iter().any(|b| b == inst) {
+            inst.validate()?;
+        }
+        if !seen.insert(inst.id.as_str()) {
+            return Err(format!("实例 ID 重复: {}", inst.id));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_library(instances: &[GameInstance]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for inst in instances {
+        inst.validate()?;
+        if !seen.insert(inst.id.as_str()) {
+            return Err(format!("实例 ID 重复: {}", inst.id));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstanceFilter {
+    favorite_only: bool,
+    min_rating: Option<u8>,
+    // rating / name / lastPlayed / playTime，默认按最近游玩
+    sort_by: Option<String>,
+}
+
+// 批量修改的内容，未填写的项保持不变
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstancePatch {
+    bottle_name: Option<String>,
+    add_tags: Vec<String>,
+    remove_tags: Vec<String>,
+    // 空字符串表示清除
+    game_file_status: Option<String>,
+    run_mode: Option<String>,
+}
+
+impl InstancePatch {
+    fn apply(&self, inst: &mut GameInstance) {
+        if let Some(bottle) = &self.bottle_name {
+            inst.bottle_name = bottle.clone();
+        }
+        inst.tags.retain(|t| !self.remove_tags.contains(t));
+        for tag in &self.add_tags {
+            if !inst.tags.contains(tag) {
+                inst.tags.push(tag.clone());
+            }
+        }
+        if let Some(status) = &self.game_file_status {
+            inst.game_file_status = Some(status.clone()).filter(|s| !s.is_empty());
+        }
+        if let Some(mode) = &self.run_mode {
+            inst.run_mode = Some(mode.clone());
+        }
+    }
+}
+
+// 修改单个实例并保存，返回修改后的实例
+async fn update_instance(app: &AppHandle, source: &str, id: &str, f: impl FnOnce(&mut GameInstance)) -> Result<GameInstance, String> {
+    db::update_instances(app, source, |instances| {
+        let inst = instances.iter_mut().find(|i| i.id == id).ok_or(format!("未找到实例: {}", id))?;
+        f(inst);
+        inst.validate()?;
+        Ok(inst.clone())
+    })
+    .await
+}
+
+#[command]
+pub async fn set_favorite(app: AppHandle, instance_id: String, favorite: bool) -> Result<GameInstance, String> {
+    update_instance(&app, "set_favorite", &instance_id, |inst| inst.favorite = favorite).await
+}
+
+// rating 为空时清除评分
+#[command]
+pub async fn set_rating(app: AppHandle, instance_id: String, rating: Option<u8>) -> Result<GameInstance, String> {
+    update_instance(&app, "set_rating", &instance_id, |inst| inst.rating = rating).await
+}
+
+#[command]
+pub async fn set_finished(app: AppHandle, instance_id: String, finished: bool) -> Result<GameInstance, String> {
+    update_instance(&app, "set_finished", &instance_id, |inst| inst.finished = finished).await
+}
+
+// 按收藏与评分筛选并排序，未评分的实例排在已评分的之后
+#[command]
+pub fn query_instances(app: AppHandle, filter: InstanceFilter) -> Vec<GameInstance> {
+    // 读取时已合并暂存的修改，这里顺便写入，失败只记日志
+    if let Err(e) = autosave::flush(&app) {
+        log_warn!("自动保存失败: {}", e);
+    }
+    let mut instances: Vec<GameInstance> = storage::read_instances(&app)
+        .into_iter()
+        .filter(|i| !filter.favorite_only || i.favorite)
+        .filter(|i| filter.min_rating.is_none_or(|min| i.rating.is_some_and(|r| r >= min)))
+        .collect();
+    match filter.sort_by.as_deref().unwrap_or("lastPlayed") {
+        "rating" => instances.sort_by_key(|i| std::cmp::Reverse((i.rating, i.favorite))),
+        "name" => instances.sort_by_cached_key(|i| i.name.to_lowercase()),
+        "playTime" => instances.sort_by_key(|i| std::cmp::Reverse(i.total_play_time.unwrap_or(0))),
+        _ => instances.sort_by_key(|i| std::cmp::Reverse(i.last_played.unwrap_or(0))),
+    }
+    instances
+}
+
+// 新增或整体替换单个实例，前端编辑时不必再提交整个游戏库。
+// expected_generation 为前端读取游戏库时的代数（见 load_instances），游戏库已被其他窗口修改时拒绝保存
+#[command]
+pub async fn upsert_instance(app: AppHandle, instance: GameInstance, expected_generation: Option<u64>) -> Result<GameInstance, String> {
+    instance.validate()?;
+    let saved = instance.clone();
+    storage::update_instances(&app, "upsert_instance", expected_generation, |instances| {
+        match instances.iter_mut().find(|i| i.id == instance.id) {
+            Some(existing) => *existing = instance,
+            None => instances.push(instance),
+        }
+        Ok(())
+    })?;
+    db::sync_library(&app).await?;
+    Ok(saved)
+}
+
+// 对一批实例应用同一修改，一次写入游戏库。任一实例不存在或修改后校验失败时都不保存
+#[command]
+pub async fn bulk_update_instances(app: AppHandle, ids: Vec<String>, patch: InstancePatch) -> Result<Vec<GameInstance>, String> {
+    let mut patch = patch;
+    patch.add_tags = patch.add_tags.iter().map(|t| db::check_tag(t)).collect::<Result<_, _>>()?;
+    patch.remove_tags = patch.remove_tags.iter().map(|t| t.trim().to_string()).collect();
+    if let Some(bottle) = patch.bottle_name.as_mut() {
+        *bottle = bottle.trim().to_string();
+    }
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let updated = db::update_instances(&app, "bulk_update_instances", |instances| {
+        let mut updated = Vec::with_capacity(ids.len());
+        for id in &ids {
+            let inst = instances.iter_mut().find(|i| i.id == *id).ok_or(format!("未找到实例: {}", id))?;
+            patch.apply(inst);
+            inst.validate()?;
+            updated.push(inst.clone());
+        }
+        Ok(updated)
+    })
+    .await?;
+    tags::remember_tags(&app, &patch.add_tags)?;
+    log_info!("已批量修改 {} 个实例", updated.len());
+    Ok(updated)
+}
+
+// 删除单个实例（移入回收站）
+#[command]
+pub async fn delete_instance(app: AppHandle, id: String) -> Result<(), String> {
+    trash::trash_instance(app, id).await
+}
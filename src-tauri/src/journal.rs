@@ -0,0 +1,209 @@
+use tauri::{AppHandle, Emitter, command};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::db;
+use crate::instance::GameInstance;
+use crate::storage;
+
+// 修改日志：记录每次写入游戏库时实例字段的变化，误操作（批量编辑、元数据刷新）后可以逐条撤销。
+// 只记录前后都存在的实例，新增与彻底删除不在此列；移入回收站是 deletedAt 的变化，可以撤销
+pub(crate) const JOURNAL_FILENAME: &str = "change_journal.json";
+const MAX_ENTRIES: usize = 500;
+// 游玩记录由游戏结束时自动写入，不算作编辑，也不应被撤销
+const IGNORED_FIELDS: [&str; 3] = ["playHistory", "totalPlayTime", "lastPlayed"];
+const UNDO_SOURCE: &str = "undo";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    // 字段不存在时为空
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeEntry {
+    id: String,
+    instance_id: String,
+    instance_name: String,
+    // Unix 毫秒
+    at: u64,
+    // 发起修改的操作，如 save_instances、set_rating、sync
+    source: String,
+    changes: BTreeMap<String, FieldChange>,
+    #[serde(default)]
+    undone: bool,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+// 实例的字段表，值为 null 的字段视为不存在
+fn fields(inst: &GameInstance) -> Map<String, Value> {
+    match serde_json::to_value(inst) {
+        Ok(Value::Object(map)) => map.into_iter().filter(|(_, v)| !v.is_null()).collect(),
+        _ => Map::new(),
+    }
+}
+
+// 同一实例两个版本之间的字段变化，不含游玩记录
+pub(crate) fn field_changes(old: &GameInstance, new: &GameInstance) -> BTreeMap<String, FieldChange> {
+    let (old_fields, new_fields) = (fields(old), fields(new));
+    let mut changes = BTreeMap::new();
+    for key in old_fields.keys().chain(new_fields.keys()) {
+        if IGNORED_FIELDS.contains(&key.as_str()) || changes.contains_key(key) {
+            continue;
+        }
+        let (b, a) = (old_fields.get(key), new_fields.get(key));
+        if b != a {
+            changes.insert(key.clone(), FieldChange { before: b.cloned(), after: a.cloned() });
+        }
+    }
+    changes
+}
+
+fn diff(source: &str, at: u64, before: &[GameInstance], after: &[GameInstance]) -> Vec<ChangeEntry> {
+    let mut entries = Vec::new();
+    for new in after {
+        let old = match before.iter().find(|i| i.id == new.id) {
+            Some(old) => old,
+            None => continue,
+        };
+        let changes = field_changes(old, new);
+        if changes.is_empty() {
+            continue;
+        }
+        entries.push(ChangeEntry {
+            id: format!("{}-{}", at, entries.len()),
+            instance_id: new.id.clone(),
+            instance_name: new.name.clone(),
+            at,
+            source: source.to_string(),
+            changes,
+            undone: false,
+        });
+    }
+    entries
+}
+
+fn read_journal(app: &AppHandle) -> Vec<ChangeEntry> {
+    let path = match storage::resolve_library_path(app, JOURNAL_FILENAME) {
+        Ok(p) => p,
+        Err(_) => return Vec::new(),
+    };
+    let data = match storage::read_data_bytes(&path) {
+        Ok(data) => data,
+        Err(_) => return Vec::new(),
+    };
+    match storage::decode_library(data).and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string())) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log_warn!("修改日志无法读取，将重新开始记录: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// 日志与游戏库一样按设置加密，超出上限时丢弃最早的记录
+fn write_journal(app: &AppHandle, entries: &[ChangeEntry]) -> Result<(), String> {
+    let start = entries.len().saturating_sub(MAX_ENTRIES);
+    let text = serde_json::to_string(&entries[start..]).map_err(|e| e.to_string())?;
+    let path = storage::resolve_library_path(app, JOURNAL_FILENAME)?;
+    storage::write_atomic(&path, &storage::encode_library(app, &text)?)
+}
+
+// 在游戏库写入锁内调用，记录本次写入的字段变化。失败只记日志，不影响已完成的写入
+pub(crate) fn record(app: &AppHandle, source: &str, before: &[GameInstance], after: &[GameInstance]) {
+    let changed = diff(source, now_millis(), before, after);
+    if changed.is_empty() {
+        return;
+    }
+    let mut entries = read_journal(app);
+    entries.extend(changed);
+    if let Err(e) = write_journal(app, &entries) {
+        log_warn!("写入修改日志失败: {}", e);
+    }
+}
+
+// 切换加密设置后按新设置重写日志
+pub(crate) fn rewrite(app: &AppHandle) -> Result<(), String> {
+    let entries = read_journal(app);
+    if entries.is_empty() {
+        return Ok(());
+    }
+    write_journal(app, &entries)
+}
+
+// 把实例的字段恢复为修改前的值
+fn revert(inst: &GameInstance, entry: &ChangeEntry) -> Result<GameInstance, String> {
+    let mut map = fields(inst);
+    for (key, change) in &entry.changes {
+        match &change.before {
+            Some(value) => map.insert(key.clone(), value.clone()),
+            None => map.remove(key),
+        };
+    }
+    serde_json::from_value(Value::Object(map)).map_err(|e| format!("无法还原实例: {}", e))
+}
+
+// 修改日志，最新的在前；指定 instance_id 时只返回该实例的记录
+#[command]
+pub fn list_changes(app: AppHandle, instance_id: Option<String>) -> Vec<ChangeEntry> {
+    let mut entries: Vec<ChangeEntry> = read_journal(&app)
+        .into_iter()
+        .filter(|e| instance_id.as_ref().is_none_or(|id| &e.instance_id == id))
+        .collect();
+    entries.reverse();
+    entries
+}
+
+// 撤销实例最近一次尚未撤销的修改，返回还原后的实例。
+// 撤销本身也会记入日志，但再次撤销时跳过这些记录，继续向前回退
+#[command]
+pub async fn undo_last_change(app: AppHandle, instance_id: String) -> Result<GameInstance, String> {
+    // 选取日志条目、还原实例与标记已撤销都在同一次写入锁内完成，并发的撤销不会重复还原同一条修改
+    let mut marked: Option<ChangeEntry> = None;
+    let result = storage::update_all_instances(&app, UNDO_SOURCE, None, |instances| {
+        let mut entries = read_journal(&app);
+        let entry = entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.instance_id == instance_id && !e.undone && e.source != UNDO_SOURCE)
+            .ok_or("该实例没有可以撤销的修改".to_string())?;
+        let inst = instances.iter_mut().find(|i| i.id == instance_id).ok_or(format!("未找到实例: {}", instance_id))?;
+        let reverted = revert(inst, entry)?;
+        reverted.validate()?;
+        entry.undone = true;
+        let entry = entry.clone();
+        write_journal(&app, &entries)?;
+        marked = Some(entry.clone());
+        *inst = reverted;
+        Ok((inst.clone(), entry))
+    });
+    let (restored, entry) = match result {
+        Ok(r) => r,
+        Err(e) => {
+            // 标记之后写入实例失败，把标记改回来，这条修改仍可撤销
+            if let Some(entry) = marked {
+                let _ = storage::with_library_lock(&app, |_| {
+                    let mut entries = read_journal(&app);
+                    if let Some(e) = entries.iter_mut().find(|e| e.id == entry.id && e.instance_id == entry.instance_id) {
+                        e.undone = false;
+                    }
+                    write_journal(&app, &entries)
+                });
+            }
+            return Err(e);
+        }
+    };
+    db::sync_library(&app).await?;
+
+    log_info!("已撤销实例 {} 的修改（{}）", restored.name, entry.source);
+    let _ = app.emit("instances-restored", "undo");
+    Ok(restored)
+}
@@ -0,0 +1,73 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// 所有条目共用的 Keychain 服务名前缀，与 bundle identifier 保持一致
+const SERVICE_PREFIX: &str = "com.jayi0908.asumigal";
+
+fn service_name(service: &str) -> String {
+    format!("{}.{}", SERVICE_PREFIX, service)
+}
+
+// security -i 的命令行参数，用双引号包住；含引号、反斜杠或换行的名称不支持
+fn quote_arg(value: &str) -> Result<String, String> {
+    if value.contains(['"', '\\', '\n', '\r']) {
+        return Err(format!("钥匙串条目名称含有不支持的字符: {}", value));
+    }
+    Ok(format!("\"{}\"", value))
+}
+
+// 写入（或覆盖）一条通用密码。密码不出现在命令行参数中（其他进程可以读到），
+// 而是以十六进制写入 security -i 的标准输入
+pub fn set_secret(service: &str, account: &str, secret: &str) -> Result<(), String> {
+    if secret.is_empty() {
+        return Err("不能保存空密码".to_string());
+    }
+    let hex: String = secret.bytes().map(|b| format!("{:02x}", b)).collect();
+    let line = format!(
+        "add-generic-password -U -s {} -a {} -X {}\n",
+        quote_arg(&service_name(service))?,
+        quote_arg(account)?,
+        hex
+    );
+    let mut child = Command::new("security")
+        .arg("-i")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("无法访问钥匙串: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(line.as_bytes()).map_err(|e| format!("无法访问钥匙串: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("无法访问钥匙串: {}", e))?;
+    // 交互模式下命令失败时退出码仍可能为 0，以错误输出为准
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() || !stderr.is_empty() {
+        return Err(format!("写入钥匙串失败: {}", stderr));
+    }
+    Ok(())
+}
+
+pub fn get_secret(service: &str, account: &str) -> Option<String> {
+    let output = Command::new("security")
+        .args(["find-generic-password", "-s", &service_name(service), "-a", account, "-w"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let secret = String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string();
+    Some(secret).filter(|s| !s.is_empty())
+}
+
+pub fn delete_secret(service: &str, account: &str) -> Result<(), String> {
+    let output = Command::new("security")
+        .args(["delete-generic-password", "-s", &service_name(service), "-a", account])
+        .output()
+        .map_err(|e| format!("无法访问钥匙串: {}", e))?;
+    // 条目本来就不存在时同样视为成功
+    if !output.status.success() && output.status.code() != Some(44) {
+        return Err(format!("删除钥匙串条目失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
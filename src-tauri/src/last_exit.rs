@@ -0,0 +1,119 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+use crate::runner::WineConfig;
+use crate::storage;
+use crate::winedbg::CrashInfo;
+
+// 每个实例最近一次退出的结果，下次启动时据此给出建议
+const LAST_EXIT_FILE: &str = "last_exit.json";
+
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastExit {
+    // clean / crash / killed
+    pub result: String,
+    // 错误特征，如 "d3d9: page fault on read access ..."，用于搜索已知问题
+    pub signature: Option<String>,
+    pub faulting_module: Option<String>,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub duration_sec: u64,
+    // 是否在启动阶段（崩溃判定时长内）就退出
+    pub at_startup: bool,
+    pub ended_at: u64,
+}
+
+impl LastExit {
+    pub fn classify(
+        crashed: bool,
+        crash_info: Option<&CrashInfo>,
+        exit_code: Option<i32>,
+        signal: Option<i32>,
+        duration_sec: u64,
+        at_startup: bool,
+        ended_at: u64,
+    ) -> LastExit {
+        let result = if crashed || crash_info.is_some() {
+            "crash"
+        } else if signal.is_some() {
+            "killed"
+        } else {
+            "clean"
+        };
+        let faulting_module = crash_info.and_then(|c| c.faulting_module.clone());
+        let signature = match crash_info {
+            Some(info) => match (&info.faulting_module, &info.exception) {
+                (Some(m), Some(e)) => Some(format!("{}: {}", m, e)),
+                (Some(m), None) => Some(m.clone()),
+                (None, e) => e.clone(),
+            },
+            None if result == "crash" => signal.map(|s| format!("signal {}", s)).or(exit_code.map(|c| format!("exit code {}", c))),
+            None => None,
+        };
+        LastExit { result: result.to_string(), signature, faulting_module, exit_code, signal, duration_sec, at_startup, ended_at }
+    }
+}
+
+fn read_store(app: &AppHandle) -> HashMap<String, LastExit> {
+    storage::resolve_data_path(app, LAST_EXIT_FILE)
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn record(app: &AppHandle, instance_id: &str, exit: LastExit) -> Result<(), String> {
+    storage::check_instance_id(instance_id)?;
+    let _guard = STORE_LOCK.lock().map_err(|_| "退出记录不可用".to_string())?;
+    let mut store = read_store(app);
+    store.insert(instance_id.to_string(), exit);
+    let text = serde_json::to_string_pretty(&store).map_err(|e| e.to_string())?;
+    fs::write(storage::resolve_data_path(app, LAST_EXIT_FILE)?, text).map_err(|e| format!("保存退出记录失败: {}", e))
+}
+
+pub fn load(app: &AppHandle, instance_id: &str) -> Option<LastExit> {
+    read_store(app).remove(instance_id)
+}
+
+fn is_unset(value: &Option<String>) -> bool {
+    value.as_deref().map(str::trim).unwrap_or("").is_empty()
+}
+
+// 根据上次的退出结果与本次配置给出建议；已经按建议修改过的不再提示
+pub fn advice_for(last: &LastExit, config: &WineConfig) -> Option<String> {
+    if last.result != "crash" {
+        return None;
+    }
+    let module = last.faulting_module.as_deref().unwrap_or("").to_lowercase();
+    let graphics = ["d3d", "dxgi", "ddraw", "opengl", "wined3d"].iter().any(|m| module.contains(m));
+    let video = ["quartz", "wmvcore", "mf", "dshow", "amstream"].iter().any(|m| module.starts_with(m));
+
+    if graphics && is_unset(&config.graphics_backend) {
+        return Some(format!("上次在图形模块 {} 中崩溃，可尝试切换图形后端（如 DXVK 或 WineD3D）", module));
+    }
+    if video {
+        return Some(format!("上次在视频模块 {} 中崩溃，可尝试通过 winetricks 安装过场动画组件", module));
+    }
+    if last.at_startup {
+        if is_unset(&config.virtual_desktop) {
+            return Some("上次启动时崩溃，可尝试开启虚拟桌面".to_string());
+        }
+        if !config.japanese_compat.unwrap_or(false) && is_unset(&config.windows_locale) {
+            return Some("上次启动时崩溃，可尝试开启日文兼容模式".to_string());
+        }
+        if !config.auto_fix.unwrap_or(false) {
+            return Some("上次启动时崩溃，可开启自动修复，依次尝试常见的解决方法".to_string());
+        }
+    }
+    last.signature.as_ref().map(|s| format!("上次运行时崩溃（{}），请查看日志", s))
+}
+
+#[command]
+pub fn get_last_exit(app: AppHandle, instance_id: String) -> Option<LastExit> {
+    load(&app, &instance_id)
+}
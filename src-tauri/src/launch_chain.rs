@@ -0,0 +1,103 @@
+use tauri::{AppHandle, Emitter};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Mutex, OnceLock};
+
+use crate::runner::{self, expand_tilde, WineConfig};
+
+// 正在执行启动链的实例，此时游戏本体尚未启动，也需要阻止重复启动
+static CHAINS_RUNNING: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+fn chains_running() -> &'static Mutex<HashSet<String>> {
+    CHAINS_RUNNING.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+#[derive(Serialize, Clone)]
+struct LaunchChainStepPayload {
+    instance_id: String,
+    exe: String,
+    // 当前步骤序号（从 1 开始）与总数
+    index: usize,
+    total: usize,
+    // running / done / failed
+    state: String,
+}
+
+// 相对路径按游戏本体所在目录解析，方便填写同目录下的设置程序
+fn resolve_step(step: &str, game_exe: &Path) -> PathBuf {
+    let path = expand_tilde(step.trim());
+    if path.is_absolute() {
+        return path;
+    }
+    game_exe.parent().map(|dir| dir.join(&path)).unwrap_or(path)
+}
+
+pub fn is_running(instance_id: &str) -> bool {
+    chains_running().lock().map(|set| set.contains(instance_id)).unwrap_or(false)
+}
+
+fn run_steps(app: &AppHandle, instance_id: &str, config: &WineConfig, steps: &[String]) -> Result<(), String> {
+    let game_exe = expand_tilde(&config.game_exe);
+    for (i, step) in steps.iter().enumerate() {
+        let exe = resolve_step(step, &game_exe);
+        let emit = |state: &str| {
+            let _ = app.emit("launch-chain-step", LaunchChainStepPayload {
+                instance_id: instance_id.to_string(),
+                exe: exe.to_string_lossy().to_string(),
+                index: i + 1,
+                total: steps.len(),
+                state: state.to_string(),
+            });
+        };
+
+        // 沿用实例的容器与环境设置，只替换要运行的程序
+        let mut step_config = config.clone();
+        step_config.game_exe = exe.to_string_lossy().to_string();
+        step_config.auto_fix = Some(false);
+        let (mut cmd, exe_path, bottle_path) = runner::build_crossover_command(&step_config)?;
+        cmd.stdout(Stdio::null());
+        cmd.stderr(Stdio::null());
+
+        log_info!("实例 {} 启动链 {}/{}: {:?}", instance_id, i + 1, steps.len(), exe_path);
+        emit("running");
+        let mut child = cmd.spawn().map_err(|e| format!("无法启动 {:?}: {}", exe_path, e))?;
+        let (status, duration) = runner::wait_for_wine_tree(&mut child, &exe_path, &bottle_path);
+
+        // 设置程序、DRM 包装器的退出码并不可靠，失败时只记录，继续启动游戏
+        if status.is_some_and(|s| !s.success()) {
+            log_warn!("启动链 {:?} 异常退出: {:?}，运行 {} 秒", exe_path, status, duration);
+            emit("failed");
+        } else {
+            emit("done");
+        }
+    }
+    Ok(())
+}
+
+// 依次运行实例配置的前置程序并等待其退出，期间不计入游玩时长
+pub fn run_chain(app: &AppHandle, instance_id: &str, config: &WineConfig) -> Result<(), String> {
+    let steps: Vec<String> = config
+        .pre_launch
+        .iter()
+        .flatten()
+        .filter(|s| !s.trim().is_empty())
+        .cloned()
+        .collect();
+    if steps.is_empty() {
+        return Ok(());
+    }
+
+    {
+        let mut running = chains_running().lock().map_err(|_| "启动链状态不可用".to_string())?;
+        if !running.insert(instance_id.to_string()) {
+            return Err("该游戏正在运行启动前程序，请稍候".to_string());
+        }
+    }
+    let result = run_steps(app, instance_id, config, &steps);
+    if let Ok(mut running) = chains_running().lock() {
+        running.remove(instance_id);
+    }
+    result
+}
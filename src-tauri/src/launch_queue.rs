@@ -0,0 +1,108 @@
+use tauri::{AppHandle, Emitter, command};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runner::{self, WineConfig};
+use crate::settings;
+
+// 达到同时运行上限后等待启动的实例
+#[derive(Clone, Serialize)]
+pub struct QueuedLaunch {
+    instance_id: String,
+    queued_at: u64,
+    #[serde(skip)]
+    config: WineConfig,
+}
+
+#[derive(Serialize, Clone)]
+struct LaunchQueuedPayload {
+    instance_id: String,
+    // 在队列中的位置（从 1 开始）
+    position: usize,
+}
+
+#[derive(Serialize, Clone)]
+struct QueuedLaunchStartedPayload {
+    instance_id: String,
+    pid: Option<u32>,
+    error: Option<String>,
+    advice: Option<String>,
+}
+
+static LAUNCH_QUEUE: OnceLock<Mutex<VecDeque<QueuedLaunch>>> = OnceLock::new();
+
+fn launch_queue() -> &'static Mutex<VecDeque<QueuedLaunch>> {
+    LAUNCH_QUEUE.get_or_init(|| Mutex::new(VecDeque::new()))
+}
+
+// 设置了同时运行上限且已达到上限时需要排队，0 表示不限制
+pub fn should_queue(app: &AppHandle) -> bool {
+    let max = settings::load_settings(app).max_concurrent_games as usize;
+    max > 0 && runner::running_instance_ids().len() >= max
+}
+
+pub fn enqueue(app: &AppHandle, instance_id: &str, config: WineConfig) -> Result<usize, String> {
+    let position = {
+        let mut queue = launch_queue().lock().map_err(|_| "启动队列不可用".to_string())?;
+        if queue.iter().any(|q| q.instance_id == instance_id) {
+            return Err("该实例已在启动队列中".to_string());
+        }
+        queue.push_back(QueuedLaunch {
+            instance_id: instance_id.to_string(),
+            queued_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            config,
+        });
+        queue.len()
+    };
+
+    log_info!("同时运行的游戏已达上限，实例 {} 排在第 {} 位", instance_id, position);
+    let _ = app.emit("launch-queued", LaunchQueuedPayload { instance_id: instance_id.to_string(), position });
+    Ok(position)
+}
+
+// 有游戏结束后调用：名额空出时启动队首的实例，启动失败则继续尝试下一个
+pub fn launch_next(app: &AppHandle) {
+    if should_queue(app) {
+        return;
+    }
+    let next = match launch_queue().lock().ok().and_then(|mut q| q.pop_front()) {
+        Some(n) => n,
+        None => return,
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = runner::launch_game(app.clone(), next.instance_id.clone(), next.config).await;
+        let failed = result.is_err();
+        let (pid, error, advice) = match result {
+            Ok(resp) => (Some(resp.pid), None, resp.advice),
+            Err(e) => {
+                log_warn!("排队的实例 {} 启动失败: {}", next.instance_id, e);
+                (None, Some(e), None)
+            }
+        };
+        let _ = app.emit("queued-launch-started", QueuedLaunchStartedPayload { instance_id: next.instance_id, pid, error, advice });
+        if failed {
+            launch_next(&app);
+        }
+    });
+}
+
+#[command]
+pub fn get_launch_queue() -> Vec<QueuedLaunch> {
+    launch_queue().lock().map(|q| q.iter().cloned().collect()).unwrap_or_default()
+}
+
+#[command]
+pub fn cancel_queued_launch(instance_id: String) -> bool {
+    launch_queue()
+        .lock()
+        .map(|mut q| {
+            let before = q.len();
+            q.retain(|item| item.instance_id != instance_id);
+            q.len() != before
+        })
+        .unwrap_or(false)
+}
@@ -1,16 +1,76 @@
-use tauri::{command, Manager};
-use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
-use font_kit::source::SystemSource;
+use tauri::{command, Manager, WebviewWindowBuilder};
 use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::fs;
 
+#[macro_use]
+mod logging;
+mod archive;
+mod assets;
+mod attachments;
+mod audio;
+mod autofix;
+mod autosave;
+mod bangumi;
+mod benchmark;
+mod bottle;
+mod compat;
+mod crash_report;
+mod custom_fields;
+mod db;
+mod discovery;
+mod diskimage;
+mod display;
+mod engine;
+mod exe_info;
+mod external_library;
+mod finder;
+mod fonts;
+mod installer;
+mod instance;
+mod journal;
+mod keychain;
+mod last_exit;
+mod libraries;
+mod launch_chain;
+mod launch_queue;
+mod library_export;
+mod list_export;
+mod locale;
+mod maintenance;
+mod matcher;
+mod native_runner;
+mod news;
+mod notes;
+mod paths;
+mod playtime;
+mod post_session;
+mod profiles;
+mod registry;
+mod repair;
 mod runner;
+mod runner_version;
+mod sandbox;
+mod search;
+mod settings;
+mod shutdown;
+mod smoke_test;
+mod startup;
+mod stats;
 mod storage;
+mod sync;
+mod tags;
+mod trash;
+mod vndb;
+mod wine_tools;
+mod winedbg;
+mod winetricks;
+mod ymgal;
 
 // --- 统一的搜索结果结构 ---
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct SearchResult {
     id: String,
     title: String,
@@ -18,6 +78,60 @@ struct SearchResult {
     source: String,
     url: String,
     date: Option<String>,
+    // 各语言的标题，键为语言代码（如 ja-jp），供之后切换显示语言使用
+    #[serde(default)]
+    titles: BTreeMap<String, String>,
+}
+
+// 按用户偏好的语言顺序选择标题，都没有时取任意一个非空标题
+fn pick_title(titles: &BTreeMap<String, String>, preferred: &[String]) -> String {
+    preferred
+        .iter()
+        .filter_map(|lang| titles.get(lang))
+        .chain(titles.values())
+        .find(|t| !t.is_empty())
+        .cloned()
+        .unwrap_or_default()
+}
+
+// --- 分页搜索参数与结果 ---
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SearchOptions {
+    page: Option<u32>,
+    limit: Option<u32>,
+    sort_field: Option<String>,
+    // asc / desc
+    sort_order: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchPage {
+    results: Vec<SearchResult>,
+    page: u32,
+    limit: u32,
+    // 服务端返回了总数时才有值
+    total: Option<u64>,
+    has_more: bool,
+}
+
+const DEFAULT_SEARCH_LIMIT: u32 = 12;
+const MAX_SEARCH_LIMIT: u32 = 50;
+
+// 排序字段会拼进请求，只允许字母、数字与下划线
+fn sanitize_sort_field(field: Option<&str>, default: &str) -> String {
+    field
+        .filter(|f| !f.is_empty() && f.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(default)
+        .to_string()
+}
+
+fn sanitize_sort_order(order: Option<&str>) -> String {
+    match order {
+        Some("asc") => "asc".to_string(),
+        _ => "desc".to_string(),
+    }
 }
 
 // --- TouchGal 辅助结构 ---
@@ -45,10 +159,12 @@ struct TouchGalRequestBody {
     selectedMonths: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 struct GameDirInfo {
     dir_name: String,
     executables: Vec<String>,
+    // 目录中的压缩包（已按分卷归并）
+    archives: Vec<archive::ArchiveSet>,
 }
 
 #[derive(Deserialize)]
@@ -88,46 +204,32 @@ fn get_home_dir() -> String {
 }
 
 #[command]
-fn get_system_fonts() -> Vec<String> {
-    let source = SystemSource::new();
-    match source.all_families() {
-        Ok(families) => {
-            let mut fonts = families;
-            fonts.sort(); // 排序
-            fonts.dedup(); // 去重
-            fonts
-        },
-        Err(_) => vec!["System Default".to_string()]
-    }
+async fn fetch_ymgal_news(app: tauri::AppHandle, page: u32) -> Result<serde_json::Value, String> {
+    log_info!("Backend fetching Ymgal news page: {}", page); // 后端日志，方便调试
+    
+    let data = news::fetch_ymgal_page(&app, page).await?;
+    Ok(news::filter_ymgal_response(&app, data))
 }
 
 #[command]
-async fn fetch_ymgal_news(page: u32) -> Result<serde_json::Value, String> {
-    println!("Backend fetching Ymgal news page: {}", page); // 后端日志，方便调试
-    
-    let client = reqwest::Client::new();
-    let url = format!("https://www.ymgal.games/co/topic/list?type=NEWS&page={}", page);
-
-    let res = client.get(&url)
-        .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-        .header("Accept", "application/json")
-        .send()
-        .await
-        .map_err(|e| format!("Request Error: {}", e))?;
-
-    if !res.status().is_success() {
-        return Err(format!("Server returned status: {}", res.status()));
-    }
-
-    let data: serde_json::Value = res.json().await.map_err(|e| format!("Parse Error: {}", e))?;
-    Ok(data)
+async fn search_game(app: tauri::AppHandle, keyword: String, source: String) -> Result<Vec<SearchResult>, String> {
+    search_game_page(app, keyword, source, None).await.map(|p| p.results)
 }
 
 #[command]
-async fn search_game(keyword: String, source: String) -> Result<Vec<SearchResult>, String> {
-    println!("\n=== 开始搜索 [{}] 关键词: {} ===", source, keyword);
+async fn search_game_page(
+    app: tauri::AppHandle,
+    keyword: String,
+    source: String,
+    options: Option<SearchOptions>,
+) -> Result<SearchPage, String> {
+    log_info!("开始搜索 [{}] 关键词: {}", source, keyword);
+    let options = options.unwrap_or_default();
+    let page = options.page.unwrap_or(1).max(1);
+    let limit = options.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
     let client = reqwest::Client::new();
     let mut results = Vec::new();
+    let mut total: Option<u64> = None;
 
     match source.as_str() {
         "touchgal" => {
@@ -139,18 +241,18 @@ async fn search_game(keyword: String, source: String) -> Result<Vec<SearchResult
 
             let body = TouchGalRequestBody {
                 queryString: query_string_json,
-                limit: 12,
+                limit: limit as i32,
                 searchOption: TouchGalSearchOption {
                     searchInIntroduction: false,
                     searchInAlias: true,
                     searchInTag: false,
                 },
-                page: 1,
+                page: page as i32,
                 selectedType: "all".to_string(),
                 selectedLanguage: "all".to_string(),
                 selectedPlatform: "all".to_string(),
-                sortField: "resource_update_time".to_string(),
-                sortOrder: "desc".to_string(),
+                sortField: sanitize_sort_field(options.sort_field.as_deref(), "resource_update_time"),
+                sortOrder: sanitize_sort_order(options.sort_order.as_deref()),
                 selectedYears: vec!["all".to_string()],
                 selectedMonths: vec!["all".to_string()],
             };
@@ -172,12 +274,13 @@ async fn search_game(keyword: String, source: String) -> Result<Vec<SearchResult
 
             // 1. 获取原始文本 (关键调试步骤)
             let raw_text = res.text().await.map_err(|e| format!("Read Text Failed: {}", e))?;
-            println!("[TouchGal] 原始响应: {}", raw_text); // <--- 请在终端查看这行输出
+            log_debug!("[TouchGal] 原始响应: {}", raw_text);
 
             // 2. 解析 JSON
             let json_val: serde_json::Value = serde_json::from_str(&raw_text)
                 .map_err(|e| format!("JSON Parse Failed: {}", e))?;
 
+            total = json_val["total"].as_u64();
             if let Some(games) = json_val["galgames"].as_array() {
                 for g in games {
                     // 健壮性解析：如果是数字转字符串，如果是字符串直接用，如果是null给默认值
@@ -191,19 +294,29 @@ async fn search_game(keyword: String, source: String) -> Result<Vec<SearchResult
                         cover: banner,
                         source: "TouchGal".to_string(),
                         url: format!("https://www.touchgal.top/{}", unique_id),
-                        date: None
+                        date: None,
+                        titles: BTreeMap::new(),
                     });
                 }
             } else {
-                println!("[TouchGal] 警告: 未找到 'galgames' 数组，可能是搜索无结果或结构变更");
+                log_warn!("[TouchGal] 警告: 未找到 'galgames' 数组，可能是搜索无结果或结构变更");
             }
         },
         "kungal" => {
+            let title_languages = settings::load_settings(&app).title_languages_for("kungal");
+
             // KunGal 需要 URL 编码
             let encoded_keyword = urlencoding::encode(&keyword);
-            let url = format!("https://www.kungal.com/api/search?keywords={}&type=galgame&page=1&limit=12", encoded_keyword);
+            let url = format!(
+                "https://www.kungal.com/api/search?keywords={}&type=galgame&page={}&limit={}&sortField={}&sortOrder={}",
+                encoded_keyword,
+                page,
+                limit,
+                sanitize_sort_field(options.sort_field.as_deref(), "resource_update_time"),
+                sanitize_sort_order(options.sort_order.as_deref())
+            );
             
-            println!("[KunGal] Request URL: {}", url);
+            log_info!("[KunGal] Request URL: {}", url);
 
             let res = client.get(&url)
                 .header("Host", "www.kungal.com")
@@ -215,13 +328,21 @@ async fn search_game(keyword: String, source: String) -> Result<Vec<SearchResult
 
             // 1. 获取原始文本
             let raw_text = res.text().await.map_err(|e| format!("Read Text Failed: {}", e))?;
-            println!("[KunGal] 原始响应: {}", raw_text); // 调试用输出
+            log_debug!("[KunGal] 原始响应: {}", raw_text); // 调试用输出
 
             // 2. 解析 JSON
             let json_val: serde_json::Value = serde_json::from_str(&raw_text)
                 .map_err(|e| format!("JSON Parse Failed: {}", e))?;
 
-            if let Some(games) = json_val.as_array() {
+            // 新版接口返回 { galgames, total }，旧版直接返回数组
+            let games = if json_val.is_array() {
+                json_val.as_array()
+            } else {
+                total = json_val["total"].as_u64().or_else(|| json_val["totalCount"].as_u64());
+                json_val["galgames"].as_array().or_else(|| json_val["data"].as_array())
+            };
+
+            if let Some(games) = games {
                 for g in games {
                     // id 有时是数字有时是字符串，统一处理
                     let id = if let Some(n) = g["id"].as_i64() {
@@ -230,19 +351,15 @@ async fn search_game(keyword: String, source: String) -> Result<Vec<SearchResult
                         g["id"].as_str().unwrap_or("").to_string()
                     };
 
-                    // 名字多语言回退逻辑
-                    let name = if let Some(name_obj) = g["name"].as_object() {
-                        let cn = name_obj.get("zh-cn").and_then(|v| v.as_str()).unwrap_or("");
-                        let tw = name_obj.get("zh-tw").and_then(|v| v.as_str()).unwrap_or("");
-                        let jp = name_obj.get("ja-jp").and_then(|v| v.as_str()).unwrap_or("");
-                        let en = name_obj.get("en-us").and_then(|v| v.as_str()).unwrap_or("");
-                        
-                        if !cn.is_empty() { cn.to_string() }
-                        else if !tw.is_empty() { tw.to_string() }
-                        else if !jp.is_empty() { jp.to_string() }
-                        else { en.to_string() }
+                    // 名字多语言：全部保留，并按用户偏好的语言顺序选择显示标题
+                    let (name, titles) = if let Some(name_obj) = g["name"].as_object() {
+                        let titles: BTreeMap<String, String> = name_obj
+                            .iter()
+                            .filter_map(|(lang, v)| v.as_str().filter(|t| !t.is_empty()).map(|t| (lang.clone(), t.to_string())))
+                            .collect();
+                        (pick_title(&titles, &title_languages), titles)
                     } else {
-                        g["name"].as_str().unwrap_or("未知标题").to_string()
+                        (g["name"].as_str().unwrap_or("未知标题").to_string(), BTreeMap::new())
                     };
 
                     let banner = g["banner"].as_str().unwrap_or("").to_string();
@@ -256,17 +373,36 @@ async fn search_game(keyword: String, source: String) -> Result<Vec<SearchResult
                         source: "KunGal".to_string(),
                         url: format!("https://www.kungal.com/galgame/{}", id),
                         date: update_time,
+                        titles,
                     });
                 }
             } else {
-                println!("[KunGal] 警告: 根节点不是数组，可能出错");
+                log_warn!("[KunGal] 警告: 未找到结果数组，可能出错");
             }
         },
+        "vndb" => {
+            let title_languages = settings::load_settings(&app).title_languages_for("vndb");
+            let sort_order = sanitize_sort_order(options.sort_order.as_deref());
+            let (found, count) = vndb::search(&client, &keyword, page, limit, options.sort_field.as_deref(), &sort_order, &title_languages).await?;
+            results = found;
+            total = count;
+        },
+        "bangumi" => {
+            let title_languages = settings::load_settings(&app).title_languages_for("bangumi");
+            let (found, count) = bangumi::search(&client, &keyword, page, limit, options.sort_field.as_deref(), &title_languages).await?;
+            results = found;
+            total = count;
+        },
         _ => return Err("未知的搜索源".to_string()),
     }
 
-    println!("=== 搜索结束，找到 {} 条结果 ===\n", results.len());
-    Ok(results)
+    log_info!("搜索结束，找到 {} 条结果", results.len());
+    // 没有总数时，本页填满即认为还有下一页
+    let has_more = match total {
+        Some(t) => (page as u64) * (limit as u64) < t,
+        None => results.len() as u32 >= limit,
+    };
+    Ok(SearchPage { results, page, limit, total, has_more })
 }
 
 #[command]
@@ -303,7 +439,7 @@ fn get_directory_keywords(path: String) -> Result<Vec<String>, String> {
     Ok(keywords)
 }
 
-// 辅助递归函数，寻找目录下所有的 .exe 文件，限制深度防止死循环
+// 辅助递归函数，寻找目录下所有的 .exe / .msi 文件，限制深度防止死循环
 fn find_exes(dir: &Path, exes: &mut Vec<String>, depth: usize) {
     if depth > 5 { return; } // 最大递归深度限制为 5 层
     if let Ok(entries) = std::fs::read_dir(dir) {
@@ -311,7 +447,7 @@ fn find_exes(dir: &Path, exes: &mut Vec<String>, depth: usize) {
             if let Ok(ft) = entry.file_type() {
                 if ft.is_file() {
                     let p = entry.path();
-                    if p.extension().and_then(|ext| ext.to_str()) == Some("exe") {
+                    if exe_info::is_windows_launchable(&p) {
                         exes.push(p.to_string_lossy().into_owned());
                     }
                 } else if ft.is_dir() {
@@ -322,7 +458,7 @@ fn find_exes(dir: &Path, exes: &mut Vec<String>, depth: usize) {
     }
 }
 
-// 扫描指定的根目录，提取包含 .exe 的一级子目录
+// 扫描指定的根目录，提取包含 .exe / .msi 的一级子目录
 #[command]
 fn scan_game_directories(path: String) -> Result<Vec<GameDirInfo>, String> {
     let mut results = Vec::new();
@@ -346,6 +482,7 @@ fn scan_game_directories(path: String) -> Result<Vec<GameDirInfo>, String> {
                     results.push(GameDirInfo {
                         dir_name,
                         executables,
+                        archives: archive::detect_archive_sets(&entry.path()),
                     });
                 }
             }
@@ -477,43 +614,219 @@ async fn migrate_game_files(payload: MigrateGameFilesPayload) -> Result<MigrateG
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let handler = tauri::generate_handler![
+        runner::launch_game,
+        runner::stop_game,
+        runner::get_running_games,
+        launch_queue::get_launch_queue,
+        launch_queue::cancel_queued_launch,
+        shutdown::confirm_quit,
+        shutdown::replay_recovered_sessions,
+        runner::pause_game,
+        runner::resume_game,
+        smoke_test::test_launch,
+        runner::get_crossover_bottles,
+        discovery::discover_bottle_games,
+        db::db_list_instances,
+        db::db_get_instance,
+        db::db_upsert_instance,
+        db::db_delete_instance,
+        db::db_list_sessions,
+        db::db_add_session,
+        db::db_delete_session,
+        db::db_list_tags,
+        db::db_set_tags,
+        tags::list_tags,
+        tags::create_tag,
+        tags::rename_tag,
+        tags::delete_tag,
+        tags::assign_tag,
+        tags::query_instances_by_tags,
+        tags::list_collections,
+        tags::create_collection,
+        tags::rename_collection,
+        tags::delete_collection,
+        tags::set_collection_instances,
+        tags::query_instances_in_collection,
+        instance::set_favorite,
+        instance::set_rating,
+        instance::set_finished,
+        instance::query_instances,
+        bangumi::bangumi_get_details,
+        bangumi::link_bangumi,
+        bangumi::bangumi_set_token,
+        bangumi::bangumi_token_status,
+        vndb::vndb_get_details,
+        vndb::link_vndb,
+        list_export::export_list,
+        autosave::stage_instances,
+        autosave::flush_autosave,
+        instance::bulk_update_instances,
+        libraries::list_libraries,
+        libraries::create_library,
+        libraries::rename_library,
+        libraries::set_library_overrides,
+        libraries::switch_library,
+        libraries::delete_library,
+        custom_fields::list_custom_fields,
+        custom_fields::define_custom_field,
+        custom_fields::delete_custom_field,
+        custom_fields::set_custom_field_value,
+        stats::get_library_stats,
+        assets::import_asset,
+        assets::localize_covers,
+        assets::gc_assets,
+        journal::list_changes,
+        journal::undo_last_change,
+        instance::upsert_instance,
+        instance::delete_instance,
+        notes::load_note,
+        notes::save_note,
+        trash::list_trash,
+        trash::trash_instance,
+        trash::restore_instance,
+        trash::purge_trash,
+        search::search_library,
+        profiles::list_launch_profiles,
+        profiles::save_launch_profile,
+        profiles::delete_launch_profile,
+        last_exit::get_last_exit,
+        runner_version::get_runner_version,
+        runner_version::get_instance_runner_versions,
+        finder::reveal_in_finder,
+        sandbox::get_bottle_sandbox,
+        sandbox::set_bottle_sandbox,
+        exe_info::inspect_exe,
+        runner::list_crossover_installs,
+        benchmark::get_boot_benchmarks,
+        benchmark::get_boot_benchmark_summary,
+        storage::save_instances,
+        storage::get_library_generation,
+        storage::load_instances,
+        storage::get_data_dir,
+        storage::migrate_data_dir,
+        storage::move_data_dir,
+        storage::get_icloud_data_dir,
+        storage::move_data_to_icloud,
+        storage::list_backups,
+        storage::preview_backup,
+        storage::restore_backup,
+        storage::set_library_encryption,
+        library_export::export_library,
+        library_export::import_library,
+        external_library::import_external_library,
+        sync::get_sync_config,
+        sync::set_sync_config,
+        sync::sync_now,
+        maintenance::get_storage_breakdown,
+        maintenance::run_cleanup,
+        storage::get_scripts,
+        storage::read_script,
+        storage::save_script,
+        settings::get_app_settings,
+        settings::save_app_settings,
+        startup::get_ready_state,
+        startup::get_safe_mode,
+        logging::subscribe_logs,
+        logging::unsubscribe_logs,
+        compat::submit_compat_report,
+        compat::fetch_compat_reports,
+        bottle::suggest_bottle,
+        bottle::create_bottle,
+        bottle::delete_bottle,
+        bottle::clone_bottle,
+        bottle::kill_bottle,
+        bottle::clean_bottle,
+        installer::install_game,
+        audio::get_bottle_audio,
+        audio::set_bottle_audio,
+        bottle::get_bottle_info,
+        bottle::get_graphics_backend,
+        bottle::set_graphics_backend,
+        diskimage::mount_disk_image,
+        diskimage::unmount_disk_image,
+        diskimage::list_mounted_images,
+        archive::scan_archives,
+        archive::estimate_archive,
+        archive::extract_archive,
+        winetricks::run_winetricks,
+        winetricks::list_provision_presets,
+        winetricks::provision_bottle,
+        wine_tools::run_exe_in_bottle,
+        wine_tools::list_wine_tools,
+        wine_tools::launch_wine_tool,
+        attachments::list_attachments,
+        attachments::add_attachment,
+        attachments::open_attachment,
+        attachments::delete_attachment,
+        get_home_dir,
+        fonts::get_system_fonts,
+        fonts::install_font,
+        fonts::install_font_to_bottle,
+        fonts::refresh_fonts,
+        fonts::set_ui_font,
+        fetch_ymgal_news,
+        news::fetch_news,
+        ymgal::ymgal_login,
+        ymgal::ymgal_logout,
+        ymgal::ymgal_account_status,
+        ymgal::ymgal_topic_action,
+        search_game,
+        search_game_page,
+        get_directory_keywords,
+        scan_game_directories,
+        matcher::start_batch_match,
+        matcher::resume_batch_match,
+        matcher::get_batch_match,
+        matcher::list_unfinished_matches,
+        matcher::cancel_batch_match,
+        get_pd_vms,
+        migrate_game_files,
+        crash_report::get_pending_crash_reports,
+        crash_report::dismiss_crash_reports,
+        crash_report::create_diagnostics_bundle
+    ];
+
     tauri::Builder::default()
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![
-            runner::launch_game,
-            runner::stop_game,
-            runner::get_crossover_bottles,
-            storage::save_instances,
-            storage::load_instances,
-            storage::get_scripts,
-            storage::read_script,
-            storage::save_script,
-            get_home_dir,
-            get_system_fonts,
-            fetch_ymgal_news,
-            search_game,
-            get_directory_keywords,
-            scan_game_directories,
-            get_pd_vms,
-            migrate_game_files
-        ])
+        // 记录最近调用的命令，崩溃时写入报告
+        .invoke_handler(move |invoke: tauri::ipc::Invoke| {
+            crash_report::record_command(invoke.message.command());
+            handler(invoke)
+        })
         .setup(|app| {
-            // 获取主窗口
-            let window = app.get_webview_window("main").unwrap();
-
-            // 仅在 macOS 上应用磨砂效果
-            #[cfg(target_os = "macos")]
-            apply_vibrancy(&window, NSVisualEffectMaterial::HudWindow, None, None)
-                .expect("Unsupported platform! 'apply_vibrancy' is only supported on macOS");
+            crash_report::install_panic_hook(app.handle());
+            let safe_mode = startup::begin_startup(app.handle());
+
+            // 主窗口改为手动创建，以便在页面脚本执行前注入所选字体（安全模式下使用默认字体）
+            let ui_font = if safe_mode { String::new() } else { settings::load_settings(app.handle()).ui_font };
+            if let Some(window_config) = app.config().app.windows.first() {
+                WebviewWindowBuilder::from_config(app.handle(), window_config)?
+                    .initialization_script(&fonts::ui_font_script(&ui_font))
+                    .build()?;
+            }
 
-            // 初始化数据库 (预留位置)
-            // database::init_db();
+            // 磨砂效果、字体枚举、数据检查等较重的工作推迟到窗口显示之后
+            startup::run_deferred_init(app.handle().clone());
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            match event {
+                // 有游戏仍在运行时拦截退出，避免丢失游玩时长
+                tauri::RunEvent::ExitRequested { api, .. } => shutdown::handle_exit_requested(app, &api),
+                // 退出前写入尚未自动保存的修改
+                tauri::RunEvent::Exit => {
+                    if let Err(e) = autosave::flush(app) {
+                        log_error!("退出前保存修改失败: {}", e);
+                    }
+                }
+                _ => {}
+            }
+        });
 }
@@ -0,0 +1,270 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::autosave;
+use crate::custom_fields;
+use crate::journal;
+use crate::playtime;
+use crate::profiles;
+use crate::storage;
+use crate::sync;
+use crate::tags;
+
+// 多个游戏库（如"本体机"与"外接硬盘"，或家庭成员各自的游戏库）。
+// 每个游戏库有自己的实例文件、修改日志、标签与合集、自定义字段、启动方案、同步状态、备份与本机数据库，并可以覆盖部分后端设置；
+// 笔记、附件、游玩时长等按实例 ID 保存的数据各游戏库共用同一份存储。
+// 切换游戏库后重启应用，数据库连接等按新游戏库重新初始化
+const LIBRARIES_FILE: &str = "libraries.json";
+pub(crate) const DEFAULT_LIBRARY: &str = "default";
+const DEFAULT_LIBRARY_NAME: &str = "默认游戏库";
+// 非默认游戏库的本机数据库目录（位于默认数据目录，不随数据目录迁移）
+pub(crate) const LIBRARY_DBS_DIR: &str = "library_dbs";
+
+// 每个游戏库各自一份的文件，文件名按 scoped_file_name 区分
+const LIBRARY_FILES: [&str; 7] = [
+    storage::DATA_FILENAME,
+    storage::GENERATION_FILENAME,
+    journal::JOURNAL_FILENAME,
+    tags::TAGS_FILE,
+    custom_fields::FIELDS_FILE,
+    profiles::PROFILES_FILE,
+    sync::STATE_FILE,
+];
+
+// 本进程使用的游戏库，首次读取后固定，切换游戏库会重启应用
+static ACTIVE_LIBRARY: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LibraryDef {
+    pub id: String,
+    pub name: String,
+    pub created_at: u64,
+    // 覆盖 settings.json 中的同名设置
+    pub settings_overrides: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LibraryRegistry {
+    pub active: String,
+    pub libraries: Vec<LibraryDef>,
+}
+
+fn read_registry(app: &AppHandle) -> LibraryRegistry {
+    let mut registry: LibraryRegistry = storage::resolve_data_path(app, LIBRARIES_FILE)
+        .ok()
+        .and_then(|p| storage::read_data_file(&p).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    if !registry.libraries.iter().any(|l| l.id == DEFAULT_LIBRARY) {
+        registry.libraries.insert(0, LibraryDef { id: DEFAULT_LIBRARY.to_string(), name: DEFAULT_LIBRARY_NAME.to_string(), ..Default::default() });
+    }
+    if !registry.libraries.iter().any(|l| l.id == registry.active) {
+        registry.active = DEFAULT_LIBRARY.to_string();
+    }
+    registry
+}
+
+fn write_registry(app: &AppHandle, registry: &LibraryRegistry) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(registry).map_err(|e| e.to_string())?;
+    storage::write_atomic(&storage::resolve_data_path(app, LIBRARIES_FILE)?, text.as_bytes()).map_err(|e| format!("保存游戏库列表失败: {}", e))
+}
+
+pub(crate) fn active_library(app: &AppHandle) -> String {
+    let mut active = ACTIVE_LIBRARY.lock().unwrap_or_else(|e| e.into_inner());
+    active.get_or_insert_with(|| read_registry(app).active).clone()
+}
+
+// 按游戏库区分的文件名：默认游戏库保持原名，其他游戏库加上 ID，如 instances-lib-1700000000000.json
+pub(crate) fn scoped_file_name(name: &str, library_id: &str) -> String {
+    if library_id == DEFAULT_LIBRARY {
+        return name.to_string();
+    }
+    match name.rsplit_once('.') {
+        Some((stem, ext)) => format!("{}-{}.{}", stem, library_id, ext),
+        None => format!("{}-{}", name, library_id),
+    }
+}
+
+// 当前游戏库的本机数据库文件（相对默认数据目录）
+pub(crate) fn db_file_name(app: &AppHandle, default_name: &str) -> String {
+    let id = active_library(app);
+    if id == DEFAULT_LIBRARY {
+        default_name.to_string()
+    } else {
+        format!("{}/{}.db", LIBRARY_DBS_DIR, id)
+    }
+}
+
+// 当前游戏库对设置的覆盖
+pub(crate) fn active_overrides(app: &AppHandle) -> Map<String, Value> {
+    let id = active_library(app);
+    read_registry(app).libraries.into_iter().find(|l| l.id == id).map(|l| l.settings_overrides).unwrap_or_default()
+}
+
+// 修改设置时，被当前游戏库覆盖的项写入覆盖表，其余写入 settings.json
+pub(crate) fn update_active_overrides(app: &AppHandle, values: &Map<String, Value>) -> Result<(), String> {
+    let id = active_library(app);
+    let mut registry = read_registry(app);
+    let library = match registry.libraries.iter_mut().find(|l| l.id == id) {
+        Some(l) => l,
+        None => return Ok(()),
+    };
+    let mut changed = false;
+    for (key, value) in library.settings_overrides.iter_mut() {
+        if let Some(new) = values.get(key).filter(|new| *new != value) {
+            *value = new.clone();
+            changed = true;
+        }
+    }
+    if changed {
+        write_registry(app, &registry)?;
+    }
+    Ok(())
+}
+
+// 其他游戏库中的实例 ID，清理按实例 ID 保存的共用数据时需要保留
+pub(crate) fn other_library_instance_ids(app: &AppHandle) -> Result<HashSet<String>, String> {
+    let active = active_library(app);
+    let mut ids = HashSet::new();
+    for library in read_registry(app).libraries.iter().filter(|l| l.id != active) {
+        ids.extend(library_instance_ids(app, &library.id)?);
+    }
+    Ok(ids)
+}
+
+// 游戏库中的实例 ID，实例文件不存在时为空。无法解密或解析时返回错误，不能当作空游戏库，
+// 否则会清除该游戏库仍在使用的共用数据
+fn library_instance_ids(app: &AppHandle, library_id: &str) -> Result<Vec<String>, String> {
+    let path = storage::resolve_data_path(app, &scoped_file_name(storage::DATA_FILENAME, library_id))?;
+    let data = match storage::read_data_bytes(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("无法读取游戏库 {}: {}", library_id, e)),
+    };
+    let text = storage::decode_library(data).map_err(|e| format!("无法读取游戏库 {}: {}", library_id, e))?;
+    let values: Vec<Value> = serde_json::from_str(&text).map_err(|e| format!("游戏库 {} 格式错误: {}", library_id, e))?;
+    Ok(values.iter().filter_map(|v| v["id"].as_str().map(|s| s.to_string())).collect())
+}
+
+#[command]
+pub fn list_libraries(app: AppHandle) -> LibraryRegistry {
+    let mut registry = read_registry(&app);
+    registry.active = active_library(&app);
+    registry
+}
+
+#[command]
+pub fn create_library(app: AppHandle, name: String, settings_overrides: Option<Map<String, Value>>) -> Result<LibraryDef, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("游戏库名称不能为空".to_string());
+    }
+    let mut registry = read_registry(&app);
+    if registry.libraries.iter().any(|l| l.name == name) {
+        return Err(format!("游戏库已存在: {}", name));
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+    let library = LibraryDef {
+        id: format!("lib-{}", now),
+        name,
+        created_at: now / 1000,
+        settings_overrides: settings_overrides.unwrap_or_default(),
+    };
+    registry.libraries.push(library.clone());
+    write_registry(&app, &registry)?;
+    log_info!("已创建游戏库 {}", library.name);
+    Ok(library)
+}
+
+#[command]
+pub fn rename_library(app: AppHandle, id: String, name: String) -> Result<LibraryDef, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("游戏库名称不能为空".to_string());
+    }
+    let mut registry = read_registry(&app);
+    if registry.libraries.iter().any(|l| l.name == name && l.id != id) {
+        return Err(format!("游戏库已存在: {}", name));
+    }
+    let library = registry.libraries.iter_mut().find(|l| l.id == id).ok_or(format!("未找到游戏库: {}", id))?;
+    library.name = name;
+    let library = library.clone();
+    write_registry(&app, &registry)?;
+    Ok(library)
+}
+
+// 整体替换游戏库的设置覆盖，键名与 settings.json 相同；当前游戏库的覆盖立即生效
+#[command]
+pub fn set_library_overrides(app: AppHandle, id: String, settings_overrides: Map<String, Value>) -> Result<LibraryDef, String> {
+    let mut registry = read_registry(&app);
+    let library = registry.libraries.iter_mut().find(|l| l.id == id).ok_or(format!("未找到游戏库: {}", id))?;
+    library.settings_overrides = settings_overrides;
+    let library = library.clone();
+    write_registry(&app, &registry)?;
+    Ok(library)
+}
+
+// 切换到另一个游戏库并重启应用
+#[command]
+pub fn switch_library(app: AppHandle, id: String) -> Result<(), String> {
+    let mut registry = read_registry(&app);
+    if !registry.libraries.iter().any(|l| l.id == id) {
+        return Err(format!("未找到游戏库: {}", id));
+    }
+    if active_library(&app) == id {
+        return Ok(());
+    }
+    autosave::flush(&app)?;
+    // 持有写入锁切换，避免正在进行的写入落到新游戏库的文件中
+    storage::with_library_lock(&app, |_| {
+        registry.active = id.clone();
+        write_registry(&app, &registry)
+    })?;
+    log_info!("切换到游戏库 {}，重启应用", id);
+    app.restart()
+}
+
+// 删除游戏库自己的文件（见 LIBRARY_FILES）、本机数据库与各实例的游玩时长。不能删除默认游戏库与当前使用的游戏库；
+// 该游戏库的备份保留在备份目录中
+#[command]
+pub fn delete_library(app: AppHandle, id: String) -> Result<(), String> {
+    if id == DEFAULT_LIBRARY {
+        return Err("不能删除默认游戏库".to_string());
+    }
+    if active_library(&app) == id {
+        return Err("不能删除正在使用的游戏库，请先切换到其他游戏库".to_string());
+    }
+    let mut registry = read_registry(&app);
+    let name = registry.libraries.iter().find(|l| l.id == id).map(|l| l.name.clone()).ok_or(format!("未找到游戏库: {}", id))?;
+
+    // 其他游戏库（包括当前游戏库）中也有的实例共用游玩时长，不能清除
+    let mut shared = HashSet::new();
+    for library in registry.libraries.iter().filter(|l| l.id != id) {
+        shared.extend(library_instance_ids(&app, &library.id)?);
+    }
+    let ids: HashSet<String> = library_instance_ids(&app, &id)?.into_iter().filter(|i| !shared.contains(i)).collect();
+    storage::with_library_lock(&app, |_| {
+        playtime::forget(&app, &ids)?;
+        for name in LIBRARY_FILES {
+            let file = storage::resolve_data_path(&app, &scoped_file_name(name, &id))?;
+            if file.exists() {
+                fs::remove_file(&file).map_err(|e| format!("无法删除游戏库文件: {}", e))?;
+            }
+        }
+        registry.libraries.retain(|l| l.id != id);
+        write_registry(&app, &registry)
+    })?;
+    let db_dir = storage::local_data_path(&app, LIBRARY_DBS_DIR)?;
+    for suffix in ["db", "db-wal", "db-shm"] {
+        let _ = fs::remove_file(db_dir.join(format!("{}.{}", id, suffix)));
+    }
+    log_info!("已删除游戏库 {}", name);
+    Ok(())
+}
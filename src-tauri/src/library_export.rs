@@ -0,0 +1,504 @@
+use tauri::{AppHandle, Emitter, Manager, command};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::attachments;
+use crate::autosave;
+use crate::db::{self, SessionRow, TagRow};
+use crate::instance::{self, GameInstance};
+use crate::notes;
+use crate::paths;
+use crate::post_session::SAVE_BACKUPS_DIR;
+use crate::profiles::{self, LaunchProfile};
+use crate::runner::expand_tilde;
+use crate::settings::{self, AppSettings};
+use crate::storage;
+use crate::tags::{self, TagStore};
+
+// 游戏库导出包：换新 Mac 时一次带走实例、封面、配置与游玩记录
+const BUNDLE_FORMAT: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const COVERS_DIR: &str = "covers";
+// 前端 convertFileSrc 生成的本地图片地址前缀
+const ASSET_PREFIX: &str = "asset://localhost/";
+// 完整迁移时下载网络封面的超时
+const COVER_DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20);
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct Manifest {
+    format: u32,
+    app_version: String,
+    created_at: u64,
+    // 实例 ID -> 导出包中的封面文件名
+    covers: BTreeMap<String, String>,
+    // 完整迁移包：额外包含网络封面、附件（截图等）与存档备份
+    full: bool,
+    // 导出时的用户目录，导入时把其中的绝对路径改写为新机器的用户目录
+    home: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ImportReport {
+    added: usize,
+    updated: usize,
+    skipped: usize,
+    covers: usize,
+    sessions: usize,
+    attachments: usize,
+    save_backups: usize,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn staging_dir(kind: &str) -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir().join(format!("asumigal-{}-{}", kind, now_secs()));
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| format!("写入 {:?} 失败: {}", path, e))
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Option<T>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(path).map_err(|e| format!("读取 {:?} 失败: {}", path, e))?;
+    serde_json::from_str(&text).map(Some).map_err(|e| format!("{:?} 格式错误: {}", path.file_name().unwrap_or_default(), e))
+}
+
+// 复制目录下的文件（不递归）；skip_existing 为 true 时不覆盖目标中已有的文件
+fn copy_dir_files(src: &Path, dest: &Path, skip_existing: bool) -> Result<usize, String> {
+    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+    let mut copied = 0;
+    for entry in fs::read_dir(src).map_err(|e| e.to_string())?.flatten() {
+        let target = dest.join(entry.file_name());
+        if !entry.path().is_file() || (skip_existing && target.exists()) {
+            continue;
+        }
+        fs::copy(entry.path(), &target).map_err(|e| format!("复制 {:?} 失败: {}", entry.path(), e))?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+// 封面为本地文件时返回其路径；网络图片不需要打包
+fn local_cover(image: &str) -> Option<PathBuf> {
+    let path = match image.strip_prefix(ASSET_PREFIX) {
+        Some(encoded) => PathBuf::from(urlencoding::decode(encoded).ok()?.into_owned()),
+        None if image.starts_with('/') || image.starts_with("~/") => expand_tilde(image),
+        None => return None,
+    };
+    path.is_file().then_some(path)
+}
+
+// 完整迁移时把网络封面下载进导出包，新机器上无需联网也能显示；返回实例 ID -> 文件名
+async fn download_remote_covers(instances: &[GameInstance], covers_dir: &Path) -> BTreeMap<String, String> {
+    let mut covers = BTreeMap::new();
+    let client = match reqwest::Client::builder().timeout(COVER_DOWNLOAD_TIMEOUT).build() {
+        Ok(c) => c,
+        Err(_) => return covers,
+    };
+    if fs::create_dir_all(covers_dir).is_err() {
+        return covers;
+    }
+    for inst in instances {
+        let url = match inst.background_image.as_deref() {
+            Some(u) if u.starts_with("http://") || u.starts_with("https://") => u,
+            _ => continue,
+        };
+        let ext = url
+            .split(['?', '#'])
+            .next()
+            .and_then(|u| u.rsplit('/').next())
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_lowercase())
+            .filter(|ext| ["png", "jpg", "jpeg", "webp", "gif", "avif"].contains(&ext.as_str()))
+            .unwrap_or_else(|| "jpg".to_string());
+        let file = format!("{}.{}", inst.id, ext);
+        let bytes = match client.get(url).send().await.and_then(|r| r.error_for_status()) {
+            Ok(res) => res.bytes().await,
+            Err(e) => Err(e),
+        };
+        match bytes {
+            Ok(bytes) if fs::write(covers_dir.join(&file), &bytes).is_ok() => {
+                covers.insert(inst.id.clone(), file);
+            }
+            Ok(_) => {}
+            Err(e) => log_warn!("下载封面 {} 失败: {}", url, e),
+        }
+    }
+    covers
+}
+
+// 导出的选项；remote_covers 为已下载进导出包的网络封面
+struct ExportOptions {
+    full: bool,
+    remote_covers: BTreeMap<String, String>,
+}
+
+fn export_to(app: &AppHandle, staging: &Path, dest: &Path, sessions: &[SessionRow], tags: &[TagRow], options: ExportOptions) -> Result<(), String> {
+    let instances = storage::read_instances(app);
+    let ExportOptions { full, remote_covers } = options;
+    let mut manifest = Manifest {
+        format: BUNDLE_FORMAT,
+        app_version: app.package_info().version.to_string(),
+        created_at: now_secs(),
+        covers: remote_covers,
+        full,
+        home: dirs::home_dir().map(|h| h.to_string_lossy().to_string()).unwrap_or_default(),
+    };
+
+    let covers_dir = staging.join(COVERS_DIR);
+    fs::create_dir_all(&covers_dir).map_err(|e| e.to_string())?;
+    for inst in &instances {
+        let cover = match inst.background_image.as_deref().and_then(local_cover) {
+            Some(c) => c,
+            None => continue,
+        };
+        let ext = cover.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "png".to_string());
+        let file = format!("{}.{}", inst.id, ext);
+        match fs::copy(&cover, covers_dir.join(&file)) {
+            Ok(_) => {
+                manifest.covers.insert(inst.id.clone(), file);
+            }
+            Err(e) => log_warn!("导出封面 {:?} 失败: {}", cover, e),
+        }
+    }
+
+    // 笔记以实例 ID 命名，原样复制
+    let notes_src = storage::resolve_data_path(app, notes::NOTES_DIR)?;
+    if notes_src.is_dir() {
+        copy_dir_files(&notes_src, &staging.join(notes::NOTES_DIR), false)?;
+    }
+
+    // 路径字段以规范形式导出，新机器上用户名不同也能解析
+    let mut stored = serde_json::to_value(&instances).map_err(|e| e.to_string())?;
+    paths::map_instance_paths(&mut stored, paths::to_stored_path);
+    write_json(&staging.join("instances.json"), &stored)?;
+    if full {
+        attachments::export_attachments(app, staging)?;
+        let saves = storage::resolve_data_path(app, SAVE_BACKUPS_DIR)?;
+        if saves.is_dir() {
+            storage::copy_tree(&saves, &staging.join(SAVE_BACKUPS_DIR))?;
+        }
+    }
+    write_json(&staging.join("settings.json"), &settings::load_settings(app))?;
+    write_json(&staging.join("launch_profiles.json"), &profiles::read_profiles(app))?;
+    write_json(&staging.join("library_tags.json"), &tags::read_store(app))?;
+    write_json(&staging.join("sessions.json"), sessions)?;
+    write_json(&staging.join("tags.json"), tags)?;
+    write_json(&staging.join(MANIFEST_FILE), &manifest)?;
+
+    let status = Command::new("ditto")
+        .args(["-c", "-k", "--sequesterRsrc"])
+        .arg(staging)
+        .arg(dest)
+        .status()
+        .map_err(|e| format!("打包游戏库失败: {}", e))?;
+    if !status.success() {
+        return Err("打包游戏库失败".to_string());
+    }
+    log_info!("游戏库已导出到 {:?}，共 {} 个实例", dest, instances.len());
+    Ok(())
+}
+
+// 把实例、封面、后端配置、启动方案与游玩记录打包为一个 zip，返回生成的文件路径。
+// full 为 true 时生成完整迁移包，额外包含网络封面、附件（截图等）与存档备份
+#[command]
+pub async fn export_library(app: AppHandle, path: String, full: Option<bool>) -> Result<String, String> {
+    autosave::flush(&app)?;
+    let full = full.unwrap_or(false);
+    let mut dest = expand_tilde(&path);
+    if dest.is_dir() {
+        dest = dest.join(format!("AsumiGal-library-{}.zip", now_secs()));
+    } else if dest.extension().is_none_or(|e| e != "zip") {
+        dest.set_extension("zip");
+    }
+    if dest.exists() {
+        return Err(format!("目标文件已存在: {:?}", dest));
+    }
+
+    // 数据库尚未就绪时只导出 instances.json 中的游玩记录
+    let sessions = db::all_sessions().await.unwrap_or_default();
+    let tags = db::db_list_tags(app.clone(), None).await.unwrap_or_default();
+
+    let staging = staging_dir("export")?;
+    let remote_covers = if full {
+        download_remote_covers(&storage::read_instances(&app), &staging.join(COVERS_DIR)).await
+    } else {
+        BTreeMap::new()
+    };
+    let result = {
+        let (app, staging, dest) = (app.clone(), staging.clone(), dest.clone());
+        let options = ExportOptions { full, remote_covers };
+        tokio::task::spawn_blocking(move || export_to(&app, &staging, &dest, &sessions, &tags, options))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+    Ok(dest.to_string_lossy().to_string())
+}
+
+// 合并两份游玩记录：同一天取较大值，避免两台机器都玩过时重复累计
+fn merge_instance(local: &mut GameInstance, incoming: GameInstance) {
+    if let Some(history) = incoming.play_history {
+        let merged = local.play_history.get_or_insert_with(Default::default);
+        for (day, secs) in history {
+            let entry = merged.entry(day).or_insert(0);
+            *entry = (*entry).max(secs);
+        }
+    }
+    local.total_play_time = local.total_play_time.max(incoming.total_play_time);
+    local.last_played = local.last_played.max(incoming.last_played);
+    for tag in incoming.tags {
+        if !local.tags.contains(&tag) {
+            local.tags.push(tag);
+        }
+    }
+    if local.background_image.is_none() {
+        local.background_image = incoming.background_image;
+    }
+}
+
+// 解析导出包后待写入的数据。解析时不写入任何文件，实例写入成功后再由 commit_files 写入其他数据
+struct ImportPlan {
+    report: ImportReport,
+    instances: Vec<GameInstance>,
+    // 实际导入（新增或合并）的实例，跳过的实例不带入封面、笔记、附件与游玩记录
+    imported: HashSet<String>,
+    // 导出包中的封面 -> 数据目录中的目标位置
+    covers: Vec<(PathBuf, PathBuf)>,
+    profiles: Vec<LaunchProfile>,
+    tag_store: Option<TagStore>,
+    settings: Option<AppSettings>,
+    sessions: Vec<SessionRow>,
+    tags: Vec<TagRow>,
+}
+
+fn import_from(app: &AppHandle, root: &Path, overwrite: bool) -> Result<ImportPlan, String> {
+    let manifest: Manifest = read_json(&root.join(MANIFEST_FILE))?.ok_or("不是有效的游戏库导出包")?;
+    if manifest.format > BUNDLE_FORMAT {
+        return Err("导出包来自更新版本的应用，请先升级".to_string());
+    }
+
+    let mut stored: Value = read_json(&root.join("instances.json"))?.unwrap_or(Value::Array(Vec::new()));
+    // 旧版本保存的绝对路径仍带着原机器的用户目录，改写为本机的用户目录
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    if !manifest.home.is_empty() && !home.is_empty() && manifest.home != home {
+        let old_home = format!("{}/", manifest.home.trim_end_matches('/'));
+        paths::map_instance_paths(&mut stored, |p| match p.strip_prefix(&old_home) {
+            Some(rest) => format!("{}/{}", home, rest),
+            None => p.to_string(),
+        });
+    }
+    paths::map_instance_paths(&mut stored, |p| paths::resolve_stored_path(p).to_string_lossy().to_string());
+    let mut incoming: Vec<GameInstance> = serde_json::from_value(stored).map_err(|e| format!("导出包中的实例数据格式错误: {}", e))?;
+
+    // 封面稍后复制到数据目录，实例先改为引用新位置
+    let mut report = ImportReport::default();
+    let covers_dest = storage::resolve_data_path(app, COVERS_DIR)?;
+    let mut covers = Vec::new();
+    for inst in incoming.iter_mut() {
+        let file = match manifest.covers.get(&inst.id) {
+            Some(f) if !f.contains('/') && !f.contains("..") => f,
+            _ => continue,
+        };
+        let (src, target) = (root.join(COVERS_DIR).join(file), covers_dest.join(file));
+        if src.is_file() {
+            inst.background_image = Some(format!("{}{}", ASSET_PREFIX, urlencoding::encode(&target.to_string_lossy())));
+            covers.push((inst.id.clone(), src, target));
+        }
+    }
+
+    let mut imported = HashSet::new();
+    let instances = if overwrite {
+        report.added = incoming.len();
+        imported.extend(incoming.iter().map(|i| i.id.clone()));
+        incoming
+    } else {
+        let mut local = storage::read_instances(app);
+        for inst in incoming {
+            match local.iter_mut().find(|l| l.id == inst.id) {
+                Some(existing) if existing.name == inst.name => {
+                    imported.insert(inst.id.clone());
+                    merge_instance(existing, inst);
+                    report.updated += 1;
+                }
+                // 同一 ID 对应不同游戏，保留本机数据
+                Some(_) => report.skipped += 1,
+                None => {
+                    imported.insert(inst.id.clone());
+                    local.push(inst);
+                    report.added += 1;
+                }
+            }
+        }
+        local
+    };
+    instance::validate_library(&instances)?;
+    let covers: Vec<(PathBuf, PathBuf)> =
+        covers.into_iter().filter(|(id, _, _)| imported.contains(id)).map(|(_, src, target)| (src, target)).collect();
+
+    let imported_profiles: Vec<LaunchProfile> = read_json(&root.join("launch_profiles.json"))?.unwrap_or_default();
+    let mut local_profiles = if overwrite { Vec::new() } else { profiles::read_profiles(app) };
+    for profile in imported_profiles {
+        if !local_profiles.iter().any(|p| p.id == profile.id) {
+            local_profiles.push(profile);
+        }
+    }
+
+    // 标签定义与合集：合并时按名称 / ID 补充本机没有的项
+    let tag_store = match read_json::<TagStore>(&root.join("library_tags.json"))? {
+        Some(imported) => {
+            let mut store = if overwrite { TagStore::default() } else { tags::read_store(app) };
+            for tag in imported.tags {
+                if !store.tags.iter().any(|t| t.name == tag.name) {
+                    store.tags.push(tag);
+                }
+            }
+            for collection in imported.collections {
+                if !store.collections.iter().any(|c| c.id == collection.id) {
+                    store.collections.push(collection);
+                }
+            }
+            Some(store)
+        }
+        None => None,
+    };
+
+    // 后端配置只在覆盖模式下替换，合并时保留本机配置
+    let settings = if overwrite { read_json::<AppSettings>(&root.join("settings.json"))? } else { None };
+
+    let mut sessions: Vec<SessionRow> = read_json(&root.join("sessions.json"))?.unwrap_or_default();
+    let mut tags: Vec<TagRow> = read_json(&root.join("tags.json"))?.unwrap_or_default();
+    sessions.retain(|s| imported.contains(&s.instance_id));
+    tags.retain(|t| imported.contains(&t.instance_id));
+    Ok(ImportPlan { report, instances, imported, covers, profiles: local_profiles, tag_store, settings, sessions, tags })
+}
+
+// 实例写入成功后写入封面、启动方案、标签、配置、笔记、附件与存档备份，只处理实际导入的实例
+fn commit_files(app: &AppHandle, root: &Path, plan: &mut ImportPlan, overwrite: bool) -> Result<(), String> {
+    for (src, target) in &plan.covers {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        match fs::copy(src, target) {
+            Ok(_) => plan.report.covers += 1,
+            Err(e) => log_warn!("导入封面 {:?} 失败: {}", src, e),
+        }
+    }
+    profiles::write_profiles(app, &plan.profiles)?;
+    if let Some(store) = &plan.tag_store {
+        tags::write_store(app, store)?;
+    }
+    if let Some(settings) = &plan.settings {
+        settings::write_settings(app, settings)?;
+    }
+
+    // 合并时保留本机已有的笔记
+    let notes_src = root.join(notes::NOTES_DIR);
+    if notes_src.is_dir() {
+        let notes_dest = storage::resolve_data_path(app, notes::NOTES_DIR)?;
+        fs::create_dir_all(&notes_dest).map_err(|e| e.to_string())?;
+        for id in &plan.imported {
+            let (src, target) = (notes_src.join(format!("{}.md", id)), notes_dest.join(format!("{}.md", id)));
+            if src.is_file() && (overwrite || !target.exists()) {
+                fs::copy(&src, &target).map_err(|e| format!("复制 {:?} 失败: {}", src, e))?;
+            }
+        }
+    }
+
+    // 完整迁移包中的附件与存档备份，已有的文件保留不变
+    plan.report.attachments = attachments::import_attachments(app, root, &plan.imported)?;
+    let saves_src = root.join(SAVE_BACKUPS_DIR);
+    if saves_src.is_dir() {
+        let saves_dest = storage::resolve_data_path(app, SAVE_BACKUPS_DIR)?;
+        for id in &plan.imported {
+            let src = saves_src.join(id);
+            if src.is_dir() {
+                storage::copy_tree(&src, &saves_dest.join(id))?;
+                plan.report.save_backups += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+// 解析导出包，备份后写入实例，成功后再写入其余数据与游玩记录
+async fn import_staged(app: &AppHandle, staging: &Path, overwrite: bool) -> Result<ImportReport, String> {
+    let mut plan = {
+        let (app, staging) = (app.clone(), staging.to_path_buf());
+        tokio::task::spawn_blocking(move || import_from(&app, &staging, overwrite))
+            .await
+            .map_err(|e| e.to_string())??
+    };
+
+    storage::create_backup(app, "import")?;
+    let instances = std::mem::take(&mut plan.instances);
+    if overwrite {
+        db::replace_instances(app, "import_library", instances).await?;
+    } else {
+        // 合并时 instances 为本机游戏库加上导入的实例，回收站中的实例原样保留
+        db::update_instances(app, "import_library", |live| {
+            *live = instances;
+            Ok(())
+        })
+        .await?;
+    }
+
+    let mut plan = {
+        let (app, staging) = (app.clone(), staging.to_path_buf());
+        tokio::task::spawn_blocking(move || commit_files(&app, &staging, &mut plan, overwrite).map(|_| plan))
+            .await
+            .map_err(|e| e.to_string())??
+    };
+    plan.report.sessions = db::import_history(app, &plan.sessions, &plan.tags, overwrite).await?;
+    Ok(plan.report)
+}
+
+// 导入 export_library 生成的 zip。mode 为 overwrite 时以导出包替换本机游戏库，
+// 为 merge 时只添加本机没有的实例并合并游玩记录
+#[command]
+pub async fn import_library(app: AppHandle, path: String, mode: String) -> Result<ImportReport, String> {
+    let overwrite = match mode.as_str() {
+        "overwrite" => true,
+        "merge" => false,
+        other => return Err(format!("未知的导入方式: {}", other)),
+    };
+    let zip = expand_tilde(&path);
+    if !zip.is_file() {
+        return Err(format!("导出包不存在: {:?}", zip));
+    }
+
+    let staging = staging_dir("import")?;
+    let status = Command::new("ditto")
+        .args(["-x", "-k"])
+        .arg(&zip)
+        .arg(&staging)
+        .status()
+        .map_err(|e| format!("解压导出包失败: {}", e));
+    if !status.as_ref().is_ok_and(|s| s.success()) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(status.err().unwrap_or_else(|| "解压导出包失败".to_string()));
+    }
+
+    let result = import_staged(&app, &staging, overwrite).await;
+    let _ = fs::remove_dir_all(&staging);
+    let report = result?;
+
+    log_info!("已导入游戏库: 新增 {}，合并 {}，跳过 {}", report.added, report.updated, report.skipped);
+    let _ = app.emit("instances-restored", "import");
+    Ok(report)
+}
@@ -0,0 +1,79 @@
+use tauri::{AppHandle, command};
+
+use crate::autosave;
+use crate::instance::GameInstance;
+use crate::storage;
+
+// 把游戏库导出为便于分享的清单（CSV 或 Markdown 表格），只包含标题、品牌、状态、评分与游玩时长，
+// 不含路径等本机信息
+const HEADERS: [&str; 5] = ["标题", "品牌", "状态", "评分", "游玩时长（小时）"];
+
+// 已通关的按通关标记，其余按是否玩过区分
+fn status(inst: &GameInstance) -> &'static str {
+    if inst.finished {
+        "已通关"
+    } else if inst.total_play_time.unwrap_or(0) > 0 {
+        "游玩中"
+    } else {
+        "未开始"
+    }
+}
+
+fn row(inst: &GameInstance) -> [String; 5] {
+    let hours = inst.total_play_time.unwrap_or(0) as f64 / 3600.0;
+    [
+        inst.name.clone(),
+        inst.extra.get("developer").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+        status(inst).to_string(),
+        inst.rating.map(|r| r.to_string()).unwrap_or_default(),
+        format!("{:.1}", hours),
+    ]
+}
+
+// 以 = + - @（及制表符、回车）开头的单元格会被表格软件当作公式执行，前面加单引号按文本显示
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) { format!("'{}", value) } else { value.to_string() };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn to_csv(rows: &[[String; 5]]) -> String {
+    // 带 BOM，Numbers 与 Excel 打开时中文不会乱码
+    let mut out = String::from("\u{feff}");
+    for cells in std::iter::once(HEADERS.map(String::from)).chain(rows.iter().cloned()) {
+        out.push_str(&cells.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn to_markdown(rows: &[[String; 5]]) -> String {
+    let line = |cells: &[String]| format!("| {} |\n", cells.iter().map(|c| markdown_cell(c)).collect::<Vec<_>>().join(" | "));
+    let mut out = line(&HEADERS.map(String::from));
+    out.push_str("| --- | --- | --- | ---: | ---: |\n");
+    for cells in rows {
+        out.push_str(&line(cells));
+    }
+    out
+}
+
+// 生成游戏库清单文本，format 为 csv 或 markdown，按标题排序，不含回收站中的实例
+#[command]
+pub fn export_list(app: AppHandle, format: String) -> Result<String, String> {
+    autosave::flush(&app)?;
+    let mut instances = storage::read_instances(&app);
+    instances.sort_by_cached_key(|i| i.name.to_lowercase());
+    let rows: Vec<[String; 5]> = instances.iter().map(row).collect();
+    match format.as_str() {
+        "csv" => Ok(to_csv(&rows)),
+        "markdown" | "md" => Ok(to_markdown(&rows)),
+        other => Err(format!("不支持的导出格式: {}", other)),
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::registry::{self, escape_reg_string};
+
+// 日文游戏所需的语言环境
+pub const JAPANESE_LOCALE: &str = "ja_JP.UTF-8";
+
+const INTERNATIONAL_KEY: &str = "Control Panel\\International";
+const CODEPAGE_KEY: &str = "System\\CurrentControlSet\\Control\\Nls\\CodePage";
+const FONT_SUBSTITUTES_KEY: &str = "Software\\Microsoft\\Windows NT\\CurrentVersion\\FontSubstitutes";
+// 日文游戏常用的字体名，替换后可避免缺字与豆腐块
+const JAPANESE_FONT_NAMES: [&str; 6] = ["MS Gothic", "MS PGothic", "MS UI Gothic", "MS Mincho", "MS PMincho", "Meiryo"];
+
+// 可为实例单独指定的 Windows 区域
+struct WindowsLocale {
+    // 如 ja-JP
+    name: &'static str,
+    // 十六进制 LCID
+    lcid: &'static str,
+    // 对应的 macOS 语言环境
+    unix: &'static str,
+}
+
+const WINDOWS_LOCALES: [WindowsLocale; 5] = [
+    WindowsLocale { name: "ja-JP", lcid: "00000411", unix: "ja_JP.UTF-8" },
+    WindowsLocale { name: "zh-CN", lcid: "00000804", unix: "zh_CN.UTF-8" },
+    WindowsLocale { name: "zh-TW", lcid: "00000404", unix: "zh_TW.UTF-8" },
+    WindowsLocale { name: "ko-KR", lcid: "00000412", unix: "ko_KR.UTF-8" },
+    WindowsLocale { name: "en-US", lcid: "00000409", unix: "en_US.UTF-8" },
+];
+const JA_JP: &WindowsLocale = &WINDOWS_LOCALES[0];
+
+fn find_locale(name: &str) -> Result<&'static WindowsLocale, String> {
+    WINDOWS_LOCALES
+        .iter()
+        .find(|l| l.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or(format!("不支持的区域设置: {}", name))
+}
+
+fn locale_applied(bottle_path: &Path, locale: &WindowsLocale) -> bool {
+    registry::parse_reg_file(&bottle_path.join("user.reg"))
+        .ok()
+        .and_then(|keys| registry::find_key(&keys, INTERNATIONAL_KEY).map(|k| k.get("Locale") == Some(locale.lcid)))
+        .unwrap_or(false)
+}
+
+fn locale_reg_body(locale: &WindowsLocale) -> String {
+    format!(
+        "[HKEY_CURRENT_USER\\{}]\n\"Locale\"=\"{}\"\n\"LocaleName\"=\"{}\"\n\n",
+        INTERNATIONAL_KEY, locale.lcid, locale.name
+    )
+}
+
+// 读取容器中当前的代码页、区域与字体替换状态，判断是否需要重新写入
+fn preset_applied(bottle_path: &Path, font: Option<&str>) -> bool {
+    let keys = match registry::parse_reg_file(&bottle_path.join("system.reg")) {
+        Ok(k) => k,
+        Err(_) => return false,
+    };
+
+    let codepage_ok = registry::find_key(&keys, CODEPAGE_KEY)
+        .map(|k| k.get("ACP") == Some("932") && k.get("OEMCP") == Some("932"))
+        .unwrap_or(false);
+    let font_ok = match font {
+        Some(font) => registry::find_key(&keys, FONT_SUBSTITUTES_KEY)
+            .map(|k| JAPANESE_FONT_NAMES.iter().all(|name| k.get(name) == Some(font)))
+            .unwrap_or(false),
+        None => true,
+    };
+    codepage_ok && font_ok && locale_applied(bottle_path, JA_JP)
+}
+
+fn japanese_reg_body(font: Option<&str>) -> String {
+    let mut body = format!(
+        "[HKEY_LOCAL_MACHINE\\{}]\n\"ACP\"=\"932\"\n\"OEMCP\"=\"932\"\n\"MACCP\"=\"10001\"\n\n",
+        CODEPAGE_KEY
+    );
+    body.push_str(&locale_reg_body(JA_JP));
+
+    if let Some(font) = font {
+        body.push_str(&format!("[HKEY_LOCAL_MACHINE\\{}]\n", FONT_SUBSTITUTES_KEY));
+        for name in JAPANESE_FONT_NAMES {
+            body.push_str(&format!("\"{}\"=\"{}\"\n", name, escape_reg_string(font)));
+        }
+        body.push('\n');
+    }
+    body
+}
+
+// 一键日文兼容：写入 932 代码页（以及可选的日文字体替换），已经写过时跳过以免拖慢启动
+pub fn apply_japanese_preset(crossover_app_path: &str, bottle_path: &Path, font: Option<&str>) -> Result<(), String> {
+    let font = font.map(str::trim).filter(|f| !f.is_empty());
+    if preset_applied(bottle_path, font) {
+        return Ok(());
+    }
+
+    log_info!("正在为容器 {:?} 写入日文代码页设置", bottle_path);
+    registry::wine_reg_import(crossover_app_path, bottle_path, &japanese_reg_body(font))
+}
+
+// 让 wine 进程以日文环境运行，覆盖 apply_bottle_env 中的默认语言
+pub fn apply_japanese_env(cmd: &mut Command) {
+    cmd.env("LANG", JAPANESE_LOCALE);
+    cmd.env("LC_ALL", JAPANESE_LOCALE);
+}
+
+// 按实例设置 Windows 区域：写入容器的 International 键（已一致时跳过），并让 wine 进程使用对应的语言环境
+pub fn apply_windows_locale(crossover_app_path: &str, bottle_path: &Path, name: &str, cmd: &mut Command) -> Result<(), String> {
+    let locale = find_locale(name)?;
+    if !locale_applied(bottle_path, locale) {
+        log_info!("正在将容器 {:?} 的区域设置为 {}", bottle_path, locale.name);
+        registry::wine_reg_import(crossover_app_path, bottle_path, &locale_reg_body(locale))?;
+    }
+    cmd.env("LANG", locale.unix);
+    cmd.env("LC_ALL", locale.unix);
+    Ok(())
+}
+
+// 只对本次启动的 wine 进程设置时区，不改动系统设置
+pub fn apply_timezone(cmd: &mut Command, timezone: &str) -> Result<(), String> {
+    let tz = timezone.trim();
+    let valid = !tz.is_empty()
+        && !tz.split('/').any(|part| part.is_empty() || part == "." || part == "..")
+        && Path::new("/usr/share/zoneinfo").join(tz).is_file();
+    if !valid {
+        return Err(format!("无效的时区: {}", timezone));
+    }
+    cmd.env("TZ", tz);
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use tauri::{AppHandle, Emitter, command};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 保留最近的日志条目，订阅时先回放
+const RECENT_LIMIT: usize = 500;
+// 每个推送周期最多发送的条目数，超出的计入 dropped
+const MAX_PENDING: usize = 200;
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    timestamp_ms: u64,
+    level: LogLevel,
+    target: String,
+    message: String,
+}
+
+#[derive(Serialize, Clone)]
+struct BackendLogPayload {
+    entries: Vec<LogEntry>,
+    // 因超出速率限制而丢弃的条目数
+    dropped: u64,
+}
+
+#[derive(Default)]
+struct LogState {
+    recent: VecDeque<LogEntry>,
+    subscriber: Option<(AppHandle, LogLevel)>,
+    pending: Vec<LogEntry>,
+    dropped: u64,
+}
+
+static LOG_STATE: OnceLock<Mutex<LogState>> = OnceLock::new();
+static FLUSHER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+fn log_state() -> &'static Mutex<LogState> {
+    LOG_STATE.get_or_init(|| Mutex::new(LogState::default()))
+}
+
+// 输出到终端，同时记录并推送给已订阅的前端
+pub fn log(level: LogLevel, target: &str, message: String) {
+    println!("{}", message);
+
+    let entry = LogEntry {
+        timestamp_ms: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0),
+        level,
+        target: target.trim_start_matches("asumigal_lib::").to_string(),
+        message,
+    };
+
+    if let Ok(mut state) = log_state().lock() {
+        if state.recent.len() >= RECENT_LIMIT {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(entry.clone());
+
+        if state.subscriber.as_ref().is_some_and(|(_, min)| level >= *min) {
+            if state.pending.len() < MAX_PENDING {
+                state.pending.push(entry);
+            } else {
+                state.dropped += 1;
+            }
+        }
+    }
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Debug, module_path!(), format!($($arg)*))
+    };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Info, module_path!(), format!($($arg)*))
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Warn, module_path!(), format!($($arg)*))
+    };
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Error, module_path!(), format!($($arg)*))
+    };
+}
+
+// 按固定间隔批量推送，避免大量日志阻塞前端
+fn start_flusher() {
+    if FLUSHER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(|| loop {
+        thread::sleep(FLUSH_INTERVAL);
+
+        let batch = match log_state().lock() {
+            Ok(mut state) => match state.subscriber.clone() {
+                Some((app, _)) => {
+                    let entries = std::mem::take(&mut state.pending);
+                    let dropped = std::mem::take(&mut state.dropped);
+                    Some((app, entries, dropped))
+                }
+                None => None,
+            },
+            Err(_) => None,
+        };
+
+        match batch {
+            Some((app, entries, dropped)) => {
+                if !entries.is_empty() || dropped > 0 {
+                    let _ = app.emit("backend-log", BackendLogPayload { entries, dropped });
+                }
+            }
+            None => {
+                FLUSHER_RUNNING.store(false, Ordering::SeqCst);
+                break;
+            }
+        }
+    });
+}
+
+// 订阅不低于指定级别的后端日志，返回缓存中的最近日志，之后通过 backend-log 事件推送
+#[command]
+pub fn subscribe_logs(app: AppHandle, level: Option<LogLevel>) -> Vec<LogEntry> {
+    let min = level.unwrap_or(LogLevel::Info);
+    let recent = match log_state().lock() {
+        Ok(mut state) => {
+            state.subscriber = Some((app, min));
+            state.pending.clear();
+            state.dropped = 0;
+            state.recent.iter().filter(|e| e.level >= min).cloned().collect()
+        }
+        Err(_) => Vec::new(),
+    };
+    start_flusher();
+    recent
+}
+
+#[command]
+pub fn unsubscribe_logs() {
+    if let Ok(mut state) = log_state().lock() {
+        state.subscriber = None;
+        state.pending.clear();
+    }
+}
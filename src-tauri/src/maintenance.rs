@@ -0,0 +1,250 @@
+use tauri::{AppHandle, Manager, command};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::post_session;
+use crate::runner;
+use crate::storage;
+
+// 可清理的存储类别
+const CATEGORY_COVERS: &str = "covers";
+const CATEGORY_THUMBNAILS: &str = "thumbnails";
+const CATEGORY_LOGS: &str = "logs";
+const CATEGORY_DOWNLOADS: &str = "downloads";
+const CATEGORY_SAVE_SNAPSHOTS: &str = "save_snapshots";
+const ALL_CATEGORIES: [&str; 5] = [
+    CATEGORY_COVERS,
+    CATEGORY_THUMBNAILS,
+    CATEGORY_LOGS,
+    CATEGORY_DOWNLOADS,
+    CATEGORY_SAVE_SNAPSHOTS,
+];
+
+// 本应用在系统临时目录中创建的文件前缀（注册表导入、诊断包暂存等）
+const TEMP_PREFIX: &str = "asumigal-";
+
+#[derive(Serialize, Clone)]
+pub struct StorageCategory {
+    id: String,
+    path: String,
+    files: u64,
+    bytes: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct StorageBreakdown {
+    categories: Vec<StorageCategory>,
+    total_bytes: u64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CleanupOptions {
+    // 要清理的类别，为空时表示全部
+    categories: Vec<String>,
+    // 删除早于该天数的项目
+    max_age_days: Option<u64>,
+    // 每个类别保留的最大体积（MB），超出时从最旧的项目开始删除
+    size_budget_mb: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct CleanupReport {
+    removed: u64,
+    freed_bytes: u64,
+    errors: Vec<String>,
+}
+
+// 清理的最小单位：单个文件，或一整个存档快照 / 临时目录
+struct CleanupItem {
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+    // 每个实例最新的存档快照、运行中实例的日志不会被删除
+    protected: bool,
+}
+
+fn category_root(app: &AppHandle, category: &str) -> Result<PathBuf, String> {
+    match category {
+        // 封面由 WebView 从网络加载，缓存位于应用的缓存目录
+        CATEGORY_COVERS => app.path().app_cache_dir().map_err(|e| e.to_string()),
+        CATEGORY_THUMBNAILS => storage::resolve_data_path(app, "test_launch"),
+        CATEGORY_LOGS => storage::resolve_data_path(app, "logs"),
+        CATEGORY_DOWNLOADS => Ok(std::env::temp_dir()),
+        CATEGORY_SAVE_SNAPSHOTS => storage::resolve_data_path(app, post_session::SAVE_BACKUPS_DIR),
+        other => Err(format!("未知的存储类别: {}", other)),
+    }
+}
+
+fn modified_time(path: &Path) -> SystemTime {
+    fs::symlink_metadata(path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+// 递归列出目录中的所有文件
+fn collect_files(root: &Path, out: &mut Vec<CleanupItem>) {
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let meta = match fs::symlink_metadata(entry.path()) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else {
+                out.push(CleanupItem {
+                    path: entry.path(),
+                    bytes: meta.len(),
+                    modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    protected: false,
+                });
+            }
+        }
+    }
+}
+
+fn collect_items(category: &str, root: &Path) -> Vec<CleanupItem> {
+    let mut items = Vec::new();
+    match category {
+        CATEGORY_DOWNLOADS => {
+            // 系统临时目录中只处理本应用创建的条目
+            if let Ok(entries) = fs::read_dir(root) {
+                for entry in entries.flatten() {
+                    if entry.file_name().to_string_lossy().starts_with(TEMP_PREFIX) {
+                        let path = entry.path();
+                        let bytes = if path.is_dir() {
+                            storage::tree_stats(&path, &[]).1
+                        } else {
+                            fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
+                        };
+                        items.push(CleanupItem { modified: modified_time(&path), path, bytes, protected: false });
+                    }
+                }
+            }
+        }
+        CATEGORY_SAVE_SNAPSHOTS => {
+            // save_backups/<instance_id>/<时间戳>，以整个快照为单位
+            let instances = fs::read_dir(root).map(|e| e.flatten().map(|e| e.path()).collect::<Vec<_>>()).unwrap_or_default();
+            for instance_dir in instances.into_iter().filter(|p| p.is_dir()) {
+                let mut snapshots: Vec<CleanupItem> = fs::read_dir(&instance_dir)
+                    .map(|e| e.flatten().map(|e| e.path()).collect::<Vec<_>>())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|path| CleanupItem {
+                        bytes: storage::tree_stats(&path, &[]).1,
+                        modified: modified_time(&path),
+                        path,
+                        protected: false,
+                    })
+                    .collect();
+                if let Some(latest) = snapshots.iter_mut().max_by_key(|s| s.modified) {
+                    latest.protected = true;
+                }
+                items.extend(snapshots);
+            }
+        }
+        CATEGORY_LOGS => {
+            collect_files(root, &mut items);
+            // 正在运行的实例仍在写日志
+            let running: Vec<PathBuf> = runner::running_instance_ids()
+                .into_iter()
+                .map(|id| root.join(format!("{}.log", id)))
+                .collect();
+            for item in items.iter_mut() {
+                item.protected = running.contains(&item.path);
+            }
+        }
+        _ => collect_files(root, &mut items),
+    }
+    items
+}
+
+fn remove_item(item: &CleanupItem) -> Result<(), String> {
+    let result = if fs::symlink_metadata(&item.path).map(|m| m.is_dir()).unwrap_or(false) {
+        fs::remove_dir_all(&item.path)
+    } else {
+        fs::remove_file(&item.path)
+    };
+    result.map_err(|e| format!("删除 {:?} 失败: {}", item.path, e))
+}
+
+fn cleanup_category(category: &str, root: &Path, options: &CleanupOptions, report: &mut CleanupReport) {
+    let mut items = collect_items(category, root);
+    // 从旧到新排序，超出预算时优先删除最旧的
+    items.sort_by_key(|i| i.modified);
+
+    let cutoff = options
+        .max_age_days
+        .and_then(|days| SystemTime::now().checked_sub(Duration::from_secs(days * 24 * 3600)));
+    let mut remaining: u64 = items.iter().map(|i| i.bytes).sum();
+    let budget = options.size_budget_mb.map(|mb| mb * 1024 * 1024);
+
+    for item in items.iter().filter(|i| !i.protected) {
+        let too_old = cutoff.map(|c| item.modified < c).unwrap_or(false);
+        let over_budget = budget.map(|b| remaining > b).unwrap_or(false);
+        if !too_old && !over_budget {
+            continue;
+        }
+        match remove_item(item) {
+            Ok(()) => {
+                remaining = remaining.saturating_sub(item.bytes);
+                report.removed += 1;
+                report.freed_bytes += item.bytes;
+            }
+            Err(e) => report.errors.push(e),
+        }
+    }
+}
+
+#[command]
+pub fn get_storage_breakdown(app: AppHandle) -> Result<StorageBreakdown, String> {
+    let mut categories = Vec::new();
+    for category in ALL_CATEGORIES {
+        let root = category_root(&app, category)?;
+        let items = collect_items(category, &root);
+        categories.push(StorageCategory {
+            id: category.to_string(),
+            path: root.to_string_lossy().to_string(),
+            files: items.len() as u64,
+            bytes: items.iter().map(|i| i.bytes).sum(),
+        });
+    }
+    let total_bytes = categories.iter().map(|c| c.bytes).sum();
+    Ok(StorageBreakdown { categories, total_bytes })
+}
+
+// 按时间或体积预算清理指定类别，两个条件都未设置时不删除任何内容
+#[command]
+pub async fn run_cleanup(app: AppHandle, options: CleanupOptions) -> Result<CleanupReport, String> {
+    if options.max_age_days.is_none() && options.size_budget_mb.is_none() {
+        return Err("请至少指定保留天数或体积上限".to_string());
+    }
+
+    let categories: Vec<String> = if options.categories.is_empty() {
+        ALL_CATEGORIES.iter().map(|c| c.to_string()).collect()
+    } else {
+        options.categories.clone()
+    };
+    let roots = categories
+        .iter()
+        .map(|c| category_root(&app, c).map(|root| (c.clone(), root)))
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut report = CleanupReport::default();
+        for (category, root) in &roots {
+            cleanup_category(category, root, &options, &mut report);
+        }
+        log_info!("存储清理完成，删除 {} 项，释放 {} 字节", report.removed, report.freed_bytes);
+        report
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
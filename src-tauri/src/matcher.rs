@@ -0,0 +1,226 @@
+use tauri::{AppHandle, Emitter, command};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::storage;
+use crate::SearchResult;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const MAX_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchRequest {
+    id: String,
+    keyword: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchItem {
+    id: String,
+    keyword: String,
+    // pending / matched / unmatched / failed
+    status: String,
+    #[serde(default)]
+    results: Vec<SearchResult>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchJob {
+    job_id: String,
+    source: String,
+    concurrency: usize,
+    items: Vec<MatchItem>,
+    finished: bool,
+}
+
+#[derive(Serialize, Clone)]
+struct MatchProgressPayload {
+    job_id: String,
+    item: MatchItem,
+    done: usize,
+    total: usize,
+}
+
+static CANCELLED_JOBS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+fn cancelled_jobs() -> &'static Mutex<HashSet<String>> {
+    CANCELLED_JOBS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+fn is_cancelled(job_id: &str) -> bool {
+    cancelled_jobs().lock().map(|s| s.contains(job_id)).unwrap_or(false)
+}
+
+fn job_path(app: &AppHandle, job_id: &str) -> Result<PathBuf, String> {
+    storage::check_instance_id(job_id)?;
+    storage::resolve_data_path(app, &format!("match_jobs/{}.json", job_id))
+}
+
+// 每完成一项就写一次检查点，中断后可以从未完成的项继续
+fn save_checkpoint(app: &AppHandle, job: &MatchJob) -> Result<(), String> {
+    let path = job_path(app, &job.job_id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string(job).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| format!("无法写入匹配进度: {}", e))
+}
+
+fn load_checkpoint(app: &AppHandle, job_id: &str) -> Result<MatchJob, String> {
+    let path = job_path(app, job_id)?;
+    let text = fs::read_to_string(&path).map_err(|_| format!("未找到匹配任务: {}", job_id))?;
+    serde_json::from_str(&text).map_err(|e| format!("匹配进度解析失败: {}", e))
+}
+
+async fn run_job(app: AppHandle, job: MatchJob) {
+    let job_id = job.job_id.clone();
+    let source = job.source.clone();
+    let total = job.items.len();
+    let pending: Vec<MatchItem> = job.items.iter().filter(|i| i.status == "pending").cloned().collect();
+    let semaphore = Arc::new(Semaphore::new(job.concurrency.clamp(1, MAX_CONCURRENCY)));
+    let state = Arc::new(Mutex::new(job));
+
+    let mut tasks = JoinSet::new();
+    for mut item in pending {
+        let (app, job_id, source) = (app.clone(), job_id.clone(), source.clone());
+        let (semaphore, state) = (semaphore.clone(), state.clone());
+
+        tasks.spawn(async move {
+            let _permit = match semaphore.acquire().await {
+                Ok(p) => p,
+                Err(_) => return,
+            };
+            if is_cancelled(&job_id) {
+                return;
+            }
+
+            match crate::search_game(app.clone(), item.keyword.clone(), source).await {
+                Ok(results) => {
+                    item.status = if results.is_empty() { "unmatched" } else { "matched" }.to_string();
+                    item.results = results;
+                }
+                Err(e) => {
+                    item.status = "failed".to_string();
+                    item.error = Some(e);
+                }
+            }
+
+            let snapshot = {
+                let mut job = match state.lock() {
+                    Ok(j) => j,
+                    Err(_) => return,
+                };
+                if let Some(slot) = job.items.iter_mut().find(|i| i.id == item.id) {
+                    *slot = item.clone();
+                }
+                job.clone()
+            };
+            let _ = save_checkpoint(&app, &snapshot);
+
+            let done = snapshot.items.iter().filter(|i| i.status != "pending").count();
+            let _ = app.emit("match-progress", MatchProgressPayload { job_id, item, done, total });
+        });
+    }
+
+    while tasks.join_next().await.is_some() {}
+
+    let snapshot = match state.lock() {
+        Ok(mut job) => {
+            job.finished = !job.items.iter().any(|i| i.status == "pending");
+            job.clone()
+        }
+        Err(_) => return,
+    };
+    let _ = save_checkpoint(&app, &snapshot);
+    if let Ok(mut set) = cancelled_jobs().lock() {
+        set.remove(&job_id);
+    }
+    let _ = app.emit("match-finished", snapshot);
+}
+
+#[command]
+pub fn start_batch_match(
+    app: AppHandle,
+    source: String,
+    items: Vec<MatchRequest>,
+    concurrency: Option<usize>,
+) -> Result<String, String> {
+    if items.is_empty() {
+        return Err("没有需要匹配的项目".to_string());
+    }
+
+    let job_id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis().to_string())
+        .unwrap_or_default();
+    let job = MatchJob {
+        job_id: job_id.clone(),
+        source,
+        concurrency: concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+        items: items
+            .into_iter()
+            .map(|r| MatchItem { id: r.id, keyword: r.keyword, status: "pending".to_string(), results: Vec::new(), error: None })
+            .collect(),
+        finished: false,
+    };
+
+    save_checkpoint(&app, &job)?;
+    tauri::async_runtime::spawn(run_job(app, job));
+    Ok(job_id)
+}
+
+// 从检查点继续一个被中断的任务，失败的项也会重新尝试
+#[command]
+pub fn resume_batch_match(app: AppHandle, job_id: String) -> Result<MatchJob, String> {
+    let mut job = load_checkpoint(&app, &job_id)?;
+    for item in job.items.iter_mut().filter(|i| i.status == "failed") {
+        item.status = "pending".to_string();
+        item.error = None;
+    }
+    job.finished = false;
+    save_checkpoint(&app, &job)?;
+
+    if let Ok(mut set) = cancelled_jobs().lock() {
+        set.remove(&job_id);
+    }
+    tauri::async_runtime::spawn(run_job(app, job.clone()));
+    Ok(job)
+}
+
+#[command]
+pub fn get_batch_match(app: AppHandle, job_id: String) -> Result<MatchJob, String> {
+    load_checkpoint(&app, &job_id)
+}
+
+// 列出尚未完成的任务，供前端在重启后提示继续
+#[command]
+pub fn list_unfinished_matches(app: AppHandle) -> Result<Vec<MatchJob>, String> {
+    let dir = storage::resolve_data_path(&app, "match_jobs")?;
+    let mut jobs = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let job = fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|t| serde_json::from_str::<MatchJob>(&t).ok());
+            if let Some(job) = job.filter(|j| !j.finished) {
+                jobs.push(job);
+            }
+        }
+    }
+    Ok(jobs)
+}
+
+#[command]
+pub fn cancel_batch_match(job_id: String) {
+    if let Ok(mut set) = cancelled_jobs().lock() {
+        set.insert(job_id);
+    }
+}
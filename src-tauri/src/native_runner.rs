@@ -0,0 +1,124 @@
+use tauri::AppHandle;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::engine;
+use crate::runner::{self, expand_tilde, ProcessInfo, WineConfig};
+use crate::storage;
+
+// 原生运行时的常见安装位置；应用数据目录下的 runtimes/ 用于存放随应用分发或用户下载的运行时
+const RUNTIME_DIRS: [&str; 2] = ["/opt/homebrew/bin", "/usr/local/bin"];
+
+// 按顺序查找运行时可执行文件
+fn find_runtime(app: &AppHandle, names: &[&str]) -> Option<PathBuf> {
+    for name in names {
+        if let Ok(p) = storage::resolve_data_path(app, &format!("runtimes/{}", name)) {
+            if p.is_file() {
+                return Some(p);
+            }
+        }
+        for dir in RUNTIME_DIRS {
+            let p = Path::new(dir).join(name);
+            if p.is_file() {
+                return Some(p);
+            }
+        }
+    }
+    None
+}
+
+// 游戏路径可以是游戏目录，也可以是目录中的某个文件（如原本的 .exe）
+fn game_dir_of(game_exe: &str) -> Result<PathBuf, String> {
+    let path = expand_tilde(game_exe);
+    let dir = if path.is_dir() { path } else { path.parent().map(Path::to_path_buf).unwrap_or(path) };
+    if !dir.is_dir() {
+        return Err(format!("找不到游戏目录，可能位于外接硬盘但未连接: {:?}", dir));
+    }
+    Ok(dir)
+}
+
+// Ren'Py：优先使用指定的 SDK（renpy.sh），其次使用游戏自带的 Linux/Mac 启动脚本
+fn renpy_command(app: &AppHandle, game_dir: &Path, runtime: Option<&Path>) -> Result<Command, String> {
+    if let Some(rt) = runtime {
+        let script = if rt.is_dir() { rt.join("renpy.sh") } else { rt.to_path_buf() };
+        let mut cmd = Command::new("sh");
+        cmd.arg(script).arg(game_dir);
+        return Ok(cmd);
+    }
+
+    let bundled = std::fs::read_dir(game_dir)
+        .ok()
+        .and_then(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .find(|p| p.extension().and_then(|e| e.to_str()) == Some("sh"))
+        });
+    if let Some(script) = bundled.filter(|_| game_dir.join("lib").is_dir()) {
+        let mut cmd = Command::new("sh");
+        cmd.arg(script);
+        return Ok(cmd);
+    }
+
+    match find_runtime(app, &["renpy.sh"]) {
+        Some(sdk) => {
+            let mut cmd = Command::new("sh");
+            cmd.arg(sdk).arg(game_dir);
+            Ok(cmd)
+        }
+        None => Err("未找到 Ren'Py 运行时，请在实例中指定 Ren'Py SDK 目录".to_string()),
+    }
+}
+
+fn onscripter_command(app: &AppHandle, game_dir: &Path, runtime: Option<&Path>) -> Result<Command, String> {
+    let bin = runtime
+        .map(Path::to_path_buf)
+        .or_else(|| find_runtime(app, &["onsyuri", "onscripter-en", "onscripter"]))
+        .ok_or("未找到 ONScripter 运行时，请先安装 onsyuri")?;
+    let mut cmd = Command::new(bin);
+    cmd.arg("--root").arg(game_dir);
+    Ok(cmd)
+}
+
+fn kirikiri_command(app: &AppHandle, game_dir: &Path, runtime: Option<&Path>) -> Result<Command, String> {
+    let bin = runtime
+        .map(Path::to_path_buf)
+        .or_else(|| find_runtime(app, &["krkrsdl2", "krkr"]))
+        .ok_or("未找到吉里吉里运行时，请先安装 krkrsdl2")?;
+    let mut cmd = Command::new(bin);
+    cmd.arg(game_dir);
+    Ok(cmd)
+}
+
+// 根据实例指定（或自动识别）的引擎构建原生启动命令，返回 (命令, 游戏目录, 引擎)
+pub fn build_native_command(app: &AppHandle, config: &WineConfig) -> Result<(Command, PathBuf, String), String> {
+    let game_dir = game_dir_of(&config.game_exe)?;
+    let engine = match config.native_engine.as_deref().map(str::trim).filter(|e| !e.is_empty() && *e != "auto") {
+        Some(e) => e.to_string(),
+        None => engine::detect_engine(&game_dir).to_string(),
+    };
+    let runtime = config.native_runtime.as_deref().map(str::trim).filter(|r| !r.is_empty()).map(expand_tilde);
+    if let Some(rt) = runtime.as_ref().filter(|r| !r.exists()) {
+        return Err(format!("指定的运行时不存在: {:?}", rt));
+    }
+
+    let mut cmd = match engine.as_str() {
+        "renpy" => renpy_command(app, &game_dir, runtime.as_deref())?,
+        "onscripter" => onscripter_command(app, &game_dir, runtime.as_deref())?,
+        "kirikiri" => kirikiri_command(app, &game_dir, runtime.as_deref())?,
+        other => return Err(format!("引擎 {} 暂不支持原生运行，请使用 CrossOver 模式", other)),
+    };
+    cmd.current_dir(&game_dir);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    Ok((cmd, game_dir, engine))
+}
+
+// 原生运行时直接由本应用启动，结束启动进程及其所有子进程即可
+pub fn stop_native_instance(launcher_pid: Option<u32>, processes: &[ProcessInfo]) -> Result<Vec<u32>, String> {
+    let launcher_pid = launcher_pid.ok_or("未找到正在运行的原生进程")?;
+    let children_map = runner::build_children_map(processes);
+    let mut pids = runner::collect_descendants(launcher_pid, &children_map);
+    pids.push(launcher_pid);
+    Ok(runner::terminate_pids(pids))
+}
@@ -0,0 +1,238 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::settings;
+use crate::ymgal;
+
+// Ymgal 每页返回的条数，不足一页说明已到末尾
+const YMGAL_PAGE_SIZE: usize = 10;
+// 预取的下一页在该时间内有效
+const PREFETCH_TTL: Duration = Duration::from_secs(300);
+// 同时保留的游标会话数
+const MAX_FEEDS: usize = 8;
+// 一页内容全部被过滤或去重时，最多向后连续翻的页数
+const MAX_SKIP_PAGES: u32 = 3;
+
+static PAGE_CACHE: OnceLock<Mutex<HashMap<u32, (Instant, Value)>>> = OnceLock::new();
+static NEWS_FEEDS: OnceLock<Mutex<HashMap<String, NewsFeed>>> = OnceLock::new();
+
+fn page_cache() -> &'static Mutex<HashMap<u32, (Instant, Value)>> {
+    PAGE_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn news_feeds() -> &'static Mutex<HashMap<String, NewsFeed>> {
+    NEWS_FEEDS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// 一次无限滚动浏览的状态：已返回过的条目用于跨页去重
+#[derive(Default)]
+struct NewsFeed {
+    seen: HashSet<String>,
+}
+
+// 游标分页的结果，next_cursor 为 None 表示没有更多
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewsPage {
+    pub items: Vec<NewsItem>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+// 资讯条目：保留原始字段，并附加关键词规则的匹配结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewsItem {
+    #[serde(flatten)]
+    pub raw: Map<String, Value>,
+    #[serde(default)]
+    pub matched_keywords: Vec<String>,
+    #[serde(default)]
+    pub highlighted: bool,
+}
+
+impl NewsItem {
+    fn id(&self) -> Option<String> {
+        match self.raw.get("topicId") {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(v) if !v.is_null() => Some(v.to_string()),
+            _ => None,
+        }
+    }
+
+    fn text_for_match(&self) -> String {
+        let title = self.raw.get("title").and_then(|v| v.as_str()).unwrap_or("");
+        let intro = self.raw.get("introduction").and_then(|v| v.as_str()).unwrap_or("");
+        format!("{}\n{}", title, intro).to_lowercase()
+    }
+}
+
+fn normalize_keywords(list: &[String]) -> Vec<String> {
+    list.iter()
+        .map(|k| k.trim().to_lowercase())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+// 按用户规则过滤资讯：命中屏蔽词的条目直接丢弃，命中关注词的条目标记高亮
+pub fn apply_keyword_rules(app: &AppHandle, items: Vec<NewsItem>) -> Vec<NewsItem> {
+    let settings = settings::load_settings(app);
+    let include = normalize_keywords(&settings.news_include_keywords);
+    let exclude = normalize_keywords(&settings.news_exclude_keywords);
+
+    items
+        .into_iter()
+        .filter_map(|mut item| {
+            let text = item.text_for_match();
+            if exclude.iter().any(|k| text.contains(k)) {
+                return None;
+            }
+            item.matched_keywords = include.iter().filter(|k| text.contains(k.as_str())).cloned().collect();
+            item.highlighted = !item.matched_keywords.is_empty();
+            Some(item)
+        })
+        .collect()
+}
+
+// 对 Ymgal 的原始响应应用规则，保持 { success, code, data } 结构不变
+pub fn filter_ymgal_response(app: &AppHandle, mut response: Value) -> Value {
+    let raw_items = match response.get_mut("data").map(Value::take) {
+        Some(Value::Array(list)) => list,
+        _ => return response,
+    };
+
+    // 记录过滤前的条数，前端据此判断是否还有下一页
+    let raw_count = raw_items.len();
+    let items: Vec<NewsItem> = raw_items
+        .into_iter()
+        .filter_map(|v| serde_json::from_value(v).ok())
+        .collect();
+    let filtered = apply_keyword_rules(app, items);
+
+    response["data"] = serde_json::to_value(filtered).unwrap_or(Value::Array(Vec::new()));
+    response["rawCount"] = Value::from(raw_count);
+    response
+}
+
+async fn request_ymgal_page(app: &AppHandle, page: u32) -> Result<Value, String> {
+    let client = reqwest::Client::new();
+    let url = format!("https://www.ymgal.games/co/topic/list?type=NEWS&page={}", page);
+
+    // 已登录时附带令牌，获取个性化资讯与点赞/收藏状态
+    let res = ymgal::authorized(app, client.get(&url))
+        .send()
+        .await
+        .map_err(|e| format!("Request Error: {}", e))?;
+
+    if !res.status().is_success() {
+        return Err(format!("Server returned status: {}", res.status()));
+    }
+    res.json().await.map_err(|e| format!("Parse Error: {}", e))
+}
+
+fn raw_count(response: &Value) -> usize {
+    response.get("data").and_then(|d| d.as_array()).map(|a| a.len()).unwrap_or(0)
+}
+
+// 后台预取下一页，用户滚动到底部时可直接从缓存返回
+fn prefetch_ymgal_page(app: &AppHandle, page: u32) {
+    let fresh = page_cache().lock().map(|c| c.get(&page).is_some_and(|(t, _)| t.elapsed() < PREFETCH_TTL)).unwrap_or(false);
+    if fresh {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match request_ymgal_page(&app, page).await {
+            Ok(data) => {
+                if let Ok(mut cache) = page_cache().lock() {
+                    cache.retain(|_, (t, _)| t.elapsed() < PREFETCH_TTL);
+                    cache.insert(page, (Instant::now(), data));
+                }
+            }
+            Err(e) => log_debug!("预取资讯第 {} 页失败: {}", page, e),
+        }
+    });
+}
+
+// 获取 Ymgal 资讯的原始响应：优先使用预取缓存，返回后在后台预取下一页
+pub async fn fetch_ymgal_page(app: &AppHandle, page: u32) -> Result<Value, String> {
+    let cached = page_cache()
+        .lock()
+        .ok()
+        .and_then(|mut c| c.remove(&page))
+        .filter(|(t, _)| t.elapsed() < PREFETCH_TTL)
+        .map(|(_, v)| v);
+    let data = match cached {
+        Some(v) => v,
+        None => request_ymgal_page(app, page).await?,
+    };
+
+    if raw_count(&data) >= YMGAL_PAGE_SIZE {
+        prefetch_ymgal_page(app, page + 1);
+    }
+    Ok(data)
+}
+
+// 游标格式为 "<会话 ID>:<页码>"
+fn parse_cursor(cursor: &str) -> Option<(String, u32)> {
+    let (feed, page) = cursor.split_once(':')?;
+    Some((feed.to_string(), page.parse().ok()?))
+}
+
+fn new_feed() -> String {
+    let feed_id = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis().to_string()).unwrap_or_default();
+    if let Ok(mut feeds) = news_feeds().lock() {
+        while feeds.len() >= MAX_FEEDS {
+            match feeds.keys().min().cloned() {
+                Some(oldest) => feeds.remove(&oldest),
+                None => break,
+            };
+        }
+        feeds.insert(feed_id.clone(), NewsFeed::default());
+    }
+    feed_id
+}
+
+// 基于游标的资讯列表：不传游标时从第一页开始新的会话，同一会话内跨页去重，
+// 整页被屏蔽词过滤或去重后为空时自动继续向后取，避免前端出现空白页
+#[command]
+pub async fn fetch_news(app: AppHandle, cursor: Option<String>) -> Result<NewsPage, String> {
+    let (feed_id, mut page) = match cursor.as_deref().and_then(parse_cursor) {
+        Some(c) => c,
+        None => (new_feed(), 1),
+    };
+
+    let mut items = Vec::new();
+    let mut has_more = true;
+    for _ in 0..=MAX_SKIP_PAGES {
+        let response = filter_ymgal_response(&app, fetch_ymgal_page(&app, page).await?);
+        let raw = response.get("rawCount").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let page_items: Vec<NewsItem> = response
+            .get("data")
+            .cloned()
+            .and_then(|d| serde_json::from_value(d).ok())
+            .unwrap_or_default();
+
+        if let Ok(mut feeds) = news_feeds().lock() {
+            // 应用重启后旧游标对应的会话已不存在，按新会话继续
+            let feed = feeds.entry(feed_id.clone()).or_default();
+            items.extend(page_items.into_iter().filter(|item| item.id().map(|id| feed.seen.insert(id)).unwrap_or(true)));
+        }
+
+        page += 1;
+        has_more = raw >= YMGAL_PAGE_SIZE;
+        if !items.is_empty() || !has_more {
+            break;
+        }
+    }
+
+    Ok(NewsPage {
+        items,
+        next_cursor: has_more.then(|| format!("{}:{}", feed_id, page)),
+        has_more,
+    })
+}
@@ -0,0 +1,46 @@
+use tauri::{AppHandle, command};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::storage;
+
+// 每个实例的笔记（攻略进度、路线记录等）单独保存为 Markdown 文件，不放进 instances.json
+pub(crate) const NOTES_DIR: &str = "notes";
+const MAX_NOTE_BYTES: usize = 8 * 1024 * 1024;
+
+fn note_path(app: &AppHandle, instance_id: &str) -> Result<PathBuf, String> {
+    storage::check_instance_id(instance_id)?;
+    Ok(storage::resolve_data_path(app, NOTES_DIR)?.join(format!("{}.md", instance_id)))
+}
+
+// 没有笔记时返回空字符串
+#[command]
+pub fn load_note(app: AppHandle, instance_id: String) -> Result<String, String> {
+    let path = note_path(&app, &instance_id)?;
+    match storage::read_data_file(&path) {
+        Ok(text) => Ok(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(format!("读取笔记失败: {}", e)),
+    }
+}
+
+// 保存笔记；内容为空时删除笔记文件
+#[command]
+pub fn save_note(app: AppHandle, instance_id: String, markdown: String) -> Result<(), String> {
+    if markdown.len() > MAX_NOTE_BYTES {
+        return Err(format!("笔记过大，最多 {} MB", MAX_NOTE_BYTES / 1024 / 1024));
+    }
+    storage::with_data_dir(|| {
+        let path = note_path(&app, &instance_id)?;
+        if markdown.trim().is_empty() {
+            return match fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("删除笔记失败: {}", e)),
+                _ => Ok(()),
+            };
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建笔记目录: {}", e))?;
+        }
+        storage::write_atomic(&path, markdown.as_bytes())
+    })
+}
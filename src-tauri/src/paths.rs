@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+// 外接卷上的路径保存为 vol:<卷 UUID>/<相对路径>，卷改名或挂载点变化后仍能找到
+const VOLUME_PREFIX: &str = "vol:";
+const VOLUMES_ROOT: &str = "/Volumes";
+
+// 实例中需要规范化的路径字段
+pub const INSTANCE_PATH_FIELDS: [&str; 4] = ["executablePath", "backgroundImage", "diskGameRoot", "localGameRoot"];
+
+// 挂载点 -> 卷 UUID 的缓存，避免每次保存都调用 diskutil
+static VOLUME_UUIDS: OnceLock<Mutex<HashMap<PathBuf, Option<String>>>> = OnceLock::new();
+
+fn volume_uuids() -> &'static Mutex<HashMap<PathBuf, Option<String>>> {
+    VOLUME_UUIDS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn read_volume_uuid(mount: &Path) -> Option<String> {
+    let output = Command::new("diskutil").arg("info").arg("-plist").arg(mount).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let after_key = text.split("<key>VolumeUUID</key>").nth(1)?;
+    let value = after_key.split("<string>").nth(1)?.split("</string>").next()?;
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn volume_uuid(mount: &Path) -> Option<String> {
+    if let Ok(cache) = volume_uuids().lock() {
+        if let Some(cached) = cache.get(mount) {
+            return cached.clone();
+        }
+    }
+    let uuid = read_volume_uuid(mount);
+    if let Ok(mut cache) = volume_uuids().lock() {
+        cache.insert(mount.to_path_buf(), uuid.clone());
+    }
+    uuid
+}
+
+fn find_volume_by_uuid(uuid: &str) -> Option<PathBuf> {
+    let mounts: Vec<PathBuf> = fs::read_dir(VOLUMES_ROOT).ok()?.flatten().map(|e| e.path()).collect();
+    if let Some(found) = mounts.iter().find(|m| volume_uuid(m).as_deref() == Some(uuid)) {
+        return Some(found.clone());
+    }
+
+    // 缓存可能已过期（同名卷被替换），清空后重新查询一次
+    if let Ok(mut cache) = volume_uuids().lock() {
+        cache.clear();
+    }
+    mounts.into_iter().find(|m| volume_uuid(m).as_deref() == Some(uuid))
+}
+
+// 形如 XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX 的卷 UUID
+fn is_volume_uuid(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('-').collect();
+    parts.len() == 5
+        && [8, 4, 4, 4, 12].iter().zip(&parts).all(|(len, p)| p.len() == *len && p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// 将路径转换为存储用的规范形式：用户目录下写成 ~/...，外接卷写成 vol:<UUID>/...，其余保持绝对路径
+pub fn to_stored_path(path_str: &str) -> String {
+    if path_str.is_empty() || path_str.starts_with(VOLUME_PREFIX) {
+        return path_str.to_string();
+    }
+    let path = resolve_stored_path(path_str);
+
+    if let Some(home) = dirs::home_dir() {
+        if let Ok(rel) = path.strip_prefix(&home) {
+            return format!("~/{}", rel.to_string_lossy());
+        }
+    }
+
+    if let Ok(rel) = path.strip_prefix(VOLUMES_ROOT) {
+        let mut components = rel.components();
+        if let Some(volume) = components.next() {
+            let mount = Path::new(VOLUMES_ROOT).join(volume);
+            if let Some(uuid) = volume_uuid(&mount) {
+                return format!("{}{}/{}", VOLUME_PREFIX, uuid, components.as_path().to_string_lossy());
+            }
+            // 卷未挂载时 resolve_stored_path 返回的是 /Volumes/<UUID>/...，仍按 vol: 形式保存，挂载后可以找到
+            let name = volume.as_os_str().to_string_lossy();
+            if !mount.exists() && is_volume_uuid(&name) {
+                return format!("{}{}/{}", VOLUME_PREFIX, name, components.as_path().to_string_lossy());
+            }
+        }
+    }
+
+    path.to_string_lossy().to_string()
+}
+
+// 在使用时把规范形式解析为当前机器上的绝对路径；卷未挂载时返回 /Volumes/<UUID>/... 以便给出明确的错误
+pub fn resolve_stored_path(path_str: &str) -> PathBuf {
+    if let Some(rest) = path_str.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+
+    if let Some(rest) = path_str.strip_prefix(VOLUME_PREFIX) {
+        let (uuid, rel) = rest.split_once('/').unwrap_or((rest, ""));
+        let mount = find_volume_by_uuid(uuid).unwrap_or_else(|| Path::new(VOLUMES_ROOT).join(uuid));
+        return if rel.is_empty() { mount } else { mount.join(rel) };
+    }
+
+    PathBuf::from(path_str)
+}
+
+// 对实例 JSON 中的路径字段逐个转换
+pub fn map_instance_paths(instances: &mut serde_json::Value, f: impl Fn(&str) -> String) {
+    if let Some(list) = instances.as_array_mut() {
+        for inst in list.iter_mut() {
+            for field in INSTANCE_PATH_FIELDS {
+                if let Some(serde_json::Value::String(s)) = inst.get_mut(field) {
+                    *s = f(s);
+                }
+            }
+        }
+    }
+}
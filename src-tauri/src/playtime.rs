@@ -0,0 +1,175 @@
+use tauri::AppHandle;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::instance::GameInstance;
+use crate::storage;
+
+// 游玩时长单独保存在只追加的 playtime.jsonl 中，每行一条记录，读取实例时再合并进去。
+// 游戏结束时只追加一行，不再重写 instances.json，写入中途崩溃最多丢失最后一行。
+// 文件中只有实例 ID 与时长，不随游戏库加密
+const PLAYTIME_FILENAME: &str = "playtime.jsonl";
+// 由时长存储管理、不写入 instances.json 的字段
+pub(crate) const STORED_FIELDS: [&str; 2] = ["totalPlayTime", "playHistory"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayEntry {
+    instance_id: String,
+    // session：一次游玩；adjust：导入、同步或旧版本游戏库中带来的时长
+    kind: String,
+    // Unix 秒，session 为开始时间
+    at: u64,
+    seconds: u64,
+    // "YYYY-MM-DD" -> 计入当天的秒数
+    #[serde(default)]
+    days: BTreeMap<String, u64>,
+}
+
+// 单个实例汇总后的时长
+#[derive(Debug, Clone, Default)]
+struct PlayTotals {
+    total: u64,
+    history: BTreeMap<String, u64>,
+    // 最近一次游玩结束的时间（Unix 毫秒）
+    last_played: Option<u64>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// 逐行解析，无法解析的行（如崩溃时写了一半的最后一行）跳过
+fn read_entries(app: &AppHandle) -> Vec<PlayEntry> {
+    let text = match storage::resolve_data_path(app, PLAYTIME_FILENAME).and_then(|p| storage::read_data_file(&p).map_err(|e| e.to_string())) {
+        Ok(text) => text,
+        Err(_) => return Vec::new(),
+    };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                log_warn!("跳过无法解析的时长记录: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn totals(entries: &[PlayEntry]) -> HashMap<String, PlayTotals> {
+    let mut totals: HashMap<String, PlayTotals> = HashMap::new();
+    for entry in entries {
+        let t = totals.entry(entry.instance_id.clone()).or_default();
+        t.total += entry.seconds;
+        for (day, secs) in &entry.days {
+            *t.history.entry(day.clone()).or_insert(0) += secs;
+        }
+        if entry.kind == "session" {
+            t.last_played = t.last_played.max(Some((entry.at + entry.seconds) * 1000));
+        }
+    }
+    totals
+}
+
+fn append_entries(path: &Path, entries: &[PlayEntry]) -> Result<(), String> {
+    let mut text = String::new();
+    // 上次追加中途中断时文件末尾没有换行，先补上，避免与新记录粘在同一行
+    let broken_tail = fs::read(path).ok().is_some_and(|data| data.last().is_some_and(|b| *b != b'\n'));
+    if broken_tail {
+        text.push('\n');
+    }
+    for entry in entries {
+        text.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+        text.push('\n');
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("无法打开时长记录: {}", e))?;
+    file.write_all(text.as_bytes())
+        .and_then(|_| file.sync_data())
+        .map_err(|e| format!("无法写入时长记录: {}", e))
+}
+
+// 把存储中的时长合并进实例。instances.json 中仍带有时长时（旧版本写入或从备份恢复）取较大值
+pub(crate) fn merge(app: &AppHandle, instances: &mut [GameInstance]) {
+    let totals = totals(&read_entries(app));
+    for inst in instances.iter_mut() {
+        let t = match totals.get(&inst.id) {
+            Some(t) => t,
+            None => continue,
+        };
+        inst.total_play_time = inst.total_play_time.max(Some(t.total));
+        if !t.history.is_empty() {
+            let history = inst.play_history.get_or_insert_with(Default::default);
+            for (day, secs) in &t.history {
+                let entry = history.entry(day.clone()).or_insert(0);
+                *entry = (*entry).max(*secs);
+            }
+        }
+        inst.last_played = inst.last_played.max(t.last_played);
+    }
+}
+
+// 写入游戏库前调用（持有写入锁）：实例中比存储多出的时长（导入、同步、手动修改）记为一条 adjust 记录。
+// 只记录增加的部分，前端持有的旧数据不会抹掉已记录的游玩
+pub(crate) fn absorb(app: &AppHandle, instances: &[GameInstance]) -> Result<(), String> {
+    let totals = totals(&read_entries(app));
+    let empty = PlayTotals::default();
+    let now = now_secs();
+    let mut adjustments = Vec::new();
+    for inst in instances {
+        let stored = totals.get(&inst.id).unwrap_or(&empty);
+        let seconds = inst.total_play_time.unwrap_or(0).saturating_sub(stored.total);
+        let days: BTreeMap<String, u64> = inst
+            .play_history
+            .iter()
+            .flatten()
+            .filter_map(|(day, secs)| {
+                let diff = secs.saturating_sub(stored.history.get(day).copied().unwrap_or(0));
+                (diff > 0).then(|| (day.clone(), diff))
+            })
+            .collect();
+        if seconds == 0 && days.is_empty() {
+            continue;
+        }
+        adjustments.push(PlayEntry { instance_id: inst.id.clone(), kind: "adjust".to_string(), at: now, seconds, days });
+    }
+    if adjustments.is_empty() {
+        return Ok(());
+    }
+    log_info!("已将 {} 个实例的时长变化写入时长记录", adjustments.len());
+    append_entries(&storage::resolve_data_path(app, PLAYTIME_FILENAME)?, &adjustments)
+}
+
+// 追加一次游玩（持有写入锁）
+pub(crate) fn append_session(app: &AppHandle, instance_id: &str, start: u64, duration_sec: u64, day: &str) -> Result<(), String> {
+    let entry = PlayEntry {
+        instance_id: instance_id.to_string(),
+        kind: "session".to_string(),
+        at: start,
+        seconds: duration_sec,
+        days: BTreeMap::from([(day.to_string(), duration_sec)]),
+    };
+    append_entries(&storage::resolve_data_path(app, PLAYTIME_FILENAME)?, &[entry])
+}
+
+// 彻底删除实例后丢弃其时长记录（持有写入锁），整体重写文件
+pub(crate) fn forget(app: &AppHandle, instance_ids: &HashSet<String>) -> Result<(), String> {
+    let entries = read_entries(app);
+    if !entries.iter().any(|e| instance_ids.contains(&e.instance_id)) {
+        return Ok(());
+    }
+    let mut text = String::new();
+    for entry in entries.iter().filter(|e| !instance_ids.contains(&e.instance_id)) {
+        text.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+        text.push('\n');
+    }
+    storage::write_atomic(&storage::resolve_data_path(app, PLAYTIME_FILENAME)?, text.as_bytes())
+}
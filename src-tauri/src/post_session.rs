@@ -0,0 +1,103 @@
+use tauri::{AppHandle, Emitter};
+use std::fs;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::bottle;
+use crate::runner::{self, expand_tilde, WineConfig};
+use crate::storage;
+
+// 由后端直接执行的动作
+const ACTION_BACKUP_SAVES: &str = "backup_saves";
+const ACTION_STOP_WINESERVER: &str = "stop_wineserver";
+// 需要前端配合的动作，通过 post-session-action 事件转交
+const FRONTEND_ACTIONS: [&str; 2] = ["open_notes", "sync_bangumi"];
+// 存档备份目录，位于数据目录中
+pub(crate) const SAVE_BACKUPS_DIR: &str = "save_backups";
+
+#[derive(serde::Serialize, Clone)]
+struct PostSessionActionPayload {
+    instance_id: String,
+    action: String,
+}
+
+// 游戏退出后要执行的动作，在启动时从 WineConfig 中取出，随等待线程一起移动
+#[derive(Clone, Default)]
+pub struct PostSessionConfig {
+    actions: Vec<String>,
+    save_dir: Option<String>,
+    bottle_path: String,
+    crossover_app_path: String,
+    run_mode: String,
+}
+
+impl PostSessionConfig {
+    pub fn from_config(config: &WineConfig) -> Self {
+        Self {
+            actions: config.post_session_actions.clone().unwrap_or_default(),
+            save_dir: config.save_dir.clone().filter(|d| !d.trim().is_empty()),
+            bottle_path: config.bottle_path.clone(),
+            crossover_app_path: runner::resolve_crossover_root(config).unwrap_or_else(|_| config.crossover_app_path.clone()),
+            run_mode: config.run_mode.clone().unwrap_or_else(|| "crossover".to_string()),
+        }
+    }
+}
+
+// 将存档目录复制到 AppLocalData/save_backups/<instance_id>/<时间戳>
+fn backup_saves(app: &AppHandle, instance_id: &str, save_dir: &str) -> Result<(), String> {
+    storage::check_instance_id(instance_id)?;
+    let src = expand_tilde(save_dir);
+    if !src.is_dir() {
+        return Err(format!("存档目录不存在: {:?}", src));
+    }
+
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let dst = storage::resolve_data_path(app, &format!("{}/{}/{}", SAVE_BACKUPS_DIR, instance_id, ts))?;
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let status = Command::new("cp")
+        .arg("-R")
+        .arg(&src)
+        .arg(&dst)
+        .status()
+        .map_err(|e| format!("备份存档失败: {}", e))?;
+    if !status.success() {
+        return Err("备份存档失败".to_string());
+    }
+    log_info!("已备份 {} 的存档到 {:?}", instance_id, dst);
+    Ok(())
+}
+
+fn stop_wineserver(post: &PostSessionConfig) -> Result<(), String> {
+    if post.run_mode != "crossover" {
+        return Ok(());
+    }
+    bottle::kill_wineserver(&post.crossover_app_path, &expand_tilde(&post.bottle_path))
+}
+
+// 在 game-finished 之后依次执行配置的动作，单个动作失败不影响后续动作
+pub fn run_post_session_actions(app: &AppHandle, instance_id: &str, post: &PostSessionConfig) {
+    for action in &post.actions {
+        let result = match action.as_str() {
+            "none" => Ok(()),
+            ACTION_BACKUP_SAVES => match post.save_dir.as_deref() {
+                Some(dir) => backup_saves(app, instance_id, dir),
+                None => Err("未配置存档目录".to_string()),
+            },
+            ACTION_STOP_WINESERVER => stop_wineserver(post),
+            a if FRONTEND_ACTIONS.contains(&a) => app
+                .emit("post-session-action", PostSessionActionPayload {
+                    instance_id: instance_id.to_string(),
+                    action: a.to_string(),
+                })
+                .map_err(|e| e.to_string()),
+            other => Err(format!("未知的结束动作: {}", other)),
+        };
+
+        if let Err(e) = result {
+            log_warn!("实例 {} 的结束动作 {} 执行失败: {}", instance_id, action, e);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::runner::WineConfig;
+use crate::storage;
+
+// 启动配置方案：多个实例引用同一方案，修改一次即对所有实例生效
+pub(crate) const PROFILES_FILE: &str = "launch_profiles.json";
+// 由启动流程自己设置，不允许方案覆盖
+const RESERVED_ENV: [&str; 3] = ["WINEPREFIX", "CX_BOTTLE", "WINEDEBUG"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LaunchProfile {
+    pub id: String,
+    pub name: String,
+    pub windows_locale: Option<String>,
+    pub japanese_compat: Option<bool>,
+    pub japanese_font: Option<String>,
+    pub timezone: Option<String>,
+    pub env: BTreeMap<String, String>,
+    pub graphics_backend: Option<String>,
+    pub sync_mode: Option<String>,
+    pub dpi: Option<u32>,
+    pub retina_mode: Option<bool>,
+    pub virtual_desktop: Option<String>,
+    // 钩子：启动前运行的程序与退出后执行的动作
+    pub pre_launch: Option<Vec<String>>,
+    pub post_session_actions: Option<Vec<String>>,
+}
+
+pub(crate) fn read_profiles(app: &AppHandle) -> Vec<LaunchProfile> {
+    storage::resolve_library_path(app, PROFILES_FILE)
+        .ok()
+        .and_then(|p| storage::read_data_file(&p).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn write_profiles(app: &AppHandle, profiles: &[LaunchProfile]) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(profiles).map_err(|e| e.to_string())?;
+    storage::with_data_dir(|| {
+        storage::write_atomic(&storage::resolve_library_path(app, PROFILES_FILE)?, text.as_bytes()).map_err(|e| format!("保存启动方案失败: {}", e))
+    })
+}
+
+fn validate_env(env: &BTreeMap<String, String>) -> Result<(), String> {
+    for key in env.keys() {
+        let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(format!("无效的环境变量名: {}", key));
+        }
+        if RESERVED_ENV.contains(&key.as_str()) {
+            return Err(format!("{} 由启动流程设置，不能在方案中修改", key));
+        }
+    }
+    Ok(())
+}
+
+fn fill<T: Clone>(target: &mut Option<T>, value: &Option<T>) {
+    if target.is_none() {
+        target.clone_from(value);
+    }
+}
+
+// 把实例引用的方案合并进启动配置：实例自身设置过的项优先，环境变量逐项合并
+pub fn apply_profile(app: &AppHandle, config: &mut WineConfig) -> Result<(), String> {
+    let id = match config.profile_id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => id.to_string(),
+        None => return Ok(()),
+    };
+    let profile = read_profiles(app)
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or(format!("实例引用的启动方案不存在: {}", id))?;
+
+    fill(&mut config.windows_locale, &profile.windows_locale);
+    fill(&mut config.japanese_compat, &profile.japanese_compat);
+    fill(&mut config.japanese_font, &profile.japanese_font);
+    fill(&mut config.timezone, &profile.timezone);
+    fill(&mut config.graphics_backend, &profile.graphics_backend);
+    fill(&mut config.sync_mode, &profile.sync_mode);
+    fill(&mut config.dpi, &profile.dpi);
+    fill(&mut config.retina_mode, &profile.retina_mode);
+    fill(&mut config.virtual_desktop, &profile.virtual_desktop);
+    fill(&mut config.pre_launch, &profile.pre_launch);
+    fill(&mut config.post_session_actions, &profile.post_session_actions);
+
+    let env = config.env.get_or_insert_with(Default::default);
+    for (key, value) in profile.env {
+        env.entry(key).or_insert(value);
+    }
+    log_debug!("实例使用启动方案 {}", profile.name);
+    Ok(())
+}
+
+#[command]
+pub fn list_launch_profiles(app: AppHandle) -> Vec<LaunchProfile> {
+    read_profiles(&app)
+}
+
+// 新建或更新方案，id 为空时自动生成
+#[command]
+pub fn save_launch_profile(app: AppHandle, mut profile: LaunchProfile) -> Result<LaunchProfile, String> {
+    if profile.name.trim().is_empty() {
+        return Err("方案名称不能为空".to_string());
+    }
+    validate_env(&profile.env)?;
+    if profile.id.trim().is_empty() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        profile.id = format!("profile-{}", now);
+    }
+
+    let mut profiles = read_profiles(&app);
+    match profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile.clone(),
+        None => profiles.push(profile.clone()),
+    }
+    write_profiles(&app, &profiles)?;
+    Ok(profile)
+}
+
+// 删除方案；仍有实例引用时拒绝，返回引用该方案的实例名称
+#[command]
+pub fn delete_launch_profile(app: AppHandle, id: String) -> Result<(), String> {
+    let referenced_by: Vec<String> = storage::read_instance_values(&app)
+        .iter()
+        .filter(|inst| inst["launchProfile"].as_str() == Some(id.as_str()))
+        .map(|inst| inst["name"].as_str().unwrap_or("未命名实例").to_string())
+        .collect();
+    if !referenced_by.is_empty() {
+        return Err(format!("以下实例仍在使用该方案: {}", referenced_by.join("、")));
+    }
+
+    let mut profiles = read_profiles(&app);
+    profiles.retain(|p| p.id != id);
+    write_profiles(&app, &profiles)
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runner;
+
+// wine 注册表文件（system.reg / user.reg）中的一个键
+#[derive(Debug, Clone)]
+pub struct RegKey {
+    // 反转义后的键路径，如 Software\Microsoft\Windows NT\CurrentVersion
+    pub path: String,
+    // 值名小写后作为键，默认值使用 "@"
+    pub values: HashMap<String, String>,
+}
+
+impl RegKey {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(&name.to_lowercase()).map(|s| s.as_str())
+    }
+}
+
+fn unescape_reg_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// 解析一行 "name"=value 或 @=value
+fn parse_value_line(line: &str) -> Option<(String, String)> {
+    let (name, rest) = if let Some(rest) = line.strip_prefix("@=") {
+        ("@".to_string(), rest)
+    } else {
+        let body = line.strip_prefix('"')?;
+        // 找到未被转义的结束引号
+        let mut end = None;
+        let mut escaped = false;
+        for (i, c) in body.char_indices() {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => {
+                    end = Some(i);
+                    break;
+                }
+                _ => escaped = false,
+            }
+        }
+        let end = end?;
+        let rest = body[end + 1..].strip_prefix('=')?;
+        (unescape_reg_string(&body[..end]), rest)
+    };
+
+    let value = if let Some(quoted) = rest.strip_prefix('"') {
+        unescape_reg_string(quoted.strip_suffix('"').unwrap_or(quoted))
+    } else {
+        // dword / hex 等类型保留原始文本
+        rest.to_string()
+    };
+
+    Some((name.to_lowercase(), value))
+}
+
+pub fn parse_reg_file(path: &Path) -> Result<Vec<RegKey>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("无法读取注册表文件 {:?}: {}", path, e))?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    let mut keys = Vec::new();
+    let mut current: Option<RegKey> = None;
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.starts_with('[') {
+            if let Some(k) = current.take() {
+                keys.push(k);
+            }
+            // [Key\\Path] 1690000000
+            let end = line.rfind(']').unwrap_or(line.len());
+            current = Some(RegKey {
+                path: unescape_reg_string(&line[1..end]),
+                values: HashMap::new(),
+            });
+        } else if let Some(key) = current.as_mut() {
+            if let Some((name, value)) = parse_value_line(line) {
+                key.values.insert(name, value);
+            }
+        }
+    }
+
+    if let Some(k) = current.take() {
+        keys.push(k);
+    }
+    Ok(keys)
+}
+
+pub fn find_key<'a>(keys: &'a [RegKey], path: &str) -> Option<&'a RegKey> {
+    keys.iter().find(|k| k.path.eq_ignore_ascii_case(path))
+}
+
+// 读取容器 .reg 文件中的某个值，文件或键不存在时返回 None
+pub fn read_value(reg_file: &Path, key: &str, name: &str) -> Option<String> {
+    let keys = parse_reg_file(reg_file).ok()?;
+    find_key(&keys, key).and_then(|k| k.get(name)).map(str::to_string)
+}
+
+// 启动前写入注册表时用于判断是否已一致，一致时跳过以免拖慢启动
+pub fn value_matches(reg_file: &Path, key: &str, name: &str, expected: &str) -> bool {
+    read_value(reg_file, key, name).is_some_and(|v| v.eq_ignore_ascii_case(expected))
+}
+
+// 通过 CrossOver 自带的 wine reg 写入注册表，wineserver 运行中也能安全生效
+pub fn wine_reg_add(
+    crossover_app_path: &str,
+    bottle_path: &Path,
+    key: &str,
+    name: &str,
+    value_type: &str,
+    data: &str,
+) -> Result<(), String> {
+    let bottle_name = bottle_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let output = Command::new(runner::crossover_tool(crossover_app_path, "wine"))
+        .env("WINEPREFIX", bottle_path)
+        .env("CX_BOTTLE", bottle_name)
+        .env("WINEDEBUG", "-all")
+        .args(["reg", "add", key, "/v", name, "/t", value_type, "/d", data, "/f"])
+        .output()
+        .map_err(|e| format!("执行 wine reg 失败: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "写入注册表失败 {}\\{}: {}",
+            key,
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+// 转义 .reg 文件中的字符串值
+pub fn escape_reg_string(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// 一次性导入多项注册表修改，比逐条执行 wine reg add 快得多；reg_body 为 REGEDIT4 头之后的内容
+pub fn wine_reg_import(crossover_app_path: &str, bottle_path: &Path, reg_body: &str) -> Result<(), String> {
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let reg_file = std::env::temp_dir().join(format!("asumigal-{}.reg", ts));
+    fs::write(&reg_file, format!("REGEDIT4\n\n{}", reg_body)).map_err(|e| format!("无法写入临时注册表文件: {}", e))?;
+
+    let mut cmd = Command::new(runner::crossover_tool(crossover_app_path, "wine"));
+    runner::apply_bottle_env(&mut cmd, bottle_path)?;
+    let output = cmd.arg("regedit").arg("/S").arg(&reg_file).output();
+    let _ = fs::remove_file(&reg_file);
+
+    let output = output.map_err(|e| format!("执行 wine regedit 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("导入注册表失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
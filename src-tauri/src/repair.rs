@@ -0,0 +1,235 @@
+use tauri::AppHandle;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::instance::{self, GameInstance};
+use crate::storage;
+
+// 读取 instances.json 时的自我修复：手工编辑或写入中断造成的错误不应让整个游戏库无法打开。
+// 格式错误的字段恢复为默认值；无法修复的条目移到 instances.json.corrupt 中保存，不直接丢弃
+const CORRUPT_FILENAME: &str = "instances.json.corrupt";
+
+// 字段的期望类型，类型不符时删除该字段（即恢复默认值）
+const FIELD_KINDS: [(&str, &str); 13] = [
+    ("info", "string"),
+    ("bottleName", "string"),
+    ("executablePath", "string"),
+    ("backgroundImage", "string"),
+    ("tags", "array"),
+    ("lastPlayed", "u64"),
+    ("totalPlayTime", "u64"),
+    ("playHistory", "object"),
+    ("runMode", "string"),
+    ("gameFileStatus", "string"),
+    ("favorite", "bool"),
+    ("rating", "u64"),
+    ("deletedAt", "u64"),
+];
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct RepairReport {
+    // 文件在数组结束前被截断
+    truncated: bool,
+    // 恢复为默认值的字段说明
+    fixed: Vec<String>,
+    // 移入 instances.json.corrupt 的条目数
+    quarantined: usize,
+}
+
+impl RepairReport {
+    pub fn is_empty(&self) -> bool {
+        !self.truncated && self.fixed.is_empty() && self.quarantined == 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CorruptEntry {
+    quarantined_at: u64,
+    reason: String,
+    raw: String,
+}
+
+// 按顶层数组的元素切分文本，单个元素损坏不影响其他元素。
+// 返回各元素的原始文本，以及数组是否在结束前被截断；文本不是数组时返回 None
+fn split_elements(text: &str) -> Option<(Vec<&str>, bool)> {
+    let start = text.find(|c: char| !c.is_whitespace())?;
+    if !text[start..].starts_with('[') {
+        return None;
+    }
+    let body = start + 1;
+    let mut elements = Vec::new();
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    let mut element_start = body;
+    for (i, c) in text[body..].char_indices() {
+        let pos = body + i;
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' if depth > 0 => depth -= 1,
+            ',' | ']' if depth == 0 => {
+                let element = text[element_start..pos].trim();
+                if !element.is_empty() {
+                    elements.push(element);
+                }
+                if c == ']' {
+                    return Some((elements, false));
+                }
+                element_start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    let tail = text[element_start..].trim();
+    if !tail.is_empty() {
+        elements.push(tail);
+    }
+    Some((elements, true))
+}
+
+fn kind_matches(value: &Value, kind: &str) -> bool {
+    match kind {
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "bool" => value.is_boolean(),
+        "u64" => value.is_u64(),
+        _ => true,
+    }
+}
+
+// 把单个条目修复为合法的实例，返回修复说明；缺少有效 ID 等无法修复的情况返回错误原因
+fn repair_instance(value: Value, fixed: &mut Vec<String>) -> Result<GameInstance, String> {
+    let mut obj: Map<String, Value> = match value {
+        Value::Object(obj) => obj,
+        _ => return Err("条目不是对象".to_string()),
+    };
+    let id = match obj.get("id").and_then(|v| v.as_str()) {
+        Some(id) if storage::check_instance_id(id).is_ok() => id.to_string(),
+        _ => return Err("缺少有效的实例 ID".to_string()),
+    };
+    let mut note = |msg: String| fixed.push(format!("实例 {}: {}", id, msg));
+
+    if obj.get("name").and_then(|v| v.as_str()).is_none_or(|n| n.trim().is_empty()) {
+        obj.insert("name".to_string(), Value::String(id.clone()));
+        note("名称为空，已用 ID 代替".to_string());
+    }
+    for (field, kind) in FIELD_KINDS {
+        let bad = obj.get(field).is_some_and(|v| !v.is_null() && !kind_matches(v, kind));
+        if bad {
+            obj.remove(field);
+            note(format!("字段 {} 格式错误，已重置", field));
+        }
+    }
+    if let Some(Value::Array(tags)) = obj.get_mut("tags") {
+        let before = tags.len();
+        tags.retain(|t| t.as_str().is_some_and(|s| !s.trim().is_empty()));
+        if tags.len() != before {
+            note("已移除无效的标签".to_string());
+        }
+    }
+    if let Some(Value::Object(history)) = obj.get_mut("playHistory") {
+        let before = history.len();
+        history.retain(|day, secs| instance::is_day_key(day) && secs.is_u64());
+        if history.len() != before {
+            note("已移除无效的游玩记录".to_string());
+        }
+    }
+
+    let mut inst: GameInstance = serde_json::from_value(Value::Object(obj)).map_err(|e| e.to_string())?;
+    if inst.validate().is_err() {
+        if inst.rating.is_some_and(|r| !(1..=instance::MAX_RATING).contains(&r)) {
+            inst.rating = None;
+            note("评分超出范围，已清除".to_string());
+        }
+        if !instance::RUN_MODES.contains(&inst.run_mode()) {
+            inst.run_mode = None;
+            note("运行模式无效，已恢复默认".to_string());
+        }
+        if inst.game_file_status.as_deref().is_some_and(|s| !instance::FILE_STATUSES.contains(&s)) {
+            inst.game_file_status = None;
+            note("游戏文件状态无效，已清除".to_string());
+        }
+    }
+    inst.validate()?;
+    Ok(inst)
+}
+
+pub(crate) struct ParsedLibrary {
+    pub instances: Vec<GameInstance>,
+    pub report: RepairReport,
+    // 需要隔离的条目：（原因，原始文本）
+    pub rejected: Vec<(String, String)>,
+}
+
+// 解析 instances.json 的内容，map_paths 在修复前处理每个条目（把规范形式的路径解析为本机路径）
+pub(crate) fn parse_library(text: &str, map_paths: impl Fn(&mut Value)) -> Result<ParsedLibrary, String> {
+    let mut report = RepairReport::default();
+    let mut rejected = Vec::new();
+
+    let (elements, truncated) = split_elements(text).ok_or("实例数据解析失败: 文件内容不是实例列表".to_string())?;
+    report.truncated = truncated;
+    let mut instances: Vec<GameInstance> = Vec::new();
+    let mut seen = HashSet::new();
+    for raw in &elements {
+        let mut value = match serde_json::from_str::<Value>(raw) {
+            Ok(v) => v,
+            Err(e) => {
+                rejected.push((format!("无法解析: {}", e), raw.to_string()));
+                continue;
+            }
+        };
+        map_paths(&mut value);
+        match repair_instance(value, &mut report.fixed) {
+            Ok(inst) if seen.insert(inst.id.clone()) => instances.push(inst),
+            Ok(inst) => rejected.push((format!("实例 ID 重复: {}", inst.id), raw.to_string())),
+            Err(reason) => rejected.push((reason, raw.to_string())),
+        }
+    }
+    // 有内容却一个实例都没能恢复时不当作空游戏库，避免后续写入覆盖原文件
+    if instances.is_empty() && !rejected.is_empty() {
+        return Err(format!("实例数据解析失败: {}", rejected[0].0));
+    }
+    report.quarantined = rejected.len();
+    Ok(ParsedLibrary { instances, report, rejected })
+}
+
+// 把无法修复的条目追加到 instances.json.corrupt，相同内容只保存一次
+pub(crate) fn quarantine(app: &AppHandle, rejected: &[(String, String)]) -> Result<(), String> {
+    if rejected.is_empty() {
+        return Ok(());
+    }
+    let path = storage::resolve_data_path(app, CORRUPT_FILENAME)?;
+    let mut entries: Vec<CorruptEntry> = storage::read_data_bytes(&path)
+        .ok()
+        .and_then(|data| storage::decode_library(data).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut added = 0;
+    for (reason, raw) in rejected {
+        if entries.iter().any(|e| e.raw == *raw) {
+            continue;
+        }
+        entries.push(CorruptEntry { quarantined_at: now, reason: reason.clone(), raw: raw.clone() });
+        added += 1;
+    }
+    if added == 0 {
+        return Ok(());
+    }
+    let text = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+    storage::write_atomic(&path, &storage::encode_library(app, &text)?)?;
+    log_warn!("已将 {} 个无法修复的实例条目移到 {:?}", added, path);
+    Ok(())
+}
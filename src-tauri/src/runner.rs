@@ -1,39 +1,161 @@
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use tauri::{AppHandle, Emitter, Manager, command};
-use std::time::Instant;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use tauri::{AppHandle, Emitter, command};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::thread;
-use std::collections::{HashMap, HashSet};
-use std::sync::{Mutex, OnceLock};
-
-#[derive(serde::Deserialize)]
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::audio;
+use crate::autofix;
+use crate::post_session::{self, PostSessionConfig};
+use crate::benchmark;
+use crate::display;
+use crate::exe_info;
+use crate::instance::{RUN_MODE_CROSSOVER, RUN_MODE_DIRECT, RUN_MODE_NATIVE, RUN_MODE_PARALLELS};
+use crate::launch_chain;
+use crate::last_exit::{self, LastExit};
+use crate::launch_queue;
+use crate::locale;
+use crate::native_runner;
+use crate::paths;
+use crate::profiles;
+use crate::runner_version;
+use crate::sandbox;
+use crate::shutdown::{self, OpenSession};
+use crate::storage::{self, SessionRecord};
+use crate::winedbg::{self, CrashInfo};
+
+#[derive(serde::Deserialize, Clone, Default)]
 pub struct WineConfig {
     pub bottle_path: String,
     pub game_exe: String,
     pub crossover_app_path: String,
     pub run_mode: Option<String>,
     pub dry_run_active: Option<bool>,
+    // 是否将 wine 输出实时推送到前端（部分游戏输出量很大，默认关闭）
+    pub stream_logs: Option<bool>,
+    // 游戏退出后执行的动作（backup_saves / stop_wineserver / open_notes / sync_bangumi）
+    pub post_session_actions: Option<Vec<String>>,
+    // 存档目录，供 backup_saves 使用
+    pub save_dir: Option<String>,
+    // Wine 虚拟桌面分辨率，如 "1280x720"，为空时全屏直接运行
+    pub virtual_desktop: Option<String>,
+    // 进程优先级: background / low / normal / high，为空时为 normal
+    pub priority: Option<String>,
+    // 日文兼容预设：使用 ja_JP.UTF-8 并写入 932 代码页
+    pub japanese_compat: Option<bool>,
+    // 日文兼容预设下强制替换的字体，如 "Hiragino Sans"，为空时不替换
+    pub japanese_font: Option<String>,
+    // 固定使用的 CrossOver.app 或 wine 可执行文件，部分游戏只能在保留下来的旧版 CrossOver 上运行
+    pub pinned_crossover: Option<String>,
+    // 本次启动使用的时区，如 "Asia/Tokyo"，用于按日期触发活动的游戏
+    pub timezone: Option<String>,
+    // 容器的 Windows 区域，如 "ja-JP"，部分游戏会检查系统区域
+    pub windows_locale: Option<String>,
+    // 同步机制: default / msync / esync / off，部分引擎在 msync 下会死锁
+    pub sync_mode: Option<String>,
+    // 图形后端: default / d3dmetal / dxvk / wined3d
+    pub graphics_backend: Option<String>,
+    // 自动修复：启动后很快出错退出时，按 autofix 中的顺序依次尝试其他配置
+    pub auto_fix: Option<bool>,
+    // 多少秒内出错退出视为启动失败，默认与崩溃判定相同
+    pub auto_fix_window_sec: Option<u64>,
+    // native 模式使用的引擎: renpy / onscripter / kirikiri，为空或 auto 时按游戏目录自动识别
+    pub native_engine: Option<String>,
+    // native 模式的运行时路径（Ren'Py SDK 目录、onsyuri 或 krkrsdl2），为空时自动查找
+    pub native_runtime: Option<String>,
+    // 写入容器的 DPI（LogPixels），如 144 对应 150% 缩放，解决 Retina 屏上画面过小
+    pub dpi: Option<u32>,
+    // CrossOver 高分辨率模式（Mac 驱动 RetinaMode）
+    pub retina_mode: Option<bool>,
+    // 排查问题时开启的 WINEDEBUG 调试通道，如 "+loaddll,+seh"，为空时关闭所有调试输出
+    pub debug_channels: Option<String>,
+    // 音频驱动: default / coreaudio / disabled，部分游戏在默认设置下卡顿或无声
+    pub audio_driver: Option<String>,
+    // DirectSound 缓冲区大小（字节）
+    pub audio_buffer_size: Option<u32>,
+    // 启动链：在游戏本体之前依次运行并等待退出的程序（设置程序、DRM 包装器等），相对路径基于游戏目录
+    pub pre_launch: Option<Vec<String>>,
+    // 引用的启动方案（profiles），实例未设置的项由方案补全
+    pub profile_id: Option<String>,
+    // 额外的环境变量
+    pub env: Option<BTreeMap<String, String>>,
+}
+
+#[derive(serde::Serialize, Clone)]
+pub struct CrossOverInstall {
+    path: String,
+    version: Option<String>,
 }
 
 #[derive(serde::Serialize, Clone)]
 struct GameFinishedPayload {
     instance_id: String,
     duration_sec: u64,
+    exit_code: Option<i32>,
+    // 被信号终止时的信号编号
+    signal: Option<i32>,
+    crashed: bool,
+    // wine 日志的最后若干行，用于展示崩溃原因
+    log_tail: Vec<String>,
+    // 从 winedbg 输出中解析出的出错模块与调用栈，便于按模块名搜索已知的解决方法
+    crash_info: Option<CrashInfo>,
+    // 应用退出后未追踪到真实的结束时间，时长按退出应用时计算
+    unknown_end: bool,
+    // 后端已记入游戏库的时长，为空表示记录失败，由前端自行累加
+    recorded: Option<SessionRecord>,
 }
 
-#[derive(Clone)]
-struct RunningInstance {
+#[derive(serde::Serialize, Clone)]
+struct GameLogPayload {
+    instance_id: String,
+    lines: Vec<String>,
+}
+
+// 每批最多推送的行数与最长等待时间
+const LOG_BATCH_LINES: usize = 50;
+const LOG_BATCH_INTERVAL: Duration = Duration::from_millis(200);
+// 短于该时长且异常退出的会话视为崩溃
+pub(crate) const CRASH_MIN_RUNTIME_SEC: u64 = 30;
+const LOG_TAIL_LINES: usize = 40;
+// winedbg 的寄存器、调用栈与模块表较长，解析崩溃信息时读取更多行
+const CRASH_SCAN_LINES: usize = 600;
+
+#[derive(serde::Serialize, Clone)]
+pub struct RunningInstance {
+    instance_id: String,
     launcher_pid: u32,
     run_mode: String,
     game_exe: String,
+    // 启动时间（Unix 秒），页面刷新后前端据此继续计时
+    started_at: u64,
+    // 是否有等待线程负责在退出时移除记录；dry run 时没有
+    monitored: bool,
+    // 暂停开始时间（Unix 秒），未暂停时为空
+    paused_at: Option<u64>,
+    // 已结束的暂停累计秒数，结算时长时扣除
+    paused_secs: u64,
+}
+
+impl RunningInstance {
+    fn total_paused_secs(&self, now: u64) -> u64 {
+        self.paused_secs + self.paused_at.map(|t| now.saturating_sub(t)).unwrap_or(0)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
 }
 
 #[derive(Clone)]
-struct ProcessInfo {
-    pid: u32,
-    ppid: u32,
-    command: String,
+pub(crate) struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub command: String,
 }
 
 static RUNNING_INSTANCES: OnceLock<Mutex<HashMap<String, RunningInstance>>> = OnceLock::new();
@@ -42,17 +164,58 @@ fn running_instances() -> &'static Mutex<HashMap<String, RunningInstance>> {
     RUNNING_INSTANCES.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
-fn track_running_instance(instance_id: &str, launcher_pid: u32, run_mode: &str, game_exe: &str) {
+pub(crate) fn running_instance_ids() -> Vec<String> {
+    running_instances().lock().map(|map| map.keys().cloned().collect()).unwrap_or_default()
+}
+
+fn track_running_instance(app: &AppHandle, instance_id: &str, launcher_pid: u32, run_mode: &str, game_exe: &str, monitored: bool) {
+    let info = RunningInstance {
+        instance_id: instance_id.to_string(),
+        launcher_pid,
+        run_mode: run_mode.to_string(),
+        game_exe: game_exe.to_string(),
+        started_at: unix_now(),
+        monitored,
+        paused_at: None,
+        paused_secs: 0,
+    };
+    if let Ok(mut map) = running_instances().lock() {
+        map.insert(instance_id.to_string(), info.clone());
+    }
+    let _ = app.emit("game-started", info);
+}
+
+// 试启动期间登记为运行中（不记录游玩时长，也不通知前端），避免同时再次启动该游戏
+pub(crate) fn register_test_run(instance_id: &str, launcher_pid: u32, game_exe: &str) {
+    let info = RunningInstance {
+        instance_id: instance_id.to_string(),
+        launcher_pid,
+        run_mode: RUN_MODE_CROSSOVER.to_string(),
+        game_exe: game_exe.to_string(),
+        started_at: unix_now(),
+        monitored: false,
+        paused_at: None,
+        paused_secs: 0,
+    };
     if let Ok(mut map) = running_instances().lock() {
-        map.insert(
-            instance_id.to_string(),
-            RunningInstance {
-                launcher_pid,
-                run_mode: run_mode.to_string(),
-                game_exe: game_exe.to_string(),
-            },
-        );
+        map.insert(instance_id.to_string(), info);
+    }
+}
+
+// 同一实例已在运行时拒绝再次启动；没有等待线程的记录以启动器进程是否存活为准
+pub(crate) fn ensure_not_running(instance_id: &str) -> Result<(), String> {
+    if launch_chain::is_running(instance_id) {
+        return Err("该游戏正在运行启动前程序，请稍候".to_string());
+    }
+    let info = match get_tracked_instance(instance_id) {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+    if info.monitored || send_signal(info.launcher_pid, "-0") {
+        return Err("该游戏已在运行中，请先退出或停止当前实例".to_string());
     }
+    remove_running_instance(instance_id);
+    Ok(())
 }
 
 fn get_tracked_instance(instance_id: &str) -> Option<RunningInstance> {
@@ -62,12 +225,118 @@ fn get_tracked_instance(instance_id: &str) -> Option<RunningInstance> {
         .and_then(|map| map.get(instance_id).cloned())
 }
 
-fn remove_running_instance(instance_id: &str) {
+pub(crate) fn remove_running_instance(instance_id: &str) {
     if let Ok(mut map) = running_instances().lock() {
         map.remove(instance_id);
     }
 }
 
+fn read_log_tail(app: &AppHandle, instance_id: &str, max_lines: usize) -> Vec<String> {
+    let text = match game_log_path(app, instance_id).ok().and_then(|p| fs::read(p).ok()) {
+        Some(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+        None => return Vec::new(),
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(max_lines)..].iter().map(|l| l.to_string()).collect()
+}
+
+// 本次会话是否出错退出：退出码非零、被信号终止，或日志中出现未处理异常
+pub(crate) fn exited_with_error(app: &AppHandle, instance_id: &str, status: Option<ExitStatus>) -> bool {
+    status.is_some_and(|s| s.signal().is_some() || s.code().is_some_and(|c| c != 0))
+        || winedbg::parse_crash(&read_log_tail(app, instance_id, CRASH_SCAN_LINES).join("\n")).is_some()
+}
+
+// 游戏退出后的统一收尾：移除运行记录、通知前端、执行结束动作
+fn finish_session(app: &AppHandle, instance_id: String, duration_sec: u64, status: Option<ExitStatus>, post: &PostSessionConfig) {
+    emit_session_finished(app, instance_id, duration_sec, status, post, false);
+}
+
+fn emit_session_finished(
+    app: &AppHandle,
+    instance_id: String,
+    duration_sec: u64,
+    status: Option<ExitStatus>,
+    post: &PostSessionConfig,
+    unknown_end: bool,
+) {
+    // 退出应用时仍在运行的会话已交给 shutdown 保存，这里不再重复上报
+    if shutdown::is_quitting() {
+        remove_running_instance(&instance_id);
+        return;
+    }
+    // 暂停期间不计入游玩时长
+    let tracked = get_tracked_instance(&instance_id);
+    let paused = tracked.as_ref().map(|i| i.total_paused_secs(unix_now())).unwrap_or(0);
+    let started_at = tracked.map(|i| i.started_at).unwrap_or_else(|| unix_now().saturating_sub(duration_sec));
+    let duration_sec = duration_sec.saturating_sub(paused);
+    remove_running_instance(&instance_id);
+
+    let recorded = match storage::record_session(app, &instance_id, started_at, duration_sec) {
+        Ok(r) => Some(r),
+        Err(e) => {
+            log_warn!("记录游玩时长失败: {}", e);
+            None
+        }
+    };
+
+    let exit_code = status.and_then(|s| s.code());
+    let signal = status.and_then(|s| s.signal());
+    let mut log_tail = read_log_tail(app, &instance_id, CRASH_SCAN_LINES);
+    let crash_info = winedbg::parse_crash(&log_tail.join("\n"));
+    log_tail.drain(..log_tail.len().saturating_sub(LOG_TAIL_LINES));
+    // wine 的启动器即使游戏崩溃也可能正常退出，因此同时检查日志中的未处理异常
+    let abnormal = signal.is_some() || exit_code.is_some_and(|c| c != 0) || crash_info.is_some();
+    let crashed = duration_sec < CRASH_MIN_RUNTIME_SEC && abnormal;
+    if crashed {
+        let module = crash_info.as_ref().and_then(|c| c.faulting_module.clone());
+        log_warn!("实例 {} 疑似崩溃: exit_code={:?}, signal={:?}, module={:?}", instance_id, exit_code, signal, module);
+    }
+
+    // 补记的会话不知道真实的退出状态，不覆盖上次的记录
+    if !unknown_end {
+        let exit = LastExit::classify(crashed, crash_info.as_ref(), exit_code, signal, duration_sec, duration_sec < CRASH_MIN_RUNTIME_SEC, unix_now());
+        if let Err(e) = last_exit::record(app, &instance_id, exit) {
+            log_warn!("{}", e);
+        }
+    }
+
+    let _ = app.emit("game-finished", GameFinishedPayload {
+        instance_id: instance_id.clone(),
+        duration_sec,
+        exit_code,
+        signal,
+        crashed,
+        log_tail,
+        crash_info,
+        unknown_end,
+        recorded,
+    });
+    post_session::run_post_session_actions(app, &instance_id, post);
+    launch_queue::launch_next(app);
+}
+
+// 应用退出时仍在追踪的会话（不含空跑），交给 shutdown 持久化
+pub(crate) fn open_sessions_snapshot() -> Vec<OpenSession> {
+    let now = unix_now();
+    get_running_games()
+        .into_iter()
+        .filter(|info| info.monitored)
+        .map(|info| OpenSession {
+            pids: instance_process_tree(&info).unwrap_or_default(),
+            instance_id: info.instance_id.clone(),
+            started_at: info.started_at,
+            paused_secs: info.total_paused_secs(now),
+            tracker_pid: None,
+            unknown_end: false,
+        })
+        .collect()
+}
+
+// 补记应用退出期间结束的会话
+pub(crate) fn finish_recovered_session(app: &AppHandle, instance_id: String, duration_sec: u64, unknown_end: bool) {
+    emit_session_finished(app, instance_id, duration_sec, None, &PostSessionConfig::default(), unknown_end);
+}
+
 fn parse_ps_line(line: &str) -> Option<ProcessInfo> {
     let mut rest = line.trim_start();
     if rest.is_empty() {
@@ -89,7 +358,7 @@ fn parse_ps_line(line: &str) -> Option<ProcessInfo> {
     Some(ProcessInfo { pid, ppid, command })
 }
 
-fn list_processes() -> Result<Vec<ProcessInfo>, String> {
+pub(crate) fn list_processes() -> Result<Vec<ProcessInfo>, String> {
     let output = Command::new("ps")
         .args(["-axww", "-o", "pid=,ppid=,command="])
         .output()
@@ -109,7 +378,7 @@ fn list_processes() -> Result<Vec<ProcessInfo>, String> {
     Ok(processes)
 }
 
-fn build_children_map(processes: &[ProcessInfo]) -> HashMap<u32, Vec<u32>> {
+pub(crate) fn build_children_map(processes: &[ProcessInfo]) -> HashMap<u32, Vec<u32>> {
     let mut children_map: HashMap<u32, Vec<u32>> = HashMap::new();
     for p in processes {
         children_map.entry(p.ppid).or_default().push(p.pid);
@@ -117,7 +386,7 @@ fn build_children_map(processes: &[ProcessInfo]) -> HashMap<u32, Vec<u32>> {
     children_map
 }
 
-fn collect_descendants(root_pid: u32, children_map: &HashMap<u32, Vec<u32>>) -> Vec<u32> {
+pub(crate) fn collect_descendants(root_pid: u32, children_map: &HashMap<u32, Vec<u32>>) -> Vec<u32> {
     let mut stack = vec![root_pid];
     let mut visited: HashSet<u32> = HashSet::new();
     let mut descendants = Vec::new();
@@ -145,7 +414,7 @@ fn send_signal(pid: u32, signal: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn terminate_pids(mut pids: Vec<u32>) -> Vec<u32> {
+pub(crate) fn terminate_pids(mut pids: Vec<u32>) -> Vec<u32> {
     pids.sort_unstable();
     pids.dedup();
 
@@ -167,6 +436,11 @@ fn is_wine_wrapper_command(cmd: &str) -> bool {
         || lower.contains("wineserver")
 }
 
+// wine 相关进程：启动器、wineserver 以及所有 Windows 进程
+pub(crate) fn is_wine_process_command(cmd: &str) -> bool {
+    is_wine_wrapper_command(cmd) || cmd.to_lowercase().contains(".exe")
+}
+
 fn looks_like_windows_game_process(cmd: &str) -> bool {
     let lower = cmd.to_lowercase();
     lower.contains(".exe") && !is_wine_wrapper_command(&lower)
@@ -377,16 +651,262 @@ fn stop_crossover_instance(
     Ok(killed)
 }
 
-// 如果字符串以 ~/ 开头，则将其替换为真实的系统家目录
-fn expand_tilde(path_str: &str) -> PathBuf {
-    if path_str.starts_with("~/") {
-        if let Some(home) = dirs::home_dir() {
-            // 去掉前缀 "~/"，把剩下的部分拼接到 home 目录后面
-            return home.join(path_str.trim_start_matches("~/"));
+// 进程树轮询间隔，以及启动器退出后连续多少次未发现游戏进程才认为游戏结束
+const TREE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const TREE_MISS_LIMIT: u32 = 3;
+
+// 很多游戏先启动一个 launcher exe 再立即退出，只等待 wine 主进程会记录到几秒的时长。
+// 这里在启动器存活期间记录它派生的 Windows 进程；启动器退出后继续轮询这些进程、
+// 它们的子进程以及命令行位于游戏目录下的进程，全部退出后才返回。
+pub(crate) fn wait_for_wine_tree(child: &mut Child, game_exe: &Path, bottle_path: &Path) -> (Option<std::process::ExitStatus>, u64) {
+    let start_time = Instant::now();
+    let launcher_pid = child.id();
+    let mut known: HashSet<u32> = HashSet::new();
+
+    let game_dir = game_exe.parent().unwrap_or(game_exe);
+    let unix_dir = canonicalize_or_original(game_dir).to_string_lossy().to_lowercase();
+    let dir_candidates = build_wine_windows_path_candidates(game_dir, bottle_path)
+        .into_iter()
+        .map(|p| format!("{}\\", normalize_windows_path_for_match(&p).trim_end_matches('\\')))
+        .collect::<Vec<_>>();
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) => {}
+            Err(e) => {
+                log_warn!("等待进程失败: {}", e);
+                break None;
+            }
+        }
+        if let Ok(processes) = list_processes() {
+            let children_map = build_children_map(&processes);
+            for pid in collect_descendants(launcher_pid, &children_map) {
+                if processes.iter().any(|p| p.pid == pid && looks_like_windows_game_process(&p.command)) {
+                    known.insert(pid);
+                }
+            }
+        }
+        thread::sleep(TREE_POLL_INTERVAL);
+    };
+
+    let mut last_seen = start_time.elapsed().as_secs();
+    let mut miss_count = 0;
+    while miss_count < TREE_MISS_LIMIT {
+        thread::sleep(TREE_POLL_INTERVAL);
+        let processes = match list_processes() {
+            Ok(p) => p,
+            Err(_) => {
+                miss_count += 1;
+                continue;
+            }
+        };
+        let children_map = build_children_map(&processes);
+
+        let mut alive: Vec<u32> = Vec::new();
+        for p in &processes {
+            if !looks_like_windows_game_process(&p.command) {
+                continue;
+            }
+            let cmd_norm = normalize_windows_path_for_match(&p.command);
+            let in_game_dir = p.command.to_lowercase().contains(&unix_dir)
+                || dir_candidates.iter().any(|d| cmd_norm.contains(d));
+            if known.contains(&p.pid) || in_game_dir {
+                alive.push(p.pid);
+            }
+        }
+        for pid in alive.clone() {
+            alive.extend(collect_descendants(pid, &children_map));
         }
+
+        if alive.is_empty() {
+            miss_count += 1;
+        } else {
+            miss_count = 0;
+            last_seen = start_time.elapsed().as_secs();
+            known.extend(alive);
+        }
+    }
+
+    (status, last_seen)
+}
+
+#[derive(serde::Serialize, Clone)]
+struct GameStatsPayload {
+    instance_id: String,
+    cpu_percent: f32,
+    memory_mb: u64,
+    process_count: usize,
+    // 持续高占用的进程，前端据此提示可能卡死的 wine 进程
+    runaway_pids: Vec<u32>,
+}
+
+const STATS_INTERVAL: Duration = Duration::from_secs(3);
+// 单个进程 CPU 占用持续超过阈值的采样次数
+const RUNAWAY_CPU_PERCENT: f32 = 95.0;
+const RUNAWAY_SAMPLES: u32 = 5;
+
+// 读取指定进程的 (CPU 百分比, 常驻内存 KB)
+fn sample_process_usage(pids: &[u32]) -> HashMap<u32, (f32, u64)> {
+    let mut usage = HashMap::new();
+    if pids.is_empty() {
+        return usage;
     }
-    // 如果没有 ~，或者获取 Home 目录失败，直接返回原路径
-    PathBuf::from(path_str)
+    let list = pids.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",");
+    let output = match Command::new("ps").args(["-o", "pid=,%cpu=,rss=", "-p", &list]).output() {
+        Ok(o) => o,
+        Err(_) => return usage,
+    };
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut parts = line.split_whitespace();
+        let pid = parts.next().and_then(|v| v.parse::<u32>().ok());
+        let cpu = parts.next().and_then(|v| v.parse::<f32>().ok());
+        let rss = parts.next().and_then(|v| v.parse::<u64>().ok());
+        if let (Some(pid), Some(cpu), Some(rss)) = (pid, cpu, rss) {
+            usage.insert(pid, (cpu, rss));
+        }
+    }
+    usage
+}
+
+// 定期采样游戏进程树的资源占用并推送 game-stats 事件，实例结束后自动退出
+fn spawn_stats_monitor(app: AppHandle, instance_id: String, launcher_pid: u32) {
+    thread::spawn(move || {
+        let mut tree: HashSet<u32> = HashSet::from([launcher_pid]);
+        let mut hot_samples: HashMap<u32, u32> = HashMap::new();
+
+        loop {
+            thread::sleep(STATS_INTERVAL);
+            match get_tracked_instance(&instance_id) {
+                Some(info) if info.launcher_pid == launcher_pid => {}
+                _ => break,
+            }
+
+            let processes = match list_processes() {
+                Ok(p) => p,
+                Err(_) => continue,
+            };
+            let children_map = build_children_map(&processes);
+            for pid in tree.clone() {
+                tree.extend(collect_descendants(pid, &children_map));
+            }
+            tree.retain(|pid| processes.iter().any(|p| p.pid == *pid));
+
+            let pids: Vec<u32> = tree.iter().copied().collect();
+            let usage = sample_process_usage(&pids);
+            hot_samples.retain(|pid, _| usage.contains_key(pid));
+            for (pid, (cpu, _)) in &usage {
+                if *cpu >= RUNAWAY_CPU_PERCENT {
+                    *hot_samples.entry(*pid).or_insert(0) += 1;
+                } else {
+                    hot_samples.remove(pid);
+                }
+            }
+
+            let mut runaway_pids: Vec<u32> = hot_samples
+                .iter()
+                .filter(|(_, n)| **n >= RUNAWAY_SAMPLES)
+                .map(|(pid, _)| *pid)
+                .collect();
+            runaway_pids.sort_unstable();
+
+            let _ = app.emit("game-stats", GameStatsPayload {
+                instance_id: instance_id.clone(),
+                cpu_percent: usage.values().map(|(cpu, _)| cpu).sum(),
+                memory_mb: usage.values().map(|(_, rss)| rss).sum::<u64>() / 1024,
+                process_count: usage.len(),
+                runaway_pids,
+            });
+        }
+    });
+}
+
+pub(crate) fn spawn_line_reader<R: Read + Send + 'static>(reader: R, tx: mpsc::Sender<String>) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            match line {
+                Ok(l) => {
+                    if tx.send(l).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+// 游戏日志文件路径: AppLocalData/logs/<instance_id>.log
+pub(crate) fn game_log_path(app: &AppHandle, instance_id: &str) -> Result<PathBuf, String> {
+    storage::resolve_data_path(app, &format!("logs/{}.log", instance_id))
+}
+
+// 接管子进程的 stdout/stderr，写入日志文件，并按需分批推送 game-log 事件
+pub(crate) fn attach_log_pump(app: &AppHandle, instance_id: &str, child: &mut Child, stream: bool) {
+    attach_log_pump_to(app, instance_id, child, stream, game_log_path(app, instance_id).ok());
+}
+
+// 同 attach_log_pump，但写入指定的日志文件
+pub(crate) fn attach_log_pump_to(app: &AppHandle, instance_id: &str, child: &mut Child, stream: bool, log_path: Option<PathBuf>) {
+    let (tx, rx) = mpsc::channel::<String>();
+    if let Some(stdout) = child.stdout.take() {
+        spawn_line_reader(stdout, tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_line_reader(stderr, tx.clone());
+    }
+    drop(tx);
+
+    let mut log_file = log_path.and_then(|path| {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        fs::File::create(&path).ok()
+    });
+
+    let app_handle = app.clone();
+    let i_id = instance_id.to_string();
+    thread::spawn(move || {
+        let mut batch = Vec::new();
+        let mut last_flush = Instant::now();
+
+        loop {
+            let disconnected = match rx.recv_timeout(LOG_BATCH_INTERVAL) {
+                Ok(line) => {
+                    if let Some(f) = log_file.as_mut() {
+                        let _ = writeln!(f, "{}", line);
+                    }
+                    if stream {
+                        batch.push(line);
+                    }
+                    false
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => false,
+                Err(mpsc::RecvTimeoutError::Disconnected) => true,
+            };
+
+            let should_flush = batch.len() >= LOG_BATCH_LINES
+                || (!batch.is_empty() && last_flush.elapsed() >= LOG_BATCH_INTERVAL)
+                || disconnected;
+            if should_flush && !batch.is_empty() {
+                let _ = app_handle.emit("game-log", GameLogPayload {
+                    instance_id: i_id.clone(),
+                    lines: std::mem::take(&mut batch),
+                });
+                last_flush = Instant::now();
+            }
+
+            if disconnected {
+                break;
+            }
+        }
+    });
+}
+
+// 如果字符串以 ~/ 开头，则将其替换为真实的系统家目录
+pub(crate) fn expand_tilde(path_str: &str) -> PathBuf {
+    // 支持 ~/ 以及外接卷的 vol:<UUID>/ 形式，其余直接返回原路径
+    paths::resolve_stored_path(path_str)
 }
 
 #[command]
@@ -414,6 +934,100 @@ pub fn get_crossover_bottles(path: String) -> Result<Vec<String>, String> {
     Ok(bottles)
 }
 
+// CrossOver 自带命令行工具（wine / cxbottle / wineserver 等）的路径；
+// 传入的也可以是直接包含 wine 的 bin 目录（固定单独的 wine 时使用）
+pub(crate) fn crossover_tool(crossover_app_path: &str, tool: &str) -> PathBuf {
+    let root = expand_tilde(crossover_app_path);
+    if root.join(tool).is_file() {
+        return root.join(tool);
+    }
+    root.join("Contents/SharedSupport/CrossOver/bin").join(tool)
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+// 确定本次启动使用的 CrossOver：实例固定了版本时校验其仍然可用，否则使用全局设置
+pub(crate) fn resolve_crossover_root(config: &WineConfig) -> Result<String, String> {
+    let pinned = match config.pinned_crossover.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(p) => p,
+        None => return Ok(config.crossover_app_path.clone()),
+    };
+
+    let path = expand_tilde(pinned);
+    let root = if path.is_dir() {
+        pinned.to_string()
+    } else if path.is_file() {
+        // 固定的是 wine 可执行文件本身，wineserver 等工具在同一目录
+        path.parent().map(|p| p.to_string_lossy().to_string()).ok_or("无法解析 wine 所在目录")?
+    } else {
+        return Err(format!("实例固定的 CrossOver 不存在，可能已被删除或移动: {:?}", path));
+    };
+
+    let wine = crossover_tool(&root, "wine");
+    if !is_executable(&wine) {
+        return Err(format!("实例固定的 CrossOver 中没有可用的 wine: {:?}", wine));
+    }
+    Ok(root)
+}
+
+// 列出 /Applications 与 ~/Applications 中安装的所有 CrossOver，供实例固定版本时选择
+#[command]
+pub fn list_crossover_installs() -> Vec<CrossOverInstall> {
+    let mut roots = vec![PathBuf::from("/Applications")];
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join("Applications"));
+    }
+
+    let mut installs = Vec::new();
+    for dir in roots {
+        let entries = match fs::read_dir(&dir) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("app") {
+                continue;
+            }
+            let path_str = path.to_string_lossy().to_string();
+            if is_executable(&crossover_tool(&path_str, "wine")) {
+                installs.push(CrossOverInstall { version: read_crossover_version(&path_str), path: path_str });
+            }
+        }
+    }
+    installs.sort_by(|a, b| a.path.cmp(&b.path));
+    installs
+}
+
+// 从 CrossOver.app 的 Info.plist 中读取版本号
+pub(crate) fn read_crossover_version(crossover_app_path: &str) -> Option<String> {
+    let plist = expand_tilde(crossover_app_path).join("Contents/Info");
+    let output = Command::new("defaults")
+        .arg("read")
+        .arg(&plist)
+        .arg("CFBundleShortVersionString")
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if version.is_empty() { None } else { Some(version) }
+}
+
+// 解析 "1280x720" 形式的分辨率
+fn parse_resolution(value: &str) -> Option<(u32, u32)> {
+    let lower = value.trim().to_lowercase();
+    let (w, h) = lower.split_once('x')?;
+    let (w, h) = (w.trim().parse::<u32>().ok()?, h.trim().parse::<u32>().ok()?);
+    if (320..=7680).contains(&w) && (200..=4320).contains(&h) { Some((w, h)) } else { None }
+}
+
 fn extract_vm_name(path: &str) -> Option<String> {
     let path_obj = Path::new(path);
     if let Some(file_name) = path_obj.file_name().and_then(|n| n.to_str()) {
@@ -427,12 +1041,225 @@ fn extract_vm_name(path: &str) -> Option<String> {
     None
 }
 
+// 按优先级包装启动命令。taskpolicy 与 nice 都会 exec 目标程序，PID 保持不变；
+// 非 root 无法设置负的 nice 值，因此提升优先级通过 taskpolicy 的吞吐/延迟等级实现
+fn priority_command(priority: Option<&str>, program: &Path) -> Result<Command, String> {
+    let cmd = match priority.unwrap_or("normal") {
+        "normal" | "" => Command::new(program),
+        "background" => {
+            // 后台 QoS：限制 CPU 与磁盘 I/O，并优先调度到能效核心
+            let mut c = Command::new("/usr/sbin/taskpolicy");
+            c.arg("-b").arg(program);
+            c
+        }
+        "low" => {
+            let mut c = Command::new("/usr/bin/nice");
+            c.args(["-n", "10"]).arg(program);
+            c
+        }
+        "high" => {
+            let mut c = Command::new("/usr/sbin/taskpolicy");
+            c.args(["-t", "0", "-l", "0"]).arg(program);
+            c
+        }
+        other => return Err(format!("未知的进程优先级: {}", other)),
+    };
+    Ok(cmd)
+}
+
+// 设置在指定容器中运行 wine 所需的环境变量
+pub(crate) fn apply_bottle_env(cmd: &mut Command, bottle_path: &Path) -> Result<(), String> {
+    let bottle_name = bottle_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("无法解析容器名称")?;
+    cmd.env("CX_BOTTLE", bottle_name);
+    cmd.env("WINEPREFIX", bottle_path);
+    cmd.env("LC_ALL", "zh_CN.UTF-8");
+    cmd.env("WINEDEBUG", "-all");
+    Ok(())
+}
+
+// 校验并设置 WINEDEBUG 调试通道。每一项形如 [class][+/-]channel，如 +seh、warn+heap、-all
+fn apply_debug_channels(cmd: &mut Command, channels: &str) -> Result<(), String> {
+    let items: Vec<&str> = channels.split(',').map(str::trim).filter(|c| !c.is_empty()).collect();
+    for item in &items {
+        let sign = match item.find(['+', '-']) {
+            Some(i) => i,
+            None => return Err(format!("无效的调试通道: {}，应形如 +loaddll", item)),
+        };
+        let (class, channel) = (&item[..sign], &item[sign + 1..]);
+        let valid_class = matches!(class, "" | "err" | "warn" | "fixme" | "trace");
+        let valid_channel = !channel.is_empty() && channel.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_class || !valid_channel {
+            return Err(format!("无效的调试通道: {}，应形如 +loaddll", item));
+        }
+    }
+    if !items.is_empty() {
+        cmd.env("WINEDEBUG", items.join(","));
+    }
+    Ok(())
+}
+
+// 通过 CrossOver 识别的 WINEMSYNC / WINEESYNC 环境变量切换同步机制，default 时沿用容器自身设置。
+// 同一容器中 wineserver 与各进程需使用相同的设置，切换后需先结束容器内已运行的程序
+fn apply_sync_mode(cmd: &mut Command, sync_mode: Option<&str>) -> Result<(), String> {
+    let (msync, esync) = match sync_mode.unwrap_or("default") {
+        "default" | "" => return Ok(()),
+        "msync" => ("1", "0"),
+        "esync" => ("0", "1"),
+        "off" => ("0", "0"),
+        other => return Err(format!("未知的同步机制: {}", other)),
+    };
+    cmd.env("WINEMSYNC", msync);
+    cmd.env("WINEESYNC", esync);
+    Ok(())
+}
+
+// 通过 CrossOver 的环境变量选择图形后端，default 时沿用容器自身设置
+fn apply_graphics_backend(cmd: &mut Command, backend: Option<&str>) -> Result<(), String> {
+    let (d3dmetal, dxvk) = match backend.unwrap_or("default") {
+        "default" | "" => return Ok(()),
+        "d3dmetal" => ("1", "0"),
+        "dxvk" => ("0", "1"),
+        "wined3d" => ("0", "0"),
+        other => return Err(format!("未知的图形后端: {}", other)),
+    };
+    cmd.env("CX_GRAPHICS_BACKEND", backend.unwrap_or_default());
+    cmd.env("WINED3DMETAL", d3dmetal);
+    cmd.env("WINEDXVK", dxvk);
+    Ok(())
+}
+
+// 构建 CrossOver 模式的 wine 启动命令，返回 (命令, 游戏路径, 容器路径)
+pub(crate) fn build_crossover_command(config: &WineConfig) -> Result<(Command, PathBuf, PathBuf), String> {
+    let game_path = expand_tilde(&config.game_exe);
+    if !game_path.exists() {
+        return Err(format!("找不到可执行文件，可能位于外接硬盘但未连接，请检查磁盘连接情况: {:?}", config.game_exe));
+    }
+
+    // 1. 定位 CrossOver（实例可能固定了特定版本）
+    let crossover_root = resolve_crossover_root(config)?;
+    let crossover_bin = crossover_tool(&crossover_root, "wine");
+
+    if !crossover_bin.exists() {
+        return Err(format!("未找到 CrossOver 核心文件，请检查设置路径: {:?}", crossover_bin));
+    }
+
+    // 2. 构建命令
+    let bottle_path_buf = expand_tilde(&config.bottle_path);
+    // 已隔离的容器没有 Z: 盘，容器外的游戏无法访问
+    if sandbox::is_sandboxed(&bottle_path_buf) {
+        let inside = match (game_path.canonicalize(), bottle_path_buf.canonicalize()) {
+            (Ok(g), Ok(b)) => g.starts_with(b),
+            _ => false,
+        };
+        if !inside {
+            return Err("该容器已开启隔离，只能运行位于容器 drive_c 中的游戏".to_string());
+        }
+    }
+    let mut cmd = priority_command(config.priority.as_deref(), &crossover_bin)?;
+    apply_bottle_env(&mut cmd, &bottle_path_buf)?;
+    if let Some(info) = exe_info::read_pe_info(&game_path) {
+        for warning in exe_info::launch_warnings(&info, &bottle_path_buf, &crossover_root) {
+            log_warn!("{:?}: {}", game_path, warning);
+        }
+    }
+    if config.japanese_compat.unwrap_or(false) {
+        locale::apply_japanese_preset(&crossover_root, &bottle_path_buf, config.japanese_font.as_deref())?;
+        locale::apply_japanese_env(&mut cmd);
+    }
+    if config.dpi.is_some() || config.retina_mode.is_some() {
+        display::apply_display_settings(&crossover_root, &bottle_path_buf, config.dpi, config.retina_mode)?;
+    }
+    if config.audio_driver.is_some() || config.audio_buffer_size.is_some() {
+        audio::apply_audio_settings(&crossover_root, &bottle_path_buf, config.audio_driver.as_deref(), config.audio_buffer_size)?;
+    }
+    if let Some(name) = config.windows_locale.as_deref().filter(|l| !l.trim().is_empty()) {
+        locale::apply_windows_locale(&crossover_root, &bottle_path_buf, name, &mut cmd)?;
+    }
+    apply_sync_mode(&mut cmd, config.sync_mode.as_deref())?;
+    apply_graphics_backend(&mut cmd, config.graphics_backend.as_deref())?;
+    if let Some(tz) = config.timezone.as_deref().filter(|t| !t.trim().is_empty()) {
+        locale::apply_timezone(&mut cmd, tz)?;
+    }
+    if let Some(env) = config.env.as_ref() {
+        cmd.envs(env);
+    }
+    if let Some(channels) = config.debug_channels.as_deref().filter(|c| !c.trim().is_empty()) {
+        apply_debug_channels(&mut cmd, channels)?;
+    }
+
+    // 使用虚拟桌面运行，避免部分全屏游戏切换 macOS 分辨率出错
+    if let Some(desktop) = config.virtual_desktop.as_deref().filter(|v| !v.trim().is_empty()) {
+        let (w, h) = parse_resolution(desktop).ok_or(format!("无效的虚拟桌面分辨率: {}", desktop))?;
+        cmd.arg("explorer").arg(format!("/desktop=AsumiGal,{}x{}", w, h));
+    }
+    // MSI 安装包交给 msiexec 处理
+    if exe_info::is_msi(&game_path) {
+        cmd.arg("msiexec").arg("/i");
+    }
+    cmd.arg(&game_path);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    Ok((cmd, game_path, bottle_path_buf))
+}
+
+// 启动 CrossOver 模式的 wine 进程并登记为运行中，返回 (子进程, 游戏路径, 容器路径)
+pub(crate) fn spawn_crossover_session(app: &AppHandle, instance_id: &str, config: &WineConfig) -> Result<(Child, PathBuf, PathBuf), String> {
+    let (mut cmd, game_path, bottle_path_buf) = build_crossover_command(config)?;
+    // 启动前解析，失败时不会留下已登记却无人知晓的进程
+    let crossover_root = resolve_crossover_root(config)?;
+
+    let mut child = cmd.spawn().map_err(|e| format!("启动失败: {}", e))?;
+    let pid = child.id();
+    attach_log_pump(app, instance_id, &mut child, config.stream_logs.unwrap_or(false));
+    if let Some(channels) = config.debug_channels.as_deref().filter(|c| !c.trim().is_empty()) {
+        // 调试输出量较大，只写入日志文件，排查完成后应关闭
+        log_info!("实例 {} 已开启调试通道 {}，输出写入 {:?}", instance_id, channels, game_log_path(app, instance_id).unwrap_or_default());
+    }
+    let exe_for_track = game_path.to_string_lossy().to_string();
+    track_running_instance(app, instance_id, pid, RUN_MODE_CROSSOVER, &exe_for_track, !config.dry_run_active.unwrap_or(false));
+
+    let boot_context = benchmark::BootContext::collect(&crossover_root, &bottle_path_buf, config.virtual_desktop.clone());
+    benchmark::spawn_boot_probe(app.clone(), instance_id.to_string(), pid, boot_context);
+    if !config.dry_run_active.unwrap_or(false) {
+        spawn_stats_monitor(app.clone(), instance_id.to_string(), pid);
+    }
+    Ok((child, game_path, bottle_path_buf))
+}
+
+#[derive(serde::Serialize, Clone)]
+pub struct LaunchResponse {
+    // 启动器进程 PID，排队时为 0
+    pub pid: u32,
+    pub queued: bool,
+    // 根据上次退出结果给出的建议，如 "上次启动时崩溃，可尝试开启虚拟桌面"
+    pub advice: Option<String>,
+}
+
 #[command]
-pub async fn launch_game(app: AppHandle, instance_id: String, config: WineConfig) -> Result<u32, String> {
-    println!("正在启动实例 ID: {}, 路径: {}", instance_id, config.game_exe);
-    let mode = config.run_mode.as_deref().unwrap_or("crossover");
+pub async fn launch_game(app: AppHandle, instance_id: String, mut config: WineConfig) -> Result<LaunchResponse, String> {
+    profiles::apply_profile(&app, &mut config)?;
+    let advice = last_exit::load(&app, &instance_id).and_then(|last| last_exit::advice_for(&last, &config));
+    let pid = start_game(app, instance_id, config).await?;
+    Ok(LaunchResponse { pid, queued: pid == 0, advice })
+}
+
+async fn start_game(app: AppHandle, instance_id: String, config: WineConfig) -> Result<u32, String> {
+    log_info!("正在启动实例 ID: {}, 路径: {}", instance_id, config.game_exe);
+    let mode = config.run_mode.as_deref().unwrap_or(RUN_MODE_CROSSOVER);
+    let post_session = PostSessionConfig::from_config(&config);
+    ensure_not_running(&instance_id)?;
 
-    if mode == "parallels" {
+    // 已达到同时运行上限时加入队列，返回 0 表示尚未启动
+    if launch_queue::should_queue(&app) {
+        launch_queue::enqueue(&app, &instance_id, config)?;
+        return Ok(0);
+    }
+
+    if mode == RUN_MODE_PARALLELS {
         let vm_app_path = expand_tilde(&config.bottle_path).join("文件资源管理器.app");
         if !vm_app_path.exists() {
             return Err(format!("找不到 Parallels 虚拟机路径: {:?}", vm_app_path));
@@ -457,11 +1284,12 @@ pub async fn launch_game(app: AppHandle, instance_id: String, config: WineConfig
 
         let pid = child.id();
         let exe_for_track = expand_tilde(&config.game_exe).to_string_lossy().to_string();
-        track_running_instance(&instance_id, pid, "parallels", &exe_for_track);
+        track_running_instance(&app, &instance_id, pid, RUN_MODE_PARALLELS, &exe_for_track, !config.dry_run_active.unwrap_or(false));
 
         if !config.dry_run_active.unwrap_or(false) {
             let app_handle = app.clone();
             let i_id = instance_id.clone();
+            let post = post_session.clone();
 
             thread::spawn(move || {
                 let start_time = Instant::now();
@@ -520,21 +1348,17 @@ pub async fn launch_game(app: AppHandle, instance_id: String, config: WineConfig
                 }
 
                 let duration = start_time.elapsed().as_secs();
-                println!("游戏 {} 已退出，总时长: {}秒", i_id, duration);
-                remove_running_instance(&i_id);
-                let _ = app_handle.emit("game-finished", GameFinishedPayload {
-                    instance_id: i_id,
-                    duration_sec: duration
-                });
+                log_info!("游戏 {} 已退出，总时长: {}秒", i_id, duration);
+                finish_session(&app_handle, i_id, duration, None, &post);
             });
         }
 
         return Ok(pid);
     }
-    else if mode == "direct" {
+    else if mode == RUN_MODE_DIRECT {
         // 如果 bottle_path 不为空且不是 "Default"，则说明指定了前置执行脚本
         if !config.bottle_path.is_empty() && config.bottle_path != "Default" {
-            let script_dir = app.path().resolve("scripts", tauri::path::BaseDirectory::AppLocalData).unwrap();
+            let script_dir = storage::resolve_data_path(&app, "scripts")?;
             let script_path = script_dir.join(format!("{}.sh", config.bottle_path));
             if script_path.exists() {
                 let log_path = "/tmp/asumigal_script.log";
@@ -547,13 +1371,13 @@ pub async fn launch_game(app: AppHandle, instance_id: String, config: WineConfig
                 {
                     Ok(status) => {
                         if !status.success() {
-                            println!("脚本 {:?} 执行失败，退出码: {:?}", script_path, status.code());
+                            log_warn!("脚本 {:?} 执行失败，退出码: {:?}", script_path, status.code());
                         } else {
-                            println!("脚本 {:?} 执行成功", script_path);
+                            log_info!("脚本 {:?} 执行成功", script_path);
                         }
                     }
                     Err(e) => {
-                        println!("无法执行脚本 {:?}: {}", script_path, e);
+                        log_warn!("无法执行脚本 {:?}: {}", script_path, e);
                     }
                 }
             }
@@ -572,103 +1396,209 @@ pub async fn launch_game(app: AppHandle, instance_id: String, config: WineConfig
 
         let pid = child.id();
         let exe_for_track = app_path.to_string_lossy().to_string();
-        track_running_instance(&instance_id, pid, "direct", &exe_for_track);
+        track_running_instance(&app, &instance_id, pid, RUN_MODE_DIRECT, &exe_for_track, !config.dry_run_active.unwrap_or(false));
 
         if !config.dry_run_active.unwrap_or(false) {
             let app_handle = app.clone();
             let i_id = instance_id.clone();
+            let post = post_session.clone();
 
             thread::spawn(move || {
                 let start_time = Instant::now();
-                let _ = child.wait();
+                let status = child.wait().ok();
                 let duration = start_time.elapsed().as_secs();
-                println!("游戏 {} 已退出，总时长: {}秒", i_id, duration);
-                remove_running_instance(&i_id);
-                let _ = app_handle.emit("game-finished", GameFinishedPayload {
-                    instance_id: i_id,
-                    duration_sec: duration,
-                });
+                log_info!("游戏 {} 已退出，总时长: {}秒", i_id, duration);
+                finish_session(&app_handle, i_id, duration, status, &post);
             });
         }
 
         return Ok(pid);
     }
+    else if mode == RUN_MODE_NATIVE {
+        // 不经过 Wine，直接用 macOS 原生的引擎运行时启动
+        let (mut cmd, game_dir, engine) = native_runner::build_native_command(&app, &config)?;
+        log_info!("使用原生 {} 运行时启动实例 {}", engine, instance_id);
 
-    let game_path = expand_tilde(&config.game_exe);
-    if !game_path.exists() {
-        return Err(format!("找不到可执行文件，可能位于外接硬盘但未连接，请检查磁盘连接情况: {:?}", config.game_exe));
-    }
+        let mut child = cmd.spawn().map_err(|e| format!("启动失败: {}", e))?;
+        let pid = child.id();
+        attach_log_pump(&app, &instance_id, &mut child, config.stream_logs.unwrap_or(false));
+        let exe_for_track = game_dir.to_string_lossy().to_string();
+        track_running_instance(&app, &instance_id, pid, RUN_MODE_NATIVE, &exe_for_track, !config.dry_run_active.unwrap_or(false));
 
-    // 1. 定位 CrossOver
-    let crossover_app_dir = expand_tilde(&config.crossover_app_path);
-    let crossover_bin = crossover_app_dir.join("Contents/SharedSupport/CrossOver/bin/wine");
+        if !config.dry_run_active.unwrap_or(false) {
+            spawn_stats_monitor(app.clone(), instance_id.clone(), pid);
+            let app_handle = app.clone();
+            let i_id = instance_id.clone();
+            let post = post_session.clone();
 
-    if !crossover_bin.exists() {
-        return Err(format!("未找到 CrossOver 核心文件，请检查设置路径: {:?}", crossover_bin));
-    }
+            thread::spawn(move || {
+                let start_time = Instant::now();
+                let status = child.wait().ok();
+                let duration = start_time.elapsed().as_secs();
+                log_info!("游戏 {} 已退出，总时长: {}秒", i_id, duration);
+                finish_session(&app_handle, i_id, duration, status, &post);
+            });
+        }
 
-    // 2. 解析容器名
-    let bottle_path_buf = expand_tilde(&config.bottle_path);
-    let bottle_name = bottle_path_buf
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or("无法解析容器名称")?;
+        return Ok(pid);
+    }
 
-    // 3. 构建命令
-    let mut cmd = Command::new(&crossover_bin);
-    cmd.env("CX_BOTTLE", bottle_name);
-    cmd.env("WINEPREFIX", &bottle_path_buf);
-    cmd.env("LC_ALL", "zh_CN.UTF-8");
-    cmd.env("WINEDEBUG", "-all");
-    cmd.arg(&game_path);
+    // 先运行启动链中的程序，等待期间不计时
+    if !config.dry_run_active.unwrap_or(false) && config.pre_launch.as_ref().is_some_and(|s| !s.is_empty()) {
+        let (chain_app, chain_id, chain_config) = (app.clone(), instance_id.clone(), config.clone());
+        tokio::task::spawn_blocking(move || launch_chain::run_chain(&chain_app, &chain_id, &chain_config))
+            .await
+            .map_err(|e| e.to_string())??;
+    }
 
-    // 4. 启动子进程
-    let mut child = cmd.spawn().map_err(|e| format!("启动失败: {}", e))?;
+    let (mut child, game_path, bottle_path_buf) = spawn_crossover_session(&app, &instance_id, &config)?;
     let pid = child.id();
-    let exe_for_track = game_path.to_string_lossy().to_string();
-    track_running_instance(&instance_id, pid, "crossover", &exe_for_track);
-    
+    runner_version::record_in_background(&app, &instance_id, &config);
+
     if !config.dry_run_active.unwrap_or(false) {
         let app_handle = app.clone();
         let i_id = instance_id.clone();
-        
+        let post = post_session.clone();
+        let fix_base = config.auto_fix.unwrap_or(false).then(|| config.clone());
+
+        let bottle_for_wait = bottle_path_buf.clone();
+        let exe_for_wait = game_path.clone();
+
         thread::spawn(move || {
-            let start_time = Instant::now();
-            match child.wait() {
-                Ok(status) => {
-                    let duration = start_time.elapsed().as_secs();
-                    println!("游戏 {} 已退出，状态: {}, 时长: {}秒", i_id, status, duration);
-                    remove_running_instance(&i_id);
-                    let _ = app_handle.emit("game-finished", GameFinishedPayload {
-                        instance_id: i_id,
-                        duration_sec: duration
-                    });
-                }
-                Err(e) => println!("等待进程失败: {}", e),
+            let (mut status, mut duration) = wait_for_wine_tree(&mut child, &exe_for_wait, &bottle_for_wait);
+            if let Some(base) = fix_base {
+                (status, duration) = autofix::run_ladder(&app_handle, &i_id, &base, status, duration);
             }
+            log_info!("游戏 {} 已退出，启动器状态: {:?}, 时长: {}秒", i_id, status, duration);
+            finish_session(&app_handle, i_id, duration, status, &post);
         });
     }
 
     Ok(pid)
 }
 
+#[derive(serde::Serialize, Clone)]
+struct GamePausedPayload {
+    instance_id: String,
+    paused: bool,
+    pids: Vec<u32>,
+}
+
+// 实例当前的进程树：启动器的子孙进程，加上启动器退出后被 launchd 接管的同名游戏进程
+fn instance_process_tree(info: &RunningInstance) -> Result<Vec<u32>, String> {
+    let processes = list_processes()?;
+    let children_map = build_children_map(&processes);
+    let mut pids: HashSet<u32> = collect_descendants(info.launcher_pid, &children_map).into_iter().collect();
+    if processes.iter().any(|p| p.pid == info.launcher_pid) {
+        pids.insert(info.launcher_pid);
+    }
+
+    let exe_path = Path::new(&info.game_exe);
+    if info.run_mode == RUN_MODE_DIRECT {
+        for p in processes.iter().filter(|p| p.command.contains(&info.game_exe)) {
+            pids.insert(p.pid);
+        }
+    } else if let Some(exe_name) = exe_path.file_name().map(|n| n.to_string_lossy().to_lowercase()) {
+        for p in &processes {
+            if looks_like_windows_game_process(&p.command) && normalize_windows_path_for_match(&p.command).contains(&exe_name) {
+                pids.insert(p.pid);
+                pids.extend(collect_descendants(p.pid, &children_map));
+            }
+        }
+    }
+
+    // wineserver 被所有 wine 进程共用，暂停它会卡住同一容器中的其他程序
+    let mut pids: Vec<u32> = pids
+        .into_iter()
+        .filter(|pid| {
+            processes
+                .iter()
+                .find(|p| p.pid == *pid)
+                .map(|p| !p.command.to_lowercase().contains("wineserver"))
+                .unwrap_or(false)
+        })
+        .collect();
+    pids.sort_unstable();
+    Ok(pids)
+}
+
+fn set_instance_paused(app: &AppHandle, instance_id: &str, pause: bool) -> Result<Vec<u32>, String> {
+    let info = get_tracked_instance(instance_id).ok_or("该游戏当前未在运行")?;
+    if info.run_mode == RUN_MODE_PARALLELS {
+        return Err("Parallels 模式暂不支持暂停".to_string());
+    }
+    if info.paused_at.is_some() == pause {
+        return Err(if pause { "游戏已处于暂停状态" } else { "游戏未处于暂停状态" }.to_string());
+    }
+
+    let pids = instance_process_tree(&info)?;
+    if pids.is_empty() {
+        return Err("未找到游戏进程".to_string());
+    }
+    let signal = if pause { "-STOP" } else { "-CONT" };
+    let signaled: Vec<u32> = pids.into_iter().filter(|pid| send_signal(*pid, signal)).collect();
+
+    if let Ok(mut map) = running_instances().lock() {
+        if let Some(entry) = map.get_mut(instance_id) {
+            let now = unix_now();
+            if pause {
+                entry.paused_at = Some(now);
+            } else if let Some(t) = entry.paused_at.take() {
+                entry.paused_secs += now.saturating_sub(t);
+            }
+        }
+    }
+
+    let _ = app.emit("game-paused", GamePausedPayload {
+        instance_id: instance_id.to_string(),
+        paused: pause,
+        pids: signaled.clone(),
+    });
+    Ok(signaled)
+}
+
+#[command]
+pub fn pause_game(app: AppHandle, instance_id: String) -> Result<Vec<u32>, String> {
+    set_instance_paused(&app, &instance_id, true)
+}
+
+#[command]
+pub fn resume_game(app: AppHandle, instance_id: String) -> Result<Vec<u32>, String> {
+    set_instance_paused(&app, &instance_id, false)
+}
+
+#[command]
+pub fn get_running_games() -> Vec<RunningInstance> {
+    running_instances()
+        .lock()
+        .map(|map| map.values().cloned().collect())
+        .unwrap_or_default()
+}
+
 #[command]
 pub async fn stop_game(instance_id: String, config: WineConfig) -> Result<Vec<u32>, String> {
-    let mode = config.run_mode.as_deref().unwrap_or("crossover");
-    if mode == "parallels" {
+    let mode = config.run_mode.as_deref().unwrap_or(RUN_MODE_CROSSOVER);
+    if mode == RUN_MODE_PARALLELS {
         return Err("Parallels 模式暂不支持停止实例".to_string());
     }
 
     let tracked = get_tracked_instance(&instance_id);
     if let Some(info) = tracked.as_ref() {
         if info.run_mode != mode {
-            println!(
+            log_info!(
                 "停止实例模式与记录不一致: tracked={}, requested={}, instance={}",
                 info.run_mode, mode, instance_id
             );
         }
     }
 
+    // 被暂停的进程收不到 TERM 的处理机会，先恢复再终止
+    if let Some(info) = tracked.as_ref().filter(|i| i.paused_at.is_some()) {
+        for pid in instance_process_tree(info).unwrap_or_default() {
+            send_signal(pid, "-CONT");
+        }
+    }
+
     let processes = list_processes()?;
     let launcher_pid = tracked.as_ref().map(|i| i.launcher_pid);
     let exe_path = tracked
@@ -676,8 +1606,10 @@ pub async fn stop_game(instance_id: String, config: WineConfig) -> Result<Vec<u3
         .map(|i| PathBuf::from(&i.game_exe))
         .unwrap_or_else(|| expand_tilde(&config.game_exe));
 
-    let killed = if mode == "direct" {
+    let killed = if mode == RUN_MODE_DIRECT {
         stop_direct_instance(launcher_pid, &exe_path, &processes)?
+    } else if mode == RUN_MODE_NATIVE {
+        native_runner::stop_native_instance(launcher_pid, &processes)?
     } else {
         let bottle_path = expand_tilde(&config.bottle_path);
         stop_crossover_instance(launcher_pid, &exe_path, &bottle_path, &processes)?
@@ -0,0 +1,96 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::bottle;
+use crate::runner::{self, WineConfig};
+use crate::storage;
+
+// 每个实例最近一次运行所用的 wine 版本，供问题反馈与排错界面展示
+const RUNNER_VERSIONS_FILE: &str = "runner_versions.json";
+
+// 多个游戏同时启动时避免互相覆盖写入
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerVersion {
+    backend: String,
+    // wine --version 的输出，如 "wine-8.0.1 (CrossOver FOSS 23.7.1)"
+    wine_version: Option<String>,
+    crossover_version: Option<String>,
+    // 运行时可执行文件的路径，便于区分同时安装的多个 CrossOver
+    runner_path: String,
+    checked_at: u64,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn detect_runner_version(config: &WineConfig) -> Result<RunnerVersion, String> {
+    let backend = config.run_mode.clone().unwrap_or_else(|| "crossover".to_string());
+    match backend.as_str() {
+        "crossover" => {
+            let root = runner::resolve_crossover_root(config)?;
+            Ok(RunnerVersion {
+                wine_version: bottle::read_wine_version(&root),
+                crossover_version: runner::read_crossover_version(&root),
+                runner_path: runner::crossover_tool(&root, "wine").to_string_lossy().to_string(),
+                backend,
+                checked_at: unix_now(),
+            })
+        }
+        // native / parallels / direct 模式都不经过 wine
+        other => Err(format!("{} 模式不经过 wine，没有运行时版本", other)),
+    }
+}
+
+fn read_store(app: &AppHandle) -> HashMap<String, RunnerVersion> {
+    storage::resolve_data_path(app, RUNNER_VERSIONS_FILE)
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn store_runner_version(app: &AppHandle, instance_id: &str, version: RunnerVersion) -> Result<(), String> {
+    storage::check_instance_id(instance_id)?;
+    let _guard = STORE_LOCK.lock().map_err(|_| "版本记录不可用".to_string())?;
+    let mut store = read_store(app);
+    store.insert(instance_id.to_string(), version);
+    let text = serde_json::to_string_pretty(&store).map_err(|e| e.to_string())?;
+    fs::write(storage::resolve_data_path(app, RUNNER_VERSIONS_FILE)?, text).map_err(|e| format!("保存运行时版本失败: {}", e))
+}
+
+// 启动时在后台记录本次使用的运行时版本，不拖慢启动
+pub fn record_in_background(app: &AppHandle, instance_id: &str, config: &WineConfig) {
+    let (app, instance_id, config) = (app.clone(), instance_id.to_string(), config.clone());
+    std::thread::spawn(move || {
+        let result = detect_runner_version(&config).and_then(|v| store_runner_version(&app, &instance_id, v));
+        if let Err(e) = result {
+            log_warn!("记录实例 {} 的运行时版本失败: {}", instance_id, e);
+        }
+    });
+}
+
+// 执行所选后端的 wine --version；传入 instance_id 时同时保存到该实例的记录
+#[command]
+pub async fn get_runner_version(app: AppHandle, config: WineConfig, instance_id: Option<String>) -> Result<RunnerVersion, String> {
+    tokio::task::spawn_blocking(move || {
+        let version = detect_runner_version(&config)?;
+        if let Some(id) = instance_id {
+            store_runner_version(&app, &id, version.clone())?;
+        }
+        Ok(version)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[command]
+pub fn get_instance_runner_versions(app: AppHandle) -> HashMap<String, RunnerVersion> {
+    read_store(&app)
+}
@@ -0,0 +1,177 @@
+use tauri::command;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+use crate::runner::expand_tilde;
+
+// 隔离时移除的符号链接记录在容器内，取消隔离时据此恢复
+const SANDBOX_STATE_FILE: &str = ".asumigal_sandbox.json";
+// wine 发现该文件内容与自身版本不一致时会运行 wineboot 重新创建 Z: 盘与家目录链接，写入 disable 可阻止
+const UPDATE_TIMESTAMP_FILE: &str = ".update-timestamp";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemovedLink {
+    // 相对容器根目录的路径
+    path: String,
+    target: PathBuf,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SandboxState {
+    links: Vec<RemovedLink>,
+    update_timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SandboxStatus {
+    enabled: bool,
+    // 仍指向容器外部的盘符与目录链接
+    exposed: Vec<String>,
+}
+
+fn canonical_bottle(bottle_path: &str) -> Result<PathBuf, String> {
+    let bottle = expand_tilde(bottle_path);
+    if !bottle.join("drive_c").is_dir() {
+        return Err(format!("不是有效的容器: {:?}", bottle));
+    }
+    bottle.canonicalize().map_err(|e| format!("容器无法访问: {}", e))
+}
+
+// 指向容器外部的链接；/dev 下的设备（COM 口等）与失效链接不算
+fn points_outside(link: &Path, bottle: &Path) -> bool {
+    match link.canonicalize() {
+        Ok(target) => !target.starts_with(bottle) && !target.starts_with("/dev"),
+        Err(_) => false,
+    }
+}
+
+// 列出 dosdevices 中除 C: 外的盘符，以及 drive_c/users/<用户> 下指向 macOS 家目录的链接
+fn find_outside_links(bottle: &Path) -> Vec<PathBuf> {
+    let mut links = Vec::new();
+    let mut scan = |dir: &Path, skip: &[&str]| {
+        let entries = match fs::read_dir(dir) {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            let is_link = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+            if is_link && !skip.contains(&name.as_str()) && points_outside(&path, bottle) {
+                links.push(path);
+            }
+        }
+    };
+
+    scan(&bottle.join("dosdevices"), &["c:"]);
+    if let Ok(users) = fs::read_dir(bottle.join("drive_c/users")) {
+        for user in users.flatten() {
+            if user.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                scan(&user.path(), &[]);
+            }
+        }
+    }
+    links
+}
+
+fn state_path(bottle: &Path) -> PathBuf {
+    bottle.join(SANDBOX_STATE_FILE)
+}
+
+fn read_state(bottle: &Path) -> Option<SandboxState> {
+    let text = fs::read_to_string(state_path(bottle)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn status_of(bottle: &Path) -> SandboxStatus {
+    let exposed = find_outside_links(bottle)
+        .iter()
+        .filter_map(|p| p.strip_prefix(bottle).ok().map(|r| r.to_string_lossy().to_string()))
+        .collect();
+    SandboxStatus { enabled: state_path(bottle).exists(), exposed }
+}
+
+pub(crate) fn is_sandboxed(bottle_path: &Path) -> bool {
+    state_path(bottle_path).exists()
+}
+
+fn enable(bottle: &Path) -> Result<(), String> {
+    // 已隔离时合并记录，wine 重新创建的链接也一并移除
+    let mut state = read_state(bottle).unwrap_or_default();
+    let first_time = !state_path(bottle).exists();
+
+    for link in find_outside_links(bottle) {
+        let target = fs::read_link(&link).map_err(|e| e.to_string())?;
+        let relative = match link.strip_prefix(bottle) {
+            Ok(r) => r.to_string_lossy().to_string(),
+            Err(_) => continue,
+        };
+        fs::remove_file(&link).map_err(|e| format!("移除链接 {} 失败: {}", relative, e))?;
+        // 家目录链接替换为空目录，游戏写入的文档、存档留在容器内
+        if !relative.starts_with("dosdevices") {
+            fs::create_dir_all(&link).map_err(|e| e.to_string())?;
+        }
+        log_info!("已移除容器外链接 {} -> {:?}", relative, target);
+        state.links.push(RemovedLink { path: relative, target });
+    }
+
+    let timestamp = bottle.join(UPDATE_TIMESTAMP_FILE);
+    if first_time {
+        state.update_timestamp = fs::read_to_string(&timestamp).ok();
+    }
+    fs::write(&timestamp, "disable\n").map_err(|e| e.to_string())?;
+
+    let text = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+    fs::write(state_path(bottle), text).map_err(|e| format!("保存隔离记录失败: {}", e))
+}
+
+fn disable(bottle: &Path) -> Result<(), String> {
+    let state = match read_state(bottle) {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+
+    for link in state.links.iter().rev() {
+        let path = bottle.join(&link.path);
+        if path.is_dir() && fs::remove_dir(&path).is_err() {
+            // 隔离期间写入了文件，保留目录以免丢失存档
+            log_warn!("{} 中已有文件，保留该目录而不恢复链接", link.path);
+            continue;
+        }
+        if path.symlink_metadata().is_ok() {
+            continue;
+        }
+        symlink(&link.target, &path).map_err(|e| format!("恢复链接 {} 失败: {}", link.path, e))?;
+    }
+
+    let timestamp = bottle.join(UPDATE_TIMESTAMP_FILE);
+    match &state.update_timestamp {
+        Some(content) => fs::write(&timestamp, content).map_err(|e| e.to_string())?,
+        None => {
+            let _ = fs::remove_file(&timestamp);
+        }
+    }
+    fs::remove_file(state_path(bottle)).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_bottle_sandbox(bottle_path: String) -> Result<SandboxStatus, String> {
+    Ok(status_of(&canonical_bottle(&bottle_path)?))
+}
+
+// 隔离容器：移除 Z: 等指向 macOS 文件系统的盘符与家目录链接，
+// 使容器内的程序无法读写容器之外的文件（与 winetricks sandbox 相同）。
+// 隔离后游戏本体也必须位于容器的 drive_c 中
+#[command]
+pub fn set_bottle_sandbox(bottle_path: String, enabled: bool) -> Result<SandboxStatus, String> {
+    let bottle = canonical_bottle(&bottle_path)?;
+    if enabled {
+        enable(&bottle)?;
+    } else {
+        disable(&bottle)?;
+    }
+    log_info!("容器 {:?} 隔离: {}", bottle, enabled);
+    Ok(status_of(&bottle))
+}
@@ -0,0 +1,201 @@
+use tauri::{AppHandle, command};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::instance::GameInstance;
+use crate::notes;
+use crate::storage;
+
+// 游戏库搜索：标题、别名、标签、开发商、简介与笔记。
+// 中日文没有空格分词，查询按二元组（相邻两个字）匹配；拉丁文字按单词匹配
+const MAX_RESULTS: usize = 100;
+const SNIPPET_BEFORE: usize = 20;
+const SNIPPET_AFTER: usize = 40;
+
+// 各字段命中时的权重
+const FIELDS: [(&str, u32); 6] = [("title", 10), ("aliases", 8), ("tags", 6), ("developer", 5), ("info", 2), ("notes", 1)];
+
+// 笔记路径 -> (修改时间, 原文)，笔记未变化时不重复读取
+static NOTES_CACHE: OnceLock<Mutex<HashMap<PathBuf, (SystemTime, String)>>> = OnceLock::new();
+
+fn notes_cache() -> &'static Mutex<HashMap<PathBuf, (SystemTime, String)>> {
+    NOTES_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    instance: GameInstance,
+    score: u32,
+    // 命中的字段
+    matched: Vec<String>,
+    // 命中笔记时的上下文片段
+    snippet: Option<String>,
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // 平假名、片假名
+        | 0x3400..=0x4DBF   // CJK 扩展 A
+        | 0x4E00..=0x9FFF   // CJK 统一汉字
+        | 0xAC00..=0xD7AF   // 谚文
+        | 0xF900..=0xFAFF)  // CJK 兼容汉字
+}
+
+// 逐字符归一化，输出与输入字符一一对应，便于按位置截取原文片段：
+// 小写、全角英数转半角、片假名转平假名
+fn normalize(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| {
+            let c = match c as u32 {
+                0xFF01..=0xFF5E => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+                0x30A1..=0x30F6 => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+                0x3000 => ' ',
+                _ => c,
+            };
+            c.to_lowercase().next().unwrap_or(c)
+        })
+        .collect()
+}
+
+// 查询分词：拉丁文字按非字母数字切分；连续的中日韩文字拆为二元组，单个字保留原样
+fn tokenize(query: &str) -> Vec<Vec<char>> {
+    let mut tokens: Vec<Vec<char>> = Vec::new();
+    let mut word: Vec<char> = Vec::new();
+    let mut cjk: Vec<char> = Vec::new();
+    let flush_cjk = |cjk: &mut Vec<char>, tokens: &mut Vec<Vec<char>>| {
+        match cjk.len() {
+            0 => {}
+            1 => tokens.push(cjk.clone()),
+            _ => tokens.extend(cjk.windows(2).map(|w| w.to_vec())),
+        }
+        cjk.clear();
+    };
+    for c in normalize(query) {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            cjk.push(c);
+        } else if c.is_alphanumeric() {
+            flush_cjk(&mut cjk, &mut tokens);
+            word.push(c);
+        } else {
+            flush_cjk(&mut cjk, &mut tokens);
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+        }
+    }
+    flush_cjk(&mut cjk, &mut tokens);
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens.sort();
+    tokens.dedup();
+    tokens
+}
+
+fn find(haystack: &[char], needle: &[char]) -> Option<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn read_note(path: PathBuf) -> String {
+    let modified = match fs::metadata(&path).and_then(|m| m.modified()) {
+        Ok(t) => t,
+        Err(_) => return String::new(),
+    };
+    if let Ok(cache) = notes_cache().lock() {
+        if let Some((cached_at, text)) = cache.get(&path) {
+            if *cached_at == modified {
+                return text.clone();
+            }
+        }
+    }
+    let text = storage::read_data_file(&path).unwrap_or_default();
+    if let Ok(mut cache) = notes_cache().lock() {
+        cache.insert(path, (modified, text.clone()));
+    }
+    text
+}
+
+fn extra_text(inst: &GameInstance, key: &str) -> String {
+    match inst.extra.get(key) {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(list)) => list.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(" "),
+        _ => String::new(),
+    }
+}
+
+fn snippet(text: &str, pos: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let start = pos.saturating_sub(SNIPPET_BEFORE);
+    let end = (pos + SNIPPET_AFTER).min(chars.len());
+    let body: String = chars[start..end].iter().map(|c| if c.is_whitespace() { ' ' } else { *c }).collect();
+    format!("{}{}{}", if start > 0 { "…" } else { "" }, body.trim(), if end < chars.len() { "…" } else { "" })
+}
+
+fn score_instance(inst: GameInstance, tokens: &[Vec<char>], query: &[char], notes_dir: &Path) -> Option<SearchHit> {
+    let note = read_note(notes_dir.join(format!("{}.md", inst.id)));
+    let texts = [
+        inst.name.clone(),
+        extra_text(&inst, "aliases"),
+        inst.tags.join(" "),
+        extra_text(&inst, "developer"),
+        inst.info.clone(),
+        note,
+    ];
+    let normalized: Vec<Vec<char>> = texts.iter().map(|t| normalize(t)).collect();
+
+    let mut score = 0;
+    let mut matched = Vec::new();
+    let mut snippet_text = None;
+    // 每个查询词都必须在某个字段中出现
+    for token in tokens {
+        let mut found = false;
+        for (i, (field, weight)) in FIELDS.iter().enumerate() {
+            if let Some(pos) = find(&normalized[i], token) {
+                found = true;
+                score += weight;
+                if !matched.iter().any(|m| m == field) {
+                    matched.push(field.to_string());
+                }
+                if *field == "notes" && snippet_text.is_none() {
+                    snippet_text = Some(snippet(&texts[i], pos));
+                }
+            }
+        }
+        if !found {
+            return None;
+        }
+    }
+    // 标题以完整查询开头时排在最前
+    if normalized[0].starts_with(query) {
+        score += 50;
+    }
+    Some(SearchHit { instance: inst, score, matched, snippet: snippet_text })
+}
+
+#[command]
+pub fn search_library(app: AppHandle, query: String) -> Result<Vec<SearchHit>, String> {
+    let tokens = tokenize(&query);
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+    let query: Vec<char> = normalize(query.trim());
+    let notes_dir = storage::resolve_data_path(&app, notes::NOTES_DIR)?;
+
+    let mut hits: Vec<SearchHit> = storage::read_instances(&app)
+        .into_iter()
+        .filter_map(|inst| score_instance(inst, &tokens, &query, &notes_dir))
+        .collect();
+    hits.sort_by_key(|h| std::cmp::Reverse((h.score, h.instance.last_played)));
+    hits.truncate(MAX_RESULTS);
+    Ok(hits)
+}
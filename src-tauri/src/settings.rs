@@ -0,0 +1,119 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::libraries;
+use crate::storage;
+
+// 后端配置文件名（前端外观配置仍保存在 localStorage 中）
+const SETTINGS_FILENAME: &str = "settings.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    // 是否参与社区兼容性报告（默认关闭）
+    pub compat_reports_enabled: bool,
+    // 兼容性报告服务地址
+    pub compat_endpoint: String,
+    // 资讯关注词（命中后高亮）与屏蔽词（命中后隐藏）
+    pub news_include_keywords: Vec<String>,
+    pub news_exclude_keywords: Vec<String>,
+    // 界面字体，由后端在窗口创建时注入，空字符串表示系统默认字体
+    pub ui_font: String,
+    // 已登录的 Ymgal 账号名，令牌本身保存在钥匙串中
+    pub ymgal_account: String,
+    // 各搜索源的标题语言优先级，如 { "kungal": ["ja-jp", "zh-cn"] }，未配置时使用默认顺序
+    pub title_languages: BTreeMap<String, Vec<String>>,
+    // 同时运行的游戏数量上限，超出后进入启动队列，0 表示不限制
+    pub max_concurrent_games: u32,
+    // 退出应用时仍有游戏运行的处理方式: ask（默认，询问）/ terminate（结束游戏）/ detach（保留游戏，会话按未知结束时间记录）
+    pub quit_behavior: String,
+    // WebDAV 同步（坚果云、Nextcloud 等），密码保存在钥匙串中
+    pub sync_enabled: bool,
+    pub sync_webdav_url: String,
+    pub sync_webdav_user: String,
+    // 加密保存游戏库（instances.json、其备份与变更记录），数据库中只保留不含名称与路径的字段；
+    // 游玩时长、WebDAV 同步与导出的文件不加密，密钥保存在钥匙串中
+    pub encrypt_library: bool,
+}
+
+// KunGal 默认的标题语言回退顺序
+const DEFAULT_TITLE_LANGUAGES: [&str; 4] = ["zh-cn", "zh-tw", "ja-jp", "en-us"];
+
+impl AppSettings {
+    pub fn title_languages_for(&self, source: &str) -> Vec<String> {
+        match self.title_languages.get(source) {
+            Some(list) if !list.is_empty() => list.clone(),
+            _ => DEFAULT_TITLE_LANGUAGES.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+}
+
+// settings.json 中的配置，不含游戏库的覆盖
+fn load_base_settings(app: &AppHandle) -> AppSettings {
+    let path = match storage::resolve_data_path(app, SETTINGS_FILENAME) {
+        Ok(p) => p,
+        Err(_) => return AppSettings::default(),
+    };
+
+    match storage::read_data_file(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+            log_warn!("后端配置解析失败，使用默认配置: {}", e);
+            AppSettings::default()
+        }),
+        Err(_) => AppSettings::default(),
+    }
+}
+
+// 读取后端配置，文件不存在或损坏时回退为默认值；当前游戏库的设置覆盖同名配置
+pub fn load_settings(app: &AppHandle) -> AppSettings {
+    let base = load_base_settings(app);
+    let overrides = libraries::active_overrides(app);
+    if overrides.is_empty() {
+        return base;
+    }
+    let mut value = serde_json::to_value(&base).unwrap_or(Value::Null);
+    if let Some(obj) = value.as_object_mut() {
+        obj.extend(overrides);
+    }
+    serde_json::from_value(value).unwrap_or_else(|e| {
+        log_warn!("游戏库的设置覆盖无效，已忽略: {}", e);
+        base
+    })
+}
+
+// 被当前游戏库覆盖的配置项写回覆盖表，settings.json 中保持原值
+pub fn write_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    storage::with_data_dir(|| {
+        let path = storage::resolve_data_path(app, SETTINGS_FILENAME)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let mut value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+        let overrides = libraries::active_overrides(app);
+        if let Some(obj) = value.as_object_mut().filter(|_| !overrides.is_empty()) {
+            libraries::update_active_overrides(app, obj)?;
+            let base = serde_json::to_value(load_base_settings(app)).map_err(|e| e.to_string())?;
+            for key in overrides.keys() {
+                if let Some(original) = base.get(key) {
+                    obj.insert(key.clone(), original.clone());
+                }
+            }
+        }
+        let text = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+        storage::write_atomic(&path, text.as_bytes()).map_err(|e| format!("无法写入配置: {}", e))
+    })
+}
+
+#[command]
+pub fn get_app_settings(app: AppHandle) -> AppSettings {
+    load_settings(&app)
+}
+
+#[command]
+pub fn save_app_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
+    write_settings(&app, &settings)
+}
@@ -0,0 +1,257 @@
+use tauri::{AppHandle, Emitter, ExitRequestApi, command};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::runner;
+use crate::settings;
+use crate::storage;
+
+// 退出应用时仍在运行的会话保存在 open_sessions/<instance_id>.json，
+// 同时启动一个独立的 sh 追踪进程，每隔几秒 touch <instance_id>.alive，游戏结束后停止
+const OPEN_SESSIONS_DIR: &str = "open_sessions";
+const TRACKER_INTERVAL_SEC: u64 = 5;
+const RECOVERY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+// 退出前结束游戏时，等待进程响应 TERM 的最长时间
+const TERMINATE_WAIT: Duration = Duration::from_secs(5);
+
+// 退出应用时仍有游戏运行的处理方式，对应 AppSettings::quit_behavior
+const QUIT_ASK: &str = "ask";
+const QUIT_TERMINATE: &str = "terminate";
+const QUIT_DETACH: &str = "detach";
+
+// 用户已确认退出，之后的退出请求直接放行
+static QUIT_CONFIRMED: AtomicBool = AtomicBool::new(false);
+// 前端已加载游戏库，可以接收 game-finished 事件
+static FRONTEND_READY: AtomicBool = AtomicBool::new(false);
+// (实例 ID, 时长, 是否未知结束时间)
+static PENDING_SESSIONS: OnceLock<Mutex<Vec<(String, u64, bool)>>> = OnceLock::new();
+
+fn pending_sessions() -> &'static Mutex<Vec<(String, u64, bool)>> {
+    PENDING_SESSIONS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenSession {
+    pub instance_id: String,
+    pub started_at: u64,
+    pub paused_secs: u64,
+    // 游戏进程树中的所有进程
+    pub pids: Vec<u32>,
+    #[serde(default)]
+    pub tracker_pid: Option<u32>,
+    // 未启动追踪进程，结束时间按退出应用的时刻记录
+    #[serde(default)]
+    pub unknown_end: bool,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// 启动与本应用脱离的追踪进程，应用退出后继续记录游戏是否仍在运行
+fn spawn_tracker(pids: &[u32], heartbeat: &Path) -> Result<u32, String> {
+    let alive = pids.iter().map(|p| format!("kill -0 {} 2>/dev/null", p)).collect::<Vec<_>>().join(" || ");
+    let script = format!("while {}; do touch \"$0\"; sleep {}; done", alive, TRACKER_INTERVAL_SEC);
+    let child = Command::new("/bin/sh")
+        .arg("-c")
+        .arg(script)
+        .arg(heartbeat)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .map_err(|e| format!("无法启动游玩时长追踪进程: {}", e))?;
+    Ok(child.id())
+}
+
+// 保存仍在运行的会话。track 为 false 时不启动追踪进程，心跳文件的时间即退出应用的时刻
+fn persist_open_sessions(app: &AppHandle, sessions: &[OpenSession], track: bool) -> Result<(), String> {
+    let dir = storage::resolve_data_path(app, OPEN_SESSIONS_DIR)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    for session in sessions {
+        let mut session = session.clone();
+        storage::check_instance_id(&session.instance_id)?;
+        if session.pids.is_empty() {
+            continue;
+        }
+        let heartbeat = dir.join(format!("{}.alive", session.instance_id));
+        let _ = fs::write(&heartbeat, b"");
+        if track {
+            session.tracker_pid = Some(spawn_tracker(&session.pids, &heartbeat)?);
+        }
+
+        let text = serde_json::to_string(&session).map_err(|e| e.to_string())?;
+        fs::write(dir.join(format!("{}.json", session.instance_id)), text).map_err(|e| format!("保存运行中的会话失败: {}", e))?;
+        log_info!("实例 {} 仍在运行，已保存会话 (追踪: {})", session.instance_id, track);
+    }
+    Ok(())
+}
+
+// 结束所有仍在运行的游戏，会话时长记到此刻
+fn terminate_open_sessions(app: &AppHandle) -> Result<(), String> {
+    let sessions = runner::open_sessions_snapshot();
+    persist_open_sessions(app, &sessions, false)?;
+
+    let pids: Vec<u32> = sessions.iter().flat_map(|s| s.pids.iter().copied()).collect();
+    runner::terminate_pids(pids.clone());
+    let started = std::time::Instant::now();
+    while started.elapsed() < TERMINATE_WAIT {
+        let alive = runner::list_processes().map(|ps| ps.iter().any(|p| pids.contains(&p.pid))).unwrap_or(false);
+        if !alive {
+            break;
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+    log_info!("退出应用前已结束 {} 个游戏", sessions.len());
+    Ok(())
+}
+
+// 保留游戏继续运行但不追踪，会话按未知结束时间记录
+fn detach_open_sessions(app: &AppHandle) -> Result<(), String> {
+    let sessions: Vec<OpenSession> = runner::open_sessions_snapshot()
+        .into_iter()
+        .map(|s| OpenSession { unknown_end: true, ..s })
+        .collect();
+    persist_open_sessions(app, &sessions, false)
+}
+
+fn tracker_alive(pid: u32) -> bool {
+    runner::list_processes()
+        .map(|ps| ps.iter().any(|p| p.pid == pid && p.command.contains("kill -0")))
+        .unwrap_or(false)
+}
+
+// 前端就绪后通过 game-finished 补记时长，否则先暂存
+fn deliver(app: &AppHandle, instance_id: String, duration: u64, unknown_end: bool) {
+    // 在锁内判断就绪状态，避免与 replay_recovered_sessions 交错时漏发
+    if let Ok(mut pending) = pending_sessions().lock() {
+        if !FRONTEND_READY.load(Ordering::SeqCst) {
+            pending.push((instance_id, duration, unknown_end));
+            return;
+        }
+    }
+    runner::finish_recovered_session(app, instance_id, duration, unknown_end);
+}
+
+fn recover_session(app: &AppHandle, json_path: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(json_path).map_err(|e| e.to_string())?;
+    let session: OpenSession = serde_json::from_str(&text).map_err(|e| format!("会话记录解析失败: {}", e))?;
+    let heartbeat = json_path.with_extension("alive");
+
+    // 游戏仍在运行时等待追踪进程结束
+    if let Some(pid) = session.tracker_pid {
+        while tracker_alive(pid) {
+            thread::sleep(RECOVERY_POLL_INTERVAL);
+        }
+    }
+
+    // 最后一次心跳即游戏仍在运行的最后时刻
+    let ended_at = fs::metadata(&heartbeat)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_else(unix_now);
+    let duration = ended_at.saturating_sub(session.started_at).saturating_sub(session.paused_secs);
+
+    let _ = fs::remove_file(json_path);
+    let _ = fs::remove_file(&heartbeat);
+    log_info!("已恢复实例 {} 在应用退出期间的会话，时长 {} 秒", session.instance_id, duration);
+    deliver(app, session.instance_id, duration, session.unknown_end);
+    Ok(())
+}
+
+// 启动时找回上次退出时仍在运行的会话，每个会话在后台线程中等待结束
+pub fn recover_open_sessions(app: &AppHandle) -> Result<(), String> {
+    let dir = storage::resolve_data_path(app, OPEN_SESSIONS_DIR)?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(_) => return Ok(()),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let app = app.clone();
+        thread::spawn(move || {
+            if let Err(e) = recover_session(&app, &path) {
+                log_warn!("恢复会话 {:?} 失败: {}", path, e);
+            }
+        });
+    }
+    Ok(())
+}
+
+// 有游戏仍在运行时按设置处理：询问用户、结束游戏，或保留游戏并记录会话
+pub fn handle_exit_requested(app: &AppHandle, api: &ExitRequestApi) {
+    if QUIT_CONFIRMED.load(Ordering::SeqCst) {
+        return;
+    }
+    let running = runner::get_running_games();
+    if running.is_empty() {
+        return;
+    }
+
+    let behavior = settings::load_settings(app).quit_behavior;
+    if behavior != QUIT_TERMINATE && behavior != QUIT_DETACH {
+        if behavior != QUIT_ASK && !behavior.is_empty() {
+            log_warn!("未知的退出处理方式 {}，改为询问", behavior);
+        }
+        api.prevent_exit();
+        let _ = app.emit("quit-requested", running);
+        return;
+    }
+
+    // 先标记退出，此后结束的会话不再单独上报，统一由保存的记录补记
+    QUIT_CONFIRMED.store(true, Ordering::SeqCst);
+    let result = if behavior == QUIT_TERMINATE { terminate_open_sessions(app) } else { detach_open_sessions(app) };
+    // 保存失败时不退出，避免丢失会话
+    if let Err(e) = result {
+        log_error!("退出前保存运行中的会话失败: {}", e);
+        QUIT_CONFIRMED.store(false, Ordering::SeqCst);
+        api.prevent_exit();
+        let _ = app.emit("quit-requested", running);
+    }
+}
+
+// 退出流程中结束的会话已保存到 open_sessions，下次启动时补记
+pub fn is_quitting() -> bool {
+    QUIT_CONFIRMED.load(Ordering::SeqCst)
+}
+
+// 用户确认退出：把仍在运行的会话交给后台追踪进程，下次启动时补记时长
+#[command]
+pub fn confirm_quit(app: AppHandle) -> Result<(), String> {
+    QUIT_CONFIRMED.store(true, Ordering::SeqCst);
+    if let Err(e) = persist_open_sessions(&app, &runner::open_sessions_snapshot(), true) {
+        QUIT_CONFIRMED.store(false, Ordering::SeqCst);
+        return Err(e);
+    }
+    app.exit(0);
+    Ok(())
+}
+
+// 前端加载完游戏库后调用，补发启动前已结束的会话
+#[command]
+pub fn replay_recovered_sessions(app: AppHandle) {
+    let pending = pending_sessions()
+        .lock()
+        .map(|mut p| {
+            FRONTEND_READY.store(true, Ordering::SeqCst);
+            std::mem::take(&mut *p)
+        })
+        .unwrap_or_default();
+    for (instance_id, duration, unknown_end) in pending {
+        runner::finish_recovered_session(&app, instance_id, duration, unknown_end);
+    }
+}
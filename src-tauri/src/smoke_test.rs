@@ -0,0 +1,141 @@
+use tauri::{AppHandle, command};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::benchmark;
+use crate::runner::{self, WineConfig};
+use crate::storage;
+
+const DEFAULT_TIMEOUT_SEC: u64 = 60;
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 窗口出现后稍等片刻再截图，避免截到空白的初始窗口
+const SCREENSHOT_DELAY: Duration = Duration::from_secs(3);
+
+#[derive(Serialize)]
+pub struct TestLaunchResult {
+    instance_id: String,
+    // ok / crashed / timeout
+    status: String,
+    window_ms: Option<u64>,
+    exit_code: Option<i32>,
+    // 截图保存路径，截图失败（如未授予屏幕录制权限）时为空
+    screenshot: Option<String>,
+}
+
+fn capture_window(app: &AppHandle, instance_id: &str, window_id: u32) -> Option<String> {
+    let path = storage::resolve_data_path(app, &format!("test_launch/{}.png", instance_id)).ok()?;
+    fs::create_dir_all(path.parent()?).ok()?;
+    let status = Command::new("screencapture")
+        .arg("-x")
+        .arg("-o")
+        .arg(format!("-l{}", window_id))
+        .arg(&path)
+        .status()
+        .ok()?;
+    if status.success() && path.exists() {
+        Some(path.to_string_lossy().to_string())
+    } else {
+        None
+    }
+}
+
+// 等待窗口出现或进程树全部退出，期间把新出现的子进程加入 tree
+fn watch_launch(app: &AppHandle, instance_id: &str, child: &mut Child, tree: &mut HashSet<u32>, result: &mut TestLaunchResult, timeout: Duration) -> Result<(), String> {
+    let start_time = Instant::now();
+    while start_time.elapsed() < timeout {
+        thread::sleep(POLL_INTERVAL);
+
+        if let Ok(Some(status)) = child.try_wait() {
+            result.exit_code = status.code();
+        }
+        let processes = runner::list_processes()?;
+        let children_map = runner::build_children_map(&processes);
+        for pid in tree.clone() {
+            tree.extend(runner::collect_descendants(pid, &children_map));
+        }
+        tree.retain(|pid| processes.iter().any(|p| p.pid == *pid));
+
+        if let Some((_, window_id)) = benchmark::list_windows().into_iter().find(|(pid, _)| tree.contains(pid)) {
+            result.status = "ok".to_string();
+            result.window_ms = Some(start_time.elapsed().as_millis() as u64);
+            thread::sleep(SCREENSHOT_DELAY);
+            result.screenshot = capture_window(app, instance_id, window_id);
+            return Ok(());
+        }
+
+        // 进程树在出现窗口前全部退出，视为崩溃
+        if tree.is_empty() {
+            result.status = "crashed".to_string();
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+// 启动游戏并等待窗口出现或进程退出，截图留证后结束整个进程树。
+// 无论等待过程是否出错，启动的进程都会被结束；日志写入 logs/<id>.test.log，不覆盖正常游玩的日志
+fn run_test_launch(app: &AppHandle, instance_id: &str, config: &WineConfig, timeout: Duration) -> Result<TestLaunchResult, String> {
+    let (mut cmd, _, _) = runner::build_crossover_command(config)?;
+    let mut child = cmd.spawn().map_err(|e| format!("启动失败: {}", e))?;
+    let log_path = storage::resolve_data_path(app, &format!("logs/{}.test.log", instance_id)).ok();
+    runner::attach_log_pump_to(app, instance_id, &mut child, false, log_path);
+
+    let launcher_pid = child.id();
+    runner::register_test_run(instance_id, launcher_pid, &config.game_exe);
+    let mut tree: HashSet<u32> = HashSet::from([launcher_pid]);
+    let mut result = TestLaunchResult {
+        instance_id: instance_id.to_string(),
+        status: "timeout".to_string(),
+        window_ms: None,
+        exit_code: None,
+        screenshot: None,
+    };
+
+    let watched = watch_launch(app, instance_id, &mut child, &mut tree, &mut result, timeout);
+
+    // 等待中读取进程列表失败时 tree 可能不完整，再尝试补全一次
+    if let Ok(processes) = runner::list_processes() {
+        let children_map = runner::build_children_map(&processes);
+        for pid in tree.clone() {
+            tree.extend(runner::collect_descendants(pid, &children_map));
+        }
+    }
+    let killed = runner::terminate_pids(tree.into_iter().collect());
+    let _ = child.kill();
+    if let Ok(status) = child.wait() {
+        result.exit_code = result.exit_code.or(status.code());
+    }
+    runner::remove_running_instance(instance_id);
+    watched?;
+    log_info!(
+        "实例 {} 试启动结果: {}，已结束 {} 个进程",
+        instance_id,
+        result.status,
+        killed.len()
+    );
+    Ok(result)
+}
+
+// 批量验证导入的游戏能否正常启动，无需逐个手动打开
+#[command]
+pub async fn test_launch(
+    app: AppHandle,
+    instance_id: String,
+    config: WineConfig,
+    timeout_sec: Option<u64>,
+) -> Result<TestLaunchResult, String> {
+    storage::check_instance_id(&instance_id)?;
+    if config.run_mode.as_deref().unwrap_or("crossover") != "crossover" {
+        return Err("试启动目前仅支持 CrossOver 模式".to_string());
+    }
+    runner::ensure_not_running(&instance_id)?;
+
+    let timeout = Duration::from_secs(timeout_sec.unwrap_or(DEFAULT_TIMEOUT_SEC));
+    tokio::task::spawn_blocking(move || run_test_launch(&app, &instance_id, &config, timeout))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,181 @@
+use tauri::{AppHandle, Emitter, Manager, command};
+use serde::Serialize;
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Instant;
+
+use crate::crash_report;
+use crate::db;
+use crate::fonts;
+use crate::shutdown;
+use crate::storage;
+use crate::sync;
+use crate::trash;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadyStatePayload {
+    stage: String,
+    ok: bool,
+    message: Option<String>,
+    elapsed_ms: u64,
+}
+
+// 启动标记文件：启动开始时写入，初始化完成后删除；下次启动时仍存在说明上次启动中途崩溃
+const STARTUP_MARKER: &str = "startup.marker";
+
+static SAFE_MODE: OnceLock<bool> = OnceLock::new();
+
+// 已完成的初始化阶段，供页面刷新后重新查询
+static READY_STAGES: OnceLock<Mutex<Vec<ReadyStatePayload>>> = OnceLock::new();
+
+fn ready_stages() -> &'static Mutex<Vec<ReadyStatePayload>> {
+    READY_STAGES.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn report_stage(app: &AppHandle, stage: &str, started: Instant, result: Result<(), String>) {
+    let payload = ReadyStatePayload {
+        stage: stage.to_string(),
+        ok: result.is_ok(),
+        message: result.err(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    if payload.ok {
+        log_info!("[Startup] {} 完成 ({}ms)", payload.stage, payload.elapsed_ms);
+    } else {
+        log_error!("[Startup] {} 失败 ({}ms): {:?}", payload.stage, payload.elapsed_ms, payload.message);
+    }
+
+    if let Ok(mut stages) = ready_stages().lock() {
+        stages.push(payload.clone());
+    }
+    let _ = app.emit("ready-state", payload);
+}
+
+pub fn is_safe_mode() -> bool {
+    SAFE_MODE.get().copied().unwrap_or(false)
+}
+
+// 在 setup 最开始调用：检测上次启动是否崩溃并决定是否进入安全模式，然后写入新的标记
+pub fn begin_startup(app: &AppHandle) -> bool {
+    let marker = match storage::resolve_data_path(app, STARTUP_MARKER) {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+    let safe_mode = marker.exists();
+    if safe_mode {
+        log_info!("[Startup] 检测到上次启动未完成，进入安全模式");
+    }
+    let _ = SAFE_MODE.set(safe_mode);
+
+    if let Some(parent) = marker.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(&marker, b"starting");
+    safe_mode
+}
+
+fn clear_startup_marker(app: &AppHandle) {
+    if let Ok(marker) = storage::resolve_data_path(app, STARTUP_MARKER) {
+        let _ = fs::remove_file(marker);
+    }
+}
+
+fn apply_window_effects(app: &AppHandle) -> Result<(), String> {
+    let window = app.get_webview_window("main").ok_or("未找到主窗口")?;
+
+    // 磨砂效果必须在主线程上应用
+    app.run_on_main_thread(move || {
+        #[cfg(target_os = "macos")]
+        {
+            use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
+            if let Err(e) = apply_vibrancy(&window, NSVisualEffectMaterial::HudWindow, None, None) {
+                log_warn!("应用磨砂效果失败: {}", e);
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        let _ = window;
+    })
+    .map_err(|e| e.to_string())
+}
+
+// 检查数据目录可写，避免用户操作到一半才发现无法保存
+fn check_data_dir(app: &AppHandle) -> Result<(), String> {
+    let probe = storage::resolve_data_path(app, ".write_probe")?;
+    if let Some(parent) = probe.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建数据目录: {}", e))?;
+    }
+    fs::write(&probe, b"ok").map_err(|e| format!("数据目录不可写: {}", e))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+fn check_library(app: &AppHandle) -> Result<(), String> {
+    storage::load_instances(app.clone()).map(|_| ())
+}
+
+// 上次运行留下的崩溃报告，由前端询问是否附带到诊断包
+fn notify_pending_crashes(app: &AppHandle) {
+    if let Ok(dumps) = crash_report::get_pending_crash_reports(app.clone()) {
+        if !dumps.is_empty() {
+            let _ = app.emit("pending-crash-reports", dumps);
+        }
+    }
+}
+
+// 窗口创建后在后台依次完成较重的初始化工作，每个阶段完成时推送 ready-state 事件
+pub fn run_deferred_init(app: AppHandle) {
+    thread::spawn(move || {
+        // 安全模式只加载游戏库，跳过磨砂效果、字体监听等可能导致崩溃的初始化
+        if is_safe_mode() {
+            let t = Instant::now();
+            report_stage(&app, "library", t, check_library(&app));
+            let t = Instant::now();
+            report_stage(&app, "database", t, tauri::async_runtime::block_on(db::init(&app)));
+            let t = Instant::now();
+            report_stage(&app, "sessions", t, shutdown::recover_open_sessions(&app));
+            let t = Instant::now();
+            report_stage(&app, "ready", t, Ok(()));
+            clear_startup_marker(&app);
+            notify_pending_crashes(&app);
+            return;
+        }
+
+        let t = Instant::now();
+        report_stage(&app, "window", t, apply_window_effects(&app));
+
+        let t = Instant::now();
+        report_stage(&app, "library", t, check_library(&app));
+
+        let t = Instant::now();
+        report_stage(&app, "database", t, tauri::async_runtime::block_on(db::init(&app)));
+
+        let t = Instant::now();
+        report_stage(&app, "sessions", t, shutdown::recover_open_sessions(&app));
+
+        let t = Instant::now();
+        report_stage(&app, "health", t, check_data_dir(&app).and_then(|_| storage::backup_daily(&app)).and_then(|_| trash::purge_expired_trash(&app)));
+
+        let t = Instant::now();
+        fonts::get_system_fonts();
+        fonts::start_font_watcher();
+        report_stage(&app, "fonts", t, Ok(()));
+
+        let t = Instant::now();
+        report_stage(&app, "ready", t, Ok(()));
+
+        clear_startup_marker(&app);
+        notify_pending_crashes(&app);
+        sync::sync_in_background(app.clone());
+    });
+}
+
+#[command]
+pub fn get_ready_state() -> Vec<ReadyStatePayload> {
+    ready_stages().lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+#[command]
+pub fn get_safe_mode() -> bool {
+    is_safe_mode()
+}
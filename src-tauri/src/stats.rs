@@ -0,0 +1,109 @@
+use tauri::{AppHandle, command};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::autosave;
+use crate::bottle;
+use crate::instance::GameInstance;
+use crate::storage;
+
+// 统计游戏目录占用需要对每个目录运行 du，结果缓存一段时间，首页反复打开时不必重新统计
+const DISK_USAGE_TTL: Duration = Duration::from_secs(10 * 60);
+
+static DISK_USAGE_CACHE: OnceLock<Mutex<HashMap<PathBuf, (Instant, u64)>>> = OnceLock::new();
+
+fn disk_usage_cache() -> &'static Mutex<HashMap<PathBuf, (Instant, u64)>> {
+    DISK_USAGE_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct LibraryStats {
+    // 游戏库中的实例，不含回收站
+    instances: usize,
+    trashed: usize,
+    favorites: usize,
+    // 标记为已通关的实例
+    finished: usize,
+    rated: usize,
+    average_rating: Option<f64>,
+    // 总游玩时长（秒），total_hours 保留一位小数
+    total_play_time: u64,
+    total_hours: f64,
+    // 各实例游戏目录的总占用（字节），嵌套的目录只统计一次
+    disk_usage_bytes: u64,
+    // crossover / parallels / direct
+    by_run_mode: BTreeMap<String, usize>,
+    // 实例来源：导入的为 importedFrom（如 playnite），其余为 manual
+    by_source: BTreeMap<String, usize>,
+}
+
+// 实例的游戏目录：迁移过的游戏按当前位置拼出目录，否则取主程序所在目录
+fn game_dir(inst: &GameInstance) -> Option<PathBuf> {
+    let extra_str = |key: &str| inst.extra.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+    let root = match inst.game_file_status.as_deref() {
+        Some("disk") => extra_str("diskGameRoot"),
+        Some("local") => extra_str("localGameRoot"),
+        _ => None,
+    };
+    match (root, extra_str("gameRelativeDir")) {
+        (Some(root), Some(rel)) => Some(Path::new(root).join(rel)),
+        _ => Path::new(&inst.executable_path).parent().filter(|p| !p.as_os_str().is_empty()).map(Path::to_path_buf),
+    }
+}
+
+fn cached_dir_size(dir: &Path) -> u64 {
+    if let Some((at, size)) = disk_usage_cache().lock().unwrap_or_else(|e| e.into_inner()).get(dir) {
+        if at.elapsed() < DISK_USAGE_TTL {
+            return *size;
+        }
+    }
+    let size = bottle::dir_size_bytes(dir);
+    disk_usage_cache().lock().unwrap_or_else(|e| e.into_inner()).insert(dir.to_path_buf(), (Instant::now(), size));
+    size
+}
+
+fn disk_usage(instances: &[GameInstance]) -> u64 {
+    let mut dirs: Vec<PathBuf> = instances.iter().filter_map(game_dir).filter(|d| d.is_dir()).collect();
+    dirs.sort();
+    dirs.dedup();
+    // 排序后父目录排在子目录之前，子目录已包含在父目录的统计中
+    let mut counted: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if !counted.iter().any(|parent| dir.starts_with(parent)) {
+            counted.push(dir);
+        }
+    }
+    counted.iter().map(|d| cached_dir_size(d)).sum()
+}
+
+// 首页摘要卡片使用的统计，在后端汇总，前端不必加载整个游戏库
+#[command]
+pub async fn get_library_stats(app: AppHandle) -> Result<LibraryStats, String> {
+    autosave::flush(&app)?;
+    let all = storage::load_all_instances(&app)?;
+    let (live, trashed): (Vec<GameInstance>, Vec<GameInstance>) = all.into_iter().partition(|i| i.deleted_at.is_none());
+
+    let mut stats = LibraryStats { instances: live.len(), trashed: trashed.len(), ..Default::default() };
+    let mut rating_sum = 0u64;
+    for inst in &live {
+        stats.favorites += inst.favorite as usize;
+        stats.finished += inst.finished as usize;
+        if let Some(rating) = inst.rating {
+            stats.rated += 1;
+            rating_sum += rating as u64;
+        }
+        stats.total_play_time += inst.total_play_time.unwrap_or(0);
+        let run_mode = inst.run_mode.clone().unwrap_or_else(|| "crossover".to_string());
+        *stats.by_run_mode.entry(run_mode).or_insert(0) += 1;
+        let source = inst.extra.get("importedFrom").and_then(|v| v.as_str()).unwrap_or("manual").to_string();
+        *stats.by_source.entry(source).or_insert(0) += 1;
+    }
+    stats.average_rating = (stats.rated > 0).then(|| (rating_sum as f64 / stats.rated as f64 * 10.0).round() / 10.0);
+    stats.total_hours = (stats.total_play_time as f64 / 360.0).round() / 10.0;
+
+    stats.disk_usage_bytes = tokio::task::spawn_blocking(move || disk_usage(&live)).await.map_err(|e| e.to_string())?;
+    Ok(stats)
+}
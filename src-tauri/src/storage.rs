@@ -1,53 +1,657 @@
-use tauri::{AppHandle, command, Manager};
-use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, command, Manager};
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::autosave;
+use crate::db;
+use crate::instance::{self, GameInstance};
+use crate::journal;
+use crate::keychain;
+use crate::libraries;
+use crate::paths;
+use crate::playtime;
+use crate::repair::{self, RepairReport};
+use crate::runner::expand_tilde;
+use crate::settings;
 
 // 定义文件名
-const DATA_FILENAME: &str = "instances.json";
+pub(crate) const DATA_FILENAME: &str = "instances.json";
+// 指向自定义数据目录的指针文件，始终保存在默认的 AppLocalData 中
+const DATA_DIR_POINTER: &str = "data_dir_pointer";
+// 实例数据的备份目录，固定在默认的 AppLocalData 中，自定义数据目录不可用时也能恢复
+const BACKUPS_DIR: &str = "backups";
+const MAX_BACKUPS: usize = 20;
+const DAILY_BACKUP_INTERVAL_SECS: u64 = 24 * 60 * 60;
+// 只属于本机的文件，始终留在默认目录，迁移数据目录时不复制也不清理。
+// SQLite 放在 iCloud 等同步目录中容易损坏，数据库也固定在本机
+const LOCAL_ONLY: [&str; 7] = [DATA_DIR_POINTER, BACKUPS_DIR, "assets", libraries::LIBRARY_DBS_DIR, "library.db", "library.db-wal", "library.db-shm"];
+// 写入 instances.json 时持有的锁文件，防止多个窗口或进程同时写入
+const LOCK_FILENAME: &str = "instances.json.lock";
+// 游戏库的代数，每次写入加一；保存时携带读取时的代数，不一致说明期间已被修改
+pub(crate) const GENERATION_FILENAME: &str = "instances.generation";
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+// 持有锁的进程崩溃时锁文件不会被删除，超过该时间视为失效
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+// iCloud 云盘在本机的位置（相对用户目录）
+const ICLOUD_DRIVE: &str = "Library/Mobile Documents/com~apple~CloudDocs";
+// 等待 iCloud 下载尚未下载到本机的文件的最长时间
+const ICLOUD_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+// 游戏库加密使用的钥匙串条目；加密由系统自带的 openssl 完成（AES-256-CBC，PBKDF2 派生密钥）
+const LIBRARY_KEY_SERVICE: &str = "library";
+const LIBRARY_KEY_ACCOUNT: &str = "instances";
+const LIBRARY_KEY_ENV: &str = "ASUMIGAL_LIBRARY_KEY";
+// openssl 加盐加密输出的文件头，用于识别已加密的文件
+const ENCRYPTED_MAGIC: &[u8] = b"Salted__";
 
-// 获取数据文件路径
-fn get_data_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let path = app.path().resolve(DATA_FILENAME, BaseDirectory::AppLocalData)
-        .map_err(|e| e.to_string())?;
-    Ok(path)
+// 同一进程内的写入互斥锁，锁文件只能区分进程，不能区分同一进程的多个线程
+static LIBRARY_LOCK: Mutex<()> = Mutex::new(());
+// 迁移数据目录时持有写锁；笔记、标签、配置等不经过游戏库写入锁的文件在写入期间持有读锁，
+// 不会在复制途中写入旧目录而丢失。需要同时持有时先取游戏库写入锁
+static DATA_DIR_LOCK: RwLock<()> = RwLock::new(());
+// 本进程写入后得到的代数。本进程内的写入之间由互斥锁串行，前端也会随之更新，
+// 只有其他进程（另一个窗口）的写入才使前端读取时的代数过期
+static OWN_GENERATIONS: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+const MAX_OWN_GENERATIONS: usize = 1000;
+// 临时文件名的序号，同一进程内并发写入同一文件时临时文件互不覆盖
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Clone)]
+pub struct DataDirInfo {
+    current: String,
+    default: String,
+    custom: bool,
+    // 数据目录位于 iCloud 云盘中
+    icloud: bool,
+    size_bytes: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct BackupInfo {
+    id: String,
+    created_at: u64,
+    // daily / migration / restore / repair
+    reason: String,
+    size_bytes: u64,
+    instance_count: usize,
+}
+
+#[derive(Serialize, Clone)]
+pub struct BackupInstanceChange {
+    id: String,
+    name: String,
+    // 恢复后会改变的字段
+    fields: Vec<String>,
+}
+
+// 恢复备份前的预览，对比对象为当前游戏库（包括回收站）
+#[derive(Serialize, Clone)]
+pub struct BackupPreview {
+    id: String,
+    created_at: u64,
+    reason: String,
+    instance_count: usize,
+    // 只在备份中存在、恢复后会重新出现的实例名称
+    added: Vec<String>,
+    // 只在当前游戏库中存在、恢复后会消失的实例名称
+    removed: Vec<String>,
+    changed: Vec<BackupInstanceChange>,
+}
+
+#[derive(Serialize, Clone)]
+struct DataMigrationPayload {
+    // copying / verifying / switching / cleaning / done
+    stage: String,
+    target: String,
+}
+
+fn default_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_local_data_dir().map_err(|e| e.to_string())
+}
+
+// 当前生效的数据目录：指针文件指向的目录存在时使用它，否则回退到默认目录
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let default = default_data_dir(app)?;
+    if let Ok(text) = fs::read_to_string(default.join(DATA_DIR_POINTER)) {
+        let custom = PathBuf::from(text.trim());
+        if !text.trim().is_empty() && custom.is_dir() {
+            return Ok(custom);
+        }
+        log_warn!("自定义数据目录不可用，回退到默认目录: {:?}", custom);
+    }
+    Ok(default)
+}
+
+// 获取应用数据目录下的文件路径
+pub fn resolve_data_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    Ok(data_dir(app)?.join(name))
+}
+
+// 只属于本机的文件（见 LOCAL_ONLY），不随数据目录迁移
+pub fn local_data_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    Ok(default_data_dir(app)?.join(name))
+}
+
+pub fn is_icloud_path(path: &Path) -> bool {
+    dirs::home_dir().is_some_and(|home| path.starts_with(home.join(ICLOUD_DRIVE)))
+}
+
+// 文件已从本机移除、只留下 iCloud 占位文件
+fn has_icloud_placeholder(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| path.with_file_name(format!(".{}.icloud", name.to_string_lossy())))
+        .is_some_and(|p| p.exists())
+}
+
+// 读取数据目录中的文件。iCloud 会把不常用的文件从本机移除，只留下 .<文件名>.icloud 占位文件，
+// 此时先请求下载并等待完成，避免把"文件不存在"误当作空数据
+pub fn read_data_bytes(path: &Path) -> std::io::Result<Vec<u8>> {
+    if !path.exists() && has_icloud_placeholder(path) {
+        log_info!("等待 iCloud 下载 {:?}", path);
+        let _ = Command::new("brctl").arg("download").arg(path).status();
+        let started = Instant::now();
+        while !path.exists() && started.elapsed() < ICLOUD_DOWNLOAD_TIMEOUT {
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    }
+    fs::read(path)
+}
+
+pub fn read_data_file(path: &Path) -> std::io::Result<String> {
+    String::from_utf8(read_data_bytes(path)?).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+// 游戏库密钥，create 为 true 且钥匙串中还没有时生成一个随机密钥
+fn library_key(create: bool) -> Result<String, String> {
+    if let Some(key) = keychain::get_secret(LIBRARY_KEY_SERVICE, LIBRARY_KEY_ACCOUNT) {
+        return Ok(key);
+    }
+    if !create {
+        return Err("游戏库已加密，但钥匙串中找不到密钥".to_string());
+    }
+    let mut bytes = [0u8; 32];
+    fs::File::open("/dev/urandom")
+        .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut bytes))
+        .map_err(|e| format!("无法生成密钥: {}", e))?;
+    let key: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    keychain::set_secret(LIBRARY_KEY_SERVICE, LIBRARY_KEY_ACCOUNT, &key)?;
+    log_info!("已生成游戏库密钥并保存到钥匙串");
+    Ok(key)
+}
+
+// 通过 openssl 加密或解密，密钥经环境变量传入，不出现在进程参数中
+fn run_openssl(data: &[u8], key: &str, decrypt: bool) -> Result<Vec<u8>, String> {
+    let mut cmd = Command::new("openssl");
+    cmd.args(["enc", "-aes-256-cbc", "-pbkdf2", "-iter", "100000", "-salt", "-pass"])
+        .arg(format!("env:{}", LIBRARY_KEY_ENV))
+        .env(LIBRARY_KEY_ENV, key)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if decrypt {
+        cmd.arg("-d");
+    }
+    let mut child = cmd.spawn().map_err(|e| format!("无法运行 openssl: {}", e))?;
+    // 单独的线程写入，避免数据较大时输入输出管道互相阻塞
+    let mut stdin = child.stdin.take().ok_or("无法写入 openssl")?;
+    let input = data.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output().map_err(|e| format!("openssl 运行失败: {}", e))?;
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(if decrypt { "游戏库解密失败，密钥不匹配或文件已损坏".to_string() } else { "游戏库加密失败".to_string() });
+    }
+    Ok(output.stdout)
+}
+
+// 读取游戏库文件（instances.json 或其备份）的内容，已加密时先解密
+pub(crate) fn decode_library(data: Vec<u8>) -> Result<String, String> {
+    let data = if data.starts_with(ENCRYPTED_MAGIC) { run_openssl(&data, &library_key(false)?, true)? } else { data };
+    String::from_utf8(data).map_err(|e| format!("实例数据不是有效的文本: {}", e))
+}
+
+// 按设置决定写入游戏库文件时是否加密
+pub(crate) fn encode_library(app: &AppHandle, text: &str) -> Result<Vec<u8>, String> {
+    if settings::load_settings(app).encrypt_library {
+        run_openssl(text.as_bytes(), &library_key(true)?, false)
+    } else {
+        Ok(text.as_bytes().to_vec())
+    }
+}
+
+// instances.json 中保存的原始 JSON（规范形式的路径），文件不存在时为空
+pub(crate) fn read_stored_library(app: &AppHandle) -> Result<Option<String>, String> {
+    match read_data_bytes(&get_data_path(app)?) {
+        Ok(data) => decode_library(data).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("无法读取文件: {}", e)),
+    }
+}
+
+// 实例 ID 会被拼进文件路径，拒绝包含路径分隔符的 ID
+pub fn check_instance_id(instance_id: &str) -> Result<(), String> {
+    if instance_id.is_empty() || instance_id.contains('/') || instance_id.contains('\\') || instance_id.contains("..") {
+        return Err(format!("无效的实例 ID: {}", instance_id));
+    }
+    Ok(())
+}
+
+// 获取只属于当前游戏库的文件路径（实例、代数、修改日志、标签、自定义字段、启动方案、同步状态），
+// 文件名按 libraries::scoped_file_name 区分
+pub(crate) fn resolve_library_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    resolve_data_path(app, &libraries::scoped_file_name(name, &libraries::active_library(app)))
+}
+
+// 获取当前游戏库的数据文件路径
+pub(crate) fn get_data_path(app: &AppHandle) -> Result<PathBuf, String> {
+    resolve_library_path(app, DATA_FILENAME)
 }
 
 // 获取脚本存储目录
 fn get_scripts_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let path = app.path().resolve("scripts", BaseDirectory::AppLocalData)
-        .map_err(|e| e.to_string())?;
+    let path = resolve_data_path(app, "scripts")?;
     if !path.exists() {
         fs::create_dir_all(&path).map_err(|e| format!("创建脚本目录失败: {}", e))?;
     }
     Ok(path)
 }
 
-#[command]
-pub fn save_instances(app: AppHandle, data: String) -> Result<(), String> {
-    let path = get_data_path(&app)?;
+// 用 ditto 把目录复制（合并）到目标位置，保留权限与时间戳
+pub(crate) fn copy_tree(src: &Path, dest: &Path) -> Result<(), String> {
+    let status = Command::new("ditto")
+        .arg(src)
+        .arg(dest)
+        .status()
+        .map_err(|e| format!("复制 {:?} 失败: {}", src, e))?;
+    if !status.success() {
+        return Err(format!("复制 {:?} 失败", src));
+    }
+    Ok(())
+}
+
+// 先写入同目录下的临时文件并落盘，再重命名覆盖原文件；
+// 写入中途崩溃只会留下临时文件，原文件保持完整
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    let dir = path.parent().ok_or(format!("无效的文件路径: {:?}", path))?;
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let seq = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp = dir.join(format!(".{}.tmp-{}-{}", name, std::process::id(), seq));
+
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("无法写入临时文件: {}", e));
+    }
+
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("无法替换数据文件: {}", e));
+    }
+    // 目录项也需要落盘，重命名才算持久化；失败不影响已完成的替换
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+// 原样写入完整的实例列表（包括回收站中的实例），调用方需持有写入锁。before 为写入前磁盘上的列表
+fn write_all_instances(app: &AppHandle, instances: &[GameInstance], before: &[GameInstance]) -> Result<(), String> {
+    // 写入前校验有变化的实例，格式错误的数据不会覆盖现有游戏库
+    instance::validate_changed(before, instances)?;
+    let path = get_data_path(app)?;
     
     // 确保目录存在
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
 
-    fs::write(&path, data).map_err(|e| format!("无法写入文件: {}", e))?;
-    println!("数据已保存到: {:?}", path);
+    // 游玩时长写入单独的时长记录，instances.json 中不再保存
+    playtime::absorb(app, instances)?;
+
+    // 路径字段以规范形式保存，换用户名或换机器后仍能解析
+    let mut value = serde_json::to_value(instances).map_err(|e| e.to_string())?;
+    paths::map_instance_paths(&mut value, paths::to_stored_path);
+    let mut values = match value {
+        serde_json::Value::Array(values) => values,
+        _ => Vec::new(),
+    };
+    // 数据库中仍保存合并后的时长
+    db::sync_in_background(app, values.clone());
+    for inst in values.iter_mut().filter_map(|v| v.as_object_mut()) {
+        for field in playtime::STORED_FIELDS {
+            inst.remove(field);
+        }
+    }
+    let data = serde_json::to_string_pretty(&values).map_err(|e| e.to_string())?;
+
+    write_atomic(&path, &encode_library(app, &data)?)?;
+    log_info!("数据已保存到: {:?}", path);
     Ok(())
 }
 
+// 锁文件在离开作用域时删除
+struct DiskLock(PathBuf);
+
+impl Drop for DiskLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn acquire_disk_lock(path: PathBuf) -> Result<DiskLock, String> {
+    let started = Instant::now();
+    loop {
+        match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                let _ = write!(file, "{}", std::process::id());
+                return Ok(DiskLock(path));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let age = fs::metadata(&path).and_then(|m| m.modified()).ok().and_then(|t| t.elapsed().ok());
+                if age.is_some_and(|age| age > STALE_LOCK_AGE) {
+                    log_warn!("游戏库锁文件已失效，强制解除: {:?}", path);
+                    let _ = fs::remove_file(&path);
+                    continue;
+                }
+                if started.elapsed() > LOCK_TIMEOUT {
+                    return Err("游戏库正在被其他窗口写入，请稍后重试".to_string());
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(format!("无法创建游戏库锁文件: {}", e)),
+        }
+    }
+}
+
+// 当前游戏库的代数，从未写入过时为 0
+pub fn read_generation(app: &AppHandle) -> u64 {
+    resolve_library_path(app, GENERATION_FILENAME)
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|text| text.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+// 读取时的代数为 expected，此后游戏库是否被其他进程写入过
+pub(crate) fn is_stale(expected: u64, current: u64) -> bool {
+    if expected > current {
+        return true;
+    }
+    let own = OWN_GENERATIONS.lock().unwrap_or_else(|e| e.into_inner());
+    (expected + 1..=current).any(|g| !own.contains(&g))
+}
+
+const STALE_ERROR: &str = "游戏库已在其他窗口中被修改，请重新加载后再保存";
+
+// 不加锁地检查 expected 是否已过期，供暂存修改等不立即写入的操作提前拒绝
+pub(crate) fn check_generation(app: &AppHandle, expected: Option<u64>) -> Result<u64, String> {
+    let generation = read_generation(app);
+    if expected.is_some_and(|expected| is_stale(expected, generation)) {
+        return Err(STALE_ERROR.to_string());
+    }
+    Ok(generation)
+}
+
+// 持有进程内互斥锁与数据目录中的锁文件执行 f，期间其他写入会等待
+pub(crate) fn with_library_lock<T>(app: &AppHandle, f: impl FnOnce(&Path) -> Result<T, String>) -> Result<T, String> {
+    let _guard = LIBRARY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = data_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("无法创建数据目录: {}", e))?;
+    let _lock = acquire_disk_lock(dir.join(LOCK_FILENAME))?;
+    f(&dir)
+}
+
+// 写入数据目录中的单个文件（解析路径与写入都在 f 中完成），数据目录迁移期间会等待迁移结束
+pub(crate) fn with_data_dir<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let _guard = DATA_DIR_LOCK.read().unwrap_or_else(|e| e.into_inner());
+    f()
+}
+
+// 持有写入锁执行写入，成功后代数加一。
+// expected 为调用方读取游戏库时的代数，此后游戏库被其他进程写入过时拒绝写入，避免覆盖其他窗口的修改
+fn locked_write<T>(app: &AppHandle, expected: Option<u64>, write: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    with_library_lock(app, |_| {
+        let generation = check_generation(app, expected)?;
+        let result = write()?;
+        write_atomic(&resolve_library_path(app, GENERATION_FILENAME)?, (generation + 1).to_string().as_bytes())?;
+        let mut own = OWN_GENERATIONS.lock().unwrap_or_else(|e| e.into_inner());
+        own.push(generation + 1);
+        let excess = own.len().saturating_sub(MAX_OWN_GENERATIONS);
+        own.drain(..excess);
+        Ok(result)
+    })
+}
+
+// 在写入锁的保护下读取完整的实例列表（包括回收站）、修改并写回。
+// source 为发起修改的操作，与字段的变化一起记入修改日志
+pub(crate) fn update_all_instances<T>(
+    app: &AppHandle,
+    source: &str,
+    expected: Option<u64>,
+    f: impl FnOnce(&mut Vec<GameInstance>) -> Result<T, String>,
+) -> Result<T, String> {
+    locked_write(app, expected, || {
+        let mut instances = load_all_instances(app)?;
+        // 先合并尚未自动保存的修改，本次写入不会基于磁盘上的旧数据
+        let staged = autosave::take_pending();
+        let loaded = instances.clone();
+        autosave::apply(&mut instances, &staged);
+        let before = instances.clone();
+        let result = match f(&mut instances).and_then(|r| write_all_instances(app, &instances, &loaded).map(|_| r)) {
+            Ok(r) => r,
+            Err(e) => {
+                autosave::requeue(staged);
+                return Err(e);
+            }
+        };
+        journal::record(app, autosave::SOURCE, &loaded, &before);
+        journal::record(app, source, &before, &instances);
+        Ok(result)
+    })
+}
+
+// 只修改游戏库中的实例（不含回收站）。修改后列表中缺少的实例不会直接删除，
+// 而是移入回收站；已在回收站中的实例原样保留
+pub(crate) fn update_instances<T>(
+    app: &AppHandle,
+    source: &str,
+    expected: Option<u64>,
+    f: impl FnOnce(&mut Vec<GameInstance>) -> Result<T, String>,
+) -> Result<T, String> {
+    update_all_instances(app, source, expected, |all| {
+        let existing = std::mem::take(all);
+        let mut live: Vec<GameInstance> = existing.iter().filter(|i| i.deleted_at.is_none()).cloned().collect();
+        let result = f(&mut live)?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        for mut inst in existing {
+            if live.iter().any(|i| i.id == inst.id) {
+                continue;
+            }
+            if inst.deleted_at.is_none() {
+                log_info!("实例 {} 已移入回收站", inst.name);
+                inst.deleted_at = Some(now);
+            }
+            live.push(inst);
+        }
+        *all = live;
+        Ok(result)
+    })
+}
+
+// 保存实例列表，列表中缺少的实例移入回收站。
+// 传入 expected_generation（见 get_library_generation）时，游戏库已被其他窗口修改则返回冲突错误
+#[command]
+pub fn save_instances(app: AppHandle, instances: Vec<GameInstance>, expected_generation: Option<u64>) -> Result<(), String> {
+    update_instances(&app, "save_instances", expected_generation, |live| {
+        *live = instances;
+        Ok(())
+    })
+}
+
 #[command]
-pub fn load_instances(app: AppHandle) -> Result<String, String> {
-    let path = get_data_path(&app)?;
+pub fn get_library_generation(app: AppHandle) -> u64 {
+    read_generation(&app)
+}
+
+// 读取完整的实例列表，包括回收站中的实例。
+// 格式错误的字段恢复为默认值，无法修复的条目隔离到 instances.json.corrupt，修复情况见返回的报告
+fn load_all_instances_checked(app: &AppHandle) -> Result<(Vec<GameInstance>, RepairReport), String> {
+    let path = get_data_path(app)?;
     
-    if !path.exists() {
-        // 如果文件不存在，返回空数组 JSON
-        return Ok("[]".to_string());
+    if !path.exists() && !has_icloud_placeholder(&path) {
+        // 文件不存在（iCloud 中也没有待下载的占位文件）时返回空列表
+        return Ok((Vec::new(), RepairReport::default()));
     }
 
-    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取文件: {}", e))?;
-    Ok(data)
+    let data = decode_library(read_data_bytes(&path).map_err(|e| format!("无法读取文件: {}", e))?)?;
+    // 把规范形式的路径解析为当前机器上的绝对路径再交给前端
+    let parsed = repair::parse_library(&data, |value| {
+        let mut wrapped = serde_json::Value::Array(vec![std::mem::take(value)]);
+        paths::map_instance_paths(&mut wrapped, |p| paths::resolve_stored_path(p).to_string_lossy().to_string());
+        if let Some(inner) = wrapped.as_array_mut().and_then(|a| a.pop()) {
+            *value = inner;
+        }
+    })?;
+    repair::quarantine(app, &parsed.rejected)?;
+    let mut instances = parsed.instances;
+    playtime::merge(app, &mut instances);
+    Ok((instances, parsed.report))
+}
+
+pub(crate) fn load_all_instances(app: &AppHandle) -> Result<Vec<GameInstance>, String> {
+    load_all_instances_checked(app).map(|(instances, _)| instances)
+}
+
+// 合并了游玩时长、路径为规范形式的实例列表（包括回收站），供数据库与同步使用
+pub(crate) fn stored_values(app: &AppHandle) -> Result<Vec<serde_json::Value>, String> {
+    let mut value = serde_json::to_value(load_all_instances(app)?).map_err(|e| e.to_string())?;
+    paths::map_instance_paths(&mut value, paths::to_stored_path);
+    match value {
+        serde_json::Value::Array(values) => Ok(values),
+        _ => Ok(Vec::new()),
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct LoadedLibrary {
+    instances: Vec<GameInstance>,
+    // 读取时的代数，保存时原样传回
+    generation: u64,
+    // 读取时做过修复才有
+    repair: Option<RepairReport>,
+}
+
+// 游戏库中的实例，不含回收站。
+// 文件需要修复时先备份原文件，再把修复后的游戏库写回，下次读取不再重复修复
+#[command]
+pub fn load_instances(app: AppHandle) -> Result<LoadedLibrary, String> {
+    // 重新读取前写入暂存的修改，前端不会看到旧数据；
+    // 暂存的修改因游戏库已被其他窗口修改而丢弃时，照常读取最新的游戏库
+    if let Err(e) = autosave::flush(&app) {
+        log_warn!("读取游戏库前写入暂存的修改失败: {}", e);
+    }
+    let (_, report) = load_all_instances_checked(&app)?;
+    let repair = if report.is_empty() {
+        None
+    } else {
+        create_backup(&app, "repair")?;
+        update_all_instances(&app, "repair", None, |_| Ok(()))?;
+        log_warn!("游戏库文件已修复: {:?}", report);
+        Some(report)
+    };
+    // 先取代数再读取，期间有写入时只会多报一次冲突，不会漏报
+    let generation = read_generation(&app);
+    let mut instances = load_all_instances(&app)?;
+    instances.retain(|i| i.deleted_at.is_none());
+    Ok(LoadedLibrary { instances, generation, repair })
+}
+
+#[derive(Serialize, Clone)]
+pub struct SessionRecord {
+    // 计入的日期（本地时间 YYYY-MM-DD）与当天累计秒数
+    day: String,
+    day_total: u64,
+    total_play_time: u64,
+}
+
+// Unix 秒对应的本地日期，与前端 toLocaleDateString("en-CA") 的格式一致
+fn local_day_key(ts: u64) -> String {
+    let output = Command::new("date").arg("-r").arg(ts.to_string()).arg("+%Y-%m-%d").output();
+    if let Ok(out) = output.as_ref() {
+        let day = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        if out.status.success() && !day.is_empty() {
+            return day;
+        }
+    }
+    // 取不到本地时区时按 UTC 计算
+    let days = (ts / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+// 游戏结束时由等待线程直接记入时长记录，不依赖前端是否打开，也不重写 instances.json：
+// 追加一条游玩记录，并在数据库中追加一条会话
+pub fn record_session(app: &AppHandle, instance_id: &str, start: u64, duration_sec: u64) -> Result<SessionRecord, String> {
+    let day = local_day_key(start + duration_sec);
+    let (day_total, total_play_time) = with_library_lock(app, |_| {
+        if !load_all_instances(app)?.iter().any(|i| i.id == instance_id && i.deleted_at.is_none()) {
+            return Err(format!("未找到实例: {}", instance_id));
+        }
+        if duration_sec > 0 {
+            playtime::append_session(app, instance_id, start, duration_sec, &day)?;
+        }
+        let instances = load_all_instances(app)?;
+        let inst = instances.iter().find(|i| i.id == instance_id).ok_or(format!("未找到实例: {}", instance_id))?;
+        let day_total = inst.play_history.as_ref().and_then(|h| h.get(&day)).copied().unwrap_or(0);
+        Ok((day_total, inst.total_play_time.unwrap_or(0)))
+    })?;
+
+    if duration_sec > 0 {
+        // 更新数据库中实例的累计时长
+        if let Ok(values) = stored_values(app) {
+            db::sync_in_background(app, values);
+        }
+        let instance_id = instance_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = db::db_add_session(instance_id, start as i64, duration_sec as i64).await {
+                log_warn!("记录会话到数据库失败: {}", e);
+            }
+        });
+    }
+    log_info!("已记录实例 {} 的游玩时长 {} 秒", instance_id, duration_sec);
+    Ok(SessionRecord { day, day_total, total_play_time })
+}
+
+// 供后端其他模块读取实例列表，解析失败时返回空列表。暂存尚未写入的修改一并合并
+pub fn read_instances(app: &AppHandle) -> Vec<GameInstance> {
+    let mut instances = load_all_instances(app).unwrap_or_default();
+    autosave::overlay(&mut instances);
+    instances.retain(|i| i.deleted_at.is_none());
+    instances
+}
+
+// 按 JSON 读取实例列表，供按字段名访问的旧代码使用
+pub fn read_instance_values(app: &AppHandle) -> Vec<serde_json::Value> {
+    read_instances(app)
+        .iter()
+        .filter_map(|inst| serde_json::to_value(inst).ok())
+        .collect()
 }
 
 #[command]
@@ -76,4 +680,378 @@ pub fn read_script(app: AppHandle, name: String) -> Result<String, String> {
 pub fn save_script(app: AppHandle, name: String, content: String) -> Result<(), String> {
     let path = get_scripts_dir(&app)?.join(format!("{}.sh", name));
     fs::write(path, content).map_err(|e| format!("无法保存脚本: {}", e))
-}
\ No newline at end of file
+}
+// 统计目录下的文件数与总字节数，用于迁移后的校验
+pub(crate) fn tree_stats(root: &Path, skip: &[&str]) -> (u64, u64) {
+    let mut files = 0;
+    let mut bytes = 0;
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            if dir == root && skip.iter().any(|s| entry.file_name() == *s) {
+                continue;
+            }
+            let meta = match fs::symlink_metadata(entry.path()) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else {
+                files += 1;
+                bytes += meta.len();
+            }
+        }
+    }
+    (files, bytes)
+}
+
+// 单个文件或目录的文件数与总字节数
+fn entry_stats(path: &Path) -> (u64, u64) {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => tree_stats(path, &[]),
+        Ok(meta) => (1, meta.len()),
+        Err(_) => (0, 0),
+    }
+}
+
+#[command]
+pub fn get_data_dir(app: AppHandle) -> Result<DataDirInfo, String> {
+    let current = data_dir(&app)?;
+    let default = default_data_dir(&app)?;
+    Ok(DataDirInfo {
+        custom: current != default,
+        icloud: is_icloud_path(&current),
+        size_bytes: crate::bottle::dir_size_bytes(&current),
+        current: current.to_string_lossy().to_string(),
+        default: default.to_string_lossy().to_string(),
+    })
+}
+
+// 将全部数据复制到新目录，校验文件数与大小一致后再切换指针；
+// target 为空时迁回默认目录
+#[command]
+pub async fn migrate_data_dir(app: AppHandle, target: String, remove_old: bool) -> Result<DataDirInfo, String> {
+    let source = data_dir(&app)?;
+    let default = default_data_dir(&app)?;
+    let dest = if target.trim().is_empty() { default.clone() } else { expand_tilde(target.trim()) };
+
+    if dest == source {
+        return Err("目标目录与当前数据目录相同".to_string());
+    }
+    if dest.starts_with(&source) || source.starts_with(&dest) {
+        return Err("目标目录不能位于当前数据目录之内（或反之）".to_string());
+    }
+    fs::create_dir_all(&dest).map_err(|e| format!("无法创建目标目录: {}", e))?;
+    if dest != default && fs::read_dir(&dest).map(|mut d| d.next().is_some()).unwrap_or(false) {
+        return Err("目标目录不为空，请选择一个空目录".to_string());
+    }
+
+    let emit = {
+        let (app, target) = (app.clone(), dest.to_string_lossy().to_string());
+        move |stage: &str| {
+            let _ = app.emit("data-migration-progress", DataMigrationPayload { stage: stage.to_string(), target: target.clone() });
+        }
+    };
+
+    // 复制、校验与切换期间持有写入锁，保存操作会等到切换完成后写入新目录
+    let (lock_app, src, dst, default_dir, emit_stage) = (app.clone(), source.clone(), dest.clone(), default.clone(), emit.clone());
+    tokio::task::spawn_blocking(move || {
+        with_library_lock(&lock_app, |_| {
+            let _data_dir = DATA_DIR_LOCK.write().unwrap_or_else(|e| e.into_inner());
+            emit_stage("copying");
+            let mut copied = Vec::new();
+            for entry in fs::read_dir(&src).map_err(|e| e.to_string())?.flatten() {
+                if entry.file_name() == LOCK_FILENAME || LOCAL_ONLY.iter().any(|name| entry.file_name() == *name) {
+                    continue;
+                }
+                let status = Command::new("cp")
+                    .arg("-Rp")
+                    .arg(entry.path())
+                    .arg(&dst)
+                    .status()
+                    .map_err(|e| format!("复制数据失败: {}", e))?;
+                if !status.success() {
+                    return Err(format!("复制 {:?} 失败", entry.path()));
+                }
+                copied.push(entry.file_name());
+            }
+
+            // 只比较复制过去的项，迁回默认目录时其中原有的备份等文件不计入
+            emit_stage("verifying");
+            let (mut expected, mut actual) = ((0, 0), (0, 0));
+            for name in &copied {
+                let (src_stats, dst_stats) = (entry_stats(&src.join(name)), entry_stats(&dst.join(name)));
+                expected = (expected.0 + src_stats.0, expected.1 + src_stats.1);
+                actual = (actual.0 + dst_stats.0, actual.1 + dst_stats.1);
+            }
+            if expected != actual {
+                return Err(format!(
+                    "迁移校验失败：源目录 {} 个文件 / {} 字节，目标目录 {} 个文件 / {} 字节，已保留原数据",
+                    expected.0, expected.1, actual.0, actual.1
+                ));
+            }
+
+            emit_stage("switching");
+            let pointer = default_dir.join(DATA_DIR_POINTER);
+            if dst == default_dir {
+                let _ = fs::remove_file(&pointer);
+            } else {
+                fs::create_dir_all(&default_dir).map_err(|e| e.to_string())?;
+                fs::write(&pointer, dst.to_string_lossy().as_bytes()).map_err(|e| format!("无法写入数据目录指针: {}", e))?;
+            }
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    log_info!("数据目录已从 {:?} 迁移到 {:?}", source, dest);
+
+    if remove_old {
+        emit("cleaning");
+        for entry in fs::read_dir(&source).map_err(|e| e.to_string())?.flatten() {
+            if LOCAL_ONLY.iter().any(|name| entry.file_name() == *name) {
+                continue;
+            }
+            let path = entry.path();
+            let result = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+            if let Err(e) = result {
+                log_warn!("清理旧数据 {:?} 失败: {}", path, e);
+            }
+        }
+    }
+
+    emit("done");
+    get_data_dir(app)
+}
+
+// 把数据目录移动到 new_path（如外接 SSD），复制并校验后切换，原目录中的数据保留
+#[command]
+pub async fn move_data_dir(app: AppHandle, new_path: String) -> Result<DataDirInfo, String> {
+    if new_path.trim().is_empty() {
+        return Err("请选择新的数据目录".to_string());
+    }
+    migrate_data_dir(app, new_path, false).await
+}
+
+// 非默认游戏库的备份放在以游戏库 ID 命名的子目录中
+fn backups_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let library = libraries::active_library(app);
+    let mut dir = default_data_dir(app)?.join(BACKUPS_DIR);
+    if library != libraries::DEFAULT_LIBRARY {
+        dir = dir.join(library);
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("无法创建备份目录: {}", e))?;
+    Ok(dir)
+}
+
+// 备份文件名形如 instances-1700000000-daily.json，ID 为去掉扩展名的文件名
+fn parse_backup_id(id: &str) -> Option<(u64, String)> {
+    let rest = id.strip_prefix("instances-")?;
+    let (ts, reason) = rest.split_once('-')?;
+    Some((ts.parse().ok()?, reason.to_string()))
+}
+
+fn read_backups(dir: &Path) -> Vec<BackupInfo> {
+    let mut backups: Vec<BackupInfo> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let id = name.strip_suffix(".json")?.to_string();
+            let (created_at, reason) = parse_backup_id(&id)?;
+            let data = fs::read(entry.path()).ok()?;
+            let size_bytes = data.len() as u64;
+            // 加密的备份在缺少密钥时无法统计实例数
+            let instance_count = decode_library(data)
+                .ok()
+                .and_then(|text| serde_json::from_str::<Vec<serde_json::Value>>(&text).ok())
+                .map(|v| v.len())
+                .unwrap_or(0);
+            Some(BackupInfo { id, created_at, reason, size_bytes, instance_count })
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    backups
+}
+
+// 保存当前 instances.json 的快照，只保留最近 MAX_BACKUPS 份；
+// 还没有实例数据时不做备份
+pub fn create_backup(app: &AppHandle, reason: &str) -> Result<Option<BackupInfo>, String> {
+    let source = get_data_path(app)?;
+    let data = match fs::read(&source) {
+        Ok(d) => d,
+        Err(_) => return Ok(None),
+    };
+    let dir = backups_dir(app)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let id = format!("instances-{}-{}", now, reason);
+    write_atomic(&dir.join(format!("{}.json", id)), &data)?;
+    log_info!("已备份实例数据: {}", id);
+
+    let backups = read_backups(&dir);
+    for old in backups.iter().skip(MAX_BACKUPS) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", old.id)));
+    }
+    Ok(backups.into_iter().find(|b| b.id == id))
+}
+
+// 启动时调用：距离上次每日备份超过一天才备份
+pub fn backup_daily(app: &AppHandle) -> Result<(), String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let last_daily = read_backups(&backups_dir(app)?)
+        .into_iter()
+        .find(|b| b.reason == "daily")
+        .map(|b| b.created_at)
+        .unwrap_or(0);
+    if now.saturating_sub(last_daily) >= DAILY_BACKUP_INTERVAL_SECS {
+        create_backup(app, "daily")?;
+    }
+    Ok(())
+}
+
+#[command]
+pub fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    Ok(read_backups(&backups_dir(&app)?))
+}
+
+// 读取并校验备份，返回备份原文与其中的实例（路径已解析为本机路径）
+fn read_backup(app: &AppHandle, id: &str) -> Result<(String, Vec<GameInstance>), String> {
+    if parse_backup_id(id).is_none() || id.contains('/') || id.contains("..") {
+        return Err(format!("无效的备份 ID: {}", id));
+    }
+    let path = backups_dir(app)?.join(format!("{}.json", id));
+    let text = decode_library(fs::read(&path).map_err(|e| format!("无法读取备份: {}", e))?)?;
+    let mut value: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("备份文件已损坏: {}", e))?;
+    if !value.is_array() {
+        return Err("备份文件已损坏: 内容不是实例列表".to_string());
+    }
+    paths::map_instance_paths(&mut value, |p| paths::resolve_stored_path(p).to_string_lossy().to_string());
+    let instances: Vec<GameInstance> = serde_json::from_value(value).map_err(|e| format!("备份文件格式错误: {}", e))?;
+    instance::validate_library(&instances)?;
+    Ok((text, instances))
+}
+
+// 预览恢复备份会带来的变化，不修改任何数据
+#[command]
+pub fn preview_backup(app: AppHandle, id: String) -> Result<BackupPreview, String> {
+    let (_, mut backup_instances) = read_backup(&app, &id)?;
+    // 游玩时长不随备份回退，对比时同样以时长记录为准
+    playtime::merge(&app, &mut backup_instances);
+    let current = load_all_instances(&app)?;
+    let (created_at, reason) = parse_backup_id(&id).unwrap_or_default();
+
+    let mut preview = BackupPreview {
+        id,
+        created_at,
+        reason,
+        instance_count: backup_instances.len(),
+        added: Vec::new(),
+        removed: current.iter().filter(|c| !backup_instances.iter().any(|b| b.id == c.id)).map(|c| c.name.clone()).collect(),
+        changed: Vec::new(),
+    };
+    for inst in &backup_instances {
+        match current.iter().find(|c| c.id == inst.id) {
+            Some(existing) => {
+                let fields: Vec<String> = journal::field_changes(existing, inst).into_keys().collect();
+                if !fields.is_empty() {
+                    preview.changed.push(BackupInstanceChange { id: inst.id.clone(), name: inst.name.clone(), fields });
+                }
+            }
+            None => preview.added.push(inst.name.clone()),
+        }
+    }
+    Ok(preview)
+}
+
+// 用备份覆盖当前实例数据。在写入锁内先备份当前数据再覆盖，期间的其他写入不会丢失，
+// 恢复错了还能再恢复回来；返回恢复前的快照，实例字段的变化同时记入修改日志
+#[command]
+pub fn restore_backup(app: AppHandle, id: String) -> Result<Option<BackupInfo>, String> {
+    let (text, _) = read_backup(&app, &id)?;
+    // 未保存的修改先写入，包含在恢复前的快照中
+    autosave::flush(&app)?;
+    let snapshot = locked_write(&app, None, || {
+        let before = load_all_instances(&app).unwrap_or_default();
+        let snapshot = create_backup(&app, "restore")?;
+        write_atomic(&get_data_path(&app)?, &encode_library(&app, &text)?)?;
+        if let Ok(after) = load_all_instances(&app) {
+            journal::record(&app, "restore_backup", &before, &after);
+        }
+        Ok(snapshot)
+    })?;
+    // 游玩时长不随备份回退，仍以时长记录为准
+    db::sync_in_background(&app, stored_values(&app)?);
+    log_info!("已从备份 {} 恢复实例数据", id);
+    let _ = app.emit("instances-restored", &id);
+    Ok(snapshot)
+}
+
+// 游戏库加密只覆盖 instances.json、其备份与变更记录；以下内容仍以明文保存或传输，开启加密时返回给前端提示用户
+const PLAINTEXT_WHEN_ENCRYPTED: [&str; 4] = [
+    "playtime.jsonl（只有实例 ID 与游玩时长）",
+    "library.db（只保存实例 ID、运行方式与游玩时长，游玩记录中的时间）",
+    "WebDAV 同步上传的 instances.json 与 history.json",
+    "导出的游戏库压缩包",
+];
+
+// 开启或关闭游戏库加密，并立即按新设置重写 instances.json、所有备份与数据库中的实例。
+// 密钥保存在钥匙串中，经标准输入写入钥匙串、经环境变量交给 openssl，不出现在命令行参数中。
+// 开启时返回仍为明文的内容
+#[command]
+pub fn set_library_encryption(app: AppHandle, enabled: bool) -> Result<Vec<String>, String> {
+    let mut current = settings::load_settings(&app);
+    if enabled {
+        // 先确认能取得密钥，失败时不修改设置
+        library_key(true)?;
+    }
+    locked_write(&app, None, || {
+        let text = read_stored_library(&app)?;
+        current.encrypt_library = enabled;
+        settings::write_settings(&app, &current)?;
+        if let Some(text) = text {
+            write_atomic(&get_data_path(&app)?, &encode_library(&app, &text)?)?;
+        }
+        journal::rewrite(&app)
+    })?;
+
+    let dir = backups_dir(&app)?;
+    for backup in read_backups(&dir) {
+        let path = dir.join(format!("{}.json", backup.id));
+        let converted = fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(decode_library)
+            .and_then(|text| encode_library(&app, &text));
+        match converted {
+            Ok(data) => write_atomic(&path, &data)?,
+            Err(e) => log_warn!("转换备份 {} 失败: {}", backup.id, e),
+        }
+    }
+    db::rewrite_in_background(&app);
+    if enabled {
+        log_info!("游戏库加密已开启，以下内容仍为明文: {}", PLAINTEXT_WHEN_ENCRYPTED.join("；"));
+        Ok(PLAINTEXT_WHEN_ENCRYPTED.iter().map(|s| s.to_string()).collect())
+    } else {
+        log_info!("游戏库加密已关闭");
+        Ok(Vec::new())
+    }
+}
+
+// iCloud 云盘已开启时建议的数据目录
+#[command]
+pub fn get_icloud_data_dir() -> Option<String> {
+    let drive = dirs::home_dir()?.join(ICLOUD_DRIVE);
+    drive.is_dir().then(|| drive.join("AsumiGal").to_string_lossy().to_string())
+}
+
+// 把数据目录迁移到 iCloud 云盘，其他 Mac 选择同一目录即可共用游戏库
+#[command]
+pub async fn move_data_to_icloud(app: AppHandle, remove_old: bool) -> Result<DataDirInfo, String> {
+    let target = get_icloud_data_dir().ok_or("未开启 iCloud 云盘")?;
+    migrate_data_dir(app, target, remove_old).await
+}
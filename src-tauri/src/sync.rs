@@ -0,0 +1,341 @@
+use tauri::{AppHandle, Emitter, command};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::autosave;
+use crate::db::{self, SessionRow, TagRow};
+use crate::instance::GameInstance;
+use crate::keychain;
+use crate::libraries;
+use crate::paths;
+use crate::settings;
+use crate::storage;
+
+// WebDAV 同步：两台 Mac 共用同一个远端目录，按最后写入者为准合并，
+// 双方都有修改时把被覆盖的一方另存为冲突副本
+const KEYCHAIN_SERVICE: &str = "webdav";
+const REMOTE_DIR: &str = "AsumiGal";
+pub(crate) const STATE_FILE: &str = "sync_state.json";
+const CONFLICTS_DIR: &str = "sync_conflicts";
+
+static SYNCING: AtomicBool = AtomicBool::new(false);
+
+// 远端目录中的同步标记，每次推送时更新
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RemoteState {
+    updated_at: u64,
+    device: String,
+    hash: String,
+}
+
+// 本机上次同步时的状态，用来判断两端各自是否有新修改
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct LocalState {
+    local_hash: String,
+    remote_hash: String,
+    last_sync: u64,
+}
+
+// 会话与标签保存在数据库中，同步时以 JSON 形式一并上传
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct History {
+    sessions: Vec<SessionRow>,
+    tags: Vec<TagRow>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SyncConfig {
+    enabled: bool,
+    url: String,
+    username: String,
+    has_password: bool,
+    last_sync: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SyncReport {
+    // none / push / pull / conflict
+    action: String,
+    // 发生冲突时被覆盖一方的副本路径
+    conflict_copy: Option<String>,
+}
+
+struct Remote {
+    base: String,
+    user: String,
+    password: String,
+    client: reqwest::Client,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+// SHA-256 的十六进制长度
+const HASH_LEN: usize = 64;
+
+// 内容的 SHA-256，不同版本、不同设备上的结果一致
+pub(crate) fn content_hash(data: &[u8]) -> String {
+    openssl::sha::sha256(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn device_name() -> String {
+    Command::new("scutil")
+        .args(["--get", "ComputerName"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| "Mac".to_string())
+}
+
+impl Remote {
+    fn from_settings(app: &AppHandle) -> Result<Remote, String> {
+        let s = settings::load_settings(app);
+        if s.sync_webdav_url.trim().is_empty() || s.sync_webdav_user.is_empty() {
+            return Err("尚未配置 WebDAV 同步".to_string());
+        }
+        // 各游戏库同步到同一地址会互相覆盖，非默认游戏库需要单独设置地址
+        if libraries::active_library(app) != libraries::DEFAULT_LIBRARY && !libraries::active_overrides(app).contains_key("sync_webdav_url") {
+            return Err("当前游戏库没有单独设置 WebDAV 地址，同步会覆盖其他游戏库的数据".to_string());
+        }
+        let password = keychain::get_secret(KEYCHAIN_SERVICE, &s.sync_webdav_user).ok_or("钥匙串中没有 WebDAV 密码")?;
+        Ok(Remote {
+            base: format!("{}/{}", s.sync_webdav_url.trim().trim_end_matches('/'), REMOTE_DIR),
+            user: s.sync_webdav_user,
+            password,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, name: &str) -> reqwest::RequestBuilder {
+        let url = if name.is_empty() { format!("{}/", self.base) } else { format!("{}/{}", self.base, name) };
+        self.client.request(method, url).basic_auth(&self.user, Some(&self.password))
+    }
+
+    // 远端目录不存在时创建；已存在时服务器返回 405
+    async fn ensure_dir(&self) -> Result<(), String> {
+        let mkcol = reqwest::Method::from_bytes(b"MKCOL").map_err(|e| e.to_string())?;
+        let res = self.request(mkcol, "").send().await.map_err(|e| format!("无法连接 WebDAV 服务器: {}", e))?;
+        match res.status().as_u16() {
+            401 | 403 => Err("WebDAV 账号或密码错误".to_string()),
+            s if res.status().is_success() || s == 405 => Ok(()),
+            s => Err(format!("创建远端目录失败: HTTP {}", s)),
+        }
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        let res = self.request(reqwest::Method::GET, name).send().await.map_err(|e| format!("下载 {} 失败: {}", name, e))?;
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !res.status().is_success() {
+            return Err(format!("下载 {} 失败: HTTP {}", name, res.status()));
+        }
+        res.bytes().await.map(|b| Some(b.to_vec())).map_err(|e| format!("下载 {} 失败: {}", name, e))
+    }
+
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<(), String> {
+        let res = self.request(reqwest::Method::PUT, name).body(data).send().await.map_err(|e| format!("上传 {} 失败: {}", name, e))?;
+        if !res.status().is_success() {
+            return Err(format!("上传 {} 失败: HTTP {}", name, res.status()));
+        }
+        Ok(())
+    }
+}
+
+fn read_local_state(app: &AppHandle) -> LocalState {
+    storage::resolve_library_path(app, STATE_FILE)
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write_local_state(app: &AppHandle, state: &LocalState) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    let path = storage::resolve_library_path(app, STATE_FILE)?;
+    storage::write_atomic(&path, text.as_bytes())
+}
+
+// 本机 instances.json 的原始内容（规范形式的路径）与修改时间
+fn read_local_library(app: &AppHandle) -> Result<(Vec<u8>, u64), String> {
+    let path = storage::get_data_path(app)?;
+    // 上传前先写入暂存的修改
+    autosave::flush(app)?;
+    // 加密的游戏库以明文同步，其他设备上没有本机的密钥；游玩时长合并后一起同步
+    let data = serde_json::to_vec_pretty(&storage::stored_values(app)?).map_err(|e| e.to_string())?;
+    let modified = fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    Ok((data, modified))
+}
+
+fn save_conflict_copy(app: &AppHandle, side: &str, data: &[u8]) -> Result<String, String> {
+    let dir = storage::resolve_data_path(app, CONFLICTS_DIR)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("instances-{}-{}.json", now_millis(), side));
+    fs::write(&path, data).map_err(|e| format!("保存冲突副本失败: {}", e))?;
+    log_warn!("同步冲突，已保存{}副本: {:?}", if side == "local" { "本机" } else { "远端" }, path);
+    Ok(path.to_string_lossy().to_string())
+}
+
+async fn push(app: &AppHandle, remote: &Remote, data: Vec<u8>) -> Result<LocalState, String> {
+    let history = History {
+        sessions: db::all_sessions().await?,
+        tags: db::db_list_tags(app.clone(), None).await?,
+    };
+    let hash = content_hash(&data);
+    remote.put("instances.json", data).await?;
+    remote.put("history.json", serde_json::to_vec_pretty(&history).map_err(|e| e.to_string())?).await?;
+    // 标记文件最后上传，中途失败时对端不会看到不完整的数据
+    let state = RemoteState { updated_at: now_millis(), device: device_name(), hash: hash.clone() };
+    remote.put("state.json", serde_json::to_vec_pretty(&state).map_err(|e| e.to_string())?).await?;
+    log_info!("已推送游戏库到 WebDAV");
+    Ok(LocalState { local_hash: hash.clone(), remote_hash: hash, last_sync: now_millis() })
+}
+
+async fn pull(app: &AppHandle, remote: &Remote, remote_state: &RemoteState) -> Result<LocalState, String> {
+    let data = remote.get("instances.json").await?.ok_or("远端缺少 instances.json")?;
+    let mut stored: Value = serde_json::from_slice(&data).map_err(|e| format!("远端游戏库格式错误: {}", e))?;
+    paths::map_instance_paths(&mut stored, |p| paths::resolve_stored_path(p).to_string_lossy().to_string());
+    let instances: Vec<GameInstance> = serde_json::from_value(stored).map_err(|e| format!("远端游戏库格式错误: {}", e))?;
+
+    storage::create_backup(app, "sync")?;
+    db::replace_instances(app, "sync", instances).await?;
+    if let Some(history) = remote.get("history.json").await? {
+        let history: History = serde_json::from_slice(&history).unwrap_or_default();
+        db::import_history(app, &history.sessions, &history.tags, true).await?;
+    }
+    let _ = app.emit("instances-restored", "sync");
+    log_info!("已从 WebDAV 拉取游戏库（来自 {}）", remote_state.device);
+
+    let (local, _) = read_local_library(app)?;
+    Ok(LocalState { local_hash: content_hash(&local), remote_hash: remote_state.hash.clone(), last_sync: now_millis() })
+}
+
+async fn run_sync(app: &AppHandle) -> Result<SyncReport, String> {
+    if SYNCING.swap(true, Ordering::SeqCst) {
+        return Err("正在同步，请稍候".to_string());
+    }
+    let result = sync_once(app).await;
+    SYNCING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn sync_once(app: &AppHandle) -> Result<SyncReport, String> {
+    let remote = Remote::from_settings(app)?;
+    remote.ensure_dir().await?;
+
+    let state = read_local_state(app);
+    let (local, local_modified) = read_local_library(app)?;
+    let remote_state: Option<RemoteState> = match remote.get("state.json").await? {
+        Some(bytes) => serde_json::from_slice(&bytes).ok(),
+        None => None,
+    };
+
+    let local_hash = content_hash(&local);
+    let (local_changed, remote_changed) = if state.local_hash.len() == HASH_LEN {
+        (local_hash != state.local_hash, remote_state.as_ref().is_some_and(|r| r.hash != state.remote_hash))
+    } else {
+        // 旧版本记录的摘要无法比较：内容与远端相同时视为没有修改，否则按双方都有修改处理，保留冲突副本
+        let differs = remote_state.as_ref().is_some_and(|r| r.hash != local_hash);
+        (differs, differs)
+    };
+    let report = |action: &str, conflict_copy: Option<String>| SyncReport { action: action.to_string(), conflict_copy };
+
+    let (new_state, result) = match (remote_state, local_changed, remote_changed) {
+        // 远端还没有数据，或只有本机有修改
+        (None, _, _) | (Some(_), true, false) => (push(app, &remote, local).await?, report("push", None)),
+        (Some(r), false, true) => (pull(app, &remote, &r).await?, report("pull", None)),
+        (Some(r), true, true) => {
+            // 双方都有修改：较新的一方获胜，另一方另存为冲突副本
+            if local_modified >= r.updated_at {
+                let remote_data = remote.get("instances.json").await?.unwrap_or_default();
+                let copy = save_conflict_copy(app, "remote", &remote_data)?;
+                (push(app, &remote, local).await?, report("conflict", Some(copy)))
+            } else {
+                let copy = save_conflict_copy(app, "local", &local)?;
+                (pull(app, &remote, &r).await?, report("conflict", Some(copy)))
+            }
+        }
+        (Some(_), false, false) => (LocalState { last_sync: now_millis(), ..state }, report("none", None)),
+    };
+    write_local_state(app, &new_state)?;
+    Ok(result)
+}
+
+// 启动时在后台同步一次，未启用时什么也不做
+pub fn sync_in_background(app: AppHandle) {
+    if !settings::load_settings(&app).sync_enabled {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        match run_sync(&app).await {
+            Ok(report) => {
+                let _ = app.emit("sync-finished", report);
+            }
+            Err(e) => log_warn!("WebDAV 同步失败: {}", e),
+        }
+    });
+}
+
+#[command]
+pub fn get_sync_config(app: AppHandle) -> SyncConfig {
+    let s = settings::load_settings(&app);
+    let has_password = !s.sync_webdav_user.is_empty() && keychain::get_secret(KEYCHAIN_SERVICE, &s.sync_webdav_user).is_some();
+    let state = read_local_state(&app);
+    SyncConfig {
+        enabled: s.sync_enabled,
+        url: s.sync_webdav_url,
+        username: s.sync_webdav_user,
+        has_password,
+        last_sync: Some(state.last_sync).filter(|t| *t > 0),
+    }
+}
+
+// 保存同步配置并测试连接；password 为空时沿用钥匙串中已有的密码
+#[command]
+pub async fn set_sync_config(app: AppHandle, enabled: bool, url: String, username: String, password: Option<String>) -> Result<SyncConfig, String> {
+    let url = url.trim().to_string();
+    let username = username.trim().to_string();
+    if enabled && !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err("WebDAV 地址必须以 http:// 或 https:// 开头".to_string());
+    }
+
+    let mut s = settings::load_settings(&app);
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        if username.is_empty() {
+            return Err("请填写 WebDAV 账号".to_string());
+        }
+        keychain::set_secret(KEYCHAIN_SERVICE, &username, &password)?;
+    }
+    if !s.sync_webdav_user.is_empty() && s.sync_webdav_user != username {
+        let _ = keychain::delete_secret(KEYCHAIN_SERVICE, &s.sync_webdav_user);
+    }
+    s.sync_enabled = enabled;
+    s.sync_webdav_url = url;
+    s.sync_webdav_user = username;
+    settings::write_settings(&app, &s)?;
+
+    if enabled {
+        Remote::from_settings(&app)?.ensure_dir().await?;
+    }
+    Ok(get_sync_config(app))
+}
+
+#[command]
+pub async fn sync_now(app: AppHandle) -> Result<SyncReport, String> {
+    run_sync(&app).await
+}
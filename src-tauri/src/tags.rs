@@ -0,0 +1,269 @@
+use tauri::{AppHandle, command};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::db;
+use crate::instance::GameInstance;
+use crate::storage;
+
+// 标签与合集：标签的分配保存在各实例的 tags 字段中，这里只保存标签本身（颜色等）与合集
+pub(crate) const TAGS_FILE: &str = "library_tags.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TagDef {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+// 合集是有序的实例列表，同一实例可以属于多个合集
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub instance_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TagStore {
+    pub tags: Vec<TagDef>,
+    pub collections: Vec<Collection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagSummary {
+    name: String,
+    color: Option<String>,
+    // 使用该标签的实例数
+    count: usize,
+}
+
+pub(crate) fn read_store(app: &AppHandle) -> TagStore {
+    storage::resolve_library_path(app, TAGS_FILE)
+        .ok()
+        .and_then(|p| storage::read_data_file(&p).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn write_store(app: &AppHandle, store: &TagStore) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    storage::with_data_dir(|| {
+        storage::write_atomic(&storage::resolve_library_path(app, TAGS_FILE)?, text.as_bytes()).map_err(|e| format!("保存标签失败: {}", e))
+    })
+}
+
+fn ensure_tag(store: &mut TagStore, name: &str) {
+    if !store.tags.iter().any(|t| t.name == name) {
+        store.tags.push(TagDef { name: name.to_string(), color: None });
+    }
+}
+
+// 把实例上新出现的标签加入标签列表
+pub(crate) fn remember_tags(app: &AppHandle, names: &[String]) -> Result<(), String> {
+    let mut store = read_store(app);
+    if names.iter().all(|n| store.tags.iter().any(|t| t.name == *n)) {
+        return Ok(());
+    }
+    for name in names {
+        ensure_tag(&mut store, name);
+    }
+    write_store(app, &store)
+}
+
+// 对所有实例的标签做同一处理，只在有实例被修改时才写入
+async fn update_instance_tags(app: &AppHandle, f: impl Fn(&mut Vec<String>)) -> Result<(), String> {
+    let changed = storage::read_instances(app).iter().any(|inst| {
+        let mut tags = inst.tags.clone();
+        f(&mut tags);
+        tags != inst.tags
+    });
+    if changed {
+        db::update_instances(app, "tags", |instances| {
+            for inst in instances.iter_mut() {
+                f(&mut inst.tags);
+            }
+            Ok(())
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+// 列出所有标签（包括只出现在实例中、尚未单独创建的标签）及其使用次数
+#[command]
+pub fn list_tags(app: AppHandle) -> Vec<TagSummary> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for inst in storage::read_instances(&app) {
+        for tag in inst.tags {
+            *counts.entry(tag).or_insert(0) += 1;
+        }
+    }
+    let store = read_store(&app);
+    let mut summaries: Vec<TagSummary> = store
+        .tags
+        .iter()
+        .map(|t| TagSummary { name: t.name.clone(), color: t.color.clone(), count: counts.remove(&t.name).unwrap_or(0) })
+        .collect();
+    summaries.extend(counts.into_iter().map(|(name, count)| TagSummary { name, color: None, count }));
+    summaries
+}
+
+#[command]
+pub fn create_tag(app: AppHandle, name: String, color: Option<String>) -> Result<TagDef, String> {
+    let name = db::check_tag(&name)?;
+    let mut store = read_store(&app);
+    if store.tags.iter().any(|t| t.name == name) {
+        return Err(format!("标签已存在: {}", name));
+    }
+    let tag = TagDef { name, color };
+    store.tags.push(tag.clone());
+    write_store(&app, &store)?;
+    Ok(tag)
+}
+
+// 重命名标签；新名称已存在时两者合并
+#[command]
+pub async fn rename_tag(app: AppHandle, name: String, new_name: String) -> Result<(), String> {
+    let new_name = db::check_tag(&new_name)?;
+    if new_name == name {
+        return Ok(());
+    }
+    update_instance_tags(&app, |tags| {
+        if let Some(pos) = tags.iter().position(|t| *t == name) {
+            tags.remove(pos);
+            if !tags.contains(&new_name) {
+                tags.insert(pos, new_name.clone());
+            }
+        }
+    })
+    .await?;
+
+    let mut store = read_store(&app);
+    let color = store.tags.iter().find(|t| t.name == name).and_then(|t| t.color.clone());
+    store.tags.retain(|t| t.name != name);
+    match store.tags.iter_mut().find(|t| t.name == new_name) {
+        Some(existing) => {
+            if existing.color.is_none() {
+                existing.color = color;
+            }
+        }
+        None => store.tags.push(TagDef { name: new_name, color }),
+    }
+    write_store(&app, &store)
+}
+
+// 删除标签，并从所有实例上移除
+#[command]
+pub async fn delete_tag(app: AppHandle, name: String) -> Result<(), String> {
+    update_instance_tags(&app, |tags| tags.retain(|t| *t != name)).await?;
+    let mut store = read_store(&app);
+    store.tags.retain(|t| t.name != name);
+    write_store(&app, &store)
+}
+
+// 给一批实例添加（assign 为 true）或移除某个标签
+#[command]
+pub async fn assign_tag(app: AppHandle, tag: String, instance_ids: Vec<String>, assign: bool) -> Result<(), String> {
+    let tag = db::check_tag(&tag)?;
+    db::update_instances(&app, "assign_tag", |instances| {
+        for inst in instances.iter_mut().filter(|i| instance_ids.contains(&i.id)) {
+            let has = inst.tags.contains(&tag);
+            if assign && !has {
+                inst.tags.push(tag.clone());
+            } else if !assign && has {
+                inst.tags.retain(|t| *t != tag);
+            }
+        }
+        Ok(())
+    })
+    .await?;
+
+    if assign {
+        remember_tags(&app, &[tag])?;
+    }
+    Ok(())
+}
+
+// 查询带有全部指定标签的实例
+#[command]
+pub fn query_instances_by_tags(app: AppHandle, tags: Vec<String>) -> Vec<GameInstance> {
+    storage::read_instances(&app)
+        .into_iter()
+        .filter(|inst| tags.iter().all(|t| inst.tags.contains(t)))
+        .collect()
+}
+
+#[command]
+pub fn list_collections(app: AppHandle) -> Vec<Collection> {
+    read_store(&app).collections
+}
+
+#[command]
+pub fn create_collection(app: AppHandle, name: String) -> Result<Collection, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("合集名称不能为空".to_string());
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let collection = Collection { id: format!("collection-{}", now), name, instance_ids: Vec::new() };
+    let mut store = read_store(&app);
+    store.collections.push(collection.clone());
+    write_store(&app, &store)?;
+    Ok(collection)
+}
+
+#[command]
+pub fn rename_collection(app: AppHandle, id: String, name: String) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("合集名称不能为空".to_string());
+    }
+    let mut store = read_store(&app);
+    let collection = store.collections.iter_mut().find(|c| c.id == id).ok_or(format!("未找到合集: {}", id))?;
+    collection.name = name;
+    write_store(&app, &store)
+}
+
+// 删除合集本身，其中的实例不受影响
+#[command]
+pub fn delete_collection(app: AppHandle, id: String) -> Result<(), String> {
+    let mut store = read_store(&app);
+    store.collections.retain(|c| c.id != id);
+    write_store(&app, &store)
+}
+
+// 整体设置合集中的实例及顺序，忽略不存在的实例
+#[command]
+pub fn set_collection_instances(app: AppHandle, id: String, instance_ids: Vec<String>) -> Result<Collection, String> {
+    let known: Vec<String> = storage::read_instances(&app).into_iter().map(|i| i.id).collect();
+    let mut store = read_store(&app);
+    let collection = store.collections.iter_mut().find(|c| c.id == id).ok_or(format!("未找到合集: {}", id))?;
+    collection.instance_ids.clear();
+    for instance_id in instance_ids {
+        if known.contains(&instance_id) && !collection.instance_ids.contains(&instance_id) {
+            collection.instance_ids.push(instance_id);
+        }
+    }
+    let result = collection.clone();
+    write_store(&app, &store)?;
+    Ok(result)
+}
+
+// 按合集中的顺序返回实例，已删除的实例会被跳过
+#[command]
+pub fn query_instances_in_collection(app: AppHandle, id: String) -> Result<Vec<GameInstance>, String> {
+    let store = read_store(&app);
+    let collection = store.collections.iter().find(|c| c.id == id).ok_or(format!("未找到合集: {}", id))?;
+    let instances = storage::read_instances(&app);
+    Ok(collection
+        .instance_ids
+        .iter()
+        .filter_map(|id| instances.iter().find(|i| i.id == *id).cloned())
+        .collect())
+}
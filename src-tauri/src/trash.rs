@@ -0,0 +1,105 @@
+use tauri::{AppHandle, Emitter, command};
+use std::collections::HashSet;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::assets;
+use crate::db;
+use crate::instance::GameInstance;
+use crate::notes;
+use crate::playtime;
+use crate::storage;
+use crate::tags;
+
+// 回收站：删除的实例保留 30 天，期间可以恢复，之后在启动时自动清除
+const TRASH_RETENTION_SECS: u64 = 30 * 24 * 60 * 60;
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// 彻底删除满足条件的回收站实例，连同笔记与合集中的引用，返回删除的数量
+fn purge(app: &AppHandle, should_purge: impl Fn(&GameInstance) -> bool) -> Result<usize, String> {
+    if !storage::load_all_instances(app)?.iter().any(|i| i.deleted_at.is_some() && should_purge(i)) {
+        return Ok(0);
+    }
+    // 修改完整列表，不经过 save_instances，否则被清除的实例又会回到回收站
+    let purged = storage::update_all_instances(app, "purge_trash", None, |all| {
+        let (purged, kept): (Vec<GameInstance>, Vec<GameInstance>) =
+            std::mem::take(all).into_iter().partition(|i| i.deleted_at.is_some() && should_purge(i));
+        *all = kept;
+        Ok(purged)
+    })?;
+
+    let notes_dir = storage::resolve_data_path(app, notes::NOTES_DIR)?;
+    let mut store = tags::read_store(app);
+    for inst in &purged {
+        let _ = fs::remove_file(notes_dir.join(format!("{}.md", inst.id)));
+        for collection in store.collections.iter_mut() {
+            collection.instance_ids.retain(|id| *id != inst.id);
+        }
+        log_info!("已彻底删除实例 {}", inst.name);
+    }
+    tags::write_store(app, &store)?;
+    let ids: HashSet<String> = purged.iter().map(|i| i.id.clone()).collect();
+    storage::with_library_lock(app, |_| playtime::forget(app, &ids))?;
+    db::forget_in_background(ids.into_iter().collect());
+    if let Err(e) = assets::collect_garbage(app) {
+        log_warn!("清理图片失败: {}", e);
+    }
+    Ok(purged.len())
+}
+
+// 启动时清除在回收站中超过保留期的实例
+pub fn purge_expired_trash(app: &AppHandle) -> Result<(), String> {
+    let cutoff = now_secs().saturating_sub(TRASH_RETENTION_SECS);
+    let purged = purge(app, |i| i.deleted_at.is_some_and(|t| t < cutoff))?;
+    if purged > 0 {
+        log_info!("已自动清除回收站中过期的 {} 个实例", purged);
+    }
+    Ok(())
+}
+
+#[command]
+pub fn list_trash(app: AppHandle) -> Result<Vec<GameInstance>, String> {
+    let mut trashed: Vec<GameInstance> = storage::load_all_instances(&app)?
+        .into_iter()
+        .filter(|i| i.deleted_at.is_some())
+        .collect();
+    trashed.sort_by_key(|i| std::cmp::Reverse(i.deleted_at));
+    Ok(trashed)
+}
+
+#[command]
+pub async fn trash_instance(app: AppHandle, instance_id: String) -> Result<(), String> {
+    db::update_all_instances(&app, "trash_instance", |instances| {
+        let inst = instances
+            .iter_mut()
+            .find(|i| i.id == instance_id && i.deleted_at.is_none())
+            .ok_or(format!("未找到实例: {}", instance_id))?;
+        inst.deleted_at = Some(now_secs());
+        Ok(())
+    })
+    .await
+}
+
+#[command]
+pub async fn restore_instance(app: AppHandle, instance_id: String) -> Result<GameInstance, String> {
+    let restored = db::update_all_instances(&app, "restore_instance", |instances| {
+        let inst = instances
+            .iter_mut()
+            .find(|i| i.id == instance_id && i.deleted_at.is_some())
+            .ok_or(format!("回收站中没有该实例: {}", instance_id))?;
+        inst.deleted_at = None;
+        Ok(inst.clone())
+    })
+    .await?;
+    let _ = app.emit("instances-restored", "trash");
+    Ok(restored)
+}
+
+// 清空回收站；传入 instance_ids 时只彻底删除其中的实例
+#[command]
+pub fn purge_trash(app: AppHandle, instance_ids: Option<Vec<String>>) -> Result<usize, String> {
+    purge(&app, |i| instance_ids.as_ref().is_none_or(|ids| ids.contains(&i.id)))
+}
@@ -0,0 +1,264 @@
+use tauri::{AppHandle, command};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::db;
+use crate::instance::GameInstance;
+use crate::{pick_title, SearchResult};
+
+// VNDB 的公开接口（kana），只读查询不需要登录
+const API_URL: &str = "https://api.vndb.org/kana/vn";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+const SEARCH_FIELDS: &str = "id, title, alttitle, titles.lang, titles.title, released, image.url";
+const DETAIL_FIELDS: &str = "id, title, alttitle, titles.lang, titles.title, released, image.url, description, length, length_minutes, rating, developers.name, tags.name, tags.rating, tags.spoiler, screenshots.url, screenshots.thumbnail, screenshots.sexual";
+// 可用于排序的字段，默认按搜索相关度
+const SORT_FIELDS: [&str; 5] = ["searchrank", "title", "released", "rating", "votecount"];
+// 简介中去掉标记、保留文字的 BBCode 标签（剧透段落另行整段删除）
+const BBCODE_TAGS: [&str; 7] = ["url", "b", "i", "u", "s", "quote", "spoiler"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VndbTag {
+    name: String,
+    // 0-3，越高越贴切
+    rating: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VndbScreenshot {
+    url: String,
+    thumbnail: String,
+    // 0-2，前端据此决定是否模糊显示
+    sexual: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VndbDetails {
+    id: String,
+    title: String,
+    titles: BTreeMap<String, String>,
+    released: Option<String>,
+    cover: Option<String>,
+    description: String,
+    developers: Vec<String>,
+    // 1（很短）到 5（很长）
+    length: Option<u8>,
+    // 玩家投票的平均游玩时长
+    length_minutes: Option<u64>,
+    // 10-100
+    rating: Option<f64>,
+    // 已去掉剧透标签，按贴切程度排序
+    tags: Vec<VndbTag>,
+    screenshots: Vec<VndbScreenshot>,
+    url: String,
+}
+
+// VNDB 的语言代码转换为本应用使用的形式（与 KunGal 的 ja-jp、zh-cn 一致）
+fn lang_code(lang: &str) -> String {
+    match lang {
+        "zh-Hans" => "zh-cn".to_string(),
+        "zh-Hant" => "zh-tw".to_string(),
+        "ja" => "ja-jp".to_string(),
+        "en" => "en-us".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+fn titles_of(vn: &Value) -> BTreeMap<String, String> {
+    vn["titles"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| Some((lang_code(t["lang"].as_str()?), t["title"].as_str().filter(|s| !s.is_empty())?.to_string())))
+        .collect()
+}
+
+// 接受 v17 与 17 两种写法
+fn normalize_id(id: &str) -> Result<String, String> {
+    let digits = id.trim().trim_start_matches('v');
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("无效的 VNDB ID: {}", id));
+    }
+    Ok(format!("v{}", digits))
+}
+
+async fn query(client: &reqwest::Client, body: Value) -> Result<Value, String> {
+    let res = client
+        .post(API_URL)
+        .timeout(REQUEST_TIMEOUT)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Request Failed: {}", e))?;
+    let status = res.status();
+    let raw_text = res.text().await.map_err(|e| format!("Read Text Failed: {}", e))?;
+    log_debug!("[VNDB] 原始响应: {}", raw_text);
+    if !status.is_success() {
+        return Err(format!("VNDB 请求失败 ({}): {}", status, raw_text.trim()));
+    }
+    serde_json::from_str(&raw_text).map_err(|e| format!("JSON Parse Failed: {}", e))
+}
+
+// 按关键词搜索，返回本页结果与结果总数
+pub(crate) async fn search(
+    client: &reqwest::Client,
+    keyword: &str,
+    page: u32,
+    limit: u32,
+    sort_field: Option<&str>,
+    sort_order: &str,
+    title_languages: &[String],
+) -> Result<(Vec<SearchResult>, Option<u64>), String> {
+    let sort = sort_field.filter(|f| SORT_FIELDS.contains(f)).unwrap_or("searchrank");
+    // 相关度排序时不反转，其余字段按指定的升降序
+    let reverse = sort != "searchrank" && sort_order == "desc";
+    let body = json!({
+        "filters": ["search", "=", keyword],
+        "fields": SEARCH_FIELDS,
+        "sort": sort,
+        "reverse": reverse,
+        "results": limit,
+        "page": page,
+        "count": true,
+    });
+    let json_val = query(client, body).await?;
+
+    let mut results = Vec::new();
+    for vn in json_val["results"].as_array().into_iter().flatten() {
+        let id = vn["id"].as_str().unwrap_or("").to_string();
+        let titles = titles_of(vn);
+        let title = match pick_title(&titles, title_languages) {
+            t if t.is_empty() => vn["title"].as_str().unwrap_or("未知标题").to_string(),
+            t => t,
+        };
+        results.push(SearchResult {
+            id: id.clone(),
+            title,
+            cover: vn["image"]["url"].as_str().unwrap_or("").to_string(),
+            source: "VNDB".to_string(),
+            url: format!("https://vndb.org/{}", id),
+            date: vn["released"].as_str().filter(|d| *d != "TBA").map(|d| d.to_string()),
+            titles,
+        });
+    }
+    Ok((results, json_val["count"].as_u64()))
+}
+
+// 去掉简介中的 BBCode：剧透段落整段删除，链接等只保留文字
+fn strip_bbcode(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        let end = match after.find(']') {
+            Some(end) => end,
+            None => {
+                out.push_str(after);
+                return out.trim().to_string();
+            }
+        };
+        let tag = after[1..end].trim_start_matches('/');
+        let name = tag.split('=').next().unwrap_or("").to_lowercase();
+        if name == "spoiler" && !after[1..end].starts_with('/') {
+            rest = match after.find("[/spoiler]") {
+                Some(close) => &after[close + "[/spoiler]".len()..],
+                None => "",
+            };
+            continue;
+        }
+        if BBCODE_TAGS.contains(&name.as_str()) {
+            rest = &after[end + 1..];
+        } else {
+            out.push('[');
+            rest = &after[1..];
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+fn parse_details(vn: &Value, title_languages: &[String]) -> VndbDetails {
+    let id = vn["id"].as_str().unwrap_or("").to_string();
+    let titles = titles_of(vn);
+    let title = match pick_title(&titles, title_languages) {
+        t if t.is_empty() => vn["title"].as_str().unwrap_or("").to_string(),
+        t => t,
+    };
+    let mut tags: Vec<VndbTag> = vn["tags"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|t| t["spoiler"].as_f64().unwrap_or(0.0) == 0.0)
+        .filter_map(|t| Some(VndbTag { name: t["name"].as_str()?.to_string(), rating: t["rating"].as_f64().unwrap_or(0.0) }))
+        .collect();
+    tags.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+    let screenshots = vn["screenshots"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|s| {
+            Some(VndbScreenshot {
+                url: s["url"].as_str()?.to_string(),
+                thumbnail: s["thumbnail"].as_str().unwrap_or("").to_string(),
+                sexual: s["sexual"].as_f64().unwrap_or(0.0),
+            })
+        })
+        .collect();
+    VndbDetails {
+        url: format!("https://vndb.org/{}", id),
+        id,
+        title,
+        titles,
+        released: vn["released"].as_str().filter(|d| *d != "TBA").map(|d| d.to_string()),
+        cover: vn["image"]["url"].as_str().map(|s| s.to_string()),
+        description: strip_bbcode(vn["description"].as_str().unwrap_or("")),
+        developers: vn["developers"].as_array().into_iter().flatten().filter_map(|d| d["name"].as_str().map(|s| s.to_string())).collect(),
+        length: vn["length"].as_u64().map(|l| l as u8),
+        length_minutes: vn["length_minutes"].as_u64(),
+        rating: vn["rating"].as_f64(),
+        tags,
+        screenshots,
+    }
+}
+
+async fn fetch_details(app: &AppHandle, id: &str) -> Result<VndbDetails, String> {
+    let id = normalize_id(id)?;
+    let body = json!({ "filters": ["id", "=", id], "fields": DETAIL_FIELDS });
+    let json_val = query(&reqwest::Client::new(), body).await?;
+    let vn = json_val["results"].as_array().and_then(|r| r.first()).ok_or(format!("VNDB 中未找到: {}", id))?;
+    let title_languages = crate::settings::load_settings(app).title_languages_for("vndb");
+    Ok(parse_details(vn, &title_languages))
+}
+
+// 获取作品详情（简介、标签、游玩时长、截图），供详情页显示
+#[command]
+pub async fn vndb_get_details(app: AppHandle, id: String) -> Result<VndbDetails, String> {
+    fetch_details(&app, &id).await
+}
+
+// 把实例关联到 VNDB 作品：记录 vndbId，并补全实例中为空的简介、品牌与封面，已有的内容不覆盖
+#[command]
+pub async fn link_vndb(app: AppHandle, instance_id: String, vndb_id: String) -> Result<GameInstance, String> {
+    let details = fetch_details(&app, &vndb_id).await?;
+    db::update_instances(&app, "link_vndb", |instances| {
+        let inst = instances.iter_mut().find(|i| i.id == instance_id).ok_or(format!("未找到实例: {}", instance_id))?;
+        inst.extra.insert("vndbId".to_string(), Value::String(details.id.clone()));
+        if inst.info.trim().is_empty() {
+            inst.info = details.description.clone();
+        }
+        let has_developer = inst.extra.get("developer").and_then(|v| v.as_str()).is_some_and(|s| !s.is_empty());
+        if !has_developer && !details.developers.is_empty() {
+            inst.extra.insert("developer".to_string(), Value::String(details.developers.join(", ")));
+        }
+        if inst.background_image.as_deref().is_none_or(|s| s.is_empty()) {
+            inst.background_image = details.cover.clone();
+        }
+        Ok(inst.clone())
+    })
+    .await
+}
@@ -0,0 +1,115 @@
+use tauri::{AppHandle, Emitter, command};
+use std::fs;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runner::{self, expand_tilde, WineConfig};
+use crate::storage;
+
+// 可通过 launch_wine_tool 打开的 wine 内置工具及其启动参数
+const WINE_TOOLS: [(&str, &[&str]); 8] = [
+    ("winecfg", &["winecfg"]),
+    ("regedit", &["regedit"]),
+    ("taskmgr", &["taskmgr"]),
+    ("control", &["control"]),
+    ("explorer", &["explorer"]),
+    ("uninstaller", &["uninstaller"]),
+    ("cmd", &["wineconsole", "cmd"]),
+    ("wineboot", &["wineboot", "-r"]),
+];
+
+#[derive(serde::Serialize, Clone)]
+struct ToolFinishedPayload {
+    pid: u32,
+    exe: String,
+    exit_code: Option<i32>,
+    log_path: Option<String>,
+}
+
+// 在容器中运行补丁、设置工具等任意 exe：复用游戏的启动流程，但不记录运行状态与游玩时长
+#[command]
+pub async fn run_exe_in_bottle(
+    app: AppHandle,
+    bottle_path: String,
+    crossover_app_path: String,
+    exe_path: String,
+    args: Vec<String>,
+) -> Result<u32, String> {
+    let config = WineConfig {
+        bottle_path,
+        crossover_app_path,
+        game_exe: exe_path.clone(),
+        ..Default::default()
+    };
+    let (mut cmd, exe, _) = runner::build_crossover_command(&config)?;
+    cmd.args(&args);
+    // 不少补丁工具按相对路径查找游戏文件，工作目录设为 exe 所在目录
+    if let Some(dir) = exe.parent() {
+        cmd.current_dir(dir);
+    }
+
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let log_path = storage::resolve_data_path(&app, &format!("logs/tools/{}.log", ts))?;
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let log_file = fs::File::create(&log_path).map_err(|e| format!("无法创建日志文件: {}", e))?;
+    cmd.stdout(log_file.try_clone().map_err(|e| e.to_string())?);
+    cmd.stderr(log_file);
+
+    let mut child = cmd.spawn().map_err(|e| format!("启动失败: {}", e))?;
+    let pid = child.id();
+    log_info!("已在容器中运行 {:?} (PID: {})", exe, pid);
+
+    thread::spawn(move || {
+        let exit_code = child.wait().ok().and_then(|s| s.code());
+        let _ = app.emit("tool-finished", ToolFinishedPayload {
+            pid,
+            exe: exe_path,
+            exit_code,
+            log_path: Some(log_path.to_string_lossy().to_string()),
+        });
+    });
+
+    Ok(pid)
+}
+
+#[command]
+pub fn list_wine_tools() -> Vec<String> {
+    WINE_TOOLS.iter().map(|(name, _)| name.to_string()).collect()
+}
+
+// 打开容器的 wine 内置工具（winecfg、注册表编辑器等），无需再进终端操作
+#[command]
+pub fn launch_wine_tool(bottle_path: String, crossover_app_path: String, tool: String) -> Result<u32, String> {
+    let (_, args) = WINE_TOOLS
+        .iter()
+        .find(|(name, _)| *name == tool)
+        .ok_or(format!("不支持的工具: {}", tool))?;
+
+    let bottle = expand_tilde(&bottle_path);
+    if !bottle.is_dir() {
+        return Err(format!("容器不存在: {:?}", bottle));
+    }
+    let wine = runner::crossover_tool(&crossover_app_path, "wine");
+    if !wine.exists() {
+        return Err(format!("未找到 CrossOver 核心文件，请检查设置路径: {:?}", wine));
+    }
+
+    let mut cmd = Command::new(&wine);
+    runner::apply_bottle_env(&mut cmd, &bottle)?;
+    let mut child = cmd
+        .args(*args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("无法启动 {}: {}", tool, e))?;
+
+    let pid = child.id();
+    // 回收子进程，避免留下僵尸进程
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(pid)
+}
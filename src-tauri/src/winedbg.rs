@@ -0,0 +1,96 @@
+use serde::Serialize;
+
+// 从 wine 日志中解析出的崩溃信息
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashInfo {
+    // 如 "page fault on read access to 0x00000000 in 32-bit code (0x0040123a)."
+    pub exception: Option<String>,
+    // 出错所在的模块名，如 d3d9 / ntdll / 游戏本体
+    pub faulting_module: Option<String>,
+    pub fault_address: Option<String>,
+    // winedbg 输出的调用栈（最多 BACKTRACE_MAX_FRAMES 帧）
+    pub backtrace: Vec<String>,
+}
+
+const BACKTRACE_MAX_FRAMES: usize = 20;
+
+fn parse_hex(s: &str) -> Option<u64> {
+    let s = s.trim().trim_end_matches(['.', ')', ',']);
+    u64::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
+// winedbg 调用栈中的一帧: "=>0 0x0040123a in game (+0x123a) (0x0032fe40)"
+fn frame_module(frame: &str) -> Option<String> {
+    let (_, rest) = frame.split_once(" in ")?;
+    let name = rest.split([' ', '(']).next()?.trim();
+    if name.is_empty() { None } else { Some(name.to_string()) }
+}
+
+// Modules 表中的一行: "PE	  400000-  500000	Export          game"
+fn module_for_address(lines: &[&str], address: u64) -> Option<String> {
+    let start = lines.iter().position(|l| l.starts_with("Modules:"))?;
+    for line in &lines[start + 1..] {
+        let (range, name) = match line.split_once('\t').map(|(_, r)| r).and_then(|r| r.split_once('\t')) {
+            Some((range, rest)) => (range, rest.split_whitespace().last()),
+            None => continue,
+        };
+        let (lo, hi) = match range.split_once('-') {
+            Some((lo, hi)) => (parse_hex(lo), parse_hex(hi)),
+            None => continue,
+        };
+        if let (Some(lo), Some(hi), Some(name)) = (lo, hi, name) {
+            if (lo..hi).contains(&address) {
+                return Some(name.to_string());
+            }
+        }
+    }
+    None
+}
+
+// 解析日志中最后一次崩溃：优先使用 winedbg 的 Backtrace，其次根据出错地址在 Modules 表中查找，
+// 没有 winedbg 输出时退回到 stderr 中的 "wine: Unhandled page fault" 行
+pub fn parse_crash(log: &str) -> Option<CrashInfo> {
+    let all: Vec<&str> = log.lines().collect();
+    let last = all
+        .iter()
+        .rposition(|l| l.starts_with("Unhandled exception:") || l.starts_with("wine: Unhandled"))?;
+    // winedbg 的输出通常紧跟在 "wine: Unhandled" 行之后，从该行开始一并解析
+    let lookback = last.saturating_sub(5);
+    let start = match all[lookback..last].iter().rposition(|l| l.starts_with("wine: Unhandled")) {
+        Some(i) if all[last].starts_with("Unhandled exception:") => lookback + i,
+        _ => last,
+    };
+    let lines = &all[start..];
+
+    let exception = lines
+        .iter()
+        .find_map(|l| l.strip_prefix("Unhandled exception:"))
+        .or_else(|| lines.iter().find_map(|l| l.strip_prefix("wine: Unhandled")))
+        .map(|s| s.trim().to_string());
+
+    // "... at address 0040123A (thread 0024)" 或 "... in 32-bit code (0x0040123a)."
+    let fault_address = lines.iter().find_map(|l| {
+        if let Some((_, rest)) = l.split_once(" at address ") {
+            return rest.split_whitespace().next().map(|a| a.to_string());
+        }
+        let (_, rest) = l.strip_prefix("Unhandled exception:")?.rsplit_once('(')?;
+        Some(rest.trim_end_matches(['.', ')']).to_string())
+    });
+
+    let backtrace: Vec<String> = lines
+        .iter()
+        .skip_while(|l| !l.starts_with("Backtrace:"))
+        .skip(1)
+        .take_while(|l| l.starts_with("=>") || l.trim_start().chars().next().is_some_and(|c| c.is_ascii_digit()))
+        .take(BACKTRACE_MAX_FRAMES)
+        .map(|l| l.trim().to_string())
+        .collect();
+
+    let faulting_module = backtrace
+        .iter()
+        .find(|f| f.starts_with("=>"))
+        .and_then(|f| frame_module(f))
+        .or_else(|| fault_address.as_deref().and_then(parse_hex).and_then(|a| module_for_address(lines, a)));
+
+    Some(CrashInfo { exception, faulting_module, fault_address, backtrace })
+}
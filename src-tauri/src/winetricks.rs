@@ -0,0 +1,225 @@
+use tauri::{AppHandle, Emitter, command};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+use crate::runner::{self, expand_tilde};
+use crate::storage;
+
+const WINETRICKS_URL: &str = "https://raw.githubusercontent.com/Winetricks/winetricks/master/src/winetricks";
+
+// 常见游戏类型需要的组件组合，供 provision_bottle 一次装齐
+#[derive(serde::Serialize, Clone)]
+pub struct ProvisionPreset {
+    id: &'static str,
+    name: &'static str,
+    verbs: &'static [&'static str],
+}
+
+const PROVISION_PRESETS: [ProvisionPreset; 5] = [
+    ProvisionPreset { id: "japanese_vn", name: "日文视觉小说基础", verbs: &["cjkfonts", "vcrun2008", "vcrun2019", "quartz", "wmp11"] },
+    ProvisionPreset { id: "legacy_vn", name: "老旧视觉小说", verbs: &["cjkfonts", "vcrun6", "mfc42", "quartz", "devenum", "amstream"] },
+    ProvisionPreset { id: "unity", name: "Unity 游戏", verbs: &["vcrun2019", "d3dcompiler_47"] },
+    ProvisionPreset { id: "dotnet", name: ".NET 程序", verbs: &["dotnet48", "vcrun2019"] },
+    ProvisionPreset { id: "video", name: "过场动画播放", verbs: &["quartz", "lavfilters", "wmp11"] },
+];
+
+#[derive(serde::Serialize, Clone)]
+pub struct ProvisionResult {
+    preset: String,
+    installed: Vec<String>,
+    // winetricks.log 中记录为已安装而跳过的组件
+    skipped: Vec<String>,
+}
+
+#[derive(serde::Serialize, Clone)]
+struct WinetricksProgressPayload {
+    bottle: String,
+    verb: String,
+    // 当前 verb 序号（从 1 开始）与总数
+    index: usize,
+    total: usize,
+    line: Option<String>,
+    // running / done / failed
+    state: String,
+}
+
+// verb 会作为命令行参数传入，只允许常见字符
+fn is_valid_verb(verb: &str) -> bool {
+    !verb.is_empty()
+        && verb.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '=' || c == '.' || c == '-')
+        && !verb.starts_with('-')
+}
+
+// 依次查找 Homebrew 安装的 winetricks 与应用缓存，都没有时从 GitHub 下载
+async fn locate_winetricks(app: &AppHandle) -> Result<PathBuf, String> {
+    for p in ["/opt/homebrew/bin/winetricks", "/usr/local/bin/winetricks"] {
+        if Path::new(p).exists() {
+            return Ok(PathBuf::from(p));
+        }
+    }
+
+    let cached = storage::resolve_data_path(app, "tools/winetricks")?;
+    if cached.exists() {
+        return Ok(cached);
+    }
+
+    log_info!("未找到 winetricks，正在下载: {}", WINETRICKS_URL);
+    let res = reqwest::get(WINETRICKS_URL)
+        .await
+        .map_err(|e| format!("下载 winetricks 失败: {}", e))?;
+    if !res.status().is_success() {
+        return Err(format!("下载 winetricks 失败: {}", res.status()));
+    }
+    let body = res.bytes().await.map_err(|e| format!("下载 winetricks 失败: {}", e))?;
+
+    if let Some(parent) = cached.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&cached, &body).map_err(|e| format!("保存 winetricks 失败: {}", e))?;
+    fs::set_permissions(&cached, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    Ok(cached)
+}
+
+// 在指定容器中依次安装 verbs，并通过 winetricks-progress 事件推送输出
+pub async fn install_verbs(
+    app: &AppHandle,
+    bottle_path: &str,
+    crossover_app_path: &str,
+    verbs: &[String],
+) -> Result<Vec<String>, String> {
+    if let Some(bad) = verbs.iter().find(|v| !is_valid_verb(v)) {
+        return Err(format!("无效的 winetricks verb: {}", bad));
+    }
+
+    let bottle = expand_tilde(bottle_path);
+    if !bottle.is_dir() {
+        return Err(format!("容器不存在: {:?}", bottle));
+    }
+    let bottle_name = bottle.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let winetricks = locate_winetricks(app).await?;
+    let wine = runner::crossover_tool(crossover_app_path, "wine");
+    let wineserver = runner::crossover_tool(crossover_app_path, "wineserver");
+    if !wine.exists() {
+        return Err(format!("未找到 CrossOver 核心文件，请检查设置路径: {:?}", wine));
+    }
+
+    let total = verbs.len();
+    let mut installed = Vec::new();
+    for (i, verb) in verbs.iter().enumerate() {
+        let mut child = Command::new("sh")
+            .arg(&winetricks)
+            .arg("-q")
+            .arg(verb)
+            .env("WINE", &wine)
+            .env("WINESERVER", &wineserver)
+            .env("WINEPREFIX", &bottle)
+            .env("CX_BOTTLE", &bottle_name)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("无法执行 winetricks: {}", e))?;
+
+        let (tx, rx) = mpsc::channel::<String>();
+        if let Some(stdout) = child.stdout.take() {
+            runner::spawn_line_reader(stdout, tx.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            runner::spawn_line_reader(stderr, tx.clone());
+        }
+        drop(tx);
+
+        let app_handle = app.clone();
+        let payload = WinetricksProgressPayload {
+            bottle: bottle_name.clone(),
+            verb: verb.clone(),
+            index: i + 1,
+            total,
+            line: None,
+            state: "running".to_string(),
+        };
+        let progress = payload.clone();
+        let status = tokio::task::spawn_blocking(move || {
+            for line in rx {
+                let _ = app_handle.emit("winetricks-progress", WinetricksProgressPayload {
+                    line: Some(line),
+                    ..progress.clone()
+                });
+            }
+            child.wait()
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("等待 winetricks 失败: {}", e))?;
+
+        let ok = status.success();
+        let _ = app.emit("winetricks-progress", WinetricksProgressPayload {
+            state: if ok { "done" } else { "failed" }.to_string(),
+            ..payload
+        });
+
+        if !ok {
+            return Err(format!("winetricks {} 安装失败，退出码: {:?}", verb, status.code()));
+        }
+        installed.push(verb.clone());
+    }
+
+    Ok(installed)
+}
+
+#[command]
+pub async fn run_winetricks(
+    app: AppHandle,
+    bottle_path: String,
+    crossover_app_path: String,
+    verbs: Vec<String>,
+) -> Result<Vec<String>, String> {
+    if verbs.is_empty() {
+        return Err("未选择要安装的组件".to_string());
+    }
+    install_verbs(&app, &bottle_path, &crossover_app_path, &verbs).await
+}
+
+// winetricks 会把装好的 verb 逐行记录在容器的 winetricks.log 中
+fn installed_verbs(bottle: &Path) -> Vec<String> {
+    fs::read_to_string(bottle.join("winetricks.log"))
+        .map(|text| text.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+#[command]
+pub fn list_provision_presets() -> Vec<ProvisionPreset> {
+    PROVISION_PRESETS.to_vec()
+}
+
+// 按预设一次装齐容器所需组件，已装过的跳过；进度沿用 winetricks-progress 事件
+#[command]
+pub async fn provision_bottle(
+    app: AppHandle,
+    bottle_path: String,
+    crossover_app_path: String,
+    preset: String,
+) -> Result<ProvisionResult, String> {
+    let found = PROVISION_PRESETS
+        .iter()
+        .find(|p| p.id == preset)
+        .ok_or(format!("未知的容器预设: {}", preset))?;
+
+    let done = installed_verbs(&expand_tilde(&bottle_path));
+    let (skipped, pending): (Vec<String>, Vec<String>) = found
+        .verbs
+        .iter()
+        .map(|v| v.to_string())
+        .partition(|v| done.contains(v));
+
+    log_info!("正在按预设 {} 配置容器 {}，需安装: {:?}", found.id, bottle_path, pending);
+    let installed = if pending.is_empty() {
+        Vec::new()
+    } else {
+        install_verbs(&app, &bottle_path, &crossover_app_path, &pending).await?
+    };
+    Ok(ProvisionResult { preset, installed, skipped })
+}
@@ -0,0 +1,120 @@
+use tauri::{AppHandle, command};
+use serde::Serialize;
+
+use crate::keychain;
+use crate::settings;
+
+const KEYCHAIN_SERVICE: &str = "ymgal";
+const YMGAL_BASE: &str = "https://www.ymgal.games";
+const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+#[derive(Serialize)]
+pub struct YmgalAccountStatus {
+    logged_in: bool,
+    account: Option<String>,
+}
+
+// 当前登录账号的令牌，未登录或钥匙串中没有条目时为空
+pub fn current_token(app: &AppHandle) -> Option<String> {
+    let account = settings::load_settings(app).ymgal_account;
+    if account.is_empty() {
+        return None;
+    }
+    keychain::get_secret(KEYCHAIN_SERVICE, &account)
+}
+
+// 为请求附加浏览器头与登录令牌
+pub fn authorized(app: &AppHandle, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let req = req.header("User-Agent", USER_AGENT).header("Accept", "application/json");
+    match current_token(app) {
+        Some(token) => req.header("Authorization", format!("Bearer {}", token)),
+        None => req,
+    }
+}
+
+async fn check_response(res: reqwest::Response) -> Result<serde_json::Value, String> {
+    if !res.status().is_success() {
+        return Err(format!("Server returned status: {}", res.status()));
+    }
+    let data: serde_json::Value = res.json().await.map_err(|e| format!("Parse Error: {}", e))?;
+    if data["success"].as_bool() == Some(false) {
+        return Err(data["msg"].as_str().unwrap_or("Ymgal 请求失败").to_string());
+    }
+    Ok(data)
+}
+
+// 令牌保存在钥匙串中，配置文件只记录账号名
+#[command]
+pub async fn ymgal_login(app: AppHandle, account: String, token: String) -> Result<YmgalAccountStatus, String> {
+    let account = account.trim().to_string();
+    let token = token.trim().to_string();
+    if account.is_empty() || token.is_empty() {
+        return Err("账号与令牌不能为空".to_string());
+    }
+
+    // 先用令牌请求一次个人资讯，确认有效后再保存
+    let res = reqwest::Client::new()
+        .get(format!("{}/co/topic/list?type=NEWS&page=1", YMGAL_BASE))
+        .header("User-Agent", USER_AGENT)
+        .header("Accept", "application/json")
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .map_err(|e| format!("Request Error: {}", e))?;
+    if res.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("令牌无效或已过期".to_string());
+    }
+    check_response(res).await?;
+
+    let mut s = settings::load_settings(&app);
+    if !s.ymgal_account.is_empty() && s.ymgal_account != account {
+        let _ = keychain::delete_secret(KEYCHAIN_SERVICE, &s.ymgal_account);
+    }
+    keychain::set_secret(KEYCHAIN_SERVICE, &account, &token)?;
+    s.ymgal_account = account.clone();
+    settings::write_settings(&app, &s)?;
+
+    Ok(YmgalAccountStatus { logged_in: true, account: Some(account) })
+}
+
+#[command]
+pub fn ymgal_logout(app: AppHandle) -> Result<(), String> {
+    let mut s = settings::load_settings(&app);
+    if s.ymgal_account.is_empty() {
+        return Ok(());
+    }
+    keychain::delete_secret(KEYCHAIN_SERVICE, &s.ymgal_account)?;
+    s.ymgal_account.clear();
+    settings::write_settings(&app, &s)
+}
+
+#[command]
+pub fn ymgal_account_status(app: AppHandle) -> YmgalAccountStatus {
+    let account = settings::load_settings(&app).ymgal_account;
+    let logged_in = !account.is_empty() && current_token(&app).is_some();
+    YmgalAccountStatus {
+        logged_in,
+        account: Some(account).filter(|a| !a.is_empty()),
+    }
+}
+
+// 点赞 / 收藏资讯，action 为 like / unlike / bookmark / unbookmark
+#[command]
+pub async fn ymgal_topic_action(app: AppHandle, topic_id: String, action: String) -> Result<serde_json::Value, String> {
+    if current_token(&app).is_none() {
+        return Err("请先登录 Ymgal 账号".to_string());
+    }
+    let path = match action.as_str() {
+        "like" => "co/topic/like",
+        "unlike" => "co/topic/unlike",
+        "bookmark" => "co/topic/collect",
+        "unbookmark" => "co/topic/uncollect",
+        other => return Err(format!("未知的操作: {}", other)),
+    };
+
+    let req = reqwest::Client::new()
+        .post(format!("{}/{}", YMGAL_BASE, path))
+        .json(&serde_json::json!({ "topicId": topic_id }));
+    let res = authorized(&app, req).send().await.map_err(|e| format!("Request Error: {}", e))?;
+    check_response(res).await
+}
@@ -19,6 +19,8 @@ function AppContent() {
   const { config } = useTheme();
   const { showToast } = useToast();
   const isLoaded = useRef(false);
+  // 读取游戏库时的代数，保存时传回后端，游戏库已被其他窗口修改时后端拒绝保存
+  const generationRef = useRef<number | null>(null);
   const [dryRunActive, setDryRunActive] = useState(false);
   const [dryRunInstanceId, setDryRunInstanceId] = useState<string | null>(null);
   const dryRunIntervalRef = useRef<number | null>(null);
@@ -39,8 +41,14 @@ function AppContent() {
   const loadInstancesData = async (isManual = false) => {
     try {
       console.log("正在从后端读取实例数据...");
-      const jsonString = await invoke<string>("load_instances");
-      const loadedData = JSON.parse(jsonString);
+      const { instances: loadedData, generation, repair } = await invoke<{ instances: GameInstance[]; generation: number; repair: { truncated: boolean; fixed: string[]; quarantined: number } | null }>("load_instances");
+      generationRef.current = generation;
+      if (repair) {
+        // 游戏库文件有损坏，后端已修复并备份原文件
+        console.warn("游戏库文件已修复:", repair);
+        const detail = repair.quarantined > 0 ? `，${repair.quarantined} 个无法恢复的条目已另存` : "";
+        showToast(`游戏库文件有损坏，已自动修复${detail}`, "error");
+      }
 
       if (Array.isArray(loadedData)) {
         const sorted = sortInstances(loadedData);
@@ -60,20 +68,114 @@ function AppContent() {
   useEffect(() => {
     if (!isLoaded.current) {
       isLoaded.current = true;
-      loadInstancesData(false);
+      // 游戏库加载完成后再补记上次退出期间结束的会话
+      loadInstancesData(false).then(() => invoke("replay_recovered_sessions").catch((e) => console.error(e)));
     }
   }, []);
 
-  const handleUpdateInstances = async (newInstances: GameInstance[]) => {
-    const sorted = sortInstances(newInstances);
-    setInstances(sorted);
+  const instancesRef = useRef<GameInstance[]>([]);
+  useEffect(() => {
+    instancesRef.current = instances;
+  }, [instances]);
+
+  useEffect(() => {
+    const unlistenPromise = listen<{ instance_id: string; pid: number | null; error: string | null; advice: string | null }>("queued-launch-started", (event) => {
+      const { instance_id, pid, error, advice } = event.payload;
+      const name = instancesRef.current.find((i) => i.id === instance_id)?.name || instance_id;
+      if (pid !== null) {
+        showToast(`排队中的 ${name} 已启动 (PID: ${pid})`, "success");
+        if (advice) showToast(advice, "info");
+      } else {
+        showToast(`排队中的 ${name} 启动失败: ${error}`, "error");
+      }
+    });
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlistenPromise = listen<{ instance_id: string }[]>("quit-requested", (event) => {
+      const names = event.payload.map((r) => instancesRef.current.find((i) => i.id === r.instance_id)?.name || r.instance_id);
+      if (window.confirm(`以下游戏仍在运行：${names.join("、")}\n退出后将在后台继续记录游玩时长，下次打开时补记。确定退出吗？`)) {
+        invoke("confirm_quit").catch((e) => showToast(`${e}`, "error"));
+      }
+    });
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+
+  // 从备份恢复或导入游戏库后重新读取
+  useEffect(() => {
+    const unlistenPromise = listen<string>("instances-restored", (event) => {
+      const messages: Record<string, string> = { import: "已导入游戏库", sync: "已从 WebDAV 同步游戏库", trash: "已从回收站恢复实例", undo: "已撤销修改" };
+      const message = messages[event.payload] || "已从备份恢复游戏库";
+      loadInstancesData(false).then(() => showToast(message, "success"));
+    });
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+
+  // 自动保存写入失败时内存中的数据与磁盘不一致，重新读取
+  useEffect(() => {
+    const unlistenPromise = listen<string>("autosave-failed", (event) => {
+      showToast(`保存失败: ${event.payload}`, "error");
+      loadInstancesData(false);
+    });
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+
+  // 切换页面时立即写入暂存的修改，其他页面读取到的是最新数据
+  useEffect(() => {
+    invoke("flush_autosave").catch((e) => {
+      console.error(e);
+      showToast(`保存失败: ${e}`, "error");
+    });
+  }, [activeTab]);
+
+  // 只提交改动的实例，避免每次编辑都重写整个游戏库
+  const handleUpsertInstances = async (changed: GameInstance[]) => {
+    setInstances((prev) => {
+      const next = [...prev];
+      for (const inst of changed) {
+        const index = next.findIndex((i) => i.id === inst.id);
+        if (index >= 0) next[index] = inst;
+        else next.push(inst);
+      }
+      return sortInstances(next);
+    });
     try {
-      await invoke("save_instances", { data: JSON.stringify(sorted, null, 2) });
-    } catch (e) { console.error(e); }
+      // 后端暂存后合并写入，连续编辑不会逐次重写游戏库
+      generationRef.current = await invoke<number>("stage_instances", { instances: changed, expectedGeneration: generationRef.current });
+    } catch (e) {
+      console.error(e);
+      showToast(`保存失败: ${e}`, "error");
+      // 保存失败时内存中的数据与磁盘不一致，重新读取
+      loadInstancesData(false);
+    }
   };
 
-  const saveInstancesSnapshot = useCallback((list: GameInstance[]) => {
-    invoke("save_instances", { data: JSON.stringify(list, null, 2) }).catch((e) => console.error(e));
+  const handleDeleteInstance = async (id: string) => {
+    setInstances((prev) => prev.filter((i) => i.id !== id));
+    try {
+      await invoke("delete_instance", { id });
+    } catch (e) {
+      console.error(e);
+      showToast(`删除失败: ${e}`, "error");
+    }
+  };
+
+  const saveInstanceSnapshot = useCallback((list: GameInstance[], instanceId: string | null) => {
+    const inst = list.find((i) => i.id === instanceId);
+    if (inst) {
+      invoke<number>("stage_instances", { instances: [inst], expectedGeneration: generationRef.current })
+        .then((generation) => { generationRef.current = generation; })
+        .catch((e) => console.error(e));
+    }
   }, []);
 
   const handleToggleDryRun = useCallback((instanceId: string) => {
@@ -83,7 +185,7 @@ function AppContent() {
         dryRunIntervalRef.current = null;
       }
       setInstances((prev) => {
-        saveInstancesSnapshot(prev);
+        saveInstanceSnapshot(prev, dryRunInstanceId);
         return prev;
       });
       setDryRunActive(false);
@@ -94,7 +196,7 @@ function AppContent() {
         dryRunIntervalRef.current = null;
       }
       setInstances((prev) => {
-        saveInstancesSnapshot(prev);
+        saveInstanceSnapshot(prev, dryRunInstanceId);
         return prev;
       });
       setDryRunInstanceId(instanceId);
@@ -134,17 +236,37 @@ function AppContent() {
         });
       }, 1000);
     }
-  }, [dryRunActive, dryRunInstanceId, saveInstancesSnapshot]);
+  }, [dryRunActive, dryRunInstanceId, saveInstanceSnapshot]);
 
   const gameFinishedGenRef = useRef(0);
 
   useEffect(() => {
     const generation = ++gameFinishedGenRef.current;
 
-    const unlistenPromise = listen<{ instance_id: string; duration_sec: number }>("game-finished", (event) => {
+    const unlistenPromise = listen<{ instance_id: string; duration_sec: number; exit_code: number | null; signal: number | null; crashed: boolean; log_tail: string[]; crash_info: { exception: string | null; faulting_module: string | null; fault_address: string | null; backtrace: string[] } | null; unknown_end: boolean; recorded: { day: string; day_total: number; total_play_time: number } | null }>("game-finished", (event) => {
       if (gameFinishedGenRef.current !== generation) return;
-      const { instance_id, duration_sec } = event.payload;
+      const { instance_id, duration_sec, crashed, exit_code, signal, log_tail, crash_info, unknown_end, recorded } = event.payload;
       console.log(`收到游戏结束事件: ID=${instance_id}, 时长=${duration_sec}s`);
+      if (unknown_end) {
+        showToast(`上次退出时游戏仍在运行，已按退出时间补记 ${Math.round(duration_sec / 60)} 分钟`, "info");
+      }
+      if (crashed) {
+        console.warn(`游戏疑似崩溃: exit_code=${exit_code}, signal=${signal}\n${log_tail.join("\n")}`);
+        const reason = crash_info?.faulting_module ? `出错模块 ${crash_info.faulting_module}` : signal !== null ? `信号 ${signal}` : `退出码 ${exit_code}`;
+        showToast(`游戏疑似崩溃 (${reason})，请查看日志`, "error");
+      }
+
+      // 后端已记入游戏库时只同步内存中的数据，避免重复累加
+      if (recorded) {
+        setInstances((prevInstances) =>
+          prevInstances.map((inst) =>
+            inst.id === instance_id
+              ? { ...inst, totalPlayTime: recorded.total_play_time, playHistory: { ...(inst.playHistory || {}), [recorded.day]: recorded.day_total } }
+              : inst
+          )
+        );
+        return;
+      }
 
       setInstances((prevInstances) => {
         const todayKey = new Date().toLocaleDateString("en-CA");
@@ -163,7 +285,7 @@ function AppContent() {
           return inst;
         });
         
-        invoke("save_instances", { data: JSON.stringify(newInstances, null, 2) });
+        saveInstanceSnapshot(newInstances, instance_id);
         return newInstances;
       });
     });
@@ -181,24 +303,47 @@ function AppContent() {
         : instance.runMode === 'direct'
           ? instance.bottleName
           : `${config.bottlesPath}/${instance.bottleName}`;
-      const response = await invoke("launch_game", {
+      if ((instance.runMode || 'crossover') === 'crossover' && !isDryRun) {
+        const report = await invoke<{ warnings: string[] }>("inspect_exe", {
+          exePath: instance.executablePath,
+          bottlePath,
+          crossoverAppPath: config.crossoverPath,
+        }).catch(() => null);
+        if (report && report.warnings.length > 0
+          && !window.confirm(`${instance.name} 可能无法运行：\n\n${report.warnings.join("\n")}\n\n仍要启动吗？`)) {
+          return;
+        }
+      }
+      const response = await invoke<{ pid: number; queued: boolean; advice: string | null }>("launch_game", {
         instanceId: instance.id,
         config: {
           bottle_path: bottlePath,
           game_exe: instance.executablePath,
           crossover_app_path: config.crossoverPath,
           run_mode: instance.runMode || 'crossover',
-          dry_run_active: isDryRun
+          dry_run_active: isDryRun,
+          audio_driver: instance.audioDriver,
+          audio_buffer_size: instance.audioBufferSize,
+          // 为空时不传，便于由启动方案补全
+          pre_launch: instance.preLaunch?.some((p) => p.trim()) ? instance.preLaunch.map((p) => p.trim()).filter(Boolean) : undefined,
+          profile_id: instance.launchProfile
         }
       });
-      showToast(`${instance.name} 启动成功 (PID: ${response})`, "success");
+      if (response.advice) {
+        showToast(response.advice, "info");
+      }
+      if (response.queued) {
+        showToast(`运行中的游戏已达上限，${instance.name} 已加入启动队列`, "info");
+        return;
+      }
+      showToast(`${instance.name} 启动成功 (PID: ${response.pid})`, "success");
 
       setInstances((prev) => {
         const now = Date.now();
         const sorted = prev.map((i) =>
           i.id === instance.id ? { ...i, lastPlayed: now } : i
         ).sort((a, b) => (b.lastPlayed || 0) - (a.lastPlayed || 0));
-        invoke("save_instances", { data: JSON.stringify(sorted, null, 2) }).catch((e) => console.error(e));
+        saveInstanceSnapshot(sorted, instance.id);
         return sorted;
       });
     } catch (error) {
@@ -276,7 +421,8 @@ function AppContent() {
       {activeTab === "instances" && (
         <InstancesPage 
           instances={instances} 
-          setInstances={handleUpdateInstances} 
+          onUpsertInstances={handleUpsertInstances}
+          onDeleteInstance={handleDeleteInstance}
           onLaunch={handleLaunch} 
           settingsTargetId={instanceSettingsTargetId}
           onConsumeSettingsTarget={() => setInstanceSettingsTargetId(null)}
@@ -24,10 +24,10 @@ interface Topic {
   publishTimeText: string;
 }
 
-interface YmgalResponse {
-  success: boolean;
-  code: number;
-  data: Topic[];
+interface NewsPage {
+  items: Topic[];
+  nextCursor: string | null;
+  hasMore: boolean;
 }
 
 export function DiscoveryPage() {
@@ -38,14 +38,15 @@ export function DiscoveryPage() {
   const [hasMore, setHasMore] = useState(true);
   const [error, setError] = useState<string | null>(null);
   
-  const pageRef = useRef(1);
+  // 后端返回的游标，null 表示从头开始
+  const cursorRef = useRef<string | null>(null);
   const loadingRef = useRef(false);
   const [loadingState, setLoadingState] = useState(false);
 
   const observerTarget = useRef<HTMLDivElement>(null);
 
   // --- 调用 Rust 后端指令 ---
-  const fetchArticles = useCallback(async (pageNum: number) => {
+  const fetchArticles = useCallback(async (reset: boolean) => {
     if (loadingRef.current) return;
     
     loadingRef.current = true;
@@ -53,29 +54,17 @@ export function DiscoveryPage() {
     setError(null);
 
     try {
-      console.log(`[Discovery] Calling Rust backend for page ${pageNum}...`);
+      const cursor = reset ? null : cursorRef.current;
+      console.log(`[Discovery] Calling Rust backend, cursor: ${cursor}`);
       
-      // 使用 invoke 调用 Rust 函数 'fetch_ymgal_news'
-      const data = await invoke<YmgalResponse>('fetch_ymgal_news', { page: pageNum });
+      // 后端负责跨页去重与预取下一页
+      const data = await invoke<NewsPage>('fetch_news', { cursor });
       
       console.log("[Discovery] Rust response:", data);
 
-      if (data.success && data.data && data.data.length > 0) {
-        setArticles(prev => {
-          if (pageNum === 1) return data.data;
-          const existingIds = new Set(prev.map(a => a.topicId));
-          const newArticles = data.data.filter(a => !existingIds.has(a.topicId));
-          return [...prev, ...newArticles];
-        });
-
-        pageRef.current = pageNum;
-
-        if (data.data.length < 10) {
-            setHasMore(false);
-        }
-      } else {
-        setHasMore(false);
-      }
+      setArticles(prev => (reset ? data.items : [...prev, ...data.items]));
+      cursorRef.current = data.nextCursor;
+      setHasMore(data.hasMore);
     } catch (err) {
       console.error("[Discovery] Rust Error:", err);
       // invoke 返回的错误通常是字符串
@@ -88,8 +77,8 @@ export function DiscoveryPage() {
 
   // --- 初始加载 ---
   useEffect(() => {
-    if (pageRef.current === 1 && articles.length === 0) {
-        fetchArticles(1);
+    if (cursorRef.current === null && articles.length === 0) {
+        fetchArticles(true);
     }
   }, []);
 
@@ -99,8 +88,7 @@ export function DiscoveryPage() {
       entries => {
         const target = entries[0];
         if (target.isIntersecting && hasMore && !loadingRef.current) {
-          const nextPage = pageRef.current + 1;
-          fetchArticles(nextPage);
+          fetchArticles(false);
         }
       },
       { threshold: 0.1, rootMargin: '100px' } 
@@ -138,7 +126,7 @@ export function DiscoveryPage() {
             <div className="p-4 rounded-lg bg-red-500/10 text-red-500 flex items-center gap-2 mb-4">
                 <AlertCircle size={20} />
                 <span>{error}</span>
-                <button onClick={() => fetchArticles(pageRef.current)} className="ml-auto underline hover:text-red-600 font-medium">重试</button>
+                <button onClick={() => fetchArticles(articles.length === 0)} className="ml-auto underline hover:text-red-600 font-medium">重试</button>
             </div>
         )}
 
@@ -14,6 +14,10 @@ export interface GameInstance {
   bottleName: string;
   executablePath: string;
   backgroundImage?: string;
+  tags?: string[];
+  favorite?: boolean;
+  finished?: boolean;
+  rating?: number;
   lastPlayed?: number;
   totalPlayTime?: number;
   playHistory?: Record<string, number>;
@@ -22,6 +26,11 @@ export interface GameInstance {
   diskGameRoot?: string;
   localGameRoot?: string;
   gameRelativeDir?: string;
+  audioDriver?: 'default' | 'coreaudio' | 'disabled';
+  audioBufferSize?: number;
+  preLaunch?: string[];
+  launchProfile?: string;
+  customFields?: Record<string, string | number>;
 }
 
 interface SearchResult {
@@ -48,7 +57,8 @@ interface BatchItem {
 
 interface InstancesPageProps {
   instances: GameInstance[];
-  setInstances: (instances: GameInstance[]) => void;
+  onUpsertInstances: (instances: GameInstance[]) => void;
+  onDeleteInstance: (id: string) => void;
   onLaunch: (instance: GameInstance) => void;
   settingsTargetId?: string | null;
   onConsumeSettingsTarget?: () => void;
@@ -59,13 +69,14 @@ interface InstancesPageProps {
 type ImportState = 'none' | 'choice' | 'search_params' | 'search_results' | 'manual_form';
 type GameFileStatus = 'disk' | 'local';
 
-export function InstancesPage({ instances, setInstances, onLaunch, settingsTargetId, onConsumeSettingsTarget, focusInstanceId, onConsumeFocusInstance }: InstancesPageProps) {
+export function InstancesPage({ instances, onUpsertInstances, onDeleteInstance, onLaunch, settingsTargetId, onConsumeSettingsTarget, focusInstanceId, onConsumeFocusInstance }: InstancesPageProps) {
   const { config } = useTheme();
   const { showToast } = useToast();
   
   const [bottles, setBottles] = useState<string[]>([]);
   const [pdVms, setPdVms] = useState<string[]>([]);
   const [scripts, setScripts] = useState<string[]>([]);
+  const [launchProfiles, setLaunchProfiles] = useState<{ id: string; name: string }[]>([]);
   const [selectedId, setSelectedId] = useState<string | null>(null);
   
   const [isImportMenuOpen, setIsImportMenuOpen] = useState(false);
@@ -111,6 +122,12 @@ export function InstancesPage({ instances, setInstances, onLaunch, settingsTarge
     fetchContainers();
   }, [config.bottlesPath, config.pdPath]);
 
+  useEffect(() => {
+    invoke<{ id: string; name: string }[]>("list_launch_profiles")
+      .then(setLaunchProfiles)
+      .catch(() => setLaunchProfiles([]));
+  }, []);
+
   useEffect(() => {
     if (!settingsTargetId) return;
     const target = instances.find(i => i.id === settingsTargetId);
@@ -269,11 +286,7 @@ export function InstancesPage({ instances, setInstances, onLaunch, settingsTarge
         bottleName: updatedFormData.bottleName || (updatedFormData.runMode === 'parallels' ? (config.defaultPdVm || '') : (updatedFormData.runMode === 'crossover' ? (config.defaultBottle || 'Default') : ''))
       } as GameInstance;
 
-      const newInstances = instances.find(i => i.id === newInstance.id)
-        ? instances.map(i => i.id === newInstance.id ? newInstance : i)
-        : [...instances, newInstance];
-
-      setInstances(newInstances);
+      onUpsertInstances([newInstance]);
       setImportState('none');
       setSelectedId(null);
       showToast("游戏文件迁移成功，已自动保存", "success");
@@ -389,22 +402,30 @@ export function InstancesPage({ instances, setInstances, onLaunch, settingsTarge
     }
   };
 
-  const handleSave = () => {
-    if (!formData.name || !formData.executablePath) {
+  const handleSave = async () => {
+    const name = formData.name?.trim() || '';
+    if (!name || !formData.executablePath?.trim()) {
       showToast("名称和可执行文件路径不能为空", "error");
       return;
     }
+    // 封面保存到本地资源库，离线时也能显示；失败时保留原地址
+    let backgroundImage = formData.backgroundImage;
+    if (backgroundImage && formData.id) {
+      try {
+        backgroundImage = await invoke<string>("import_asset", { instanceId: formData.id, source: backgroundImage });
+      } catch (e) {
+        console.warn("保存封面失败", e);
+      }
+    }
     const newInstance = {
       ...formData,
+      name,
+      backgroundImage,
       runMode: formData.runMode || 'crossover',
       bottleName: formData.bottleName || (formData.runMode === 'parallels' ? (config.defaultPdVm || '') : (formData.runMode === 'crossover' ? (config.defaultBottle || 'Default') : ''))
     } as GameInstance;
 
-    let newInstances = instances.find(i => i.id === newInstance.id) 
-      ? instances.map(i => i.id === newInstance.id ? newInstance : i)
-      : [...instances, newInstance];
-      
-    setInstances(newInstances);
+    onUpsertInstances([newInstance]);
     showToast("保存成功", "success");
     setImportState('none');
     setSelectedId(null);
@@ -412,11 +433,11 @@ export function InstancesPage({ instances, setInstances, onLaunch, settingsTarge
 
   const handleDelete = () => {
     if (!deleteModal.instance) return;
-    setInstances(instances.filter(i => i.id !== deleteModal.instance!.id));
+    onDeleteInstance(deleteModal.instance.id);
     setDeleteModal({ isOpen: false, instance: null });
     setSelectedId(null);
     setImportState('none');
-    showToast("实例已删除", "success");
+    showToast("实例已移入回收站，30 天内可以恢复", "success");
   };
 
   const handleBatchImportClick = async () => {
@@ -504,7 +525,7 @@ export function InstancesPage({ instances, setInstances, onLaunch, settingsTarge
         backgroundImage: finalCover,
       };
     });
-    setInstances([...instances, ...newInstances]);
+    onUpsertInstances(newInstances);
     setIsBatchModalOpen(false);
     setBatchItems([]);
     showToast(`成功批量导入 ${newInstances.length} 个游戏`, "success");
@@ -677,7 +698,7 @@ export function InstancesPage({ instances, setInstances, onLaunch, settingsTarge
                       const res = await open({ filters: [{ name: 'Application', extensions: ['app'] }] }); 
                       selected = Array.isArray(res) ? res[0] : res;
                     } else {
-                      const res = await open({ filters: [{ name: 'Executable', extensions: ['exe'] }] });
+                      const res = await open({ filters: [{ name: 'Executable', extensions: ['exe', 'msi'] }] });
                       selected = Array.isArray(res) ? res[0] : res;
                     }
                     if (selected && typeof selected === 'string') setFormData({ ...formData, executablePath: selected });
@@ -793,6 +814,63 @@ export function InstancesPage({ instances, setInstances, onLaunch, settingsTarge
                 </div>
               </div>
 
+              <div>
+                <label className="block text-sm font-medium mb-2">启动方案</label>
+                <div className="relative">
+                  <select
+                    value={formData.launchProfile || ''}
+                    onChange={e => setFormData({ ...formData, launchProfile: e.target.value || undefined })}
+                    className="w-full bg-black/5 dark:bg-white/5 border border-black/10 dark:border-white/10 rounded-lg px-4 py-2 pr-8 outline-none appearance-none transition-colors truncate"
+                  >
+                    <option value="">不使用</option>
+                    {launchProfiles.map(p => <option key={p.id} value={p.id}>{p.name}</option>)}
+                  </select>
+                  <DropdownArrow />
+                </div>
+              </div>
+
+              <div className="grid grid-cols-2 gap-6">
+                <div className="min-w-0">
+                  <label className="block text-sm font-medium mb-2 truncate">音频驱动</label>
+                  <div className="relative">
+                    <select
+                      value={formData.audioDriver || 'default'}
+                      onChange={e => setFormData({ ...formData, audioDriver: e.target.value as 'default' | 'coreaudio' | 'disabled' })}
+                      className="w-full bg-black/5 dark:bg-white/5 border border-black/10 dark:border-white/10 rounded-lg px-4 py-2 pr-8 outline-none appearance-none transition-colors truncate"
+                    >
+                      <option value="default">默认</option>
+                      <option value="coreaudio">CoreAudio</option>
+                      <option value="disabled">禁用音频</option>
+                    </select>
+                    <DropdownArrow />
+                  </div>
+                </div>
+                <div className="min-w-0">
+                  <label className="block text-sm font-medium mb-2 truncate">音频缓冲区 (字节)</label>
+                  <input
+                    type="number"
+                    min={4096}
+                    max={1048576}
+                    step={4096}
+                    value={formData.audioBufferSize ?? ''}
+                    onChange={e => setFormData({ ...formData, audioBufferSize: e.target.value ? Number(e.target.value) : undefined })}
+                    className="w-full bg-black/5 dark:bg-white/5 border border-black/10 dark:border-white/10 rounded-lg px-4 py-2 outline-none"
+                    placeholder="默认 65536，爆音时可调大"
+                  />
+                </div>
+              </div>
+
+              <div>
+                <label className="block text-sm font-medium mb-2">启动前运行 (每行一个，依次运行并等待退出)</label>
+                <textarea
+                  rows={2}
+                  placeholder="例如 config.exe，相对路径基于游戏目录"
+                  value={(formData.preLaunch || []).join('\n')}
+                  onChange={e => setFormData({ ...formData, preLaunch: e.target.value.split('\n') })}
+                  className="w-full bg-black/5 dark:bg-white/5 border border-black/10 dark:border-white/10 rounded-lg px-4 py-2 outline-none resize-none font-mono text-sm"
+                />
+              </div>
+
               <div>
                 <button
                   onClick={handleMigrateGameFiles}
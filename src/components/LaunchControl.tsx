@@ -1,5 +1,6 @@
 import { useState } from "react";
-import { Settings, ArrowLeftRight, Play, Box, History, Clock, Square } from "lucide-react";
+import { Settings, ArrowLeftRight, Play, Box, History, Clock, Square, FolderOpen } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
 import { motion, AnimatePresence } from "framer-motion";
 import { clsx } from "clsx";
 import { GameInstance } from "./InstancesPage";
@@ -53,6 +54,14 @@ export function LaunchControl({ instances, onLaunch, onGoToSettings, onStop, dry
     }
   };
 
+  // 在 Finder 中打开游戏目录，方便放置补丁、查找存档
+  const handleRevealGameDir = () => {
+    if (currentInstance) {
+      invoke("reveal_in_finder", { kind: "game", instanceId: currentInstance.id })
+        .catch((e) => console.error("打开游戏目录失败:", e));
+    }
+  };
+
   const handleOpenCurrentSettings = () => {
     if (currentInstance) {
       onGoToSettings(currentInstance);
@@ -192,6 +201,7 @@ export function LaunchControl({ instances, onLaunch, onGoToSettings, onStop, dry
 
               <div className="absolute top-2 right-2 flex gap-1 bg-black/20 backdrop-blur rounded-lg p-0.5 border border-white/5 transition-opacity duration-200">
                   <ToolBtn icon={<Settings size={12} />} onClick={handleOpenCurrentSettings} />
+                  <ToolBtn icon={<FolderOpen size={12} />} onClick={handleRevealGameDir} />
                   <ToolBtn icon={<ArrowLeftRight size={12} />} onClick={() => setIsOpen(!isOpen)} active={isOpen} />
                   <ToolBtn icon={<Square size={12} />} onClick={handleStopInstance} />
               </div>
@@ -127,7 +127,7 @@ export function SearchModal({ isOpen, onClose }: SearchModalProps) {
                 </div>
 
                 {/* 2. 快捷操作区 (始终显示，在底部或结果上方) */}
-                <div className="p-3 bg-gray-50/50 dark:bg-white/5 grid grid-cols-2 gap-3 flex-shrink-0 border-b border-white/5">
+                <div className="p-3 bg-gray-50/50 dark:bg-white/5 grid grid-cols-2 md:grid-cols-4 gap-3 flex-shrink-0 border-b border-white/5">
                     <SearchOption 
                         title="TouchGal" 
                         desc="搜索游戏元数据" 
@@ -144,6 +144,22 @@ export function SearchModal({ isOpen, onClose }: SearchModalProps) {
                         onClick={() => handleSearch('kungal')} 
                         isDark={isDark} 
                     />
+                    <SearchOption 
+                        title="VNDB" 
+                        desc="视觉小说数据库" 
+                        colorClass="text-emerald-500" 
+                        isLoading={loadingSource === 'vndb'}
+                        onClick={() => handleSearch('vndb')} 
+                        isDark={isDark} 
+                    />
+                    <SearchOption 
+                        title="Bangumi" 
+                        desc="番组计划条目" 
+                        colorClass="text-rose-500" 
+                        isLoading={loadingSource === 'bangumi'}
+                        onClick={() => handleSearch('bangumi')} 
+                        isDark={isDark} 
+                    />
                 </div>
 
                 {/* 3. 结果列表区域 (可滚动) */}
@@ -1,4 +1,5 @@
 import { createContext, useContext, useState, useEffect, ReactNode } from "react";
+import { invoke } from "@tauri-apps/api/core";
 
 export type ThemeMode = "light" | "dark" | "system";
 
@@ -99,6 +100,11 @@ export function ThemeProvider({ children }: { children: ReactNode }) {
     
   }, [config, currentTheme]);
 
+  // 同步到后端，下次启动时在页面加载前注入字体
+  useEffect(() => {
+    invoke("set_ui_font", { family: config.fontFamily }).catch(console.error);
+  }, [config.fontFamily]);
+
   const updateConfig = (newConfig: Partial<AppConfig>) => {
     setConfig(prev => ({ ...prev, ...newConfig }));
   };